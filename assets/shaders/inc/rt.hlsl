@@ -5,6 +5,10 @@
 #include "gbuffer.hlsl"
 #include "ray_cone.hlsl"
 
+// Instance mask bits, set per instance in `world_renderer.rs`
+#define RT_INSTANCE_MASK_DEFAULT 0x01
+#define RT_INSTANCE_MASK_SHADOW_CASTER 0x02
+
 struct GbufferRayPayload {
     GbufferDataPacked gbuffer_packed;
     float t;
@@ -63,7 +67,7 @@ bool rt_is_shadowed(
     TraceRay(
         acceleration_structure,
         RAY_FLAG_ACCEPT_FIRST_HIT_AND_END_SEARCH | RAY_FLAG_SKIP_CLOSEST_HIT_SHADER,
-        0xff, 0, 0, 1, ray, shadow_payload
+        RT_INSTANCE_MASK_SHADOW_CASTER, 0, 0, 1, ray, shadow_payload
     );
 
     return shadow_payload.is_shadowed;
@@ -25,5 +25,6 @@ fn main() -> Result<()> {
         path: opt.scene,
         output_name: opt.output_name,
         scale: opt.scale,
+        node: None,
     })
 }
@@ -0,0 +1,360 @@
+use std::{fs::File, path::PathBuf, time::Duration};
+
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    asset_paths::AssetSearchPaths,
+    culling::{default_true, FrustumCullingConfig},
+    math::{OcclusionCullingConfig, TriangleCullingConfig},
+    persisted::{EditingState, FarPlaneConfig, PersistedState},
+    render_presets::RenderPreset,
+    selection::SelectionHighlightConfig,
+};
+
+const APP_CONFIG_FILE_PATH: &str = "app_config.ron";
+
+/// What to load when the engine starts without `--scene` or `--mesh`
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum StartupScene {
+    /// Start with an empty scene
+    None,
+    /// Reload the most recently opened scene file
+    LastScene,
+    /// Always load this scene file
+    Path(PathBuf),
+}
+
+impl Default for StartupScene {
+    fn default() -> Self {
+        Self::None
+    }
+}
+
+/// What the camera transform key (`print_camera_transform` in the keymap) does
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CameraKeyAction {
+    /// Print position and look-at point to stdout
+    Print,
+    /// Copy the camera as RON, in the same format as `camera` in the persisted state
+    CopyToClipboard,
+    /// Add the current view as a sequence keyframe
+    AddKeyframe,
+}
+
+impl Default for CameraKeyAction {
+    fn default() -> Self {
+        Self::Print
+    }
+}
+
+/// Frame rate cap applied while the window is unfocused, to save power during editing pauses
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct IdleThrottle {
+    pub enabled: bool,
+    pub max_fps: u32,
+    /// The reference path tracer keeps accumulating in the background unless this is set
+    pub throttle_path_tracing: bool,
+    /// Also cap the focused window to `power_saver_fps` while there's no input and nothing in
+    /// the scene moves. Never applies to the reference path tracer.
+    #[serde(default)]
+    pub power_saver: bool,
+    #[serde(default = "default_power_saver_fps")]
+    pub power_saver_fps: u32,
+}
+
+impl Default for IdleThrottle {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_fps: 10,
+            throttle_path_tracing: false,
+            power_saver: false,
+            power_saver_fps: default_power_saver_fps(),
+        }
+    }
+}
+
+impl IdleThrottle {
+    /// How long the scene has to stay still before the power saver kicks in
+    pub const POWER_SAVER_DELAY: Duration = Duration::from_secs(1);
+
+    /// `max_fps` lowered by whichever caps apply. `idle_for` is the time since the last input
+    /// or scene change.
+    pub fn cap_fps(&self, max_fps: u32, window_focused: bool, idle_for: Duration, path_tracing: bool) -> u32 {
+        let accumulating = path_tracing && !self.throttle_path_tracing;
+        let mut fps = max_fps;
+
+        if self.enabled && !window_focused && !accumulating {
+            fps = fps.min(self.max_fps.max(1));
+        }
+        if self.power_saver && !path_tracing && idle_for >= Self::POWER_SAVER_DELAY {
+            fps = fps.min(self.power_saver_fps.max(1));
+        }
+
+        fps
+    }
+}
+
+fn default_power_saver_fps() -> u32 {
+    30
+}
+
+/// Past this many elements, editor features whose per-frame cost grows with the scene are turned off:
+/// triangle culling, per-element debug colors and node trees in the Outliner, which also starts
+/// laying out only the rows in view
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LargeSceneConfig {
+    /// 0 disables the cap
+    pub element_soft_cap: u32,
+}
+
+impl Default for LargeSceneConfig {
+    fn default() -> Self {
+        Self {
+            element_soft_cap: 5000,
+        }
+    }
+}
+
+impl LargeSceneConfig {
+    pub fn is_large(&self, element_count: usize) -> bool {
+        self.element_soft_cap > 0 && element_count > self.element_soft_cap as usize
+    }
+}
+
+/// Size of the editor interface. Fonts are rasterized for the display scale at startup;
+/// changes made afterwards scale the existing glyphs instead.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct GuiConfig {
+    /// Applied on top of the display scale when `match_display_scale` is set
+    pub scale: f32,
+    /// Follow the window's scale factor, e.g. 1.5 on a 150% DPI monitor
+    pub match_display_scale: bool,
+    /// Last state of the GUI toggle (`ui.toggle` in the keymap)
+    #[serde(default = "default_true")]
+    pub visible: bool,
+}
+
+impl Default for GuiConfig {
+    fn default() -> Self {
+        Self {
+            scale: 1.0,
+            match_display_scale: true,
+            visible: true,
+        }
+    }
+}
+
+impl GuiConfig {
+    pub const MIN_SCALE: f32 = 0.5;
+    pub const MAX_SCALE: f32 = 4.0;
+
+    pub fn effective_scale(&self, window_scale_factor: f32) -> f32 {
+        let display_scale = if self.match_display_scale {
+            window_scale_factor
+        } else {
+            1.0
+        };
+        (display_scale * self.scale).clamp(Self::MIN_SCALE, Self::MAX_SCALE)
+    }
+}
+
+/// Application preferences that outlive any single scene or session state
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AppConfig {
+    #[serde(default)]
+    pub startup_scene: StartupScene,
+    #[serde(default)]
+    pub last_scene: Option<PathBuf>,
+    #[serde(default)]
+    pub camera_key_action: CameraKeyAction,
+    #[serde(default)]
+    pub asset_search_paths: AssetSearchPaths,
+    #[serde(default)]
+    pub idle_throttle: IdleThrottle,
+    #[serde(default)]
+    pub frustum_culling: FrustumCullingConfig,
+    #[serde(default)]
+    pub occlusion_culling: OcclusionCullingConfig,
+    #[serde(default)]
+    pub triangle_culling: TriangleCullingConfig,
+    #[serde(default)]
+    pub selection_highlight: SelectionHighlightConfig,
+    #[serde(default)]
+    pub editing: EditingState,
+    #[serde(default)]
+    pub far_plane: FarPlaneConfig,
+    /// User-saved presets, shown after the built-in ones
+    #[serde(default)]
+    pub render_presets: Vec<RenderPreset>,
+    /// Parse glTF files for their nodes as soon as they're in the scene, for per-node culling.
+    /// When off, glTFs are culled as single objects until analyzed from the Attributes window.
+    #[serde(default = "default_true")]
+    pub auto_analyze_gltf: bool,
+    #[serde(default)]
+    pub large_scene: LargeSceneConfig,
+    #[serde(default)]
+    pub gui: GuiConfig,
+}
+
+impl Default for AppConfig {
+    fn default() -> Self {
+        Self {
+            startup_scene: StartupScene::default(),
+            last_scene: None,
+            camera_key_action: CameraKeyAction::default(),
+            asset_search_paths: AssetSearchPaths::default(),
+            idle_throttle: IdleThrottle::default(),
+            frustum_culling: FrustumCullingConfig::default(),
+            occlusion_culling: OcclusionCullingConfig::default(),
+            triangle_culling: TriangleCullingConfig::default(),
+            selection_highlight: SelectionHighlightConfig::default(),
+            editing: EditingState::default(),
+            far_plane: FarPlaneConfig::default(),
+            render_presets: Vec::new(),
+            auto_analyze_gltf: true,
+            large_scene: LargeSceneConfig::default(),
+            gui: GuiConfig::default(),
+        }
+    }
+}
+
+impl AppConfig {
+    /// Load the config, falling back to defaults if there isn't one yet or it can't be read
+    pub fn load() -> Self {
+        File::open(APP_CONFIG_FILE_PATH)
+            .map_err(anyhow::Error::from)
+            .and_then(|file| Ok(ron::de::from_reader(file)?))
+            .unwrap_or_else(|err| {
+                log::info!("Using default app config ({:#})", err);
+                Self::default()
+            })
+    }
+
+    pub fn save(&self) -> anyhow::Result<()> {
+        ron::ser::to_writer_pretty(
+            File::create(APP_CONFIG_FILE_PATH)
+                .with_context(|| format!("Creating {}", APP_CONFIG_FILE_PATH))?,
+            self,
+            Default::default(),
+        )?;
+        Ok(())
+    }
+
+    /// Move preferences found in a view state saved before they lived here. Returns whether
+    /// anything was migrated; the view state no longer writes them, so this happens once.
+    pub fn migrate_legacy_preferences(&mut self, persisted: &mut PersistedState) -> bool {
+        let mut migrated = false;
+
+        macro_rules! migrate {
+            ($legacy:ident, $field:ident) => {
+                if let Some(value) = persisted.$legacy.take() {
+                    self.$field = value;
+                    migrated = true;
+                }
+            };
+        }
+
+        migrate!(legacy_frustum_culling, frustum_culling);
+        migrate!(legacy_occlusion_culling, occlusion_culling);
+        migrate!(legacy_triangle_culling, triangle_culling);
+        migrate!(legacy_selection_highlight, selection_highlight);
+        migrate!(legacy_editing, editing);
+
+        migrated
+    }
+
+    /// The scene to open on startup, if any
+    pub fn startup_scene_path(&self) -> Option<&PathBuf> {
+        match &self.startup_scene {
+            StartupScene::None => None,
+            StartupScene::LastScene => self.last_scene.as_ref(),
+            StartupScene::Path(path) => Some(path),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn legacy_preferences_move_to_app_config_once() {
+        let mut persisted = PersistedState::default();
+        persisted.legacy_editing = Some(EditingState {
+            nudge_step: 0.5,
+            ..Default::default()
+        });
+
+        let mut config = AppConfig::default();
+        assert!(config.migrate_legacy_preferences(&mut persisted));
+        assert_eq!(config.editing.nudge_step, 0.5);
+        assert!(persisted.legacy_editing.is_none());
+
+        assert!(!config.migrate_legacy_preferences(&mut persisted));
+    }
+
+    #[test]
+    fn large_scene_degrades_past_the_soft_cap() {
+        let config = LargeSceneConfig {
+            element_soft_cap: 100,
+        };
+        assert!(!config.is_large(0));
+        assert!(!config.is_large(100));
+        assert!(config.is_large(101));
+
+        let uncapped = LargeSceneConfig {
+            element_soft_cap: 0,
+        };
+        assert!(!uncapped.is_large(1_000_000));
+    }
+
+    #[test]
+    fn gui_scale_follows_display_scale() {
+        let mut gui = GuiConfig::default();
+        assert_eq!(gui.effective_scale(1.5), 1.5);
+
+        gui.scale = 1.25;
+        assert_eq!(gui.effective_scale(2.0), 2.5);
+
+        gui.match_display_scale = false;
+        assert_eq!(gui.effective_scale(2.0), 1.25);
+
+        gui.scale = 100.0;
+        assert_eq!(gui.effective_scale(1.0), GuiConfig::MAX_SCALE);
+    }
+
+    #[test]
+    fn power_saver_caps_static_scenes_only() {
+        let throttle = IdleThrottle {
+            power_saver: true,
+            ..Default::default()
+        };
+        let still = IdleThrottle::POWER_SAVER_DELAY;
+
+        assert_eq!(throttle.cap_fps(144, true, Duration::ZERO, false), 144);
+        assert_eq!(throttle.cap_fps(144, true, still, false), 30);
+        assert_eq!(throttle.cap_fps(144, true, still, true), 144);
+
+        // The unfocused cap is lower and still wins
+        assert_eq!(throttle.cap_fps(144, false, still, false), 10);
+
+        let disabled = IdleThrottle::default();
+        assert_eq!(disabled.cap_fps(144, true, still, false), 144);
+    }
+
+    #[test]
+    fn gui_visibility_round_trips() {
+        // Written before the GUI toggle was remembered
+        let old: GuiConfig = ron::from_str("(scale: 1.0, match_display_scale: true)").unwrap();
+        assert!(old.visible);
+
+        let hidden = GuiConfig {
+            visible: false,
+            ..Default::default()
+        };
+        let saved: GuiConfig = ron::from_str(&ron::to_string(&hidden).unwrap()).unwrap();
+        assert!(!saved.visible);
+    }
+}
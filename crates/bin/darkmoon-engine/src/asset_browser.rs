@@ -13,6 +13,8 @@ pub struct AssetBrowser {
 pub enum AssetAction {
     None,
     LoadScene(PathBuf),
+    /// Use a sphere-mapped .hdr/.exr as the scene's IBL
+    LoadIbl(PathBuf),
 }
 
 impl AssetBrowser {
@@ -71,7 +73,16 @@ impl AssetBrowser {
                             let model_label = ImString::from(get_file_icon_label(extension, file_name.to_str()));
                             ui.bullet_text(&model_label);
                         }
-                        "png" | "jpg" | "jpeg" | "tga" | "dds" | "hdr" | "exr" => {
+                        "hdr" | "exr" => {
+                            let ibl_label = ImString::from(get_file_icon_label(extension, file_name.to_str()));
+                            if ui.selectable(&ibl_label) {
+                                *action = AssetAction::LoadIbl(path.clone());
+                            }
+                            if ui.is_item_hovered() {
+                                ui.tooltip_text("Load as IBL");
+                            }
+                        }
+                        "png" | "jpg" | "jpeg" | "tga" | "dds" => {
                             let image_label = ImString::from(get_file_icon_label(extension, file_name.to_str()));
                             ui.bullet_text(&image_label);
                         }
@@ -0,0 +1,82 @@
+use std::path::{Component, Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+/// Directories tried, in order, when resolving a relative mesh or texture path,
+/// so scenes authored with a different asset layout still find their files
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct AssetSearchPaths {
+    pub dirs: Vec<PathBuf>,
+}
+
+impl Default for AssetSearchPaths {
+    fn default() -> Self {
+        let mut dirs = vec![PathBuf::from("assets")];
+
+        // The assets shipped next to the engine binary, for when it's run from elsewhere
+        if let Some(exe_dir) = std::env::current_exe()
+            .ok()
+            .and_then(|exe| exe.parent().map(Path::to_path_buf))
+        {
+            dirs.push(exe_dir.join("assets"));
+        }
+
+        Self { dirs }
+    }
+}
+
+impl AssetSearchPaths {
+    /// Finds `path` under each search directory in turn, then as given.
+    /// VFS-style paths (`/meshes/...`) are treated as relative to the search directories.
+    pub fn resolve(&self, path: &Path) -> Option<PathBuf> {
+        let relative: PathBuf = path
+            .components()
+            .filter(|component| !matches!(component, Component::RootDir | Component::Prefix(_)))
+            .collect();
+
+        let found = self
+            .dirs
+            .iter()
+            .map(|dir| dir.join(&relative))
+            .chain(std::iter::once(path.to_path_buf()))
+            .find(|candidate| candidate.exists());
+
+        match &found {
+            Some(resolved) => log::info!("Resolved asset {:?} to {:?}", path, resolved),
+            None => log::warn!("Asset {:?} not found in any of {:?}", path, self.dirs),
+        }
+
+        found
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolves_from_second_search_dir() {
+        let root = std::env::temp_dir().join(format!("asset-search-paths-test-{}", std::process::id()));
+        let first = root.join("project");
+        let second = root.join("engine");
+        std::fs::create_dir_all(&first).unwrap();
+        std::fs::create_dir_all(second.join("meshes")).unwrap();
+        std::fs::write(second.join("meshes/rock.gltf"), "{}").unwrap();
+
+        let search_paths = AssetSearchPaths {
+            dirs: vec![first, second.clone()],
+        };
+
+        assert_eq!(
+            search_paths.resolve(Path::new("meshes/rock.gltf")),
+            Some(second.join("meshes/rock.gltf"))
+        );
+        assert_eq!(
+            search_paths.resolve(Path::new("/meshes/rock.gltf")),
+            Some(second.join("meshes/rock.gltf"))
+        );
+        assert_eq!(search_paths.resolve(Path::new("meshes/missing.gltf")), None);
+
+        let _ = std::fs::remove_dir_all(root);
+    }
+}
@@ -0,0 +1,134 @@
+use std::{
+    fs::File,
+    path::PathBuf,
+    sync::Mutex,
+};
+
+use anyhow::Context;
+
+use crate::scene::SceneDesc;
+
+/// The most recent scene, kept up to date by the runtime so a panic can still save it
+struct CrashSnapshot {
+    scene: SceneDesc,
+    path: PathBuf,
+}
+
+static CRASH_SNAPSHOT: Mutex<Option<CrashSnapshot>> = Mutex::new(None);
+
+/// Where the emergency copy of `scene_path` goes: next to it, with `.crash` appended
+pub fn crash_file_path(scene_path: Option<&PathBuf>) -> PathBuf {
+    match scene_path {
+        Some(path) => {
+            let mut file_name = path.file_name().unwrap_or_default().to_os_string();
+            file_name.push(".crash");
+            path.with_file_name(file_name)
+        }
+        None => PathBuf::from("untitled.dmoon.crash"),
+    }
+}
+
+pub fn update_snapshot(scene: SceneDesc, path: PathBuf) {
+    if let Ok(mut snapshot) = CRASH_SNAPSHOT.lock() {
+        *snapshot = Some(CrashSnapshot { scene, path });
+    }
+}
+
+/// Writes the current snapshot, if any. Doesn't block, as the panic may have happened while it was being updated.
+fn write_snapshot() -> anyhow::Result<Option<PathBuf>> {
+    let snapshot = match CRASH_SNAPSHOT.try_lock() {
+        Ok(snapshot) => snapshot,
+        Err(std::sync::TryLockError::Poisoned(poisoned)) => poisoned.into_inner(),
+        Err(std::sync::TryLockError::WouldBlock) => anyhow::bail!("Scene snapshot is locked"),
+    };
+
+    let snapshot = match snapshot.as_ref() {
+        Some(snapshot) => snapshot,
+        None => return Ok(None),
+    };
+
+    ron::ser::to_writer_pretty(
+        File::create(&snapshot.path).with_context(|| format!("Creating {:?}", snapshot.path))?,
+        &snapshot.scene,
+        Default::default(),
+    )?;
+
+    Ok(Some(snapshot.path.clone()))
+}
+
+/// Saves the scene to its `.crash` file on panic, then runs the previous hook
+pub fn install_panic_hook() {
+    let previous_hook = std::panic::take_hook();
+
+    std::panic::set_hook(Box::new(move |info| {
+        let location = info
+            .location()
+            .map_or_else(|| "unknown location".to_string(), ToString::to_string);
+        log::error!("Panic at {}", location);
+
+        match write_snapshot() {
+            Ok(Some(path)) => log::error!("Saved the current scene to {:?}", path),
+            Ok(None) => {}
+            Err(err) => log::error!("Failed to save the scene after a panic: {:#}", err),
+        }
+
+        previous_hook(info);
+    }));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scene::SceneInstanceDesc;
+
+    #[test]
+    fn test_crash_file_path() {
+        assert_eq!(
+            crash_file_path(Some(&PathBuf::from("scenes/room.dmoon"))),
+            PathBuf::from("scenes/room.dmoon.crash")
+        );
+        assert_eq!(crash_file_path(None), PathBuf::from("untitled.dmoon.crash"));
+    }
+
+    #[test]
+    fn test_panic_hook_writes_snapshot() {
+        let path = std::env::temp_dir().join(format!("crash-hook-test-{}.dmoon.crash", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let scene = SceneDesc {
+            instances: vec![SceneInstanceDesc {
+                id: Some(7),
+                position: [1.0, 2.0, 3.0],
+                scale: [1.0, 1.0, 1.0],
+                rotation: [0.0, 0.0, 0.0],
+                pivot: [0.0, 0.0, 0.0],
+                mesh: "/meshes/rock/scene.gltf".to_string(),
+                tags: vec!["prop".to_string()],
+                casts_shadow: true,
+                visible: true,
+                occluder: false,
+                cached: false,
+                is_compound: false,
+                bounding_box: None,
+                name: None,
+                mesh_nodes: Vec::new(),
+                split_nodes: false,
+            }],
+            pvs: None,
+            layers: Vec::new(),
+            seed: 0,
+            auto_bounds: true,
+        };
+        update_snapshot(scene.clone(), path.clone());
+
+        install_panic_hook();
+        let result = std::panic::catch_unwind(|| panic!("simulated crash"));
+        let _ = std::panic::take_hook();
+        assert!(result.is_err());
+
+        let written: SceneDesc = ron::de::from_reader(File::open(&path).unwrap()).unwrap();
+        assert_eq!(written, scene);
+
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -1,6 +1,20 @@
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap},
+    fs::{File, OpenOptions},
+    hash::{Hash, Hasher},
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+use kajiya_simple::{RenderMode, Vec3};
 use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+use crate::{
+    math::Aabb,
+    persisted::{SceneElement, SceneElementTransform},
+};
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum CullingMethod {
     /// Make objects invisible by setting emissive multiplier to 0
     EmissiveMultiplier,
@@ -16,7 +30,73 @@ impl Default for CullingMethod {
     }
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+impl CullingMethod {
+    /// Transform to hand to the renderer for an element with the given authoring transform.
+    ///
+    /// The result is derived from `authoring` every frame and never written back,
+    /// so edits made while an object is culled are picked up as soon as it's visible again.
+    pub fn render_transform(
+        &self,
+        authoring: &SceneElementTransform,
+        is_visible: bool,
+    ) -> SceneElementTransform {
+        let mut transform = authoring.clone();
+
+        if !is_visible {
+            match self {
+                Self::EmissiveMultiplier => {}
+                Self::MoveAway => transform.position = Vec3::splat(1000000.0),
+                Self::ScaleToZero => transform.scale = Vec3::ZERO,
+            }
+        }
+
+        transform
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CullingLogFormat {
+    /// Human-readable summary lines through the `log` crate
+    Text,
+    /// One CSV row per logged frame, appended to `csv_log_path`
+    Csv,
+}
+
+impl Default for CullingLogFormat {
+    fn default() -> Self {
+        Self::Text
+    }
+}
+
+fn default_csv_log_path() -> String {
+    "culling_stats.csv".to_string()
+}
+
+fn default_auto_sphere_max_screen_size() -> f32 {
+    0.1
+}
+
+pub(crate) fn default_true() -> bool {
+    true
+}
+
+fn default_max_sphere_scale_ratio() -> f32 {
+    4.0
+}
+
+fn default_update_interval_frames() -> u32 {
+    1
+}
+
+fn default_update_move_threshold() -> f32 {
+    0.1
+}
+
+fn default_update_turn_threshold_degrees() -> f32 {
+    1.0
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct FrustumCullingConfig {
     pub enabled: bool,
     pub debug_logging: bool,
@@ -24,6 +104,45 @@ pub struct FrustumCullingConfig {
     pub default_object_size: f32,
     pub use_sphere_culling: bool, // Alternative to AABB culling
     pub culling_method: CullingMethod, // How to hide culled objects
+    #[serde(default)]
+    pub log_format: CullingLogFormat,
+    #[serde(default = "default_csv_log_path")]
+    pub csv_log_path: String,
+    /// Pick sphere or AABB tests per object by screen size, instead of `use_sphere_culling`
+    #[serde(default)]
+    pub auto_sphere_culling: bool,
+    /// In auto mode, objects covering less than this fraction of the view height get sphere tests
+    #[serde(default = "default_auto_sphere_max_screen_size")]
+    pub auto_sphere_max_screen_size: f32,
+    /// Skip frustum, occlusion and triangle culling while the reference path tracer is active
+    #[serde(default = "default_true")]
+    pub disable_in_reference_mode: bool,
+    /// Elements whose largest scale axis exceeds the smallest by more than this always get AABB
+    /// tests; scaling the sphere by the largest axis leaves it far too loose for them
+    #[serde(default = "default_max_sphere_scale_ratio")]
+    pub max_sphere_scale_ratio: f32,
+    /// World-space distance bounds are grown by before frustum tests, so objects show up just
+    /// before entering the view. Negative values cull more aggressively.
+    #[serde(default)]
+    pub frustum_margin: f32,
+    /// With `EmissiveMultiplier`, emissive ramps down to zero over this many meters before the
+    /// far plane instead of snapping off. 0 keeps the hard cutoff.
+    #[serde(default)]
+    pub emissive_fade_band: f32,
+    /// Keep culled objects rendered and outline them in the viewport instead, to judge
+    /// whether frustum and occlusion culling are too aggressive
+    #[serde(default)]
+    pub preview: bool,
+    /// Rerun frustum and occlusion tests every this many frames, reusing the last results in
+    /// between. 1 tests every frame; 0 only when the camera moves past the thresholds below.
+    #[serde(default = "default_update_interval_frames")]
+    pub update_interval_frames: u32,
+    /// Between updates, moving the camera farther than this many meters forces one
+    #[serde(default = "default_update_move_threshold")]
+    pub update_move_threshold: f32,
+    /// Between updates, turning the camera by more than this forces one
+    #[serde(default = "default_update_turn_threshold_degrees")]
+    pub update_turn_threshold_degrees: f32,
 }
 
 impl Default for FrustumCullingConfig {
@@ -35,6 +154,626 @@ impl Default for FrustumCullingConfig {
             default_object_size: 2.0,
             use_sphere_culling: false,
             culling_method: CullingMethod::default(),
+            log_format: CullingLogFormat::default(),
+            csv_log_path: default_csv_log_path(),
+            auto_sphere_culling: false,
+            auto_sphere_max_screen_size: default_auto_sphere_max_screen_size(),
+            disable_in_reference_mode: true,
+            max_sphere_scale_ratio: default_max_sphere_scale_ratio(),
+            frustum_margin: 0.0,
+            emissive_fade_band: 0.0,
+            preview: false,
+            update_interval_frames: default_update_interval_frames(),
+            update_move_threshold: default_update_move_threshold(),
+            update_turn_threshold_degrees: default_update_turn_threshold_degrees(),
+        }
+    }
+}
+
+impl FrustumCullingConfig {
+    /// Whether to test a bounding sphere rather than the AABB for an object.
+    ///
+    /// In auto mode small or distant objects get the cheap sphere test, while large or
+    /// near ones, where the sphere's looseness would keep many of them alive, get the AABB test.
+    pub fn use_sphere_test(&self, radius: f32, distance: f32, vertical_fov_degrees: f32) -> bool {
+        if !self.auto_sphere_culling {
+            return self.use_sphere_culling;
+        }
+
+        projected_sphere_size(radius, distance, vertical_fov_degrees)
+            .map_or(false, |size| size < self.auto_sphere_max_screen_size)
+    }
+
+    /// Emissive scale for an object whose nearest point is `depth` along the view direction,
+    /// when objects are culled at `cull_depth`. See `emissive_fade_band`.
+    pub fn emissive_fade(&self, depth: f32, cull_depth: f32) -> f32 {
+        if self.emissive_fade_band <= 0.0 || !matches!(self.culling_method, CullingMethod::EmissiveMultiplier) {
+            return 1.0;
+        }
+
+        ((cull_depth - depth) / self.emissive_fade_band).clamp(0.0, 1.0)
+    }
+
+    /// Whether `scale` is too stretched for sphere tests, see `max_sphere_scale_ratio`
+    pub fn is_non_uniform_scale(&self, scale: Vec3) -> bool {
+        scale_ratio(scale) > self.max_sphere_scale_ratio
+    }
+}
+
+/// Largest over smallest scale axis magnitude. Flattened axes make it infinite; an all-zero scale counts as uniform.
+pub fn scale_ratio(scale: Vec3) -> f32 {
+    let scale = scale.abs();
+    if scale.max_element() == 0.0 {
+        1.0
+    } else if scale.min_element() == 0.0 {
+        f32::INFINITY
+    } else {
+        scale.max_element() / scale.min_element()
+    }
+}
+
+/// Which culling passes run this frame
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CullingPasses {
+    pub frustum: bool,
+    pub occlusion: bool,
+    pub triangle: bool,
+}
+
+impl CullingPasses {
+    pub fn for_frame(
+        frustum_config: &FrustumCullingConfig,
+        occlusion_enabled: bool,
+        triangle_enabled: bool,
+        render_mode: RenderMode,
+    ) -> Self {
+        // The path tracer should see the scene exactly as authored
+        if render_mode == RenderMode::Reference && frustum_config.disable_in_reference_mode {
+            return Self {
+                frustum: false,
+                occlusion: false,
+                triangle: false,
+            };
+        }
+
+        Self {
+            frustum: frustum_config.enabled,
+            occlusion: occlusion_enabled,
+            triangle: triangle_enabled,
+        }
+    }
+
+    pub fn any_visibility(&self) -> bool {
+        self.frustum || self.occlusion
+    }
+}
+
+/// Hash of what an element's culling result depends on: its transform and bounds, and those
+/// of its mesh nodes. The result is stale once any of it changes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CullingKey(u64);
+
+impl CullingKey {
+    pub fn of(elem: &SceneElement) -> Self {
+        let mut hasher = DefaultHasher::new();
+        hash_culling_inputs(&elem.transform, elem.bounding_box, &mut hasher);
+        elem.mesh_nodes.len().hash(&mut hasher);
+        for node in &elem.mesh_nodes {
+            hash_culling_inputs(&node.local_transform, node.bounding_box, &mut hasher);
+        }
+        Self(hasher.finish())
+    }
+}
+
+fn hash_culling_inputs(transform: &SceneElementTransform, bounding_box: Option<Aabb>, hasher: &mut impl Hasher) {
+    let bounds = bounding_box.map_or([Vec3::NAN; 2], |aabb| [aabb.min, aabb.max]);
+    let vectors = [
+        transform.position,
+        transform.rotation_euler_degrees,
+        transform.scale,
+        transform.pivot,
+    ];
+    for value in vectors.iter().chain(&bounds).flat_map(|v| v.to_array()) {
+        value.to_bits().hash(hasher);
+    }
+}
+
+/// The camera a culling update ran from
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CullingView {
+    pub position: Vec3,
+    pub forward: Vec3,
+    pub vertical_fov: f32,
+    pub aspect_ratio: f32,
+    pub z_far: f32,
+}
+
+/// One element's culling result, with what it added to the frame's counters
+#[derive(Clone, Debug, PartialEq)]
+pub struct CachedVisibility {
+    /// The element as it was tested
+    pub key: CullingKey,
+    pub visible: bool,
+    pub sub_objects: usize,
+    pub visible_sub_objects: usize,
+    pub frustum_culled: usize,
+    pub occlusion_culled: usize,
+}
+
+/// Culling results kept between updates, see `FrustumCullingConfig::update_interval_frames`.
+/// Unused when every frame updates, so culling doesn't pay for it then.
+#[derive(Default)]
+pub struct CullingCache {
+    results: HashMap<u64, CachedVisibility>,
+    /// Camera at the last update, and what it ran with
+    last_update: Option<(CullingView, FrustumCullingConfig, CullingPasses)>,
+    frames_since_update: u32,
+}
+
+impl CullingCache {
+    /// Whether results can outlive the frame they were computed in with `config`
+    pub fn reuses_results(config: &FrustumCullingConfig) -> bool {
+        config.update_interval_frames != 1
+    }
+
+    /// Whether every element gets tested this frame. Otherwise every element keeps its last
+    /// result, along with the occluders of the last update.
+    ///
+    /// `scene_changed` forces an update: a moved element would otherwise be tested against
+    /// occluders that no longer match the scene, and could leave stale results behind it.
+    pub fn begin_frame(
+        &mut self,
+        config: &FrustumCullingConfig,
+        passes: CullingPasses,
+        view: CullingView,
+        scene_changed: bool,
+    ) -> bool {
+        self.frames_since_update += 1;
+
+        let reuse = match &self.last_update {
+            Some((last_view, last_config, last_passes)) => {
+                let interval_elapsed = config.update_interval_frames > 0
+                    && self.frames_since_update >= config.update_interval_frames;
+                let turned = last_view.forward.dot(view.forward).clamp(-1.0, 1.0).acos().to_degrees();
+
+                !interval_elapsed
+                    && !scene_changed
+                    && last_config == config
+                    && *last_passes == passes
+                    && last_view.vertical_fov == view.vertical_fov
+                    && last_view.aspect_ratio == view.aspect_ratio
+                    && last_view.z_far == view.z_far
+                    && last_view.position.distance(view.position) <= config.update_move_threshold
+                    && turned <= config.update_turn_threshold_degrees
+            }
+            None => false,
+        };
+        if reuse {
+            return false;
         }
+
+        self.results.clear();
+        self.last_update = Some((view, config.clone(), passes));
+        self.frames_since_update = 0;
+        true
+    }
+
+    /// The last result for `elem`, if nothing it depends on has changed since
+    pub fn get(&self, elem: &SceneElement) -> Option<&CachedVisibility> {
+        self.results
+            .get(&elem.id)
+            .filter(|cached| cached.key == CullingKey::of(elem))
+    }
+
+    /// Whether the shown elements differ from those the last update tested: added, removed,
+    /// hidden, shown, moved or reshaped
+    pub fn scene_changed<'a>(&self, shown: impl Iterator<Item = &'a SceneElement>) -> bool {
+        let mut count = 0;
+        for elem in shown {
+            if self.get(elem).is_none() {
+                return true;
+            }
+            count += 1;
+        }
+        count != self.results.len()
+    }
+
+    pub fn insert(&mut self, id: u64, result: CachedVisibility) {
+        self.results.insert(id, result);
+    }
+
+    /// Forget everything, so the next frame tests every element
+    pub fn clear(&mut self) {
+        self.results.clear();
+        self.last_update = None;
+    }
+}
+
+/// Approximate fraction of the view height covered by a sphere; `None` if the camera is inside it
+pub fn projected_sphere_size(radius: f32, distance: f32, vertical_fov_degrees: f32) -> Option<f32> {
+    if distance <= radius {
+        return None;
+    }
+
+    let half_fov_tan = (vertical_fov_degrees.to_radians() * 0.5).tan();
+    Some(radius / (distance * half_fov_tan))
+}
+
+/// Per-frame culling counters, as gathered by `RuntimeState::update_objects`
+#[derive(Clone, Debug, Default)]
+pub struct CullingFrameStats {
+    pub visible: usize,
+    pub total: usize,
+    pub elements: usize,
+    pub frustum_culled: usize,
+    pub occlusion_culled: usize,
+    pub occluders: usize,
+    pub triangles_tested: u32,
+    pub triangles_rendered: u32,
+    pub triangles_culled: u32,
+}
+
+const CSV_HEADER: &str =
+    "frame,visible,total,elements,frustum_culled,occlusion_culled,occluders,triangles_tested,triangles_rendered,triangles_culled";
+
+/// Writes culling statistics every `log_interval_frames`, either as text or as CSV rows
+#[derive(Default)]
+pub struct CullingStatsLogger {
+    frame: u64,
+    csv: Option<(PathBuf, BufWriter<File>)>,
+    // Path that failed to open; kept so we don't retry (and spam errors) every interval
+    failed_csv_path: Option<PathBuf>,
+}
+
+impl CullingStatsLogger {
+    /// Advance the logged frame counter, and emit `stats` if this is a logging frame
+    pub fn end_frame(&mut self, config: &FrustumCullingConfig, stats: &CullingFrameStats) {
+        self.frame += 1;
+
+        if config.log_interval_frames == 0 {
+            return;
+        }
+
+        if self.frame % config.log_interval_frames as u64 != 0 {
+            return;
+        }
+
+        match config.log_format {
+            CullingLogFormat::Text => {
+                log::info!(
+                    "Culling Stats: {}/{} sub-objects visible from {} elements (Frustum: {} culled, Occlusion: {} culled)",
+                    stats.visible,
+                    stats.total,
+                    stats.elements,
+                    stats.frustum_culled,
+                    stats.occlusion_culled
+                );
+
+                if stats.occluders > 0 {
+                    log::info!("  Occlusion Stats: {} occluders", stats.occluders);
+                }
+            }
+            CullingLogFormat::Csv => self.write_csv_row(Path::new(&config.csv_log_path), stats),
+        }
+    }
+
+    fn write_csv_row(&mut self, path: &Path, stats: &CullingFrameStats) {
+        if self.csv.as_ref().map_or(true, |(open_path, _)| open_path != path) {
+            self.csv = None;
+
+            if self.failed_csv_path.as_deref() == Some(path) {
+                return;
+            }
+
+            match Self::open_csv(path) {
+                Ok(writer) => {
+                    log::info!("Writing culling statistics to {:?}", path);
+                    self.csv = Some((path.to_path_buf(), writer));
+                    self.failed_csv_path = None;
+                }
+                Err(err) => {
+                    log::error!("Failed to open culling CSV log {:?}: {}", path, err);
+                    self.failed_csv_path = Some(path.to_path_buf());
+                    return;
+                }
+            }
+        }
+
+        if let Some((_, writer)) = self.csv.as_mut() {
+            let res = writeln!(
+                writer,
+                "{},{},{},{},{},{},{},{},{},{}",
+                self.frame,
+                stats.visible,
+                stats.total,
+                stats.elements,
+                stats.frustum_culled,
+                stats.occlusion_culled,
+                stats.occluders,
+                stats.triangles_tested,
+                stats.triangles_rendered,
+                stats.triangles_culled
+            )
+            .and_then(|_| writer.flush());
+
+            if let Err(err) = res {
+                log::error!("Failed to write culling CSV log {:?}: {}", path, err);
+                self.csv = None;
+                self.failed_csv_path = Some(path.to_path_buf());
+            }
+        }
+    }
+
+    fn open_csv(path: &Path) -> std::io::Result<BufWriter<File>> {
+        let is_new = std::fs::metadata(path).map_or(true, |meta| meta.len() == 0);
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        let mut writer = BufWriter::new(file);
+
+        if is_new {
+            writeln!(writer, "{}", CSV_HEADER)?;
+        }
+
+        Ok(writer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_culling_does_not_modify_authoring_transform() {
+        let authoring = SceneElementTransform {
+            position: Vec3::new(1.0, 2.0, 3.0),
+            rotation_euler_degrees: Vec3::new(0.0, 45.0, 0.0),
+            scale: Vec3::splat(2.0),
+            pivot: Vec3::ZERO,
+        };
+
+        let culled = CullingMethod::ScaleToZero.render_transform(&authoring, false);
+        assert_eq!(culled.scale, Vec3::ZERO);
+        assert_eq!(culled.position, authoring.position);
+        assert_eq!(authoring.scale, Vec3::splat(2.0));
+
+        let culled = CullingMethod::MoveAway.render_transform(&authoring, false);
+        assert_eq!(culled.scale, authoring.scale);
+        assert_ne!(culled.position, authoring.position);
+    }
+
+    #[test]
+    fn test_auto_sphere_culling_threshold() {
+        let config = FrustumCullingConfig {
+            auto_sphere_culling: true,
+            auto_sphere_max_screen_size: 0.1,
+            ..Default::default()
+        };
+        let fov = 90.0; // tan(45°) == 1, so the screen size is just radius / distance
+
+        // Small and far away: cheap sphere test
+        assert!(config.use_sphere_test(1.0, 100.0, fov));
+        // Right around the threshold
+        assert!(config.use_sphere_test(1.0, 10.5, fov));
+        assert!(!config.use_sphere_test(1.0, 9.5, fov));
+        // Large and near, or containing the camera: accurate AABB test
+        assert!(!config.use_sphere_test(50.0, 100.0, fov));
+        assert!(!config.use_sphere_test(5.0, 2.0, fov));
+
+        // Without auto mode the global toggle wins
+        let config = FrustumCullingConfig {
+            use_sphere_culling: true,
+            ..Default::default()
+        };
+        assert!(config.use_sphere_test(50.0, 1.0, fov));
+    }
+
+    #[test]
+    fn test_emissive_fades_over_band_before_cull() {
+        let mut config = FrustumCullingConfig {
+            culling_method: CullingMethod::EmissiveMultiplier,
+            emissive_fade_band: 10.0,
+            ..Default::default()
+        };
+
+        assert_eq!(config.emissive_fade(50.0, 100.0), 1.0);
+        assert_eq!(config.emissive_fade(90.0, 100.0), 1.0);
+        assert_eq!(config.emissive_fade(95.0, 100.0), 0.5);
+        assert_eq!(config.emissive_fade(100.0, 100.0), 0.0);
+        assert_eq!(config.emissive_fade(120.0, 100.0), 0.0);
+
+        // Other methods hide objects by moving them, so there's nothing to fade
+        config.culling_method = CullingMethod::MoveAway;
+        assert_eq!(config.emissive_fade(95.0, 100.0), 1.0);
+
+        config.culling_method = CullingMethod::EmissiveMultiplier;
+        config.emissive_fade_band = 0.0;
+        assert_eq!(config.emissive_fade(95.0, 100.0), 1.0);
+    }
+
+    #[test]
+    fn test_non_uniform_scale_threshold() {
+        let config = FrustumCullingConfig {
+            max_sphere_scale_ratio: 4.0,
+            ..Default::default()
+        };
+
+        assert!(!config.is_non_uniform_scale(Vec3::splat(3.0)));
+        assert!(!config.is_non_uniform_scale(Vec3::new(1.0, 4.0, 2.0)));
+        assert!(config.is_non_uniform_scale(Vec3::new(1.0, 4.1, 2.0)));
+        // Mirroring doesn't stretch anything
+        assert!(!config.is_non_uniform_scale(Vec3::new(-2.0, 2.0, 2.0)));
+        // A flattened axis is as stretched as it gets, but a zeroed element isn't stretched at all
+        assert!(config.is_non_uniform_scale(Vec3::new(1.0, 0.0, 1.0)));
+        assert!(!config.is_non_uniform_scale(Vec3::ZERO));
+    }
+
+    #[test]
+    fn test_reference_mode_never_modifies_transforms() {
+        let config = FrustumCullingConfig {
+            culling_method: CullingMethod::ScaleToZero,
+            ..Default::default()
+        };
+
+        let passes = CullingPasses::for_frame(&config, true, true, RenderMode::Reference);
+        assert!(!passes.any_visibility());
+        assert!(!passes.triangle);
+
+        // With no visibility passes every element stays visible, so the authoring transform goes through untouched
+        let authoring = SceneElementTransform {
+            position: Vec3::new(1.0, 2.0, 3.0),
+            rotation_euler_degrees: Vec3::new(10.0, 20.0, 30.0),
+            scale: Vec3::splat(0.5),
+            pivot: Vec3::ZERO,
+        };
+        let is_visible = !passes.any_visibility();
+        assert_eq!(config.culling_method.render_transform(&authoring, is_visible), authoring);
+
+        // Switching back restores culling
+        let passes = CullingPasses::for_frame(&config, true, true, RenderMode::Standard);
+        assert_eq!(
+            passes,
+            CullingPasses {
+                frustum: true,
+                occlusion: true,
+                triangle: true,
+            }
+        );
+
+        // Opting out keeps culling on in reference mode
+        let config = FrustumCullingConfig {
+            disable_in_reference_mode: false,
+            ..config
+        };
+        assert!(CullingPasses::for_frame(&config, false, false, RenderMode::Reference).frustum);
+    }
+
+    #[test]
+    fn test_editing_culled_object_restores_correctly() {
+        let mut authoring = SceneElementTransform::IDENTITY;
+        let method = CullingMethod::ScaleToZero;
+
+        // Culled, then edited through the GUI while still culled
+        let _ = method.render_transform(&authoring, false);
+        authoring.scale.x = 3.0;
+        authoring.position.y = 5.0;
+        assert_eq!(method.render_transform(&authoring, false).scale, Vec3::ZERO);
+
+        // Back in view: the edit shows up, and the scale was never zeroed out
+        let visible = method.render_transform(&authoring, true);
+        assert_eq!(visible, authoring);
+        assert_eq!(visible.scale, Vec3::new(3.0, 1.0, 1.0));
+        assert_eq!(visible.position, Vec3::new(0.0, 5.0, 0.0));
+    }
+
+    #[test]
+    fn test_culling_results_reused_while_camera_is_still() {
+        use crate::persisted::{MeshNode, MeshSource};
+
+        let config = FrustumCullingConfig {
+            update_interval_frames: 4,
+            ..Default::default()
+        };
+        let passes = CullingPasses::for_frame(&config, true, false, RenderMode::Standard);
+        let view = CullingView {
+            position: Vec3::new(0.0, 1.0, 5.0),
+            forward: -Vec3::Z,
+            vertical_fov: 52.0,
+            aspect_ratio: 16.0 / 9.0,
+            z_far: 1000.0,
+        };
+        let elem = SceneElement {
+            bounding_box: Some(Aabb::from_center_size(Vec3::ZERO, Vec3::ONE)),
+            ..SceneElement::new(7, MeshSource::File("test.gltf".into()), SceneElementTransform::IDENTITY)
+        };
+        let result = CachedVisibility {
+            key: CullingKey::of(&elem),
+            visible: false,
+            sub_objects: 1,
+            visible_sub_objects: 0,
+            frustum_culled: 1,
+            occlusion_culled: 0,
+        };
+
+        let mut cache = CullingCache::default();
+        assert!(cache.begin_frame(&config, passes, view, false));
+        cache.insert(7, result.clone());
+
+        // Stationary: the result stands until the interval is up
+        for _ in 0..3 {
+            assert!(!cache.scene_changed([&elem].into_iter()));
+            assert!(!cache.begin_frame(&config, passes, view, false));
+            assert_eq!(cache.get(&elem), Some(&result));
+        }
+        assert!(cache.begin_frame(&config, passes, view, false));
+        assert_eq!(cache.get(&elem), None);
+        cache.insert(7, result.clone());
+
+        // Moving, resizing or re-analyzing an element invalidates its result and changes the scene
+        let moved = SceneElement {
+            transform: SceneElementTransform {
+                position: Vec3::X,
+                ..SceneElementTransform::IDENTITY
+            },
+            ..elem.clone()
+        };
+        let resized = SceneElement {
+            bounding_box: Some(Aabb::from_center_size(Vec3::ZERO, Vec3::splat(2.0))),
+            ..elem.clone()
+        };
+        let analyzed = SceneElement {
+            mesh_nodes: vec![MeshNode::default()],
+            ..elem.clone()
+        };
+        for changed in [&moved, &resized, &analyzed] {
+            assert_eq!(cache.get(changed), None);
+            assert!(cache.scene_changed([changed].into_iter()));
+        }
+        // So do hiding or adding elements
+        assert!(cache.scene_changed(std::iter::empty()));
+        let added = SceneElement { id: 8, ..elem.clone() };
+        assert!(cache.scene_changed([&elem, &added].into_iter()));
+        assert!(cache.begin_frame(&config, passes, view, true));
+        cache.insert(7, result.clone());
+
+        // Moving or turning the camera past the thresholds forces an update
+        let shifted = CullingView {
+            position: view.position + Vec3::X,
+            ..view
+        };
+        assert!(cache.begin_frame(&config, passes, shifted, false));
+        assert!(!cache.begin_frame(&config, passes, shifted, false));
+        let turned = CullingView {
+            forward: -Vec3::X,
+            ..shifted
+        };
+        assert!(cache.begin_frame(&config, passes, turned, false));
+
+        // As does changing the lens
+        assert!(cache.begin_frame(&config, passes, CullingView { vertical_fov: 70.0, ..turned }, false));
+        assert!(cache.begin_frame(&config, passes, CullingView { aspect_ratio: 1.0, ..turned }, false));
+        assert!(cache.begin_frame(&config, passes, CullingView { z_far: 50.0, ..turned }, false));
+        assert!(cache.begin_frame(&config, passes, turned, false));
+
+        // Or the settings
+        let config = FrustumCullingConfig {
+            frustum_margin: 1.0,
+            ..config
+        };
+        assert!(cache.begin_frame(&config, passes, turned, false));
+
+        // Only camera movement triggers updates with no interval
+        let config = FrustumCullingConfig {
+            update_interval_frames: 0,
+            ..config
+        };
+        assert!(cache.begin_frame(&config, passes, view, false));
+        for _ in 0..100 {
+            assert!(!cache.begin_frame(&config, passes, view, false));
+        }
+
+        // The default tests every frame, so it doesn't keep results at all
+        let config = FrustumCullingConfig::default();
+        assert!(!CullingCache::reuses_results(&config));
+        assert!(cache.begin_frame(&config, passes, view, false));
+        assert!(cache.begin_frame(&config, passes, view, false));
     }
 }
@@ -0,0 +1,157 @@
+use std::{
+    fs::File,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Context;
+use kajiya::backend::mounted_path_from_vfs;
+
+use crate::{
+    primitives::{primitives_dir, Primitive},
+    scene::{SceneDesc, SceneInstanceDesc},
+};
+
+/// Directories the engine reads from or writes to on its own, as VFS or plain paths
+const REQUIRED_DIRS: &[&str] = &["/cache", "/meshes", SCENES_DIR];
+
+const SCENES_DIR: &str = "assets/scenes";
+const SAMPLE_SCENE_FILE: &str = "sample.dmoon";
+
+/// Create whatever is missing of the directory layout, so a fresh checkout or install doesn't fail
+/// on its first file access. With `sample_scene`, also writes a small scene to start from when
+/// there are no scenes yet. Existing files and directories are never touched.
+///
+/// Returns what was created.
+pub fn prepare_working_directory(sample_scene: bool) -> anyhow::Result<Vec<PathBuf>> {
+    let dirs = REQUIRED_DIRS
+        .iter()
+        .map(|dir| mounted_path_from_vfs(*dir))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
+    let mut created = create_missing_dirs(&dirs)?;
+
+    let scenes_dir = Path::new(SCENES_DIR);
+    if sample_scene && !contains_scenes(scenes_dir)? {
+        created.extend(write_sample_scene(
+            &scenes_dir.join(SAMPLE_SCENE_FILE),
+            &primitives_dir()?,
+        )?);
+    }
+
+    for path in &created {
+        log::info!("First run: created {:?}", path);
+    }
+
+    Ok(created)
+}
+
+fn create_missing_dirs(dirs: &[PathBuf]) -> anyhow::Result<Vec<PathBuf>> {
+    let mut created = Vec::new();
+    for dir in dirs {
+        if !dir.exists() {
+            std::fs::create_dir_all(dir).with_context(|| format!("Creating {:?}", dir))?;
+            created.push(dir.clone());
+        }
+    }
+
+    Ok(created)
+}
+
+fn contains_scenes(dir: &Path) -> anyhow::Result<bool> {
+    for entry in std::fs::read_dir(dir).with_context(|| format!("Reading {:?}", dir))? {
+        if entry?.path().extension().map_or(false, |ext| ext == "dmoon") {
+            return Ok(true);
+        }
+    }
+
+    Ok(false)
+}
+
+/// A floor and a cube, built from the generated primitives. Returns the files written.
+fn write_sample_scene(scene_path: &Path, primitives_dir: &Path) -> anyhow::Result<Vec<PathBuf>> {
+    let mut created = Vec::new();
+
+    for primitive in [Primitive::Plane, Primitive::Cube] {
+        let existed = primitives_dir.join(format!("{}.gltf", primitive.file_stem())).exists();
+        let path = primitive.ensure_file(primitives_dir)?;
+        if !existed {
+            created.push(path);
+        }
+    }
+
+    let instance = |primitive: Primitive, position: [f32; 3], scale: [f32; 3]| SceneInstanceDesc {
+        id: Some(crate::scene::new_element_id()),
+        position,
+        scale,
+        rotation: [0.0, 0.0, 0.0],
+        pivot: [0.0, 0.0, 0.0],
+        mesh: primitive.vfs_path(),
+        tags: Vec::new(),
+        casts_shadow: true,
+        visible: true,
+        occluder: false,
+        cached: false,
+        is_compound: false,
+        bounding_box: None,
+        name: None,
+        mesh_nodes: Vec::new(),
+        split_nodes: false,
+    };
+    let scene = SceneDesc {
+        instances: vec![
+            instance(Primitive::Plane, [0.0, 0.0, 0.0], [20.0, 1.0, 20.0]),
+            instance(Primitive::Cube, [0.0, 0.5, 0.0], [1.0, 1.0, 1.0]),
+        ],
+        pvs: None,
+        layers: Vec::new(),
+        seed: 0,
+        auto_bounds: true,
+    };
+
+    // `create_new`, so a scene that appeared in the meantime isn't overwritten
+    match File::options().write(true).create_new(true).open(scene_path) {
+        Ok(file) => {
+            ron::ser::to_writer_pretty(file, &scene, Default::default())
+                .with_context(|| format!("Writing {:?}", scene_path))?;
+            created.push(scene_path.to_owned());
+        }
+        Err(err) if err.kind() == ErrorKind::AlreadyExists => {}
+        Err(err) => return Err(err).with_context(|| format!("Creating {:?}", scene_path)),
+    }
+
+    Ok(created)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_first_run_creates_only_what_is_missing() {
+        let root = std::env::temp_dir().join(format!("darkmoon_first_run_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+
+        let dirs = vec![root.join("cache"), root.join("assets/scenes")];
+        assert_eq!(create_missing_dirs(&dirs).unwrap(), dirs);
+        assert!(create_missing_dirs(&dirs).unwrap().is_empty());
+
+        let scenes_dir = root.join("assets/scenes");
+        assert!(!contains_scenes(&scenes_dir).unwrap());
+
+        let scene_path = scenes_dir.join(SAMPLE_SCENE_FILE);
+        let created = write_sample_scene(&scene_path, &root.join("primitives")).unwrap();
+        assert_eq!(created.len(), 3);
+        assert!(contains_scenes(&scenes_dir).unwrap());
+
+        let scene: SceneDesc = ron::de::from_reader(File::open(&scene_path).unwrap()).unwrap();
+        assert_eq!(scene.instances.len(), 2);
+
+        // A second run leaves the existing scene alone
+        std::fs::write(&scene_path, "edited").unwrap();
+        assert!(write_sample_scene(&scene_path, &root.join("primitives")).unwrap().is_empty());
+        assert_eq!(std::fs::read_to_string(&scene_path).unwrap(), "edited");
+
+        let _ = std::fs::remove_dir_all(root);
+    }
+}
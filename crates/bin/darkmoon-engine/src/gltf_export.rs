@@ -0,0 +1,118 @@
+use std::path::Path;
+
+use anyhow::Context;
+use kajiya::asset::mesh::PackedTriMesh;
+use kajiya_simple::{Mat4, Vec3};
+
+use crate::{persisted::SceneElementTransform, primitives::ProceduralMesh};
+
+/// Where elements exported from the editor are written, relative to the working directory
+pub const EXPORT_DIR: &str = "exports";
+
+/// Write the baked mesh at `baked_path` to a glTF file at `path`, placed with `transform`,
+/// so it lands where it was in the scene when opened in another tool.
+pub fn export_baked_mesh(
+    baked_path: &Path,
+    name: &str,
+    transform: &SceneElementTransform,
+    path: &Path,
+) -> anyhow::Result<()> {
+    let mesh = kajiya::mmap::mmapped_asset::<PackedTriMesh::Flat, _>(baked_path)
+        .with_context(|| format!("Reading baked mesh {:?}", baked_path))?;
+
+    mesh_from_packed(mesh).write_gltf_with_transform(name, path, Mat4::from(transform.affine_transform()))
+}
+
+fn mesh_from_packed(mesh: &PackedTriMesh::Flat) -> ProceduralMesh {
+    let positions: Vec<Vec3> = mesh.verts.iter().map(|vert| Vec3::from(vert.pos)).collect();
+    let indices = mesh.indices.as_slice().to_vec();
+
+    let uvs = mesh.uvs.as_slice();
+    let uvs = if uvs.len() == positions.len() {
+        uvs.to_vec()
+    } else {
+        vec![[0.0, 0.0]; positions.len()]
+    };
+
+    // Baked normals are packed in a renderer-specific format, so they're rebuilt from the faces
+    let normals = vertex_normals(&positions, &indices);
+
+    ProceduralMesh {
+        positions,
+        normals,
+        uvs,
+        indices,
+    }
+}
+
+/// Area-weighted average of the normals of the triangles sharing each vertex
+fn vertex_normals(positions: &[Vec3], indices: &[u32]) -> Vec<Vec3> {
+    let mut normals = vec![Vec3::ZERO; positions.len()];
+    for tri in indices.chunks_exact(3) {
+        let [a, b, c] = [tri[0], tri[1], tri[2]].map(|i| i as usize);
+        let face_normal = (positions[b] - positions[a]).cross(positions[c] - positions[a]);
+        for i in [a, b, c] {
+            normals[i] += face_normal;
+        }
+    }
+
+    // glTF wants unit normals even on vertices no triangle uses
+    normals
+        .into_iter()
+        .map(|normal| normal.try_normalize().unwrap_or(Vec3::Y))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::primitives::Primitive;
+    use kajiya::asset::mesh::{pack_triangle_mesh, TriangleMesh};
+
+    #[test]
+    fn test_exported_primitive_keeps_transform() {
+        let dir = std::env::temp_dir().join(format!("darkmoon_gltf_export_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let cube = Primitive::Cube.generate();
+        let baked = TriangleMesh {
+            positions: cube.positions.iter().map(|p| p.to_array()).collect(),
+            normals: cube.normals.iter().map(|n| n.to_array()).collect(),
+            uvs: cube.uvs.clone(),
+            material_ids: vec![0; cube.indices.len()],
+            indices: cube.indices.clone(),
+            ..Default::default()
+        };
+        pack_triangle_mesh(&baked).flatten_into(&mut std::fs::File::create(dir.join("cube.mesh")).unwrap());
+        kajiya::backend::set_vfs_mount_point("/test-gltf-export", &dir);
+
+        let transform = SceneElementTransform {
+            position: Vec3::new(1.0, 2.0, -3.0),
+            rotation_euler_degrees: Vec3::new(0.0, 45.0, 10.0),
+            scale: Vec3::new(2.0, 1.0, 0.5),
+            pivot: Vec3::new(0.5, 0.0, 0.0),
+        };
+        let path = dir.join("cube.gltf");
+        export_baked_mesh(Path::new("/test-gltf-export/cube.mesh"), "Cube", &transform, &path).unwrap();
+
+        let (document, buffers, _) = gltf::import(&path).unwrap();
+        let node = document.nodes().next().unwrap();
+        assert_eq!(node.name(), Some("Cube"));
+
+        let matrix = Mat4::from_cols_array_2d(&node.transform().matrix());
+        assert!(matrix.abs_diff_eq(Mat4::from(transform.affine_transform()), 1e-5));
+
+        let prim = node.mesh().unwrap().primitives().next().unwrap();
+        let reader = prim.reader(|buffer| Some(&buffers[buffer.index()]));
+        let positions: Vec<Vec3> = reader.read_positions().unwrap().map(Vec3::from).collect();
+        assert_eq!(positions, cube.positions);
+        assert_eq!(reader.read_indices().unwrap().into_u32().collect::<Vec<_>>(), cube.indices);
+
+        // The cube's faces are flat, so rebuilt normals match the generated ones
+        for (normal, expected) in reader.read_normals().unwrap().zip(&cube.normals) {
+            assert!(Vec3::from(normal).abs_diff_eq(*expected, 1e-5));
+        }
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
@@ -6,11 +6,174 @@ use darkmoon_icons::*;
 use imgui::*;
 
 use crate::{
-    runtime::{RuntimeState, MAX_FPS_LIMIT},
+    history::EditAction,
+    persisted::{LengthUnit, TransformSpace},
+    placement::DropPlacement,
+    render_presets::RenderPreset,
+    notifications::ToastKind,
+    runtime::{LeftClickEditMode, RuntimeState, MAX_FPS_LIMIT},
+    scatter::ScatterArea,
+    selection::SelectionTarget,
     PersistedState,
 };
 
+/// Drags a length stored in meters, displaying and editing it in `unit`
+fn drag_length(ui: &Ui, label: &str, meters: &mut f32, unit: LengthUnit, speed: f32, limit: Option<f32>) -> bool {
+    let per_meter = unit.per_meter();
+    let mut value = *meters * per_meter;
+
+    let mut drag = Drag::new(label)
+        .speed(speed * per_meter)
+        .display_format(unit.drag_format());
+    if let Some(limit) = limit {
+        drag = drag.range(-limit * per_meter, limit * per_meter);
+    }
+
+    let changed = drag.build(ui, &mut value);
+    if changed {
+        *meters = value / per_meter;
+    }
+    changed
+}
+
+/// Name an element is listed under in the Outliner: the one given to it, else its first node's,
+/// else its source
+fn element_display_name(elem: &crate::persisted::SceneElement) -> String {
+    match &elem.name {
+        Some(name) => name.clone(),
+        None => element_fallback_name(elem),
+    }
+}
+
+/// What an element without a custom name is listed as
+fn element_fallback_name(elem: &crate::persisted::SceneElement) -> String {
+    match elem.mesh_nodes.get(0).and_then(|n| n.name.as_ref()) {
+        Some(name) => name.clone(),
+        None => format!("{:?}", elem.source),
+    }
+}
+
+fn node_display_name(node: &crate::persisted::MeshNode, nidx: usize) -> String {
+    node.name.clone().unwrap_or_else(|| format!("Node {}", nidx))
+}
+
+/// Tint the bytes `range` of the last item's `label`, to show what an Outliner search matched
+fn highlight_label_match(ui: &Ui, label: &str, range: std::ops::Range<usize>) {
+    if range.is_empty() {
+        return;
+    }
+
+    // Selectables draw their label half the item spacing inside their rect
+    let spacing = ui.clone_style().item_spacing;
+    let min = ui.item_rect_min();
+    let x = min[0] + (spacing[0] * 0.5).floor();
+    let y = min[1] + (spacing[1] * 0.5).floor();
+    let start = x + ui.calc_text_size(&label[..range.start])[0];
+    let end = x + ui.calc_text_size(&label[..range.end])[0];
+
+    ui.get_window_draw_list()
+        .add_rect([start, y], [end, y + ui.text_line_height()], [1.0, 0.8, 0.0, 0.35])
+        .filled(true)
+        .build();
+}
+
+/// Units and transform space selectors shared by the Attributes windows
+fn transform_display_options(ui: &Ui, editing: &mut crate::persisted::EditingState) {
+    ui.text("Units:");
+    for unit in [LengthUnit::Meters, LengthUnit::Centimeters] {
+        ui.same_line();
+        if ui.radio_button_bool(unit.suffix(), editing.display_unit == unit) {
+            editing.display_unit = unit;
+        }
+    }
+
+    ui.same_line();
+    ui.text("  Space:");
+    ui.same_line();
+    if ui.radio_button_bool("Local", editing.transform_space == TransformSpace::Local) {
+        editing.transform_space = TransformSpace::Local;
+    }
+    ui.same_line();
+    if ui.radio_button_bool("World", editing.transform_space == TransformSpace::World) {
+        editing.transform_space = TransformSpace::World;
+    }
+}
+
 impl RuntimeState {
+    /// Index of the scene element selected in the Outliner, if any (the sun doesn't count)
+    pub(crate) fn selected_element_index(&self) -> Option<usize> {
+        self.selected_element.and_then(SelectionTarget::element_index)
+    }
+
+    pub(crate) fn mark_unsaved_changes(&mut self) {
+        self.scene_dirty = true;
+    }
+
+    /// Applies a render preset and saves the app config, since presets also switch culling passes
+    fn apply_render_preset(&mut self, preset: &RenderPreset, world_renderer: &mut kajiya::world_renderer::WorldRenderer) {
+        preset.apply(world_renderer, &mut self.app_config);
+        log::info!("Applied the {} render preset", preset.name);
+        if let Err(err) = self.app_config.save() {
+            log::error!("Failed to save app config: {:#}", err);
+        }
+    }
+
+    /// Keeps the selection on the same element after the one at `removed` is deleted
+    pub(crate) fn on_element_removed(&mut self, removed: usize) {
+        if self.selected_element == Some(SelectionTarget::Element(removed)) {
+            self.selected_node = None;
+        }
+        self.selected_element = self.selected_element.and_then(|target| target.after_removal(removed));
+        crate::selection::selection_after_removal(&mut self.selected_elements, removed);
+        if self.selected_element.is_none() {
+            self.selected_element = self.selected_elements.last().copied().map(SelectionTarget::Element);
+        }
+    }
+
+    /// Select just the element at `idx`
+    pub(crate) fn select_only(&mut self, idx: usize) {
+        self.selected_element = Some(SelectionTarget::Element(idx));
+        self.selected_node = None;
+        self.selected_elements = vec![idx];
+    }
+
+    pub(crate) fn clear_selection(&mut self) {
+        self.selected_element = None;
+        self.selected_node = None;
+        self.selected_elements.clear();
+    }
+
+    /// Drops a selection left pointing past the end of the scene, e.g. after loading a smaller one
+    fn validate_selection(&mut self, persisted: &PersistedState) {
+        if let Some(idx) = self.selected_element_index() {
+            match persisted.scene.elements.get(idx) {
+                Some(elem) => {
+                    if self.selected_node.map_or(false, |nidx| nidx >= elem.mesh_nodes.len()) {
+                        self.selected_node = None;
+                    }
+                }
+                None => {
+                    self.selected_element = None;
+                    self.selected_node = None;
+                }
+            }
+        }
+
+        let element_count = persisted.scene.elements.len();
+        self.selected_elements.retain(|&idx| idx < element_count);
+        if self.selected_element.is_none() {
+            self.selected_elements.clear();
+        }
+    }
+
+    fn window_reset_condition(&self) -> imgui::Condition {
+        if self.reset_window_positions {
+            imgui::Condition::Always
+        } else {
+            imgui::Condition::FirstUseEver
+        }
+    }
+
     fn get_element_icon(elem: &crate::persisted::SceneElement) -> char {
         if elem.is_compound {
             ICON_OBJECT_GROUP
@@ -19,7 +182,7 @@ impl RuntimeState {
                 crate::persisted::MeshSource::File(path) => {
                     if let Some(extension) = path.extension().and_then(|ext| ext.to_str()) {
                         match extension.to_lowercase().as_str() {
-                            "dmoon" => ICON_FILM,           
+                            "dmoon" => ICON_DMOON_SCENE,
                             "gltf" | "glb" => ICON_CUBE,    
                             _ => ICON_CUBE,                
                         }
@@ -42,6 +205,22 @@ impl RuntimeState {
         ICON_SUN 
     }
 
+    /// World to clip space for the current camera, for viewport overlays
+    fn view_projection(&self, persisted: &PersistedState, ctx: &FrameContext) -> Mat4 {
+        let lens = CameraLens {
+            aspect_ratio: ctx.aspect_ratio(),
+            vertical_fov: persisted.camera.vertical_fov,
+            ..Default::default()
+        };
+        let camera_matrices = self
+            .camera
+            .final_transform
+            .into_position_rotation()
+            .through(&lens);
+
+        camera_matrices.view_to_clip * camera_matrices.world_to_view
+    }
+
     pub fn do_gui(&mut self, persisted: &mut PersistedState, ctx: &mut FrameContext) {
         // --- Asset Browser State ---
         if self.ui_windows.asset_browser.is_none() {
@@ -51,16 +230,31 @@ impl RuntimeState {
         // Pipeline compilation counts are automatically reported by the pipeline cache
         kajiya_backend::shader_progress::update_pipeline_compilation_frame(0);
 
-        if self.keyboard.was_just_pressed(self.keymap_config.ui.toggle) {
+        if self.hotkey_pressed(self.keymap_config.ui.toggle) {
             self.show_gui = !self.show_gui;
+            self.app_config.gui.visible = self.show_gui;
             log::info!("GUI toggle pressed. show_gui is now: {}", self.show_gui);
+
+            if self.show_gui {
+                self.toasts.info("GUI shown");
+            } else {
+                self.toasts.info(format!("GUI hidden, press {:?} to show it", self.keymap_config.ui.toggle));
+            }
         }
 
         ctx.world_renderer.rg_debug_hook = self.locked_rg_debug_hook.clone();
+        self.validate_selection(persisted);
 
-        // Always show GUI when shaders are compiling, even if normally hidden
+        // Only set again below if imgui actually runs this frame
+        self.gui_wants_keyboard = false;
+        self.gui_wants_mouse = false;
+
+        // Always show GUI when shaders are compiling, even if normally hidden. This only forces the
+        // frame to run and never touches `show_gui`, so the user's choice applies again once it's done.
         let is_compiling = Self::is_shader_compilation_active() || kajiya_backend::shader_progress::is_compilation_or_heavy_work_active();
         let should_show_gui = self.show_gui || is_compiling;
+        // Toasts still show while the GUI is hidden, e.g. the one confirming it was hidden
+        let has_toasts = !self.toasts.active().is_empty();
         
         // Debug logging for GUI state
         static mut LAST_GUI_STATE: Option<(bool, bool, bool)> = None;
@@ -73,16 +267,27 @@ impl RuntimeState {
             }
         }
 
-        if should_show_gui || is_compiling {
+        if should_show_gui || has_toasts || self.paused {
             log::debug!("Starting ImGui frame with show_gui={}, is_compiling={}", self.show_gui, is_compiling);
             
             // Variable to track save requests outside the UI closure
             let mut save_scene_requested = false;
+            let mut delete_selected_requested = false;
+            let mut reset_selected_requested = false;
+            let mut pause_toggle_requested = false;
+            let mut duplicate_selected_requested = false;
             
-            if let Some(imgui_ctx) = ctx.imgui.take() {
+            if let Some(mut imgui_ctx) = ctx.imgui.take() {
                 log::info!("ImGui context taken successfully, calling frame()");
+                imgui_ctx.set_ui_scale(self.app_config.gui.effective_scale(ctx.window.scale_factor() as f32));
                 imgui_ctx.frame(|ui| {
                     log::debug!("Inside ImGui frame callback");
+                    self.gui_wants_keyboard = ui.io().want_capture_keyboard || ui.io().want_text_input;
+                    self.gui_wants_mouse = ui.io().want_capture_mouse;
+
+                // Editor windows and viewport overlays stay hidden while the frame only runs
+                // for the compilation popup or toasts
+                if self.show_gui {
                     // --- Asset Browser Window ---
                 if let Some(asset_browser) = self.ui_windows.asset_browser.as_mut() {
                     if self.ui_windows.show_asset_browser && asset_browser.open {
@@ -101,6 +306,18 @@ impl RuntimeState {
                                     log::error!("Failed to convert scene path to string: {:?}", scene_path);
                                 }
                             }
+                            AssetAction::LoadIbl(ibl_path) => {
+                                match ctx.world_renderer.ibl.load_image(&ibl_path) {
+                                    Ok(_) => {
+                                        log::info!("Loaded IBL from asset browser: {:?}", ibl_path);
+                                        persisted.scene.ibl = Some(ibl_path);
+                                    }
+                                    Err(err) => {
+                                        log::error!("Failed to load IBL from asset browser {:?}: {:#}", ibl_path, err);
+                                        self.toasts.error(format!("Failed to load IBL {:?}", ibl_path));
+                                    }
+                                }
+                            }
                             AssetAction::None => {
                                 // No action taken
                             }
@@ -109,79 +326,204 @@ impl RuntimeState {
                 }
                 // --- Hierarchy Window ---
                 // Outliner window (was Hierarchy)
-                static mut SELECTED_ELEMENT: Option<usize> = None;
-                static mut RESET_WINDOW_POSITIONS: bool = false;
-                static mut UNSAVED_CHANGES: bool = false;
-                
                 if self.ui_windows.show_hierarchy {
-                    let reset_condition = unsafe {
-                        if RESET_WINDOW_POSITIONS {
-                            imgui::Condition::Always
-                        } else {
-                            imgui::Condition::FirstUseEver
-                        }
-                    };
+                    let reset_condition = self.window_reset_condition();
                     
+                    let mut duplicates_to_remove: Vec<u64> = Vec::new();
+                    let mut visibility_toggled: Option<usize> = None;
                     ui.window("Outliner")
                         .opened(&mut self.ui_windows.show_hierarchy)
                         .size([350.0, 500.0], reset_condition)
                         .position([10.0, 30.0], reset_condition)  // Posición segura con margen
                         .build(|| {
                             // Sun as a selectable item
-                            let sun_selected = unsafe { SELECTED_ELEMENT == Some(usize::MAX) };
+                            let sun_selected = self.selected_element == Some(SelectionTarget::Sun);
                             let sun_label = create_icon_label(Self::get_sun_icon(), "Sun Direction");
                             if ui.selectable_config(&format!("{}", sun_label))
                                 .selected(sun_selected)
                                 .build() {
-                                unsafe { SELECTED_ELEMENT = Some(usize::MAX); }
+                                self.selected_element = Some(SelectionTarget::Sun);
+                                self.selected_node = None;
+                                self.selected_elements.clear();
+                            }
+                            ui.set_next_item_width(-1.0);
+                            ui.input_text("##outliner_search", &mut self.ui_windows.outliner_search)
+                                .hint(&format!("{} Search by name", ICON_MAGNIFYING_GLASS))
+                                .build();
+                            let search = self.ui_windows.outliner_search.trim();
+                            ui.set_next_item_width(-1.0);
+                            ui.input_text("##tag_filter", &mut self.ui_windows.outliner_tag_filter)
+                                .hint("Filter by tags, e.g. prop !collision")
+                                .build();
+                            let tag_query = crate::tags::TagQuery::parse(&self.ui_windows.outliner_tag_filter);
+
+                            if !self.duplicate_elements.is_empty() {
+                                ui.text_colored(
+                                    [1.0, 0.8, 0.0, 1.0],
+                                    &format!("{} {} duplicate element(s)", ICON_TRIANGLE_EXCLAMATION, self.duplicate_elements.len()),
+                                );
+                                ui.same_line();
+                                if ui.small_button("Remove duplicates") {
+                                    duplicates_to_remove.extend(self.duplicate_elements.iter().map(|dup| dup.id));
+                                }
+                            }
+                            let large_scene = self.app_config.large_scene.is_large(persisted.scene.elements.len());
+                            if large_scene {
+                                ui.text_colored(
+                                    [1.0, 0.8, 0.0, 1.0],
+                                    &format!("{} Large scene: node lists and debug colors are off", ICON_TRIANGLE_EXCLAMATION),
+                                );
                             }
-                            for (idx, elem) in persisted.scene.elements.iter().enumerate() {
+
+                            let rows: Vec<usize> = persisted
+                                .scene
+                                .elements
+                                .iter()
+                                .enumerate()
+                                .filter(|(_, elem)| tag_query.matches(&elem.tags))
+                                .filter(|(_, elem)| {
+                                    search.is_empty()
+                                        || crate::selection::find_ignore_case(&element_display_name(elem), search).is_some()
+                                        || elem.mesh_nodes.iter().enumerate().any(|(nidx, node)| {
+                                            crate::selection::find_ignore_case(&node_display_name(node, nidx), search).is_some()
+                                        })
+                                })
+                                .map(|(idx, _)| idx)
+                                .collect();
+
+                            let mut outliner_row = |idx: usize| {
+                                let elem = &persisted.scene.elements[idx];
                                 let element_icon = Self::get_element_icon(elem);
-                                let element_name = if let Some(name) = elem.mesh_nodes.get(0).and_then(|n| n.name.as_ref()) {
-                                    name.clone()
-                                } else {
-                                    format!("{:?}", elem.source)
-                                };
+                                let element_name = element_display_name(elem);
                                 let element_label = create_icon_label(element_icon, &element_name);
+                                // The label ends with the name, after the icon
+                                let name_offset = element_label.len() - element_name.len();
+                                let name_match = crate::selection::find_ignore_case(&element_name, search).filter(|_| !search.is_empty());
+
+                                let eye = if elem.visible { ICON_EYE } else { ICON_EYE_SLASH };
+                                if ui.small_button(&format!("{}##visible{}", eye, idx)) {
+                                    visibility_toggled = Some(idx);
+                                }
+                                if ui.is_item_hovered() {
+                                    ui.tooltip_text(if elem.visible { "Hide element" } else { "Show element" });
+                                }
+                                ui.same_line();
                                 
-                                let is_selected = unsafe { SELECTED_ELEMENT == Some(idx) };
+                                if self.show_element_debug_colors && !large_scene {
+                                    ui.text_colored(crate::selection::debug_color_for_id(elem.id), &ICON_SQUARE.to_string());
+                                    ui.same_line();
+                                }
+
+                                if let Some(dup) = self.duplicate_elements.iter().find(|dup| dup.id == elem.id) {
+                                    ui.text_colored([1.0, 0.8, 0.0, 1.0], &ICON_TRIANGLE_EXCLAMATION.to_string());
+                                    if ui.is_item_hovered() {
+                                        ui.tooltip_text(format!(
+                                            "Same model and placement as element {:016x}; click to remove this copy",
+                                            dup.duplicate_of
+                                        ));
+                                    }
+                                    if ui.is_item_clicked() {
+                                        duplicates_to_remove.push(elem.id);
+                                    }
+                                    ui.same_line();
+                                }
+
+                                let is_selected = self.selected_elements.contains(&idx) && self.selected_node.is_none();
                                 if ui.selectable_config(&format!("{}##{}", element_label, idx))
                                     .selected(is_selected)
                                     .build() {
-                                    unsafe { SELECTED_ELEMENT = Some(idx); }
+                                    let active = crate::selection::click_selection(
+                                        &mut self.selected_elements,
+                                        self.selected_element.and_then(SelectionTarget::element_index),
+                                        &rows,
+                                        idx,
+                                        ui.io().key_ctrl,
+                                        ui.io().key_shift,
+                                    );
+                                    self.selected_element = active.map(SelectionTarget::Element);
+                                    self.selected_node = None;
                                 }
-                                if elem.is_compound && !elem.mesh_nodes.is_empty() {
-                                    ui.tree_node_config(&format!("Nodes##{}", idx))
-                                        .build(|| {
+                                if let Some(range) = &name_match {
+                                    highlight_label_match(ui, &element_label, range.start + name_offset..range.end + name_offset);
+                                }
+                                // Expandable rows would break the list clipper's fixed row height
+                                if elem.is_compound && !elem.mesh_nodes.is_empty() && !large_scene {
+                                    let mut nodes_tree = ui.tree_node_config(&format!("Nodes##{}", idx));
+                                    if !search.is_empty() && name_match.is_none() {
+                                        // Listed for a node match, so show it
+                                        nodes_tree = nodes_tree.opened(true, Condition::Appearing);
+                                    }
+                                    nodes_tree.build(|| {
                                         for (nidx, node) in elem.mesh_nodes.iter().enumerate() {
                                             let node_icon = Self::get_node_icon();
-                                            let node_name = if let Some(n) = &node.name {
-                                                n.clone()
-                                            } else {
-                                                format!("Node {}", nidx)
-                                            };
+                                            let node_name = node_display_name(node, nidx);
+                                            let node_match = crate::selection::find_ignore_case(&node_name, search);
+                                            // Under an element listed by its own name, every node stays listed
+                                            if name_match.is_none() && node_match.is_none() {
+                                                continue;
+                                            }
                                             let node_label = create_icon_label(node_icon, &node_name);
-                                            ui.bullet_text(&format!("{}##{}-{}", node_label, idx, nidx));
+                                            let node_selected = self.selected_element == Some(SelectionTarget::Element(idx)) && self.selected_node == Some(nidx);
+                                            if ui.selectable_config(&format!("{}##{}-{}", node_label, idx, nidx))
+                                                .selected(node_selected)
+                                                .build() {
+                                                self.selected_element = Some(SelectionTarget::Element(idx));
+                                                self.selected_node = Some(nidx);
+                                                self.selected_elements = vec![idx];
+                                            }
+                                            if let Some(range) = node_match.filter(|_| !search.is_empty()) {
+                                                let offset = node_label.len() - node_name.len();
+                                                highlight_label_match(ui, &node_label, range.start + offset..range.end + offset);
+                                            }
                                         }
                                     });
                                 }
+                            };
+
+                            if large_scene {
+                                // Only lay out the rows that are scrolled into view
+                                let mut clipper = ListClipper::new(rows.len() as i32).begin(ui);
+                                while clipper.step() {
+                                    for row in clipper.display_start()..clipper.display_end() {
+                                        outliner_row(rows[row as usize]);
+                                    }
+                                }
+                            } else {
+                                for &idx in &rows {
+                                    outliner_row(idx);
+                                }
                             }
                         });
+
+                    if let Some(elem) = visibility_toggled.and_then(|idx| persisted.scene.elements.get_mut(idx)) {
+                        elem.visible = !elem.visible;
+                        self.mark_unsaved_changes();
+                    }
+
+                    if !duplicates_to_remove.is_empty() {
+                        // Back to front, so the indices still to visit don't shift
+                        for idx in (0..persisted.scene.elements.len()).rev() {
+                            if duplicates_to_remove.contains(&persisted.scene.elements[idx].id) {
+                                let elem = persisted.scene.elements.remove(idx);
+                                for &instance in elem.instances() {
+                                    ctx.world_renderer.remove_instance(instance);
+                                }
+                                self.on_element_removed(idx);
+                                self.edit_history.push(EditAction::ElementRemoved { element: elem, idx });
+                            }
+                        }
+                        log::info!("Removed {} duplicate element(s)", duplicates_to_remove.len());
+                        self.mark_unsaved_changes();
+                    }
                 }
 
                 // Attributes window for selected object
-                let selected_idx = unsafe { SELECTED_ELEMENT };
+                let selected_idx = self.selected_element_index();
                 
-                if let Some(idx) = selected_idx {
-                    let reset_condition = unsafe {
-                        if RESET_WINDOW_POSITIONS {
-                            imgui::Condition::Always
-                        } else {
-                            imgui::Condition::FirstUseEver
-                        }
-                    };
+                if let Some(target) = self.selected_element {
+                    let reset_condition = self.window_reset_condition();
                     
-                    if idx == usize::MAX {
+                    if target == SelectionTarget::Sun {
                         // Sun attributes
                         ui.window("Attributes")
                             .size([350.0, 200.0], reset_condition)
@@ -202,22 +544,186 @@ impl RuntimeState {
                                 ui.separator();
                                 ui.text(&format!("Current: ({:.3}, {:.3}, {:.3})", dir.x, dir.y, dir.z));
                             });
-                    } else if let Some(elem) = persisted.scene.elements.get_mut(idx) {
+                    } else if let Some((idx, nidx)) = selected_idx.zip(self.selected_node).filter(|&(idx, nidx)| {
+                        persisted.scene.elements.get(idx).map_or(false, |elem| nidx < elem.mesh_nodes.len())
+                    }) {
+                        // Mesh node attributes, relative to the parent element. The element is
+                        // drawn as a single instance of the whole file until it's split into one
+                        // instance per node; only then do node edits show up.
+                        let elem = &mut persisted.scene.elements[idx];
+                        let parent_transform = elem.transform.affine_transform();
+                        let node = &elem.mesh_nodes[nidx];
+                        let node_name = node.name.clone().unwrap_or_else(|| "Unnamed node".to_string());
+                        let transform_before = node.local_transform.clone();
+                        let mut transform = transform_before.clone();
+                        let (split, can_split) = (elem.split_nodes, elem.can_split_nodes());
+                        let mut split_requested = false;
+
+                        ui.window("Attributes")
+                            .size([350.0, 360.0], reset_condition)
+                            .position([370.0, 30.0], reset_condition)
+                            .build(|| {
+                                ui.text(&format!("{} {}", Self::get_node_icon(), node_name));
+                                ui.separator();
+
+                                transform_display_options(ui, &mut self.app_config.editing);
+                                let unit = self.app_config.editing.display_unit;
+                                ui.separator();
+
+                                if !split {
+                                    if can_split {
+                                        split_requested = ui.button("Split into node instances");
+                                        if ui.is_item_hovered() {
+                                            ui.tooltip_text("Bake each node of the glTF file on its own, so nodes can be moved\nwithin the element. Saved with the scene.");
+                                        }
+                                    } else {
+                                        ui.text_wrapped("Re-analyze the element's glTF nodes to edit them; these were analyzed before node editing.");
+                                    }
+                                    ui.separator();
+                                }
+                                let _disabled = ui.begin_disabled(!split);
+
+                                // World positions go through the parent element's transform
+                                let world_space = self.app_config.editing.transform_space == TransformSpace::World;
+                                let mut position = if world_space {
+                                    parent_transform.transform_point3(transform.position)
+                                } else {
+                                    transform.position
+                                };
+                                ui.text(if world_space { "World position:" } else { "Local position:" });
+                                ui.indent();
+                                let mut pos_changed = false;
+                                pos_changed |= drag_length(ui, "X##npos", &mut position.x, unit, 0.1, None);
+                                pos_changed |= drag_length(ui, "Y##npos", &mut position.y, unit, 0.1, None);
+                                pos_changed |= drag_length(ui, "Z##npos", &mut position.z, unit, 0.1, None);
+                                ui.unindent();
+                                if pos_changed {
+                                    transform.position = if world_space {
+                                        parent_transform.inverse().transform_point3(position)
+                                    } else {
+                                        position
+                                    };
+                                }
+
+                                ui.text("Local rotation (degrees):");
+                                ui.indent();
+                                Drag::new("X##nrot").speed(1.0).range(-360.0, 360.0).build(ui, &mut transform.rotation_euler_degrees.x);
+                                Drag::new("Y##nrot").speed(1.0).range(-360.0, 360.0).build(ui, &mut transform.rotation_euler_degrees.y);
+                                Drag::new("Z##nrot").speed(1.0).range(-360.0, 360.0).build(ui, &mut transform.rotation_euler_degrees.z);
+                                ui.unindent();
+
+                                ui.text("Local scale:");
+                                ui.indent();
+                                Drag::new("X##nscale").speed(0.01).range(0.001, 100.0).build(ui, &mut transform.scale.x);
+                                Drag::new("Y##nscale").speed(0.01).range(0.001, 100.0).build(ui, &mut transform.scale.y);
+                                Drag::new("Z##nscale").speed(0.01).range(0.001, 100.0).build(ui, &mut transform.scale.z);
+                                ui.unindent();
+                            });
+
+                        if split_requested {
+                            if let Err(err) = self.split_element_nodes(ctx.world_renderer, elem) {
+                                log::error!("Failed to split {:?} into nodes: {:#}", elem.source, err);
+                                self.toasts.error(format!("Failed to split {:?} into nodes", elem.source));
+                            }
+                        }
+                        if transform != transform_before {
+                            // Only the authoring transform is edited; `update_objects` places the
+                            // node's instance from it later this frame
+                            elem.mesh_nodes[nidx].local_transform = transform.clone();
+                            self.edit_history.record_node_transform(idx, nidx, transform_before, transform);
+                            self.mark_unsaved_changes();
+                        }
+                    } else if let Some((idx, elem)) = selected_idx.and_then(|idx| Some((idx, persisted.scene.elements.get_mut(idx)?))) {
+                        let transform_before = elem.transform.clone();
                         ui.window("Attributes")
                             .size([350.0, 400.0], reset_condition)
                             .position([370.0, 30.0], reset_condition)  // A la derecha del Outliner
                             .build(|| {
+                                if self.selected_elements.len() > 1 {
+                                    ui.text_colored([0.7, 0.7, 0.7, 1.0], &format!(
+                                        "{} elements selected; transform changes apply to all",
+                                        self.selected_elements.len()
+                                    ));
+                                    ui.separator();
+                                }
+                                let mut name = elem.name.clone().unwrap_or_default();
+                                if ui.input_text("Name", &mut name).hint(element_fallback_name(elem)).build() {
+                                    // Clearing the field goes back to the automatic label
+                                    elem.name = (!name.trim().is_empty()).then_some(name);
+                                    self.mark_unsaved_changes();
+                                }
                                 ui.text(&format!("Source: {:?}", elem.source));
                                 ui.text(&format!("Compound: {}", elem.is_compound));
+                                match self.mesh_geometry(&elem.source) {
+                                    Ok(geometry) => ui.text(&format!(
+                                        "Vertices: {}  Triangles: {}",
+                                        geometry.vertex_count(),
+                                        geometry.triangle_count()
+                                    )),
+                                    Err(err) => ui.text_disabled(&format!("Geometry unavailable: {:#}", err)),
+                                }
+                                ui.separator();
+
+                                if ui.checkbox("Casts shadow", &mut elem.casts_shadow) {
+                                    self.mark_unsaved_changes();
+                                }
+                                ui.same_line();
+                                {
+                                    let _disabled = ui.begin_disabled(true);
+                                    let mut receives_shadow = true;
+                                    ui.checkbox("Receives shadow", &mut receives_shadow);
+                                }
+                                if ui.is_item_hovered_with_flags(ItemHoveredFlags::ALLOW_WHEN_DISABLED) {
+                                    ui.tooltip_text("Not supported by the renderer: shadows are resolved per pixel,\nwithout knowing which element is there.");
+                                }
+                                if ui.checkbox("PVS occluder", &mut elem.occluder) {
+                                    self.mark_unsaved_changes();
+                                }
+                                if ui.is_item_hovered() {
+                                    ui.tooltip_text("Blocks sight lines when baking the PVS, with its whole bounding box.\nOnly mark elements that fill their box, like walls and floors. Takes effect on the next bake.");
+                                }
+                                ui.separator();
+
+                                ui.text("Tags:");
+                                let mut tag_to_remove = None;
+                                for (tidx, tag) in elem.tags.iter().enumerate() {
+                                    ui.same_line();
+                                    if ui.small_button(&format!("{} {}##tag{}", tag, ICON_XMARK, tidx)) {
+                                        tag_to_remove = Some(tidx);
+                                    }
+                                }
+                                if let Some(tidx) = tag_to_remove {
+                                    elem.tags.remove(tidx);
+                                    self.mark_unsaved_changes();
+                                }
+
+                                ui.set_next_item_width(150.0);
+                                let entered = ui.input_text("##new_tag", &mut self.ui_windows.new_tag)
+                                    .hint("New tag")
+                                    .enter_returns_true(true)
+                                    .build();
+                                ui.same_line();
+                                if (ui.button("Add tag") || entered) && crate::tags::add_tag(&mut elem.tags, &self.ui_windows.new_tag) {
+                                    self.ui_windows.new_tag.clear();
+                                    self.mark_unsaved_changes();
+                                }
+                                ui.separator();
+
+                                transform_display_options(ui, &mut self.app_config.editing);
+                                let unit = self.app_config.editing.display_unit;
                                 ui.separator();
                                 
-                                // Transform controls with grouping
-                                ui.text("Position:");
+                                // Transform controls with grouping. Elements have no parent yet,
+                                // so their world and local positions are the same.
+                                ui.text(match self.app_config.editing.transform_space {
+                                    TransformSpace::World => "World position:",
+                                    TransformSpace::Local => "Position:",
+                                });
                                 ui.indent();
                                 let mut pos_changed = false;
-                                pos_changed |= Drag::new("X##pos").speed(0.1).range(-1000.0, 1000.0).build(ui, &mut elem.transform.position.x);
-                                pos_changed |= Drag::new("Y##pos").speed(0.1).range(-1000.0, 1000.0).build(ui, &mut elem.transform.position.y);
-                                pos_changed |= Drag::new("Z##pos").speed(0.1).range(-1000.0, 1000.0).build(ui, &mut elem.transform.position.z);
+                                pos_changed |= drag_length(ui, "X##pos", &mut elem.transform.position.x, unit, 0.1, Some(1000.0));
+                                pos_changed |= drag_length(ui, "Y##pos", &mut elem.transform.position.y, unit, 0.1, Some(1000.0));
+                                pos_changed |= drag_length(ui, "Z##pos", &mut elem.transform.position.z, unit, 0.1, Some(1000.0));
                                 ui.unindent();
                                 
                                 ui.text("Rotation (degrees):");
@@ -226,6 +732,10 @@ impl RuntimeState {
                                 rot_changed |= Drag::new("X##rot").speed(1.0).range(-360.0, 360.0).build(ui, &mut elem.transform.rotation_euler_degrees.x);
                                 rot_changed |= Drag::new("Y##rot").speed(1.0).range(-360.0, 360.0).build(ui, &mut elem.transform.rotation_euler_degrees.y);
                                 rot_changed |= Drag::new("Z##rot").speed(1.0).range(-360.0, 360.0).build(ui, &mut elem.transform.rotation_euler_degrees.z);
+                                if ui.small_button("Random yaw") {
+                                    elem.transform.rotation_euler_degrees.y = self.rng.range(-180.0, 180.0);
+                                    rot_changed = true;
+                                }
                                 ui.unindent();
                                 
                                 ui.text("Scale:");
@@ -235,29 +745,78 @@ impl RuntimeState {
                                 scale_changed |= Drag::new("Y##scale").speed(0.01).range(0.001, 100.0).build(ui, &mut elem.transform.scale.y);
                                 scale_changed |= Drag::new("Z##scale").speed(0.01).range(0.001, 100.0).build(ui, &mut elem.transform.scale.z);
                                 ui.unindent();
+
+                                if self.app_config.frustum_culling.is_non_uniform_scale(elem.transform.scale) {
+                                    ui.text_colored(
+                                        [1.0, 0.8, 0.0, 1.0],
+                                        &format!("{} Highly non-uniform scale", ICON_TRIANGLE_EXCLAMATION),
+                                    );
+                                    if ui.is_item_hovered() {
+                                        ui.tooltip_text(format!(
+                                            "Axis scales differ by {:.1}x. Bounding spheres fit this element poorly,\nso it always gets the AABB frustum test.",
+                                            crate::culling::scale_ratio(elem.transform.scale)
+                                        ));
+                                    }
+                                }
                                 
                                 let any_changed = pos_changed || rot_changed || scale_changed;
                                 
-                                // Apply changes to renderer immediately for real-time feedback
+                                // Only the authoring transform is edited here; `update_objects` derives
+                                // the render transform from it later this frame, even if the object is culled.
                                 if any_changed {
-                                    ctx.world_renderer.set_instance_transform(elem.instance, elem.transform.affine_transform());
                                     // Mark scene as having unsaved changes
-                                    unsafe { UNSAVED_CHANGES = true; }
+                                    self.mark_unsaved_changes();
                                 }
                                 
                                 ui.separator();
                                 
                                 // Reset transform button
                                 if ui.button("Reset Transform") {
-                                    elem.transform = crate::persisted::SceneElementTransform::IDENTITY;
-                                    ctx.world_renderer.set_instance_transform(elem.instance, elem.transform.affine_transform());
-                                    unsafe { UNSAVED_CHANGES = true; }
+                                    reset_selected_requested = true;
+                                }
+
+                                ui.same_line();
+                                if ui.button("Center Pivot on Geometry") {
+                                    elem.center_pivot(self.app_config.frustum_culling.default_object_size);
+                                    self.mark_unsaved_changes();
+                                }
+                                let pivot = elem.transform.pivot * unit.per_meter();
+                                ui.text_colored([0.7, 0.7, 0.7, 1.0], &format!(
+                                    "Pivot: ({:.3}, {:.3}, {:.3}) {}",
+                                    pivot.x, pivot.y, pivot.z, unit.suffix()
+                                ));
+
+                                if ui.button(&format!("{} Export to glTF", ICON_FILE_EXPORT)) {
+                                    match self.export_element_gltf(elem) {
+                                        Ok(path) => self.toasts.info(format!("Exported to {}", path.display())),
+                                        Err(err) => {
+                                            log::error!("Failed to export {:?}: {:#}", elem.source, err);
+                                            self.toasts.error(format!("Failed to export {:?}", elem.source));
+                                        }
+                                    }
+                                }
+                                if ui.is_item_hovered() {
+                                    ui.tooltip_text(format!(
+                                        "Write the mesh with its current transform to {}/",
+                                        crate::gltf_export::EXPORT_DIR
+                                    ));
+                                }
+                                ui.same_line();
+                                if ui.button(&format!("{} Duplicate", ICON_CLONE)) {
+                                    duplicate_selected_requested = true;
+                                }
+                                if ui.is_item_hovered() {
+                                    ui.tooltip_text("Ctrl+D");
+                                }
+                                ui.same_line();
+                                if ui.button(&format!("{} Delete", ICON_TRASH)) {
+                                    delete_selected_requested = true;
                                 }
                                 
                                 ui.separator();
                                 
                                 // Show save status and quick save button
-                                let has_unsaved = unsafe { UNSAVED_CHANGES };
+                                let has_unsaved = self.scene_dirty;
                                 if let Some(scene_path) = &self.current_scene_path {
                                     let scene_name = scene_path.file_name()
                                         .and_then(|name| name.to_str())
@@ -279,6 +838,26 @@ impl RuntimeState {
                                     ui.text_colored([0.7, 0.7, 0.7, 1.0], "No scene file loaded - drag & drop a .dmoon file");
                                 }
                                 
+                                let is_gltf = matches!(
+                                    &elem.source,
+                                    crate::persisted::MeshSource::File(path)
+                                        if matches!(path.extension().and_then(|ext| ext.to_str()), Some("gltf" | "glb"))
+                                );
+                                if is_gltf {
+                                    ui.separator();
+                                    let label = if elem.mesh_nodes.is_empty() { "Analyze glTF nodes" } else { "Re-analyze glTF nodes" };
+                                    if ui.button(label) {
+                                        // Analysis resets the node transforms, so the whole file is drawn again
+                                        let merged = self.merge_element_nodes(ctx.world_renderer, elem);
+                                        if let Err(err) = merged.and_then(|()| self.analyze_gltf_nodes(elem, ctx.world_renderer)) {
+                                            log::error!("Failed to analyze glTF nodes: {:#}", err);
+                                        }
+                                    }
+                                    if elem.mesh_nodes.is_empty() && ui.is_item_hovered() {
+                                        ui.tooltip_text("Culled as a single object until its nodes are analyzed");
+                                    }
+                                }
+
                                 // Show mesh node information if available
                                 if !elem.mesh_nodes.is_empty() {
                                     ui.separator();
@@ -294,8 +873,197 @@ impl RuntimeState {
                                     ui.unindent();
                                 }
                             });
+
+                        if elem.transform != transform_before {
+                            // Everything else selected moves, turns and scales along with it
+                            let transform_after = elem.transform.clone();
+                            let mut changes = Vec::new();
+                            for &other in self.selected_elements.iter().filter(|&&other| other != idx) {
+                                if let Some(other_elem) = persisted.scene.elements.get_mut(other) {
+                                    let old = other_elem.transform.clone();
+                                    other_elem.transform = old.with_edit(&transform_before, &transform_after);
+                                    changes.push((other, old, other_elem.transform.clone()));
+                                }
+                            }
+                            changes.push((idx, transform_before, transform_after));
+                            changes.sort_by_key(|(idx, _, _)| *idx);
+                            self.edit_history.record_transforms(changes);
+                        }
+                    }
+                }
+                if !ui.is_any_item_active() {
+                    self.edit_history.end_gesture();
+                }
+                if reset_selected_requested {
+                    let mut resets = Vec::new();
+                    for &idx in &self.selected_elements {
+                        if let Some(elem) = persisted.scene.elements.get_mut(idx) {
+                            let old = std::mem::replace(&mut elem.transform, crate::persisted::SceneElementTransform::IDENTITY);
+                            resets.push(EditAction::TransformChanged {
+                                idx,
+                                old,
+                                new: elem.transform.clone(),
+                            });
+                        }
+                    }
+                    if !resets.is_empty() {
+                        self.edit_history.push(EditAction::Batch(resets));
+                        self.mark_unsaved_changes();
+                    }
+                }
+                if duplicate_selected_requested {
+                    self.duplicate_selected(persisted, ctx.world_renderer);
+                }
+                if delete_selected_requested {
+                    // Back to front, so the indices still to remove don't shift; undoing the batch
+                    // reinserts them front to back
+                    let mut removals = Vec::new();
+                    for idx in self.selected_elements.clone().into_iter().rev() {
+                        if idx < persisted.scene.elements.len() {
+                            let elem = persisted.scene.elements.remove(idx);
+                            for &instance in elem.instances() {
+                                ctx.world_renderer.remove_instance(instance);
+                            }
+                            self.on_element_removed(idx);
+                            removals.push(EditAction::ElementRemoved { element: elem, idx });
+                        }
+                    }
+                    if !removals.is_empty() {
+                        self.edit_history.push(EditAction::Batch(removals));
+                        self.mark_unsaved_changes();
+                    }
+                }
+                // Per-element debug colors in the viewport
+                if self.show_element_debug_colors && !self.app_config.large_scene.is_large(persisted.scene.elements.len()) {
+                    let view_proj = self.view_projection(persisted, ctx);
+                    let draw_list = ui.get_background_draw_list();
+                    for elem in &persisted.scene.elements {
+                        let config = crate::selection::SelectionHighlightConfig {
+                            enabled: true,
+                            style: crate::selection::SelectionHighlightStyle::Tint,
+                            color: crate::selection::debug_color_for_id(elem.id),
+                            tint_opacity: 0.25,
+                        };
+                        crate::selection::draw_selection_highlight(
+                            &draw_list,
+                            &config,
+                            &elem.local_bounds(self.app_config.frustum_culling.default_object_size),
+                            &Mat4::from(elem.transform.affine_transform()),
+                            &view_proj,
+                            ui.io().display_size,
+                        );
                     }
                 }
+
+                // Culling preview: outline what culling would have hidden
+                if self.app_config.frustum_culling.preview && !self.app_config.large_scene.is_large(persisted.scene.elements.len()) {
+                    let view_proj = self.view_projection(persisted, ctx);
+                    let draw_list = ui.get_background_draw_list();
+                    let config = crate::selection::SelectionHighlightConfig {
+                        enabled: true,
+                        style: crate::selection::SelectionHighlightStyle::Tint,
+                        color: [1.0, 0.15, 0.15, 1.0],
+                        tint_opacity: 0.15,
+                    };
+                    for elem in self.culled_elements.iter().filter_map(|&idx| persisted.scene.elements.get(idx)) {
+                        crate::selection::draw_selection_highlight(
+                            &draw_list,
+                            &config,
+                            &elem.local_bounds(self.app_config.frustum_culling.default_object_size),
+                            &Mat4::from(elem.transform.affine_transform()),
+                            &view_proj,
+                            ui.io().display_size,
+                        );
+                    }
+                }
+
+                // Selection highlight in the viewport
+                if self.app_config.selection_highlight.enabled && !self.selected_elements.is_empty() {
+                    let view_proj = self.view_projection(persisted, ctx);
+                    for &idx in &self.selected_elements {
+                        let elem = match persisted.scene.elements.get(idx) {
+                            Some(elem) => elem,
+                            None => continue,
+                        };
+                        let selected_node = self
+                            .selected_node
+                            .filter(|_| selected_idx == Some(idx))
+                            .and_then(|nidx| elem.mesh_nodes.get(nidx));
+
+                        // Highlight where the element is authored, even while it's culled
+                        let (local_aabb, transform) = match selected_node.and_then(|node| Some((node, node.bounding_box?))) {
+                            Some((node, node_aabb)) => (
+                                node_aabb,
+                                elem.transform.affine_transform() * node.local_transform.affine_transform(),
+                            ),
+                            None => (
+                                elem.local_bounds(self.app_config.frustum_culling.default_object_size),
+                                elem.transform.affine_transform(),
+                            ),
+                        };
+                        crate::selection::draw_selection_highlight(
+                            &ui.get_background_draw_list(),
+                            &self.app_config.selection_highlight,
+                            &local_aabb,
+                            &Mat4::from(transform),
+                            &view_proj,
+                            ui.io().display_size,
+                        );
+                    }
+                }
+
+                if self.pending_camera_copy {
+                    self.pending_camera_copy = false;
+                    match persisted.camera.to_ron_bookmark() {
+                        Ok(ron) => {
+                            ui.set_clipboard_text(&ron);
+                            log::info!("Copied camera to clipboard:\n{}", ron);
+                        }
+                        Err(err) => log::error!("Failed to serialize camera: {:#}", err),
+                    }
+                }
+
+                } // Close the editor windows `if self.show_gui` block
+
+                // Toasts, stacked in the bottom-right corner
+                let toasts = self.toasts.active();
+                if !toasts.is_empty() {
+                    let [display_width, display_height] = ui.io().display_size;
+                    ui.window("##toasts")
+                        .position([display_width - 10.0, display_height - 10.0], imgui::Condition::Always)
+                        .position_pivot([1.0, 1.0])
+                        .flags(
+                            WindowFlags::NO_DECORATION
+                                | WindowFlags::ALWAYS_AUTO_RESIZE
+                                | WindowFlags::NO_SAVED_SETTINGS
+                                | WindowFlags::NO_FOCUS_ON_APPEARING
+                                | WindowFlags::NO_NAV,
+                        )
+                        .bg_alpha(0.8)
+                        .build(|| {
+                            for toast in toasts {
+                                let alpha = (toast.remaining_fraction() * 4.0).clamp(0.0, 1.0);
+                                let (color, icon) = match toast.kind {
+                                    ToastKind::Error => ([1.0, 0.4, 0.4, alpha], ICON_TRIANGLE_EXCLAMATION),
+                                    ToastKind::Info => ([0.9, 0.9, 0.9, alpha], ICON_CIRCLE_INFO),
+                                };
+                                ui.text_colored(color, &format!("{} {}", icon, toast.message));
+                            }
+                        });
+                }
+
+                if self.paused {
+                    // Shown even with the GUI hidden, so a frozen frame isn't mistaken for a hang
+                    let label = format!("{} PAUSED", ICON_PAUSE);
+                    let [display_width, _] = ui.io().display_size;
+                    let [label_width, _] = ui.calc_text_size(&label);
+                    let draw_list = ui.get_foreground_draw_list();
+                    let min = [(display_width - label_width) * 0.5 - 12.0, 40.0];
+                    let max = [min[0] + label_width + 24.0, min[1] + ui.text_line_height() + 12.0];
+                    draw_list.add_rect(min, max, [0.0, 0.0, 0.0, 0.6]).filled(true).rounding(4.0).build();
+                    draw_list.add_text([min[0] + 12.0, min[1] + 6.0], [1.0, 0.8, 0.0, 1.0], &label);
+                }
+
                 // --- Shader Compilation Progress Popup (always first, even if GUI is hidden) ---
                 if is_compiling {
                     Self::show_shader_compilation_popup(ui);
@@ -323,7 +1091,7 @@ impl RuntimeState {
                             ];
                             
                             for (name, path) in &scene_files {
-                                if ui.menu_item(name) {
+                                if ui.menu_item(create_icon_label(ICON_DMOON_SCENE, name)) {
                                     if let Err(err) = self.load_scene_from_path(persisted, ctx, path) {
                                         log::error!("Failed to load scene {}: {:#}", name, err);
                                     }
@@ -342,7 +1110,7 @@ impl RuntimeState {
                         ui.separator();
                         
                         // Save options with visual status
-                        let has_unsaved = unsafe { UNSAVED_CHANGES };
+                        let has_unsaved = self.scene_dirty;
                         if let Some(scene_path) = &self.current_scene_path {
                             let scene_name = scene_path.file_name()
                                 .and_then(|name| name.to_str())
@@ -359,7 +1127,7 @@ impl RuntimeState {
                                     log::error!("Failed to save current scene: {:#}", err);
                                 } else {
                                     log::info!("Scene saved successfully!");
-                                    unsafe { UNSAVED_CHANGES = false; }
+                                    self.scene_dirty = false;
                                 }
                             }
                             
@@ -374,6 +1142,41 @@ impl RuntimeState {
                             ui.text_colored([0.7, 0.7, 0.7, 1.0], "  No scene loaded");
                         }
                         
+                        if let Some(startup_menu) = ui.begin_menu("Startup Scene") {
+                            use crate::app_config::StartupScene;
+
+                            let current = self.app_config.startup_scene.clone();
+                            let mut new_startup = None;
+
+                            if ui.menu_item_config("Empty").selected(current == StartupScene::None).build() {
+                                new_startup = Some(StartupScene::None);
+                            }
+                            if ui.menu_item_config("Last opened scene").selected(current == StartupScene::LastScene).build() {
+                                new_startup = Some(StartupScene::LastScene);
+                            }
+                            if ui.menu_item_config("Current scene")
+                                .selected(matches!(&current, StartupScene::Path(path) if Some(path) == self.current_scene_path.as_ref()))
+                                .enabled(self.current_scene_path.is_some())
+                                .build()
+                            {
+                                new_startup = self.current_scene_path.clone().map(StartupScene::Path);
+                            }
+
+                            if let StartupScene::Path(path) = &current {
+                                ui.separator();
+                                ui.text_colored([0.7, 0.7, 0.7, 1.0], &format!("  {}", path.display()));
+                            }
+
+                            if let Some(startup) = new_startup {
+                                self.app_config.startup_scene = startup;
+                                if let Err(err) = self.app_config.save() {
+                                    log::error!("Failed to save app config: {:#}", err);
+                                }
+                            }
+
+                            startup_menu.end();
+                        }
+
                         ui.separator();
                         ui.text_colored([0.6, 0.6, 0.6, 1.0], "Shortcut: S key for quick save");
                         
@@ -383,6 +1186,39 @@ impl RuntimeState {
                         
                         file_menu.end();
                     }
+                    if let Some(edit_menu) = ui.begin_menu("Edit") {
+                        if ui.menu_item_config("Undo").shortcut("Ctrl+Z").enabled(self.edit_history.can_undo()).build() {
+                            self.undo_edit(persisted, ctx.world_renderer);
+                        }
+                        if ui.menu_item_config("Redo").shortcut("Ctrl+Y").enabled(self.edit_history.can_redo()).build() {
+                            self.redo_edit(persisted, ctx.world_renderer);
+                        }
+                        ui.separator();
+                        if ui.menu_item_config("Duplicate").shortcut("Ctrl+D").enabled(!self.selected_elements.is_empty()).build() {
+                            self.duplicate_selected(persisted, ctx.world_renderer);
+                        }
+                        edit_menu.end();
+                    }
+                    if let Some(add_menu) = ui.begin_menu("Add") {
+                        if let Some(primitive_menu) = ui.begin_menu("Primitive") {
+                            for primitive in crate::primitives::Primitive::ALL {
+                                if ui.menu_item(primitive.name()) {
+                                    match self.add_primitive(persisted, ctx.world_renderer, primitive) {
+                                        Ok(()) => self.mark_unsaved_changes(),
+                                        Err(err) => {
+                                            log::error!("Failed to add {}: {:#}", primitive.name(), err);
+                                            self.toasts.error(format!("Failed to add {}", primitive.name()));
+                                        }
+                                    }
+                                }
+                            }
+                            primitive_menu.end();
+                        }
+                        if ui.menu_item_config("Scatter...").selected(self.ui_windows.show_scatter).build() {
+                            self.ui_windows.show_scatter = !self.ui_windows.show_scatter;
+                        }
+                        add_menu.end();
+                    }
                     if let Some(window_menu) = ui.begin_menu("Window") {
                         let show_assets = self.ui_windows.asset_browser.as_ref().map_or(false, |a| a.open && self.ui_windows.show_asset_browser);
                         if ui.menu_item_config("Assets Browser").selected(show_assets).build() {
@@ -391,28 +1227,77 @@ impl RuntimeState {
                                 self.ui_windows.show_asset_browser = asset_browser.open;
                             }
                         }
-                        if ui.menu_item_config("Hierarchy").selected(self.ui_windows.show_hierarchy).build() {
+                        if ui.menu_item_config("Outliner").selected(self.ui_windows.show_hierarchy).build() {
                             self.ui_windows.show_hierarchy = !self.ui_windows.show_hierarchy;
                         }
                         if ui.menu_item_config("Debug").selected(self.ui_windows.show_debug).build() {
                             self.ui_windows.show_debug = !self.ui_windows.show_debug;
                         }
+                        if ui.menu_item_config("Layers").selected(self.ui_windows.show_layers).build() {
+                            self.ui_windows.show_layers = !self.ui_windows.show_layers;
+                        }
+                        if ui.menu_item_config("Preferences").selected(self.ui_windows.show_preferences).build() {
+                            self.ui_windows.show_preferences = !self.ui_windows.show_preferences;
+                            if !self.ui_windows.show_preferences {
+                                if let Err(err) = self.app_config.save() {
+                                    log::error!("Failed to save app config: {:#}", err);
+                                }
+                            }
+                        }
                         
                         ui.separator();
                         if ui.menu_item("Reset Window Positions") {
                             // Reset all window positions to default
-                            unsafe { RESET_WINDOW_POSITIONS = true; }
+                            self.reset_window_positions = true;
                         }
                         
                         window_menu.end();
                     }
-                    if let Some(view_menu) = ui.begin_menu("View") {
-                        if let Some(rendering_menu) = ui.begin_menu("Rendering Type") {
-                            // Rasterization mode (RTX OFF)
-                            let is_rasterization = !ctx.world_renderer.is_ray_tracing_enabled() && 
-                                                  ctx.world_renderer.get_render_mode() == RenderMode::Standard;
-                            if ui.menu_item_config("Rasterization").selected(is_rasterization).build() {
-                                ctx.world_renderer.set_ray_tracing_enabled(false);
+                    if let Some(camera_menu) = ui.begin_menu("Camera") {
+                        if ui.menu_item("Copy camera to clipboard (RON)") {
+                            self.pending_camera_copy = true;
+                        }
+                        if ui.menu_item("Add camera as sequence keyframe") {
+                            self.add_sequence_keyframe(persisted);
+                        }
+                        if ui.menu_item("Print camera transform") {
+                            self.print_camera_transform(persisted);
+                        }
+
+                        ui.separator();
+                        if let Some(action_menu) = ui.begin_menu("Camera key action") {
+                            use crate::app_config::CameraKeyAction;
+
+                            let current = self.app_config.camera_key_action;
+                            let mut new_action = None;
+                            for (label, action) in [
+                                ("Print to console", CameraKeyAction::Print),
+                                ("Copy to clipboard", CameraKeyAction::CopyToClipboard),
+                                ("Add sequence keyframe", CameraKeyAction::AddKeyframe),
+                            ] {
+                                if ui.menu_item_config(label).selected(current == action).build() {
+                                    new_action = Some(action);
+                                }
+                            }
+
+                            if let Some(action) = new_action {
+                                self.app_config.camera_key_action = action;
+                                if let Err(err) = self.app_config.save() {
+                                    log::error!("Failed to save app config: {:#}", err);
+                                }
+                            }
+                            action_menu.end();
+                        }
+
+                        camera_menu.end();
+                    }
+                    if let Some(view_menu) = ui.begin_menu("View") {
+                        if let Some(rendering_menu) = ui.begin_menu("Rendering Type") {
+                            // Rasterization mode (RTX OFF)
+                            let is_rasterization = !ctx.world_renderer.is_ray_tracing_enabled() && 
+                                                  ctx.world_renderer.get_render_mode() == RenderMode::Standard;
+                            if ui.menu_item_config("Rasterization").selected(is_rasterization).build() {
+                                ctx.world_renderer.set_ray_tracing_enabled(false);
                                 ctx.world_renderer.set_render_mode(RenderMode::Standard);
                             }
                             
@@ -437,12 +1322,101 @@ impl RuntimeState {
                             
                             rendering_menu.end();
                         }
+                        if let Some(preset_menu) = ui.begin_menu("Quality Preset") {
+                            let mut preset_to_apply = None;
+                            for preset in RenderPreset::built_in() {
+                                if ui.menu_item(&preset.name) {
+                                    preset_to_apply = Some(preset);
+                                }
+                            }
+                            if !self.app_config.render_presets.is_empty() {
+                                ui.separator();
+                            }
+                            for (i, preset) in self.app_config.render_presets.iter().enumerate() {
+                                if ui.menu_item(&format!("{}##custom_preset_menu{}", preset.name, i)) {
+                                    preset_to_apply = Some(preset.clone());
+                                }
+                            }
+                            if let Some(preset) = preset_to_apply {
+                                self.apply_render_preset(&preset, ctx.world_renderer);
+                            }
+                            preset_menu.end();
+                        }
+                        if let Some(click_menu) = ui.begin_menu("Left Click") {
+                            for (label, mode) in [
+                                ("Rotate sun", LeftClickEditMode::MoveSun),
+                                ("Select objects", LeftClickEditMode::Select),
+                            ] {
+                                if ui.menu_item_config(label).selected(self.left_click_edit_mode == mode).build() {
+                                    self.left_click_edit_mode = mode;
+                                }
+                            }
+                            click_menu.end();
+                        }
+                        ui.separator();
+                        if ui
+                            .menu_item_config("Pause Scene")
+                            .shortcut(format!("{:?}", self.keymap_config.misc.pause))
+                            .selected(self.paused)
+                            .build()
+                        {
+                            pause_toggle_requested = true;
+                        }
                         view_menu.end();
                     }
                     bar.end();
                 }
 
+                if self.ui_windows.show_preferences {
+                    self.do_preferences_window(ui, persisted);
+                }
+
+                if self.ui_windows.show_layers {
+                    self.do_layers_window(ui, persisted);
+                }
+
+                if self.ui_windows.show_scatter {
+                    self.do_scatter_window(ui, persisted, ctx.world_renderer);
+                }
+
                 if ui.collapsing_header("RTX", TreeNodeFlags::DEFAULT_OPEN) {
+                    ui.text("Presets:");
+                    let mut preset_to_apply = None;
+                    let mut custom_to_delete = None;
+                    for preset in RenderPreset::built_in() {
+                        ui.same_line();
+                        if ui.button(&preset.name) {
+                            preset_to_apply = Some(preset);
+                        }
+                    }
+                    for (i, preset) in self.app_config.render_presets.iter().enumerate() {
+                        if ui.button(&format!("{}##custom_preset{}", preset.name, i)) {
+                            preset_to_apply = Some(preset.clone());
+                        }
+                        ui.same_line();
+                        if ui.small_button(&format!("{}##delete_preset{}", ICON_TRASH, i)) {
+                            custom_to_delete = Some(i);
+                        }
+                    }
+                    let save_preset = ui.button("Save current as preset");
+
+                    if save_preset {
+                        let name = crate::render_presets::next_custom_preset_name(&self.app_config.render_presets);
+                        let preset = RenderPreset::capture(name, ctx.world_renderer, &self.app_config);
+                        self.app_config.render_presets.push(preset);
+                    }
+                    if let Some(i) = custom_to_delete {
+                        self.app_config.render_presets.remove(i);
+                    }
+                    if let Some(preset) = preset_to_apply {
+                        self.apply_render_preset(&preset, ctx.world_renderer);
+                    } else if save_preset || custom_to_delete.is_some() {
+                        if let Err(err) = self.app_config.save() {
+                            log::error!("Failed to save app config: {:#}", err);
+                        }
+                    }
+                    ui.separator();
+
                     Drag::new("EV shift").range(-8.0, 12.0).speed(0.01).build(ui, &mut persisted.exposure.ev_shift);
 
                     ui.checkbox(
@@ -450,6 +1424,14 @@ impl RuntimeState {
                         &mut persisted.exposure.use_dynamic_adaptation,
                     );
 
+                    ui.same_line();
+                    if ui.button("Reset exposure") {
+                        ctx.world_renderer.dynamic_exposure.reset();
+                    }
+                    if ui.is_item_hovered() {
+                        ui.tooltip_text("Restart dynamic adaptation from the static exposure");
+                    }
+
                     Drag::new("Adaptation speed").range(-4.0, 4.0).speed(0.01).build(ui, &mut persisted.exposure.dynamic_adaptation_speed);
 
                     Drag::new("Luminance histogram low clip").range(0.0, 1.0).speed(0.001).build(ui, &mut persisted.exposure.dynamic_adaptation_low_clip);
@@ -479,11 +1461,65 @@ impl RuntimeState {
 
                     Drag::new("Camera smoothness").range(0.0, 20.0).speed(0.1).build(ui, &mut persisted.movement.camera_smoothness);
 
+                    ui.checkbox("Walk mode (ground clamp)", &mut persisted.movement.walk_mode);
+                    if persisted.movement.walk_mode {
+                        ui.indent();
+                        let walk_ground = &mut persisted.movement.walk_ground;
+                        if ui.radio_button_bool("Ground plane", *walk_ground == crate::walk::WalkGround::GroundPlane) {
+                            *walk_ground = crate::walk::WalkGround::GroundPlane;
+                        }
+                        ui.same_line();
+                        if ui.radio_button_bool("Scene bounds", *walk_ground == crate::walk::WalkGround::SceneBounds) {
+                            *walk_ground = crate::walk::WalkGround::SceneBounds;
+                        }
+                        Drag::new("Eye height").range(0.1, 10.0).speed(0.01).build(ui, &mut persisted.movement.eye_height);
+                        Drag::new("Collision radius").range(0.0, 5.0).speed(0.01).build(ui, &mut persisted.movement.collision_radius);
+                        ui.unindent();
+                    }
+
                     Drag::new("Sun rotation smoothness").range(0.0, 20.0).speed(0.1).build(ui, &mut persisted.movement.sun_rotation_smoothness);
 
+                    ui.checkbox("Keep smoothing in reference mode", &mut persisted.movement.smoothing_in_reference_mode);
+                    if ui.is_item_hovered() {
+                        ui.tooltip_text("By default, camera and sun smoothing are disabled while path tracing");
+                    }
+
                     Drag::new("Field of view").range(1.0, 120.0).speed(0.25).build(ui, &mut persisted.camera.vertical_fov);
 
+                    let mut infinite_far_plane = persisted.camera.z_far.is_infinite();
+                    if ui.checkbox("Infinite far plane", &mut infinite_far_plane) {
+                        if infinite_far_plane {
+                            persisted.camera.z_far = f32::INFINITY;
+                            self.app_config.far_plane.auto_fit = false;
+                        } else {
+                            // Empty scenes have nothing to fit to
+                            persisted.camera.z_far = 1000.0;
+                            self.fit_far_plane(persisted);
+                        }
+                    }
+                    if !infinite_far_plane {
+                        Drag::new("Far plane").range(1.0, 1_000_000.0).speed(1.0).build(ui, &mut persisted.camera.z_far);
+                        if ui.is_item_hovered() {
+                            ui.tooltip_text("Objects beyond this distance are culled");
+                        }
+                        ui.same_line();
+                        if ui.button("Fit to scene") {
+                            self.fit_far_plane(persisted);
+                        }
+                        ui.checkbox("Fit to the scene on load", &mut self.app_config.far_plane.auto_fit);
+                        if self.app_config.far_plane.auto_fit {
+                            ui.indent();
+                            Drag::new("Far plane margin").range(0.0, 10_000.0).speed(0.5).build(ui, &mut self.app_config.far_plane.margin);
+                            ui.unindent();
+                        }
+                    }
+
                     Drag::new("Sun size").range(0.0, 10.0).speed(0.02).build(ui, &mut persisted.light.sun.size_multiplier);
+                    Drag::new("Sun intensity").range(0.0, 100.0).speed(0.01).build(ui, &mut persisted.light.sun.intensity);
+                    let mut sun_color = persisted.light.sun.color.to_array();
+                    if ui.color_edit3("Sun color", &mut sun_color) {
+                        persisted.light.sun.color = Vec3::from(sun_color);
+                    }
 
                     /*ui.checkbox(
                         "Object motion blur",
@@ -626,382 +1662,152 @@ impl RuntimeState {
                             persisted.scene.ibl = None;
                         }
                     } else {
-                        ui.text("Drag a sphere-mapped .hdr/.exr to load as IBL");
+                        ui.text("Drag a sphere-mapped .hdr/.exr, or pick one in the Assets Browser, to load as IBL");
                     }
 
-                    // --- Hierarchy ---
-                    if ui.collapsing_header("Hierarchy", TreeNodeFlags::DEFAULT_OPEN)
-                    {
-                        for (idx, elem) in persisted.scene.elements.iter().enumerate() {
-                            let element_icon = Self::get_element_icon(elem);
-                            let element_name = if let Some(name) = elem.mesh_nodes.get(0).and_then(|n| n.name.as_ref()) {
-                                name.clone()
-                            } else {
-                                format!("{:?}", elem.source)
-                            };
-                            let element_label = create_icon_label(element_icon, &element_name);
-                            
-                            if elem.is_compound && !elem.mesh_nodes.is_empty() {
-                                ui.tree_node_config(format!("{}##{}", element_label, idx))
-                                    .build(|| {
-                                        for (nidx, node) in elem.mesh_nodes.iter().enumerate() {
-                                            let node_icon = Self::get_node_icon();
-                                            let node_name = if let Some(n) = &node.name {
-                                                n.clone()
-                                            } else {
-                                                format!("Node {}", nidx)
-                                            };
-                                            let node_label = create_icon_label(node_icon, &node_name);
-                                            ui.bullet_text(format!("{}##{}-{}", node_label, idx, nidx));
-                                        }
-                                    });
-                            } else {
-                                ui.bullet_text(format!("{}##{}", element_label, idx));
-                            }
-                        }
+                    let mut seed = persisted.scene.seed;
+                    if ui.input_scalar("Random seed", &mut seed).build() {
+                        self.set_scene_seed(persisted, seed);
+                        self.mark_unsaved_changes();
+                    }
+                    if ui.is_item_hovered() {
+                        ui.tooltip_text("Seeds randomized editing tools. Repeating an edit from the same seed gives the same result.");
+                    }
+                    ui.same_line();
+                    if ui.small_button("Restart") {
+                        self.set_scene_seed(persisted, seed);
                     }
 
-                    let mut element_to_remove = None;
-                    for (idx, elem) in persisted.scene.elements.iter_mut().enumerate() {
-                        ui.dummy([0.0, 10.0]);
-
-                        let id_token = ui.push_id_usize(idx);
-                        ui.text(format!("{:?}", elem.source));
-
-                        {
-                            ui.set_next_item_width(200.0);
+                    // --- Baked visibility ---
+                    match self.pvs() {
+                        _ if self.is_baking_pvs() => ui.text("PVS: baking..."),
+                        Some(pvs) => ui.text(format!(
+                            "PVS: {} static elements, {} cells of {:.1}m",
+                            pvs.elements.len(),
+                            pvs.cells.len(),
+                            pvs.cell_size
+                        )),
+                        None => ui.text("PVS: not baked"),
+                    }
+                    if ui.is_item_hovered() {
+                        ui.tooltip_text("Precomputed occlusion for elements that don't move. Elements moved or added after baking are culled every frame.");
+                    }
 
-                            let mut scale = elem.transform.scale.x;
-                            Drag::new("scale").range(0.001, 1000.0).speed(1.0).build(ui, &mut scale);
-                            if scale != elem.transform.scale.x {
-                                elem.transform.scale = Vec3::splat(scale);
-                            }
+                    Drag::new("PVS cell size")
+                        .range(0.5, 64.0)
+                        .speed(0.1)
+                        .build(ui, &mut self.app_config.occlusion_culling.pvs_cell_size);
+                    {
+                        let _disabled = ui.begin_disabled(self.is_baking_pvs());
+                        if ui.button("Bake PVS") {
+                            self.start_pvs_bake(persisted);
                         }
-
+                    }
+                    if self.pvs().is_some() {
                         ui.same_line();
-                        if ui.button("Delete") {
-                            element_to_remove = Some(idx);
-                        }
-
-                        // Position
-                        {
-                            ui.set_next_item_width(100.0);
-                            Drag::new("x").speed(0.01).build(ui, &mut elem.transform.position.x);
-
-                            ui.same_line();
-
-                            ui.set_next_item_width(100.0);
-                            Drag::new("y").speed(0.01).build(ui, &mut elem.transform.position.y);
-
-                            ui.same_line();
-
-                            ui.set_next_item_width(100.0);
-                            Drag::new("z").speed(0.01).build(ui, &mut elem.transform.position.z);
-                        }
-
-                        // Rotation
-                        {
-                            ui.set_next_item_width(100.0);
-                            Drag::new("rx").speed(0.1).build(ui, &mut elem.transform.rotation_euler_degrees.x);
-
-                            ui.same_line();
-
-                            ui.set_next_item_width(100.0);
-                            Drag::new("ry").speed(0.1).build(ui, &mut elem.transform.rotation_euler_degrees.y);
-
-                            ui.same_line();
-
-                            ui.set_next_item_width(100.0);
-                            Drag::new("rz").speed(0.1).build(ui, &mut elem.transform.rotation_euler_degrees.z);
+                        if ui.button("Clear PVS") {
+                            self.clear_pvs();
+                            self.mark_unsaved_changes();
                         }
-
-                        id_token.pop();
                     }
 
-                    if let Some(idx) = element_to_remove {
-                        let elem = persisted.scene.elements.remove(idx);
-                        ctx.world_renderer.remove_instance(elem.instance);
+                    // Elements are picked in the Outliner and edited in Attributes
+                    ui.text(format!("Elements: {}", persisted.scene.elements.len()));
+                    if !self.ui_windows.show_hierarchy {
+                        ui.same_line();
+                        if ui.small_button("Show Outliner") {
+                            self.ui_windows.show_hierarchy = true;
+                        }
                     }
-                }
-
-                // Frustum Culling settings
-                if ui.collapsing_header("Frustum Culling", TreeNodeFlags::DEFAULT_OPEN)
-                {
-                    ui.checkbox(
-                        "Enable frustum culling",
-                        &mut persisted.frustum_culling.enabled,
-                    );
-
-                    ui.checkbox(
-                        "Debug logging",
-                        &mut persisted.frustum_culling.debug_logging,
-                    );
-
-                    ui.checkbox(
-                        "Use sphere culling (faster)",
-                        &mut persisted.frustum_culling.use_sphere_culling,
-                    );
 
-                    // Culling method selection
-                    ui.text("Culling Method:");
-                    let current_method = &mut persisted.frustum_culling.culling_method;
-                    
-                    let mut is_emissive = matches!(current_method, crate::culling::CullingMethod::EmissiveMultiplier);
-                    let mut is_move_away = matches!(current_method, crate::culling::CullingMethod::MoveAway);
-                    let mut is_scale_zero = matches!(current_method, crate::culling::CullingMethod::ScaleToZero);
-                    
-                    if ui.checkbox("Emissive Multiplier", &mut is_emissive) && is_emissive {
-                        *current_method = crate::culling::CullingMethod::EmissiveMultiplier;
-                    }
-                    if ui.checkbox("Move Away", &mut is_move_away) && is_move_away {
-                        *current_method = crate::culling::CullingMethod::MoveAway;
+                    // Recovery for scenes left half-loaded
+                    if ui.button("Check renderer instances") {
+                        self.instance_check = Some(crate::scene_validation::find_instance_mismatches(
+                            &persisted.scene.elements,
+                            ctx.world_renderer.instance_handles(),
+                        ));
                     }
-                    if ui.checkbox("Scale to Zero", &mut is_scale_zero) && is_scale_zero {
-                        *current_method = crate::culling::CullingMethod::ScaleToZero;
+                    if ui.is_item_hovered() {
+                        ui.tooltip_text("Compare the scene's elements with the instances the renderer draws");
                     }
-                    
-                    // Show description for the selected method
-                    ui.separator();
-                    ui.text("Method Description:");
-                    match current_method {
-                        crate::culling::CullingMethod::EmissiveMultiplier => {
-                            ui.text_wrapped("Makes objects invisible by setting emissive to 0. Least GPU-efficient.");
-                        }
-                        crate::culling::CullingMethod::MoveAway => {
-                            ui.text_wrapped("Moves objects far away. More GPU-efficient as objects are naturally culled by depth.");
+                    match &self.instance_check {
+                        Some(check) if check.is_empty() => {
+                            ui.text_colored([0.0, 1.0, 0.0, 1.0], &format!("{} Scene and renderer agree", ICON_CHECK));
                         }
-                        crate::culling::CullingMethod::ScaleToZero => {
-                            ui.text_wrapped("Scales objects to zero size. Very GPU-efficient for triangle culling.");
+                        Some(check) => {
+                            ui.text_colored(
+                                [1.0, 0.8, 0.0, 1.0],
+                                &format!(
+                                    "{} {} orphan instance(s), {} element(s) without a live instance",
+                                    ICON_TRIANGLE_EXCLAMATION,
+                                    check.orphan_instances.len(),
+                                    check.dead_elements.len()
+                                ),
+                            );
+                            if ui.small_button("Fix") {
+                                let fixed = self.reconcile_instances(persisted, ctx.world_renderer);
+                                self.toasts.info(format!(
+                                    "Removed {} orphan instance(s), fixed {} element(s)",
+                                    fixed.orphan_instances.len(),
+                                    fixed.dead_elements.len()
+                                ));
+                                self.instance_check = Some(crate::scene_validation::find_instance_mismatches(
+                                    &persisted.scene.elements,
+                                    ctx.world_renderer.instance_handles(),
+                                ));
+                            }
                         }
+                        None => {}
                     }
+                }
 
-                    Drag::new("Default object size").range(0.1, 10.0).speed(0.1).build(ui, &mut persisted.frustum_culling.default_object_size);
-
-                    Drag::new("Log interval (frames)").range(30, 600).speed(10.0).build(ui, &mut persisted.frustum_culling.log_interval_frames);
-
-                    // Display culling statistics
-                    ui.text("Culling Stats:");
-                    
-                    let total_elements = persisted.scene.elements.len();
-                    let total_nodes: usize = persisted.scene.elements.iter()
-                        .map(|elem| if elem.is_compound { elem.mesh_nodes.len().max(1) } else { 1 })
-                        .sum();
-                    let compound_elements = persisted.scene.elements.iter()
-                        .filter(|elem| elem.is_compound)
-                        .count();
-                        
-                    ui.text(format!("Scene elements: {}", total_elements));
-                    ui.text(format!("Total mesh nodes: {}", total_nodes));
-                    ui.text(format!("GLTF compound objects: {}", compound_elements));
-                    
-                    if persisted.frustum_culling.enabled {
-                        ui.text_colored([0.0, 1.0, 0.0, 1.0], "Status: Enabled");
-                        ui.text(format!(
-                            "Method: {}",
-                            if persisted.frustum_culling.use_sphere_culling {
-                                "Sphere"
-                            } else {
-                                "AABB"
-                            }
-                        ));
-                    } else {
-                        ui.text_colored([1.0, 0.0, 0.0, 1.0], "Status: Disabled");
-                    }
+                // Resource Streaming Section
+                if imgui::CollapsingHeader::new("Resource Streaming")
+                    .default_open(false)
+                    .build(ui)
+                {
+                    self.streaming_integration.render_gui(ui, persisted);
                 }
 
-                // Occlusion Culling settings
-                if imgui::CollapsingHeader::new("Occlusion Culling")
+                if imgui::CollapsingHeader::new("Overrides")
                     .default_open(false)
                     .build(ui)
                 {
-                    ui.checkbox(
-                        "Enable occlusion culling",
-                        &mut persisted.occlusion_culling.enabled,
-                    );
+                    macro_rules! do_flag {
+                        ($flag:path, $name:literal) => {
+                            let mut is_set: bool =
+                                ctx.world_renderer.render_overrides.has_flag($flag);
+                            ui.checkbox($name, &mut is_set);
+                            ctx.world_renderer.render_overrides.set_flag($flag, is_set);
+                        };
+                    }
 
-                    ui.checkbox(
-                        "Debug visualization",
-                        &mut persisted.occlusion_culling.debug_visualize,
+                    do_flag!(
+                        RenderOverrideFlags::FORCE_FACE_NORMALS,
+                        "Force face normals"
+                    );
+                    do_flag!(RenderOverrideFlags::NO_NORMAL_MAPS, "No normal maps");
+                    do_flag!(
+                        RenderOverrideFlags::FLIP_NORMAL_MAP_YZ,
+                        "Flip normal map YZ"
                     );
+                    do_flag!(RenderOverrideFlags::NO_METAL, "No metal");
 
-                    Drag::new("Depth buffer resolution")
-                        .range(64, 512)
-                        .speed(1.0)
-                        .build(ui, &mut persisted.occlusion_culling.depth_buffer_resolution);
+                    ui.checkbox("Per-element debug colors", &mut self.show_element_debug_colors);
+                    if ui.is_item_hovered() {
+                        ui.tooltip_text("Outline and tint each element in a color derived from its ID");
+                    }
 
-                    Drag::new("Depth bias")
-                        .range(0.0, 0.1)
+                    Drag::new("Roughness scale")
+                        .range(0.0, 4.0)
                         .speed(0.001)
-                        .build(ui, &mut persisted.occlusion_culling.depth_bias);
-
-                    Drag::new("Sample count per object")
-                        .range(1, 8)
-                        .speed(1.0)
-                        .build(ui, &mut persisted.occlusion_culling.sample_count);
-
-                    Drag::new("Max test distance")
-                        .range(10.0, 5000.0)
-                        .speed(10.0)
-                        .build(ui, &mut persisted.occlusion_culling.max_test_distance);
-
-                    ui.separator();
-                    ui.text("Occlusion Culling Info:");
-                    ui.text_wrapped("Hides objects that are blocked by other objects closer to the camera. Works in combination with frustum culling for maximum efficiency.");
-                    
-                    if persisted.occlusion_culling.enabled {
-                        ui.text_colored([0.0, 1.0, 0.0, 1.0], "Status: Enabled");
-                        ui.text(format!("Depth resolution: {}x{}", 
-                            persisted.occlusion_culling.depth_buffer_resolution,
-                            persisted.occlusion_culling.depth_buffer_resolution));
-                    } else {
-                        ui.text_colored([1.0, 0.0, 0.0, 1.0], "Status: Disabled");
-                    }
+                        .build(
+                            ui,
+                            &mut ctx.world_renderer.render_overrides.material_roughness_scale,
+                        );
                 }
 
-                // Triangle Culling settings
-                if imgui::CollapsingHeader::new("Triangle Culling")
-                    .default_open(false)
-                    .build(ui)
-                {
-                    ui.checkbox(
-                        "Enable triangle culling",
-                        &mut persisted.triangle_culling.enabled,
-                    );
-                    ui.same_line();
-                    ui.text_colored([0.7, 0.7, 0.7, 1.0], "Per-triangle visibility tests");
-
-                    if persisted.triangle_culling.enabled {
-                        ui.separator();
-                        
-                        ui.checkbox(
-                            "Debug logging",
-                            &mut persisted.triangle_culling.debug_logging,
-                        );
-                        
-                            Drag::new("Log interval (frames)")
-                                .range(1, 300)
-                                .speed(1.0)
-                                .build(ui, &mut persisted.triangle_culling.log_interval_frames);                        ui.separator();
-                        ui.text("Culling Methods:");
-
-                        // Back-face culling checkbox
-                        let mut has_backface = persisted.triangle_culling.methods.contains(&crate::math::PrimitiveCullingMethod::BackFace);
-                        if ui.checkbox("Back-face culling", &mut has_backface) {
-                            if has_backface {
-                                if !persisted.triangle_culling.methods.contains(&crate::math::PrimitiveCullingMethod::BackFace) {
-                                    persisted.triangle_culling.methods.push(crate::math::PrimitiveCullingMethod::BackFace);
-                                }
-                            } else {
-                                persisted.triangle_culling.methods.retain(|m| m != &crate::math::PrimitiveCullingMethod::BackFace);
-                            }
-                        }
-                        ui.same_line();
-                        ui.text_colored([0.7, 0.7, 0.7, 1.0], "Hide faces pointing away");
-
-                        // Small triangle culling checkbox
-                        let mut has_small = persisted.triangle_culling.methods.contains(&crate::math::PrimitiveCullingMethod::SmallTriangle);
-                        if ui.checkbox("Small triangle culling", &mut has_small) {
-                            if has_small {
-                                if !persisted.triangle_culling.methods.contains(&crate::math::PrimitiveCullingMethod::SmallTriangle) {
-                                    persisted.triangle_culling.methods.push(crate::math::PrimitiveCullingMethod::SmallTriangle);
-                                }
-                            } else {
-                                persisted.triangle_culling.methods.retain(|m| m != &crate::math::PrimitiveCullingMethod::SmallTriangle);
-                            }
-                        }
-                        ui.same_line();
-                        ui.text_colored([0.7, 0.7, 0.7, 1.0], "Hide very small triangles");
-
-                        ui.separator();
-                        ui.text("Parameters:");
-
-                        Drag::new("Min triangle area (pixels)")
-                            .range(0.1, 100.0)
-                            .speed(0.1)
-                            .build(ui, &mut persisted.triangle_culling.min_triangle_area);
-
-                        Drag::new("Back-face epsilon")
-                            .range(0.0, 0.1)
-                            .speed(0.001)
-                            .build(ui, &mut persisted.triangle_culling.backface_epsilon);
-
-                        Drag::new("Max distance")
-                            .range(10.0, 5000.0)
-                            .speed(10.0)
-                            .build(ui, &mut persisted.triangle_culling.max_distance);
-                    }
-
-                    ui.separator();
-                    ui.text("Triangle Culling Info:");
-                    ui.text_wrapped("Culls individual triangles based on various criteria. Works at the finest level of detail, complementing object-level frustum and occlusion culling.");
-                    
-                    if persisted.triangle_culling.enabled {
-                        ui.text_colored([0.0, 1.0, 0.0, 1.0], "Status: Enabled");
-                        ui.text(format!("Active methods: {}", persisted.triangle_culling.methods.len()));
-                        
-                        // Show triangle culling statistics
-                        let triangle_stats = self.get_triangle_culling_statistics();
-                        if triangle_stats.triangles_tested > 0 {
-                            ui.separator();
-                            ui.text("Triangle Statistics:");
-                            ui.text(format!("Triangles tested: {}", triangle_stats.triangles_tested));
-                            ui.text(format!("Triangles rendered: {}", triangle_stats.triangles_rendered));
-                            ui.text(format!("Culling efficiency: {:.1}%", triangle_stats.culling_efficiency()));
-                            
-                            if triangle_stats.total_culled > 0 {
-                                ui.text(format!("  Backface: {}", triangle_stats.backface_culled));
-                                ui.text(format!("  Degenerate: {}", triangle_stats.degenerate_culled));
-                                ui.text(format!("  Small: {}", triangle_stats.small_triangle_culled));
-                                ui.text(format!("  View-dependent: {}", triangle_stats.view_dependent_culled));
-                            }
-                        }
-                    } else {
-                        ui.text_colored([1.0, 0.0, 0.0, 1.0], "Status: Disabled");
-                    }
-                }
-
-                // Resource Streaming Section
-                if imgui::CollapsingHeader::new("Resource Streaming")
-                    .default_open(false)
-                    .build(ui)
-                {
-                    self.streaming_integration.render_gui(ui);
-                }
-
-                if imgui::CollapsingHeader::new("Overrides")
-                    .default_open(false)
-                    .build(ui)
-                {
-                    macro_rules! do_flag {
-                        ($flag:path, $name:literal) => {
-                            let mut is_set: bool =
-                                ctx.world_renderer.render_overrides.has_flag($flag);
-                            ui.checkbox($name, &mut is_set);
-                            ctx.world_renderer.render_overrides.set_flag($flag, is_set);
-                        };
-                    }
-
-                    do_flag!(
-                        RenderOverrideFlags::FORCE_FACE_NORMALS,
-                        "Force face normals"
-                    );
-                    do_flag!(RenderOverrideFlags::NO_NORMAL_MAPS, "No normal maps");
-                    do_flag!(
-                        RenderOverrideFlags::FLIP_NORMAL_MAP_YZ,
-                        "Flip normal map YZ"
-                    );
-                    do_flag!(RenderOverrideFlags::NO_METAL, "No metal");
-
-                    Drag::new("Roughness scale")
-                        .range(0.0, 4.0)
-                        .speed(0.001)
-                        .build(
-                            ui,
-                            &mut ctx.world_renderer.render_overrides.material_roughness_scale,
-                        );
-                }
-
-                if imgui::CollapsingHeader::new("Sequence")
+                if imgui::CollapsingHeader::new("Sequence")
                     .default_open(false)
                     .build(ui)
                 {
@@ -1024,14 +1830,106 @@ impl RuntimeState {
                         .range(0.0, 4.0)
                         .speed(0.01)
                         .build(ui, &mut self.sequence_playback_speed);
+                    if ui.is_item_hovered() {
+                        ui.tooltip_text("Preview speed for this session; Time scale below is saved with the sequence");
+                    }
+
+                    ui.set_next_item_width(80.0);
+                    Drag::new("Time scale")
+                        .range(0.01, 10.0)
+                        .speed(0.01)
+                        .build(ui, &mut persisted.sequence.time_scale);
+                    if ui.is_item_hovered() {
+                        ui.tooltip_text("Stretches every key's duration; 2 plays the whole sequence twice as slow");
+                    }
+                    ui.same_line();
+                    ui.set_next_item_width(80.0);
+                    Drag::new("New key duration")
+                        .range(0.01, 60.0)
+                        .speed(0.01)
+                        .display_format("%.2fs")
+                        .build(ui, &mut persisted.sequence.default_key_duration);
 
                     if self.active_camera_key.is_some() {
                         ui.same_line();
                         if ui.button("Deselect key") {
                             self.active_camera_key = None;
+                            self.solo_camera_key = None;
+                        }
+
+                        ui.same_line();
+                        let mut solo = self.solo_camera_key.is_some();
+                        if ui.checkbox("Solo key", &mut solo) {
+                            if solo {
+                                self.start_solo_camera_key(persisted);
+                            } else {
+                                self.solo_camera_key = None;
+                            }
+                        }
+                        if ui.is_item_hovered() {
+                            ui.tooltip_text("Pin the view to the active key and edit it below.\nReplace writes the edits into the key.");
                         }
                     }
 
+                    if let (Some(idx), Some(mut value)) = (self.active_camera_key, self.solo_camera_key) {
+                        ui.separator();
+                        ui.text(format!("Key {}", idx));
+
+                        let unit = self.app_config.editing.display_unit;
+                        ui.text("Camera position:");
+                        ui.indent();
+                        drag_length(ui, "X##solo_pos", &mut value.camera_position.x, unit, 0.1, None);
+                        drag_length(ui, "Y##solo_pos", &mut value.camera_position.y, unit, 0.1, None);
+                        drag_length(ui, "Z##solo_pos", &mut value.camera_position.z, unit, 0.1, None);
+                        ui.unindent();
+
+                        // Directions are edited as angles so they stay unit length
+                        let drag_direction = |label: &str, dir: &mut Vec3, max_elevation: f32| {
+                            let (mut heading, mut elevation) = crate::misc::direction_to_heading_elevation(*dir);
+                            ui.text(label);
+                            ui.indent();
+                            let mut changed = Drag::new(format!("Heading##{}", label))
+                                .range(-180.0, 180.0)
+                                .speed(0.25)
+                                .display_format("%.1f°")
+                                .build(ui, &mut heading);
+                            changed |= Drag::new(format!("Elevation##{}", label))
+                                .range(-max_elevation, max_elevation)
+                                .speed(0.25)
+                                .display_format("%.1f°")
+                                .build(ui, &mut elevation);
+                            ui.unindent();
+
+                            if changed {
+                                *dir = crate::misc::direction_from_heading_elevation(heading, elevation);
+                            }
+                        };
+                        // Looking straight up or down leaves the camera's roll undefined
+                        drag_direction("Camera direction:", &mut value.camera_direction, 89.0);
+                        drag_direction("Towards sun:", &mut value.towards_sun, 90.0);
+
+                        if let Some(intensity) = &mut value.sun_intensity {
+                            Drag::new("Sun intensity##solo").range(0.0, 100.0).speed(0.01).build(ui, intensity);
+                        }
+                        if let Some(color) = &mut value.sun_color {
+                            let mut rgb = color.to_array();
+                            if ui.color_edit3("Sun color##solo", &mut rgb) {
+                                *color = Vec3::from(rgb);
+                            }
+                        }
+
+                        self.solo_camera_key = Some(value);
+
+                        if ui.button("Replace##solo") {
+                            self.commit_solo_camera_key(persisted);
+                        }
+                        ui.same_line();
+                        if ui.button("Revert##solo") {
+                            self.start_solo_camera_key(persisted);
+                        }
+                        ui.separator();
+                    }
+
                     enum Cmd {
                         JumpToKey(usize),
                         DeleteKey(usize),
@@ -1072,6 +1970,18 @@ impl RuntimeState {
                         ui.same_line();
                         ui.checkbox(&format!("Sun##{}", i), &mut item.value.towards_sun.is_some);
 
+                        ui.same_line();
+                        ui.checkbox(&format!("Int##{}", i), &mut item.value.sun_intensity.is_some);
+                        if ui.is_item_hovered() {
+                            ui.tooltip_text("Animate the sun intensity");
+                        }
+
+                        ui.same_line();
+                        ui.checkbox(&format!("Col##{}", i), &mut item.value.sun_color.is_some);
+                        if ui.is_item_hovered() {
+                            ui.tooltip_text("Animate the sun color");
+                        }
+
                         ui.same_line();
                         if ui.button(&format!("Delete##{}", i)) {
                             cmd = Cmd::DeleteKey(i);
@@ -1089,6 +1999,9 @@ impl RuntimeState {
                         Cmd::ReplaceKey(i) => self.replace_camera_sequence_key(persisted, i),
                         Cmd::None => {}
                     }
+
+                    // Preview the flythrough in the viewport while the panel is open
+                    self.draw_sequence_preview(ui, persisted, ctx);
                 }
 
                 if self.ui_windows.show_debug {
@@ -1170,18 +2083,19 @@ impl RuntimeState {
                         log::error!("Failed to save scene: {:#}", err);
                     } else {
                         log::info!("Scene saved successfully!");
-                        unsafe { UNSAVED_CHANGES = false; }
+                        self.scene_dirty = false;
                     }
                 }
+                if pause_toggle_requested {
+                    self.set_paused(!self.paused, ctx);
+                }
                 
                 } // Close the if self.show_gui block
                 
                 // Reset window positions flag after frame
-                unsafe {
-                    if RESET_WINDOW_POSITIONS {
-                        RESET_WINDOW_POSITIONS = false;
-                        log::info!("Window positions reset to default");
-                    }
+                if self.reset_window_positions {
+                    self.reset_window_positions = false;
+                    log::info!("Window positions reset to default");
                 }
                 });
                 log::debug!("ImGui frame callback completed");
@@ -1344,6 +2258,698 @@ impl RuntimeState {
         });
     }
 
+    /// Draws the sequence's camera path, with a marker per position key
+    fn draw_sequence_preview(&self, ui: &Ui, persisted: &PersistedState, ctx: &FrameContext) {
+        let path = persisted.sequence.sample_camera_path(16.0, 1024);
+        let view_proj = self.view_projection(persisted, ctx);
+        let display_size = ui.io().display_size;
+        let draw_list = ui.get_background_draw_list();
+
+        crate::selection::draw_world_polyline(
+            &draw_list,
+            &path,
+            [0.3, 0.8, 1.0, 0.8],
+            2.0,
+            &view_proj,
+            display_size,
+        );
+
+        for (i, position) in persisted.sequence.camera_position_keys() {
+            let (radius, color) = if Some(i) == self.active_camera_key {
+                (7.0, [1.0, 0.6, 0.1, 1.0])
+            } else {
+                (4.0, [0.3, 0.8, 1.0, 1.0])
+            };
+            crate::selection::draw_world_marker(&draw_list, position, radius, color, &view_proj, display_size);
+        }
+    }
+
+    /// Scene layers with visibility toggles. Membership is by tag, so layers also show up in Attributes.
+    fn do_layers_window(&mut self, ui: &Ui, persisted: &mut PersistedState) {
+        let reset_condition = self.window_reset_condition();
+
+        let selected = self.selected_element_index();
+        let scene = &mut persisted.scene;
+        let mut layer_to_remove = None;
+        let mut changed = false;
+
+        ui.window("Layers")
+            .opened(&mut self.ui_windows.show_layers)
+            .size([300.0, 300.0], reset_condition)
+            .position([10.0, 540.0], reset_condition)
+            .build(|| {
+                for (lidx, layer) in scene.layers.iter_mut().enumerate() {
+                    let id_token = ui.push_id_usize(lidx);
+
+                    let eye = if layer.visible { ICON_EYE } else { ICON_EYE_SLASH };
+                    if ui.small_button(&eye.to_string()) {
+                        layer.visible = !layer.visible;
+                        changed = true;
+                    }
+                    if ui.is_item_hovered() {
+                        ui.tooltip_text(if layer.visible { "Hide layer" } else { "Show layer" });
+                    }
+
+                    let members = scene.elements.iter().filter(|elem| elem.tags.contains(&layer.name)).count();
+                    ui.same_line();
+                    ui.text(&format!("{} ({})", layer.name, members));
+
+                    if let Some(elem) = selected.and_then(|idx| scene.elements.get_mut(idx)) {
+                        ui.same_line();
+                        if elem.tags.contains(&layer.name) {
+                            if ui.small_button("Remove selected") {
+                                elem.tags.retain(|tag| tag != &layer.name);
+                                changed = true;
+                            }
+                        } else if ui.small_button("Add selected") {
+                            crate::tags::add_tag(&mut elem.tags, &layer.name);
+                            changed = true;
+                        }
+                    }
+
+                    ui.same_line();
+                    if ui.small_button(&ICON_TRASH.to_string()) {
+                        layer_to_remove = Some(lidx);
+                    }
+                    if ui.is_item_hovered() {
+                        ui.tooltip_text("Delete the layer; its members keep the tag");
+                    }
+
+                    id_token.pop();
+                }
+
+                ui.separator();
+                ui.set_next_item_width(150.0);
+                let entered = ui.input_text("##new_layer", &mut self.ui_windows.new_layer)
+                    .hint("Layer name")
+                    .enter_returns_true(true)
+                    .build();
+                ui.same_line();
+                if (ui.button("New layer") || entered)
+                    && crate::layers::add_layer(&mut scene.layers, crate::layers::Layer::new(&self.ui_windows.new_layer))
+                {
+                    self.ui_windows.new_layer.clear();
+                    changed = true;
+                }
+                ui.text_colored([0.7, 0.7, 0.7, 1.0], "Elements tagged with a layer's name belong to it");
+            });
+
+        if let Some(lidx) = layer_to_remove {
+            scene.layers.remove(lidx);
+            changed = true;
+        }
+        if changed {
+            self.mark_unsaved_changes();
+        }
+    }
+
+    /// Scatter copies of the selected element, for set dressing
+    fn do_scatter_window(&mut self, ui: &Ui, persisted: &mut PersistedState, world_renderer: &mut kajiya::world_renderer::WorldRenderer) {
+        let reset_condition = self.window_reset_condition();
+
+        let selected = self.selected_element_index().and_then(|idx| persisted.scene.elements.get(idx));
+        let selected_position = selected.map(|elem| elem.transform.position);
+        let unit = self.app_config.editing.display_unit;
+        let mut scatter_requested = false;
+
+        let mut open = self.ui_windows.show_scatter;
+        ui.window("Scatter")
+            .opened(&mut open)
+            .size([320.0, 420.0], reset_condition)
+            .position([370.0, 30.0], reset_condition)
+            .build(|| {
+                match selected {
+                    Some(elem) => ui.text(format!("Source: {:?}", elem.source)),
+                    None => ui.text_disabled("Select an element to scatter"),
+                }
+                ui.separator();
+
+                let params = &mut self.ui_windows.scatter;
+                Drag::new("Count").range(1, 10_000).build(ui, &mut params.count);
+
+                if ui.radio_button_bool("On ground", params.area == ScatterArea::Ground) {
+                    params.area = ScatterArea::Ground;
+                }
+                ui.same_line();
+                if ui.radio_button_bool("In volume", params.area == ScatterArea::Volume) {
+                    params.area = ScatterArea::Volume;
+                }
+                if ui.is_item_hovered() {
+                    ui.tooltip_text("On ground places copies on the bottom face of the area");
+                }
+
+                let mut center = params.bounds.center();
+                let mut size = params.bounds.size();
+                ui.text("Area center:");
+                ui.indent();
+                drag_length(ui, "X##scatter_center", &mut center.x, unit, 0.1, None);
+                drag_length(ui, "Y##scatter_center", &mut center.y, unit, 0.1, None);
+                drag_length(ui, "Z##scatter_center", &mut center.z, unit, 0.1, None);
+                if let Some(position) = selected_position {
+                    if ui.small_button("Center on selection") {
+                        center = position;
+                    }
+                }
+                ui.unindent();
+                ui.text("Area size:");
+                ui.indent();
+                drag_length(ui, "X##scatter_size", &mut size.x, unit, 0.1, None);
+                drag_length(ui, "Y##scatter_size", &mut size.y, unit, 0.1, None);
+                drag_length(ui, "Z##scatter_size", &mut size.z, unit, 0.1, None);
+                ui.unindent();
+                params.bounds = crate::math::Aabb::from_center_size(center, size.max(Vec3::ZERO));
+
+                let [min_scale, max_scale] = &mut params.scale_range;
+                Drag::new("Min scale").range(0.001, 100.0).speed(0.01).build(ui, min_scale);
+                Drag::new("Max scale").range(0.001, 100.0).speed(0.01).build(ui, max_scale);
+
+                ui.text("Rotation range (degrees):");
+                ui.indent();
+                Drag::new("X##scatter_rot").range(0.0, 180.0).build(ui, &mut params.rotation_range.x);
+                Drag::new("Y##scatter_rot").range(0.0, 180.0).build(ui, &mut params.rotation_range.y);
+                Drag::new("Z##scatter_rot").range(0.0, 180.0).build(ui, &mut params.rotation_range.z);
+                ui.unindent();
+
+                drag_length(ui, "Min spacing", &mut params.min_spacing, unit, 0.05, None);
+                params.min_spacing = params.min_spacing.max(0.0);
+
+                ui.separator();
+                ui.text_disabled(format!("Seed {} (set in the Scene panel)", persisted.scene.seed));
+                if selected.is_some() && ui.button("Scatter") {
+                    scatter_requested = true;
+                }
+            });
+        self.ui_windows.show_scatter = open;
+
+        if scatter_requested {
+            let params = self.ui_windows.scatter.clone();
+            match self.scatter_selected(persisted, world_renderer, &params) {
+                Ok(count) => {
+                    log::info!("Scattered {} copies", count);
+                    if count < params.count as usize {
+                        self.toasts.error(format!(
+                            "Only {} of {} copies fit with the minimum spacing",
+                            count, params.count
+                        ));
+                    }
+                    self.mark_unsaved_changes();
+                }
+                Err(err) => {
+                    log::error!("Scatter failed: {:#}", err);
+                    self.toasts.error(format!("Scatter failed: {:#}", err));
+                }
+            }
+        }
+    }
+
+    /// Editor preferences that persist across scenes, stored in the app config
+    fn do_preferences_window(&mut self, ui: &Ui, persisted: &PersistedState) {
+        let reset_condition = self.window_reset_condition();
+
+        // Read up front; the window closure below borrows parts of `self` mutably
+        let triangle_stats = self.get_triangle_culling_statistics().clone();
+        let triangle_average = self.get_triangle_culling_average();
+        let occlusion_resolution = self.get_occlusion_culling_statistics().depth_buffer_resolution;
+
+        let mut open = self.ui_windows.show_preferences;
+        ui.window("Preferences")
+            .opened(&mut open)
+            .size([420.0, 600.0], reset_condition)
+            .position([370.0, 30.0], reset_condition)
+            .build(|| {
+                if ui.collapsing_header("Selection & Editing", TreeNodeFlags::DEFAULT_OPEN) {
+                    let highlight = &mut self.app_config.selection_highlight;
+                    ui.checkbox("Highlight selected element", &mut highlight.enabled);
+
+                    if highlight.enabled {
+                        ui.indent();
+                        if ui.radio_button_bool("Wireframe", highlight.style == crate::selection::SelectionHighlightStyle::Wireframe) {
+                            highlight.style = crate::selection::SelectionHighlightStyle::Wireframe;
+                        }
+                        ui.same_line();
+                        if ui.radio_button_bool("Tint", highlight.style == crate::selection::SelectionHighlightStyle::Tint) {
+                            highlight.style = crate::selection::SelectionHighlightStyle::Tint;
+                        }
+
+                        ui.color_edit4("Highlight color", &mut highlight.color);
+
+                        if highlight.style == crate::selection::SelectionHighlightStyle::Tint {
+                            Drag::new("Tint opacity").range(0.0, 1.0).speed(0.01).build(ui, &mut highlight.tint_opacity);
+                        }
+                        ui.unindent();
+                    }
+
+                    Drag::new("Nudge step").range(0.001, 10.0).speed(0.01).build(ui, &mut self.app_config.editing.nudge_step);
+                    if ui.is_item_hovered() {
+                        ui.tooltip_text("Distance moved by the nudge keys (arrows and Page Up/Down by default).\nHold Shift for coarse steps, Ctrl for fine steps.");
+                    }
+
+                    ui.text("Place dropped meshes:");
+                    let editing = &mut self.app_config.editing;
+                    for (label, placement) in [
+                        ("At origin", DropPlacement::Origin),
+                        ("In front of camera", DropPlacement::InFrontOfCamera),
+                        ("On surface in view", DropPlacement::GroundHit),
+                    ] {
+                        ui.same_line();
+                        if ui.radio_button_bool(label, editing.drop_placement == placement) {
+                            editing.drop_placement = placement;
+                        }
+                    }
+                    if editing.drop_placement != DropPlacement::Origin {
+                        Drag::new("Drop distance").range(0.1, 100.0).speed(0.05).build(ui, &mut editing.drop_distance);
+                        if ui.is_item_hovered() {
+                            ui.tooltip_text("Also used when nothing is in view to drop onto.\nHold Alt while dropping to place at the origin.");
+                        }
+                    }
+                    let mut duplicate_offset = editing.duplicate_offset.to_array();
+                    if Drag::new("Duplicate offset").speed(0.01).build_array(ui, &mut duplicate_offset) {
+                        editing.duplicate_offset = Vec3::from(duplicate_offset);
+                    }
+                }
+
+                // Frustum Culling settings
+                if ui.collapsing_header("Frustum Culling", TreeNodeFlags::DEFAULT_OPEN)
+                {
+                    ui.checkbox(
+                        "Enable frustum culling",
+                        &mut self.app_config.frustum_culling.enabled,
+                    );
+
+                    ui.checkbox(
+                        "Disable all culling in reference mode",
+                        &mut self.app_config.frustum_culling.disable_in_reference_mode,
+                    );
+
+                    if ui.is_item_hovered() {
+                        ui.tooltip_text("Keeps frustum, occlusion and triangle culling from altering what the path tracer sees");
+                    }
+
+                    ui.checkbox("Preview culling", &mut self.app_config.frustum_culling.preview);
+                    if ui.is_item_hovered() {
+                        ui.tooltip_text("Keep culled objects visible and outline them in red instead of hiding them");
+                    }
+
+                    ui.checkbox(
+                        "Debug logging",
+                        &mut self.app_config.frustum_culling.debug_logging,
+                    );
+
+                    if self.app_config.frustum_culling.debug_logging {
+                        ui.indent();
+                        let log_format = &mut self.app_config.frustum_culling.log_format;
+                        if ui.radio_button_bool("Log as text", *log_format == crate::culling::CullingLogFormat::Text) {
+                            *log_format = crate::culling::CullingLogFormat::Text;
+                        }
+                        ui.same_line();
+                        if ui.radio_button_bool("Log as CSV", *log_format == crate::culling::CullingLogFormat::Csv) {
+                            *log_format = crate::culling::CullingLogFormat::Csv;
+                        }
+
+                        if *log_format == crate::culling::CullingLogFormat::Csv {
+                            ui.set_next_item_width(200.0);
+                            ui.input_text("CSV file", &mut self.app_config.frustum_culling.csv_log_path)
+                                .build();
+                        }
+                        ui.unindent();
+                    }
+
+                    ui.checkbox(
+                        "Auto sphere/AABB by screen size",
+                        &mut self.app_config.frustum_culling.auto_sphere_culling,
+                    );
+
+                    if self.app_config.frustum_culling.auto_sphere_culling {
+                        ui.indent();
+                        Drag::new("Max sphere screen size")
+                            .range(0.0, 1.0)
+                            .speed(0.005)
+                            .build(ui, &mut self.app_config.frustum_culling.auto_sphere_max_screen_size);
+                        if ui.is_item_hovered() {
+                            ui.tooltip_text("Objects covering less than this fraction of the view height use sphere tests");
+                        }
+                        ui.unindent();
+                    } else {
+                        ui.checkbox(
+                            "Use sphere culling (faster)",
+                            &mut self.app_config.frustum_culling.use_sphere_culling,
+                        );
+                    }
+
+                    // Culling method selection
+                    ui.text("Culling Method:");
+                    let current_method = &mut self.app_config.frustum_culling.culling_method;
+                    
+                    let mut is_emissive = matches!(current_method, crate::culling::CullingMethod::EmissiveMultiplier);
+                    let mut is_move_away = matches!(current_method, crate::culling::CullingMethod::MoveAway);
+                    let mut is_scale_zero = matches!(current_method, crate::culling::CullingMethod::ScaleToZero);
+                    
+                    if ui.checkbox("Emissive Multiplier", &mut is_emissive) && is_emissive {
+                        *current_method = crate::culling::CullingMethod::EmissiveMultiplier;
+                    }
+                    if ui.checkbox("Move Away", &mut is_move_away) && is_move_away {
+                        *current_method = crate::culling::CullingMethod::MoveAway;
+                    }
+                    if ui.checkbox("Scale to Zero", &mut is_scale_zero) && is_scale_zero {
+                        *current_method = crate::culling::CullingMethod::ScaleToZero;
+                    }
+                    
+                    // Show description for the selected method
+                    ui.separator();
+                    ui.text("Method Description:");
+                    match current_method {
+                        crate::culling::CullingMethod::EmissiveMultiplier => {
+                            ui.text_wrapped("Makes objects invisible by setting emissive to 0. Least GPU-efficient.");
+
+                            Drag::new("Emissive fade band")
+                                .range(0.0, 1000.0)
+                                .speed(0.5)
+                                .build(ui, &mut self.app_config.frustum_culling.emissive_fade_band);
+                            if ui.is_item_hovered() {
+                                ui.tooltip_text("Ramp emissive down over this many meters before the far plane, instead of snapping it off.\n0 disables the fade.");
+                            }
+                        }
+                        crate::culling::CullingMethod::MoveAway => {
+                            ui.text_wrapped("Moves objects far away. More GPU-efficient as objects are naturally culled by depth.");
+                        }
+                        crate::culling::CullingMethod::ScaleToZero => {
+                            ui.text_wrapped("Scales objects to zero size. Very GPU-efficient for triangle culling.");
+                        }
+                    }
+
+                    Drag::new("Frustum margin").range(-10.0, 10.0).speed(0.01).build(ui, &mut self.app_config.frustum_culling.frustum_margin);
+                    if ui.is_item_hovered() {
+                        ui.tooltip_text("Grow bounds by this many meters before frustum tests, to avoid pop-in at the screen edges.\nNegative values cull more aggressively.");
+                    }
+
+                    Drag::new("Update every N frames").range(0, 60).speed(0.1).build(ui, &mut self.app_config.frustum_culling.update_interval_frames);
+                    if ui.is_item_hovered() {
+                        ui.tooltip_text("Reuse culling results in between, trading a little latency for CPU time.\n1 updates every frame; 0 only when the camera moves.");
+                    }
+                    if self.app_config.frustum_culling.update_interval_frames != 1 {
+                        ui.indent();
+                        Drag::new("Camera move threshold").range(0.0, 10.0).speed(0.01).build(ui, &mut self.app_config.frustum_culling.update_move_threshold);
+                        Drag::new("Camera turn threshold (deg)").range(0.0, 45.0).speed(0.1).build(ui, &mut self.app_config.frustum_culling.update_turn_threshold_degrees);
+                        if ui.is_item_hovered() {
+                            ui.tooltip_text("Moving or turning the camera past these updates culling right away");
+                        }
+                        ui.unindent();
+                    }
+
+                    Drag::new("Max scale ratio for spheres").range(1.0, 100.0).speed(0.1).build(ui, &mut self.app_config.frustum_culling.max_sphere_scale_ratio);
+                    if ui.is_item_hovered() {
+                        ui.tooltip_text("Elements stretched more than this along one axis use AABB tests instead of spheres");
+                    }
+
+                    Drag::new("Default object size").range(0.1, 10.0).speed(0.1).build(ui, &mut self.app_config.frustum_culling.default_object_size);
+                    if ui.is_item_hovered() {
+                        ui.tooltip_text("Bounds guessed for elements without computed ones");
+                    }
+
+                    if ui.checkbox("Computed bounds (this scene)", &mut persisted.scene.auto_bounds) {
+                        self.mark_unsaved_changes();
+                    }
+                    if ui.is_item_hovered() {
+                        ui.tooltip_text("Cull with each mesh's own bounds. The default object size is only used for meshes\nwhose geometry can't be read. Off guesses the default size for everything.");
+                    }
+                    let guessed = self.guessed_bounds_count(persisted);
+                    if guessed > 0 {
+                        ui.text_colored(
+                            [1.0, 0.8, 0.0, 1.0],
+                            &format!("{} {} element(s) culled with guessed bounds", ICON_TRIANGLE_EXCLAMATION, guessed),
+                        );
+                    }
+
+                    Drag::new("Log interval (frames)").range(30, 600).speed(10.0).build(ui, &mut self.app_config.frustum_culling.log_interval_frames);
+
+                    // Display culling statistics
+                    ui.text("Culling Stats:");
+                    
+                    let total_elements = persisted.scene.elements.len();
+                    let total_nodes: usize = persisted.scene.elements.iter()
+                        .map(|elem| if elem.is_compound { elem.mesh_nodes.len().max(1) } else { 1 })
+                        .sum();
+                    let compound_elements = persisted.scene.elements.iter()
+                        .filter(|elem| elem.is_compound)
+                        .count();
+                        
+                    ui.text(format!("Scene elements: {}", total_elements));
+                    ui.text(format!("Total mesh nodes: {}", total_nodes));
+                    ui.text(format!("GLTF compound objects: {}", compound_elements));
+                    
+                    if self.app_config.frustum_culling.enabled {
+                        ui.text_colored([0.0, 1.0, 0.0, 1.0], "Status: Enabled");
+                        ui.text(format!(
+                            "Method: {}",
+                            if self.app_config.frustum_culling.use_sphere_culling {
+                                "Sphere"
+                            } else {
+                                "AABB"
+                            }
+                        ));
+                    } else {
+                        ui.text_colored([1.0, 0.0, 0.0, 1.0], "Status: Disabled");
+                    }
+                }
+
+                // Occlusion Culling settings
+                if imgui::CollapsingHeader::new("Occlusion Culling")
+                    .default_open(false)
+                    .build(ui)
+                {
+                    ui.checkbox(
+                        "Enable occlusion culling",
+                        &mut self.app_config.occlusion_culling.enabled,
+                    );
+
+                    ui.checkbox(
+                        "Debug visualization",
+                        &mut self.app_config.occlusion_culling.debug_visualize,
+                    );
+
+                    ui.checkbox("Preview culling##occlusion", &mut self.app_config.frustum_culling.preview);
+                    if ui.is_item_hovered() {
+                        ui.tooltip_text("Keep culled objects visible and outline them in red instead of hiding them.\nShared with the Frustum Culling setting.");
+                    }
+
+                    let occlusion = &mut self.app_config.occlusion_culling;
+                    ui.checkbox("Adaptive depth resolution", &mut occlusion.adaptive_resolution);
+                    if ui.is_item_hovered() {
+                        ui.tooltip_text("Lower the resolution when over the frame time budget, raise it when there's headroom");
+                    }
+                    if occlusion.adaptive_resolution {
+                        ui.indent();
+                        Drag::new("Min resolution").range(16, 1024).speed(1.0).build(ui, &mut occlusion.min_adaptive_resolution);
+                        Drag::new("Max resolution").range(16, 1024).speed(1.0).build(ui, &mut occlusion.max_adaptive_resolution);
+                        occlusion.max_adaptive_resolution = occlusion.max_adaptive_resolution.max(occlusion.min_adaptive_resolution);
+                        Drag::new("Frame time budget (ms)").range(1.0, 100.0).speed(0.1).build(ui, &mut occlusion.frame_time_budget_ms);
+                        ui.unindent();
+                    } else {
+                        Drag::new("Depth buffer resolution")
+                            .range(64, 512)
+                            .speed(1.0)
+                            .build(ui, &mut occlusion.depth_buffer_resolution);
+                    }
+
+                    Drag::new("Depth bias")
+                        .range(0.0, 0.1)
+                        .speed(0.001)
+                        .build(ui, &mut self.app_config.occlusion_culling.depth_bias);
+
+                    Drag::new("Sample count per object")
+                        .range(1, 8)
+                        .speed(1.0)
+                        .build(ui, &mut self.app_config.occlusion_culling.sample_count);
+
+                    Drag::new("Max test distance")
+                        .range(10.0, 5000.0)
+                        .speed(10.0)
+                        .build(ui, &mut self.app_config.occlusion_culling.max_test_distance);
+
+                    ui.separator();
+                    ui.text("Occlusion Culling Info:");
+                    ui.text_wrapped("Hides objects that are blocked by other objects closer to the camera. Works in combination with frustum culling for maximum efficiency.");
+                    
+                    if self.app_config.occlusion_culling.enabled {
+                        ui.text_colored([0.0, 1.0, 0.0, 1.0], "Status: Enabled");
+                        ui.text(format!("Depth resolution: {}x{}", 
+                            occlusion_resolution,
+                            occlusion_resolution));
+                    } else {
+                        ui.text_colored([1.0, 0.0, 0.0, 1.0], "Status: Disabled");
+                    }
+                }
+
+                // Triangle Culling settings
+                if imgui::CollapsingHeader::new("Triangle Culling")
+                    .default_open(false)
+                    .build(ui)
+                {
+                    ui.checkbox(
+                        "Enable triangle culling",
+                        &mut self.app_config.triangle_culling.enabled,
+                    );
+                    ui.same_line();
+                    ui.text_colored([0.7, 0.7, 0.7, 1.0], "Per-triangle visibility tests");
+
+                    if self.app_config.triangle_culling.enabled {
+                        ui.separator();
+                        
+                        ui.checkbox(
+                            "Debug logging",
+                            &mut self.app_config.triangle_culling.debug_logging,
+                        );
+                        
+                            Drag::new("Log interval (frames)")
+                                .range(1, 300)
+                                .speed(1.0)
+                                .build(ui, &mut self.app_config.triangle_culling.log_interval_frames);                        ui.separator();
+                        ui.text("Culling Methods:");
+
+                        // Back-face culling checkbox
+                        let mut has_backface = self.app_config.triangle_culling.methods.contains(&crate::math::PrimitiveCullingMethod::BackFace);
+                        if ui.checkbox("Back-face culling", &mut has_backface) {
+                            if has_backface {
+                                if !self.app_config.triangle_culling.methods.contains(&crate::math::PrimitiveCullingMethod::BackFace) {
+                                    self.app_config.triangle_culling.methods.push(crate::math::PrimitiveCullingMethod::BackFace);
+                                }
+                            } else {
+                                self.app_config.triangle_culling.methods.retain(|m| m != &crate::math::PrimitiveCullingMethod::BackFace);
+                            }
+                        }
+                        ui.same_line();
+                        ui.text_colored([0.7, 0.7, 0.7, 1.0], "Hide faces pointing away");
+
+                        // Small triangle culling checkbox
+                        let mut has_small = self.app_config.triangle_culling.methods.contains(&crate::math::PrimitiveCullingMethod::SmallTriangle);
+                        if ui.checkbox("Small triangle culling", &mut has_small) {
+                            if has_small {
+                                if !self.app_config.triangle_culling.methods.contains(&crate::math::PrimitiveCullingMethod::SmallTriangle) {
+                                    self.app_config.triangle_culling.methods.push(crate::math::PrimitiveCullingMethod::SmallTriangle);
+                                }
+                            } else {
+                                self.app_config.triangle_culling.methods.retain(|m| m != &crate::math::PrimitiveCullingMethod::SmallTriangle);
+                            }
+                        }
+                        ui.same_line();
+                        ui.text_colored([0.7, 0.7, 0.7, 1.0], "Hide very small triangles");
+
+                        ui.separator();
+                        ui.text("Parameters:");
+
+                        Drag::new("Min triangle area (pixels)")
+                            .range(0.1, 100.0)
+                            .speed(0.1)
+                            .build(ui, &mut self.app_config.triangle_culling.min_triangle_area);
+
+                        Drag::new("Back-face epsilon")
+                            .range(0.0, 0.1)
+                            .speed(0.001)
+                            .build(ui, &mut self.app_config.triangle_culling.backface_epsilon);
+
+                        Drag::new("Max distance")
+                            .range(10.0, 5000.0)
+                            .speed(10.0)
+                            .build(ui, &mut self.app_config.triangle_culling.max_distance);
+
+                        Drag::new("Max triangles per frame")
+                            .range(0, 1_000_000)
+                            .speed(1000.0)
+                            .build(ui, &mut self.app_config.triangle_culling.max_triangles_per_frame);
+                        if ui.is_item_hovered() {
+                            ui.tooltip_text("Larger scenes are sampled, a different subset each frame. 0 tests every triangle.");
+                        }
+
+                        Drag::new("Statistics window (frames)")
+                            .range(1, 600)
+                            .speed(1.0)
+                            .build(ui, &mut self.app_config.triangle_culling.stats_window_frames);
+                    }
+
+                    ui.separator();
+                    ui.text("Triangle Culling Info:");
+                    ui.text_wrapped("Culls individual triangles based on various criteria. Works at the finest level of detail, complementing object-level frustum and occlusion culling.");
+                    
+                    if self.app_config.triangle_culling.enabled {
+                        ui.text_colored([0.0, 1.0, 0.0, 1.0], "Status: Enabled");
+                        ui.text(format!("Active methods: {}", self.app_config.triangle_culling.methods.len()));
+                        
+                        // Show triangle culling statistics
+                        if triangle_average.triangles_tested > 0.0 {
+                            ui.separator();
+                            ui.text(format!("Triangle Statistics (average of {} frames):", triangle_average.frames));
+                            ui.text(format!("Triangles tested: {:.0}", triangle_average.triangles_tested));
+                            ui.text(format!("Triangles rendered: {:.0}", triangle_average.triangles_rendered));
+                            ui.text(format!("Culling efficiency: {:.1}%", triangle_average.culling_efficiency));
+                            
+                            if triangle_stats.total_culled > 0 {
+                                ui.text("Last frame:");
+                                ui.text(format!("  Backface: {}", triangle_stats.backface_culled));
+                                ui.text(format!("  Degenerate: {}", triangle_stats.degenerate_culled));
+                                ui.text(format!("  Small: {}", triangle_stats.small_triangle_culled));
+                                ui.text(format!("  View-dependent: {}", triangle_stats.view_dependent_culled));
+                            }
+                        }
+                    } else {
+                        ui.text_colored([1.0, 0.0, 0.0, 1.0], "Status: Disabled");
+                    }
+                }
+
+                if ui.collapsing_header("Interface", TreeNodeFlags::DEFAULT_OPEN) {
+                    let gui = &mut self.app_config.gui;
+                    Drag::new("UI scale")
+                        .range(crate::app_config::GuiConfig::MIN_SCALE, crate::app_config::GuiConfig::MAX_SCALE)
+                        .speed(0.01)
+                        .display_format("%.2f")
+                        .build(ui, &mut gui.scale);
+                    ui.checkbox("Match display scale", &mut gui.match_display_scale);
+                    if ui.is_item_hovered() {
+                        ui.tooltip_text("Multiply the UI scale by the monitor's scale factor (DPI).\nText stays sharpest near that factor.");
+                    }
+                }
+
+                if ui.collapsing_header("Performance", TreeNodeFlags::DEFAULT_OPEN) {
+                    Drag::new("Large scene element cap").range(0, 1_000_000).speed(10.0).build(ui, &mut self.app_config.large_scene.element_soft_cap);
+                    if ui.is_item_hovered() {
+                        ui.tooltip_text("Past this many elements, triangle culling, per-element debug colors\nand Outliner node lists are turned off. 0 for no cap.");
+                    }
+
+                    ui.checkbox("Analyze glTF nodes automatically", &mut self.app_config.auto_analyze_gltf);
+                    if ui.is_item_hovered() {
+                        ui.tooltip_text("Parse glTF files for per-node culling as they're added.\nWhen off, use Analyze in the Attributes window.");
+                    }
+
+                    let idle_throttle = &mut self.app_config.idle_throttle;
+                    ui.checkbox("Cap FPS when unfocused", &mut idle_throttle.enabled);
+                    if idle_throttle.enabled {
+                        ui.indent();
+                        Drag::new("Idle max FPS").range(1, MAX_FPS_LIMIT).build(ui, &mut idle_throttle.max_fps);
+                        ui.checkbox("Also cap while path tracing", &mut idle_throttle.throttle_path_tracing);
+                        ui.unindent();
+                    }
+
+                    ui.checkbox("Power saver", &mut idle_throttle.power_saver);
+                    if ui.is_item_hovered() {
+                        ui.tooltip_text("Lower the frame rate while there's no input and nothing moves.\nFull rate resumes on any activity. Not applied while path tracing.");
+                    }
+                    if idle_throttle.power_saver {
+                        ui.indent();
+                        Drag::new("Static max FPS").range(1, MAX_FPS_LIMIT).build(ui, &mut idle_throttle.power_saver_fps);
+                        ui.unindent();
+                    }
+                }
+            });
+
+        // Drags change the config every frame, so it's written once the window is closed
+        // (and again on exit, in case it never is)
+        if !open {
+            self.ui_windows.show_preferences = false;
+            if let Err(err) = self.app_config.save() {
+                log::error!("Failed to save app config: {:#}", err);
+            }
+        }
+    }
+
     /// Show shader compilation progress popup
     fn show_shader_compilation_popup(ui: &imgui::Ui) {
         if let Ok(tracker) = GLOBAL_SHADER_PROGRESS.lock() {
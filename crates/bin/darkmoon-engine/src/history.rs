@@ -0,0 +1,289 @@
+use crate::persisted::{SceneElement, SceneElementTransform};
+
+/// A reversible scene edit. Indices are into `SceneState::elements` as they were right after
+/// the edit; they stay valid because every change to the element list goes through the history.
+#[derive(Clone)]
+pub enum EditAction {
+    TransformChanged {
+        idx: usize,
+        old: SceneElementTransform,
+        new: SceneElementTransform,
+    },
+    /// `local_transform` of mesh node `node` of the element at `idx`, relative to the element
+    NodeTransformChanged {
+        idx: usize,
+        node: usize,
+        old: SceneElementTransform,
+        new: SceneElementTransform,
+    },
+    /// The element now at `idx` was added
+    ElementAdded { idx: usize },
+    /// `element` was removed from `idx`
+    ElementRemoved { element: SceneElement, idx: usize },
+    /// Edits made together, e.g. to a multi-selection, undone in one step
+    Batch(Vec<EditAction>),
+}
+
+/// Undo and redo stacks for scene edits. Undoing or redoing an action reverts it, and the
+/// action that reverts that goes on the opposite stack.
+#[derive(Default)]
+pub struct EditHistory {
+    undo: Vec<EditAction>,
+    redo: Vec<EditAction>,
+    /// What is being dragged; further changes to it extend the last entry
+    open_gesture: Option<Gesture>,
+}
+
+#[derive(PartialEq)]
+enum Gesture {
+    /// The transforms of these elements
+    Elements(Vec<usize>),
+    /// A node's transform, as `(idx, node)`
+    Node(usize, usize),
+}
+
+impl EditHistory {
+    /// Oldest entries are dropped past this
+    pub const MAX_ENTRIES: usize = 100;
+
+    /// Record a new edit. Anything undone before it can no longer be redone.
+    pub fn push(&mut self, action: EditAction) {
+        self.open_gesture = None;
+        self.redo.clear();
+        self.push_undo(action);
+    }
+
+    /// Record a transform edit, merged with the previous one while the same element is still
+    /// being dragged, so one undo reverts the whole gesture
+    pub fn record_transform(&mut self, idx: usize, old: SceneElementTransform, new: SceneElementTransform) {
+        self.record_transforms(vec![(idx, old, new)]);
+    }
+
+    /// `record_transform` for elements edited together, as `(idx, old, new)`
+    pub fn record_transforms(&mut self, changes: Vec<(usize, SceneElementTransform, SceneElementTransform)>) {
+        let gesture = Gesture::Elements(changes.iter().map(|(idx, _, _)| *idx).collect());
+
+        if self.open_gesture.as_ref() == Some(&gesture) {
+            let last = match self.undo.last_mut() {
+                Some(EditAction::Batch(actions)) => actions.iter_mut().collect(),
+                Some(action) => vec![action],
+                None => Vec::new(),
+            };
+            let matches_gesture = last.len() == changes.len()
+                && last.iter().zip(&changes).all(|(action, (idx, _, _))| {
+                    matches!(action, EditAction::TransformChanged { idx: last_idx, .. } if last_idx == idx)
+                });
+
+            if matches_gesture {
+                for (action, (_, _, new)) in last.into_iter().zip(changes) {
+                    if let EditAction::TransformChanged { new: last_new, .. } = action {
+                        *last_new = new;
+                    }
+                }
+                self.redo.clear();
+                return;
+            }
+        }
+
+        let mut actions: Vec<EditAction> = changes
+            .into_iter()
+            .map(|(idx, old, new)| EditAction::TransformChanged { idx, old, new })
+            .collect();
+        let action = if actions.len() == 1 {
+            actions.pop().unwrap()
+        } else {
+            EditAction::Batch(actions)
+        };
+
+        self.push(action);
+        self.open_gesture = Some(gesture);
+    }
+
+    /// `record_transform` for a mesh node's local transform
+    pub fn record_node_transform(
+        &mut self,
+        idx: usize,
+        node: usize,
+        old: SceneElementTransform,
+        new: SceneElementTransform,
+    ) {
+        let gesture = Gesture::Node(idx, node);
+
+        if self.open_gesture.as_ref() == Some(&gesture) {
+            if let Some(EditAction::NodeTransformChanged { idx: last_idx, node: last_node, new: last_new, .. }) =
+                self.undo.last_mut()
+            {
+                if (*last_idx, *last_node) == (idx, node) {
+                    *last_new = new;
+                    self.redo.clear();
+                    return;
+                }
+            }
+        }
+
+        self.push(EditAction::NodeTransformChanged { idx, node, old, new });
+        self.open_gesture = Some(gesture);
+    }
+
+    /// Called once no widget is being dragged; the next transform edit starts a new entry
+    pub fn end_gesture(&mut self) {
+        self.open_gesture = None;
+    }
+
+    pub fn pop_undo(&mut self) -> Option<EditAction> {
+        self.open_gesture = None;
+        self.undo.pop()
+    }
+
+    pub fn pop_redo(&mut self) -> Option<EditAction> {
+        self.open_gesture = None;
+        self.redo.pop()
+    }
+
+    /// Store the action reverting an undo, for redo
+    pub fn undone(&mut self, redo: EditAction) {
+        self.redo.push(redo);
+    }
+
+    /// Store the action reverting a redo, for undo; unlike `push`, the rest of the redo stack stays
+    pub fn redone(&mut self, undo: EditAction) {
+        self.push_undo(undo);
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    /// Forget everything, e.g. when the scene is replaced and the indices no longer apply
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+        self.open_gesture = None;
+    }
+
+    fn push_undo(&mut self, action: EditAction) {
+        self.undo.push(action);
+        if self.undo.len() > Self::MAX_ENTRIES {
+            self.undo.remove(0);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kajiya_simple::Vec3;
+
+    fn at(x: f32) -> SceneElementTransform {
+        SceneElementTransform {
+            position: Vec3::new(x, 0.0, 0.0),
+            ..SceneElementTransform::IDENTITY
+        }
+    }
+
+    fn transform_change(action: Option<EditAction>) -> (usize, f32, f32) {
+        match action {
+            Some(EditAction::TransformChanged { idx, old, new }) => (idx, old.position.x, new.position.x),
+            _ => panic!("expected a transform change"),
+        }
+    }
+
+    #[test]
+    fn test_drag_gesture_is_one_entry() {
+        let mut history = EditHistory::default();
+        history.record_transform(0, at(0.0), at(1.0));
+        history.record_transform(0, at(1.0), at(2.0));
+        history.record_transform(0, at(2.0), at(3.0));
+        history.end_gesture();
+        history.record_transform(0, at(3.0), at(4.0));
+
+        assert_eq!(transform_change(history.pop_undo()), (0, 3.0, 4.0));
+        assert_eq!(transform_change(history.pop_undo()), (0, 0.0, 3.0));
+        assert!(!history.can_undo());
+    }
+
+    #[test]
+    fn test_dragging_another_element_starts_a_new_entry() {
+        let mut history = EditHistory::default();
+        history.record_transform(0, at(0.0), at(1.0));
+        history.record_transform(1, at(5.0), at(6.0));
+
+        assert_eq!(transform_change(history.pop_undo()), (1, 5.0, 6.0));
+        assert_eq!(transform_change(history.pop_undo()), (0, 0.0, 1.0));
+    }
+
+    #[test]
+    fn test_multi_element_drag_is_one_batch() {
+        let mut history = EditHistory::default();
+        history.record_transforms(vec![(0, at(0.0), at(1.0)), (3, at(5.0), at(6.0))]);
+        history.record_transforms(vec![(0, at(1.0), at(2.0)), (3, at(6.0), at(7.0))]);
+
+        match history.pop_undo() {
+            Some(EditAction::Batch(actions)) => {
+                let changes: Vec<_> = actions.into_iter().map(|action| transform_change(Some(action))).collect();
+                assert_eq!(changes, vec![(0, 0.0, 2.0), (3, 5.0, 7.0)]);
+            }
+            _ => panic!("expected a batch"),
+        }
+        assert!(!history.can_undo());
+    }
+
+    #[test]
+    fn test_node_drag_is_one_entry_apart_from_its_element() {
+        let mut history = EditHistory::default();
+        history.record_node_transform(0, 1, at(0.0), at(1.0));
+        history.record_node_transform(0, 1, at(1.0), at(2.0));
+        history.record_transform(0, at(5.0), at(6.0));
+        history.record_node_transform(0, 2, at(0.0), at(1.0));
+
+        assert!(matches!(history.pop_undo(), Some(EditAction::NodeTransformChanged { node: 2, .. })));
+        assert_eq!(transform_change(history.pop_undo()), (0, 5.0, 6.0));
+        match history.pop_undo() {
+            Some(EditAction::NodeTransformChanged { idx: 0, node: 1, old, new }) => {
+                assert_eq!((old.position.x, new.position.x), (0.0, 2.0));
+            }
+            _ => panic!("expected a node transform change"),
+        }
+        assert!(!history.can_undo());
+    }
+
+    #[test]
+    fn test_new_edit_clears_redo() {
+        let mut history = EditHistory::default();
+        history.push(EditAction::ElementAdded { idx: 0 });
+
+        let undone = history.pop_undo().unwrap();
+        history.undone(undone);
+        assert!(history.can_redo());
+
+        let redo = history.pop_redo().unwrap();
+        history.redone(redo);
+        assert!(history.can_undo() && !history.can_redo());
+
+        history.pop_undo();
+        history.undone(EditAction::ElementAdded { idx: 0 });
+        history.push(EditAction::ElementAdded { idx: 1 });
+        assert!(!history.can_redo());
+    }
+
+    #[test]
+    fn test_history_is_bounded() {
+        let mut history = EditHistory::default();
+        for i in 0..EditHistory::MAX_ENTRIES + 10 {
+            history.push(EditAction::ElementAdded { idx: i });
+        }
+
+        let mut count = 0;
+        let mut oldest = None;
+        while let Some(EditAction::ElementAdded { idx }) = history.pop_undo() {
+            count += 1;
+            oldest = Some(idx);
+        }
+        assert_eq!(count, EditHistory::MAX_ENTRIES);
+        assert_eq!(oldest, Some(10));
+    }
+}
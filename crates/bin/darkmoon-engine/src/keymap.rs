@@ -4,7 +4,8 @@ use serde::{Deserialize, Serialize};
 use std::{
     fs::{canonicalize, File},
     io::Read,
-    path::PathBuf,
+    path::{Path, PathBuf},
+    time::{Duration, Instant, SystemTime},
 };
 use toml::from_str;
 
@@ -15,12 +16,19 @@ pub struct KeymapConfig {
     pub sequencer: Sequencer,
     pub rendering: Rendering,
     pub misc: Misc,
+    #[serde(default)]
+    pub editing: Editing,
+    #[serde(default)]
+    pub camera: Camera,
 }
 
 impl KeymapConfig {
+    pub(crate) fn path(path: &Option<PathBuf>) -> PathBuf {
+        path.clone().unwrap_or("keymap.toml".into())
+    }
+
     pub(crate) fn load(path: &Option<PathBuf>) -> anyhow::Result<Self> {
-        let path = path.clone().unwrap_or("keymap.toml".into());
-        let path = canonicalize(path).with_context(|| {
+        let path = canonicalize(Self::path(path)).with_context(|| {
             "Failed to find keymap.toml. Make sure it is in the same directory as the executable."
         })?;
 
@@ -32,24 +40,169 @@ impl KeymapConfig {
             .with_context(|| "Failed to read keymap.toml")?;
 
         // Don't use anyhow context here because it doesn't show the parsing error.
-        let keymap = from_str(&buffer)
+        let keymap: Self = from_str(&buffer)
             .map_err(|e| anyhow!("Failed to parse keymap.toml: {}", e.to_string()))?;
+        keymap.validate()?;
 
         Ok(keymap)
     }
+
+    /// Rejects configs where one key triggers two different commands, or with negative
+    /// activation times. Movement and modifier keys may overlap commands (e.g. S both moves back and saves).
+    pub(crate) fn validate(&self) -> anyhow::Result<()> {
+        let commands = [
+            ("ui.toggle", self.ui.toggle),
+            ("sequencer.add_keyframe", self.sequencer.add_keyframe),
+            ("sequencer.play", self.sequencer.play),
+            ("rendering.switch_to_reference_path_tracing", self.rendering.switch_to_reference_path_tracing),
+            ("rendering.reset_path_tracer", self.rendering.reset_path_tracer),
+            ("rendering.light_enable_emissive", self.rendering.light_enable_emissive),
+            ("misc.print_camera_transform", self.misc.print_camera_transform),
+            ("misc.save_scene", self.misc.save_scene),
+            ("misc.reload_keymap", self.misc.reload_keymap),
+            ("misc.pause", self.misc.pause),
+            ("camera.toggle_walk_mode", self.camera.toggle_walk_mode),
+            ("camera.look_at_selected", self.camera.look_at_selected),
+            ("editing.undo", self.editing.undo),
+            ("editing.redo", self.editing.redo),
+            ("editing.duplicate", self.editing.duplicate),
+        ];
+
+        let m = &self.movement;
+        for (name, binding) in [
+            ("forward", m.forward),
+            ("backward", m.backward),
+            ("left", m.left),
+            ("right", m.right),
+            ("up", m.up),
+            ("down", m.down),
+            ("boost", m.boost),
+            ("slow", m.slow),
+        ] {
+            if let Some(time) = binding.activation_time() {
+                if !(time >= 0.0 && time.is_finite()) {
+                    return Err(anyhow!("movement.{} has invalid activation_time {}", name, time));
+                }
+            }
+        }
+        if !(m.default_activation_time >= 0.0 && m.default_activation_time.is_finite()) {
+            return Err(anyhow!(
+                "movement.default_activation_time must be non-negative, got {}",
+                m.default_activation_time
+            ));
+        }
+
+        let conflicts: Vec<String> = commands
+            .iter()
+            .enumerate()
+            .flat_map(|(i, (name, key))| {
+                commands[i + 1..]
+                    .iter()
+                    .filter(move |(_, other_key)| other_key == key)
+                    .map(move |(other_name, _)| format!("{} and {} are both {:?}", name, other_name, key))
+            })
+            .collect();
+
+        if conflicts.is_empty() {
+            Ok(())
+        } else {
+            Err(anyhow!("Conflicting key bindings: {}", conflicts.join("; ")))
+        }
+    }
+}
+
+/// Polls the keymap file's modification time, so edits can be picked up without a restart
+pub(crate) struct KeymapWatcher {
+    path: PathBuf,
+    last_modified: Option<SystemTime>,
+    last_poll: Instant,
+}
+
+impl KeymapWatcher {
+    const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+    pub(crate) fn new(path: PathBuf) -> Self {
+        let last_modified = Self::modified_time(&path);
+        Self {
+            path,
+            last_modified,
+            last_poll: Instant::now(),
+        }
+    }
+
+    /// True once per change to the file
+    pub(crate) fn poll(&mut self) -> bool {
+        if self.last_poll.elapsed() < Self::POLL_INTERVAL {
+            return false;
+        }
+        self.last_poll = Instant::now();
+
+        let modified = Self::modified_time(&self.path);
+        if modified.is_some() && modified != self.last_modified {
+            self.last_modified = modified;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn modified_time(path: &Path) -> Option<SystemTime> {
+        std::fs::metadata(path).and_then(|meta| meta.modified()).ok()
+    }
 }
 
 impl From<Movement> for KeyboardMap {
     fn from(val: Movement) -> Self {
-        KeyboardMap::new()
-            .bind(val.forward, KeyMap::new("move_fwd", 1.0))
-            .bind(val.backward, KeyMap::new("move_fwd", -1.0))
-            .bind(val.right, KeyMap::new("move_right", 1.0))
-            .bind(val.left, KeyMap::new("move_right", -1.0))
-            .bind(val.up, KeyMap::new("move_up", 1.0))
-            .bind(val.down, KeyMap::new("move_up", -1.0))
-            .bind(val.boost, KeyMap::new("boost", 1.0).activation_time(0.25))
-            .bind(val.slow, KeyMap::new("boost", -1.0).activation_time(0.5))
+        let bind = |map: KeyboardMap, binding: KeyBinding, axis: &'static str, value: f32, default_time: f32| {
+            map.bind(
+                binding.key(),
+                KeyMap::new(axis, value).activation_time(binding.activation_time().unwrap_or(default_time)),
+            )
+        };
+
+        let ramp = val.default_activation_time;
+        let map = KeyboardMap::new();
+        let map = bind(map, val.forward, "move_fwd", 1.0, ramp);
+        let map = bind(map, val.backward, "move_fwd", -1.0, ramp);
+        let map = bind(map, val.right, "move_right", 1.0, ramp);
+        let map = bind(map, val.left, "move_right", -1.0, ramp);
+        let map = bind(map, val.up, "move_up", 1.0, ramp);
+        let map = bind(map, val.down, "move_up", -1.0, ramp);
+        let map = bind(map, val.boost, "boost", 1.0, 0.25);
+        bind(map, val.slow, "boost", -1.0, 0.5)
+    }
+}
+
+/// A key, optionally with the time in seconds its input takes to ramp up to full strength:
+/// either `forward = "W"` or `forward = { key = "W", activation_time = 0.05 }`
+#[derive(Serialize, Deserialize, Clone, Copy)]
+#[serde(untagged)]
+pub enum KeyBinding {
+    Key(VirtualKeyCode),
+    Ramped {
+        key: VirtualKeyCode,
+        activation_time: f32,
+    },
+}
+
+impl KeyBinding {
+    pub fn key(self) -> VirtualKeyCode {
+        match self {
+            Self::Key(key) | Self::Ramped { key, .. } => key,
+        }
+    }
+
+    pub fn activation_time(self) -> Option<f32> {
+        match self {
+            Self::Key(_) => None,
+            Self::Ramped { activation_time, .. } => Some(activation_time),
+        }
+    }
+}
+
+impl From<VirtualKeyCode> for KeyBinding {
+    fn from(key: VirtualKeyCode) -> Self {
+        Self::Key(key)
     }
 }
 
@@ -73,14 +226,21 @@ impl From<Movement> for GamepadMap {
 
 #[derive(Serialize, Deserialize, Clone)]
 pub struct Movement {
-    forward: VirtualKeyCode,
-    backward: VirtualKeyCode,
-    left: VirtualKeyCode,
-    right: VirtualKeyCode,
-    up: VirtualKeyCode,
-    down: VirtualKeyCode,
-    boost: VirtualKeyCode,
-    slow: VirtualKeyCode,
+    forward: KeyBinding,
+    backward: KeyBinding,
+    left: KeyBinding,
+    right: KeyBinding,
+    up: KeyBinding,
+    down: KeyBinding,
+    boost: KeyBinding,
+    slow: KeyBinding,
+    /// Ramp-up time for the direction keys that don't set their own
+    #[serde(default = "default_activation_time")]
+    default_activation_time: f32,
+}
+
+fn default_activation_time() -> f32 {
+    0.15
 }
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -104,20 +264,83 @@ pub struct Rendering {
 #[derive(Serialize, Deserialize, Clone)]
 pub struct Misc {
     pub print_camera_transform: VirtualKeyCode,
+    #[serde(default = "default_save_scene")]
     pub save_scene: VirtualKeyCode,
+    #[serde(default = "default_reload_keymap")]
+    pub reload_keymap: VirtualKeyCode,
+    /// Freezes the camera, sun and object updates while the GUI stays usable
+    #[serde(default = "default_pause")]
+    pub pause: VirtualKeyCode,
+}
+
+fn default_save_scene() -> VirtualKeyCode {
+    S
+}
+
+fn default_reload_keymap() -> VirtualKeyCode {
+    F5
+}
+
+fn default_pause() -> VirtualKeyCode {
+    Pause
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Editing {
+    pub nudge_left: VirtualKeyCode,
+    pub nudge_right: VirtualKeyCode,
+    pub nudge_forward: VirtualKeyCode,
+    pub nudge_backward: VirtualKeyCode,
+    pub nudge_up: VirtualKeyCode,
+    pub nudge_down: VirtualKeyCode,
+    pub nudge_coarse: VirtualKeyCode,
+    pub nudge_fine: VirtualKeyCode,
+    /// Pressed with Ctrl
+    #[serde(default = "default_undo")]
+    pub undo: VirtualKeyCode,
+    /// Pressed with Ctrl
+    #[serde(default = "default_redo")]
+    pub redo: VirtualKeyCode,
+    /// Pressed with Ctrl
+    #[serde(default = "default_duplicate")]
+    pub duplicate: VirtualKeyCode,
+}
+
+fn default_undo() -> VirtualKeyCode {
+    Z
+}
+
+fn default_redo() -> VirtualKeyCode {
+    Y
+}
+
+fn default_duplicate() -> VirtualKeyCode {
+    D
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Camera {
+    pub toggle_walk_mode: VirtualKeyCode,
+    #[serde(default = "default_look_at_selected")]
+    pub look_at_selected: VirtualKeyCode,
+}
+
+fn default_look_at_selected() -> VirtualKeyCode {
+    F
 }
 
 impl Default for Movement {
     fn default() -> Self {
         Self {
-            forward: W,
-            backward: S,
-            left: A,
-            right: D,
-            up: E,
-            down: Q,
-            boost: LShift,
-            slow: LControl,
+            forward: W.into(),
+            backward: S.into(),
+            left: A.into(),
+            right: D.into(),
+            up: E.into(),
+            down: Q.into(),
+            boost: LShift.into(),
+            slow: LControl.into(),
+            default_activation_time: default_activation_time(),
         }
     }
 }
@@ -151,7 +374,84 @@ impl Default for Misc {
     fn default() -> Self {
         Self {
             print_camera_transform: C,
-            save_scene: S,
+            save_scene: default_save_scene(),
+            reload_keymap: default_reload_keymap(),
+            pause: default_pause(),
+        }
+    }
+}
+
+impl Default for Editing {
+    fn default() -> Self {
+        Self {
+            nudge_left: Left,
+            nudge_right: Right,
+            nudge_forward: Up,
+            nudge_backward: Down,
+            nudge_up: PageUp,
+            nudge_down: PageDown,
+            nudge_coarse: LShift,
+            nudge_fine: LControl,
+            undo: default_undo(),
+            redo: default_redo(),
         }
     }
 }
+
+impl Default for Camera {
+    fn default() -> Self {
+        Self {
+            toggle_walk_mode: G,
+            look_at_selected: default_look_at_selected(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_keymap_is_valid() {
+        KeymapConfig::default().validate().unwrap();
+    }
+
+    #[test]
+    fn test_activation_time_per_binding() {
+        let movement: Movement = from_str(
+            r#"
+            forward = { key = "W", activation_time = 0.05 }
+            backward = "S"
+            left = "A"
+            right = "D"
+            up = "Q"
+            down = "E"
+            boost = "LShift"
+            slow = "LControl"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(movement.forward.key(), W);
+        assert_eq!(movement.forward.activation_time(), Some(0.05));
+        assert_eq!(movement.backward.activation_time(), None);
+        assert_eq!(movement.default_activation_time, 0.15);
+
+        let mut keymap = KeymapConfig::default();
+        keymap.movement = movement;
+        keymap.validate().unwrap();
+
+        keymap.movement.left = KeyBinding::Ramped { key: A, activation_time: -1.0 };
+        assert!(keymap.validate().is_err());
+    }
+
+    #[test]
+    fn test_conflicting_commands_are_rejected() {
+        let mut keymap = KeymapConfig::default();
+        keymap.sequencer.play = keymap.camera.toggle_walk_mode;
+
+        let err = keymap.validate().unwrap_err().to_string();
+        assert!(err.contains("sequencer.play"));
+        assert!(err.contains("camera.toggle_walk_mode"));
+    }
+}
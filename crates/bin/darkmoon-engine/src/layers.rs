@@ -0,0 +1,54 @@
+/// A named visibility group. Elements belong to every layer whose name is among their tags.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Layer {
+    pub name: String,
+    pub visible: bool,
+}
+
+impl Layer {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: crate::tags::normalize_tag(name),
+            visible: true,
+        }
+    }
+}
+
+/// Whether an element with `tags` is in any hidden layer
+pub fn hidden_by_layers(tags: &[String], layers: &[Layer]) -> bool {
+    layers
+        .iter()
+        .any(|layer| !layer.visible && tags.contains(&layer.name))
+}
+
+/// Add `layer` unless one with its name exists. Returns whether it was added.
+pub fn add_layer(layers: &mut Vec<Layer>, layer: Layer) -> bool {
+    if layer.name.is_empty() || layers.iter().any(|existing| existing.name == layer.name) {
+        return false;
+    }
+
+    layers.push(layer);
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hidden_by_any_hidden_layer() {
+        let mut layers = vec![Layer::new("Props"), Layer::new("lights")];
+        assert!(!add_layer(&mut layers, Layer::new("props ")));
+        assert!(!add_layer(&mut layers, Layer::new("")));
+
+        let tags = vec!["props".to_string(), "hero".to_string()];
+        assert!(!hidden_by_layers(&tags, &layers));
+
+        layers[1].visible = false;
+        assert!(!hidden_by_layers(&tags, &layers));
+
+        layers[0].visible = false;
+        assert!(hidden_by_layers(&tags, &layers));
+        assert!(!hidden_by_layers(&[], &layers));
+    }
+}
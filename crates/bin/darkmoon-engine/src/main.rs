@@ -1,15 +1,34 @@
 mod gui;
+mod app_config;
+mod asset_paths;
 mod asset_browser;
+mod crash;
 mod culling;
+mod first_run;
+mod gltf_export;
+mod history;
 mod keymap;
+mod layers;
 mod math;
+mod mesh_geometry;
 mod misc;
+mod notifications;
 mod opt;
 mod persisted;
+mod placement;
+mod primitives;
+mod render_presets;
+mod rng;
 mod runtime;
+mod scatter;
 mod scene;
+mod scene_diff;
+mod scene_validation;
+mod selection;
 mod sequence;
 mod streaming_integration;
+mod tags;
+mod walk;
 
 use std::{
     fs::File,
@@ -64,6 +83,29 @@ impl AppState {
         )
     }
 
+    /// Load the configured startup scene. Failures are reported, but the engine still starts.
+    fn load_startup_scene(&mut self) {
+        let scene_path = match self.runtime.app_config.startup_scene_path() {
+            Some(path) => path.clone(),
+            None => return,
+        };
+
+        if !scene_path.exists() {
+            log::warn!("Startup scene {:?} not found", scene_path);
+            self.runtime
+                .toasts
+                .error(format!("Startup scene {:?} not found", scene_path));
+            return;
+        }
+
+        if let Err(err) = self.load_scene(&scene_path) {
+            log::error!("Failed to load startup scene {:?}: {:#}", scene_path, err);
+            self.runtime
+                .toasts
+                .error(format!("Failed to load startup scene {:?}", scene_path));
+        }
+    }
+
     fn add_standalone_mesh(&mut self, path: PathBuf, mesh_scale: f32) -> anyhow::Result<()> {
         self.runtime.add_mesh_instance(
             &mut self.persisted,
@@ -73,6 +115,7 @@ impl AppState {
                 position: Vec3::ZERO,
                 rotation_euler_degrees: Vec3::ZERO,
                 scale: Vec3::splat(mesh_scale),
+                pivot: Vec3::ZERO,
             },
         )
     }
@@ -86,12 +129,44 @@ impl AppState {
 
         kajiya.run(|ctx| runtime.frame(ctx, &mut persisted))?;
 
+        if let Err(err) = runtime.app_config.save() {
+            log::error!("Failed to save app config: {:#}", err);
+        }
+
         Ok(persisted)
     }
 }
 
 const APP_STATE_CONFIG_FILE_PATH: &str = "view_state.dmoon";
 
+/// `--diff-scenes`: print what changed between two scene files, and optionally write their merge
+/// with the scene both were edited from
+fn diff_scenes(base: &Path, other: &Path, merge: Option<(&Path, &Path)>) -> anyhow::Result<()> {
+    let base_desc = scene_diff::load_scene_desc(base)?;
+    let other_desc = scene_diff::load_scene_desc(other)?;
+
+    println!("{:?} -> {:?}", base, other);
+    print!("{}", scene_diff::diff_scenes(&base_desc, &other_desc));
+
+    if let Some((ancestor, merge_output)) = merge {
+        let ancestor_desc = scene_diff::load_scene_desc(ancestor)?;
+        let merge = scene_diff::merge_scenes(&ancestor_desc, &base_desc, &other_desc);
+
+        for conflict in &merge.conflicts {
+            println!("Conflict on {}: keeping the version from {:?}", conflict.key, base);
+        }
+
+        ron::ser::to_writer_pretty(
+            File::create(merge_output)?,
+            &merge.scene,
+            ron::ser::PrettyConfig::default(),
+        )?;
+        println!("Merged scene written to {:?}", merge_output);
+    }
+
+    Ok(())
+}
+
 
 fn main() -> anyhow::Result<()> {
     // Only force X11 on Linux
@@ -107,6 +182,25 @@ fn main() -> anyhow::Result<()> {
 
     let opt = Opt::from_args();
 
+    crash::install_panic_hook();
+
+    if let [base, other] = opt.diff_scenes.as_slice() {
+        let merge = match (opt.merge_ancestor.as_deref(), opt.merge_output.as_deref()) {
+            (Some(ancestor), Some(output)) => Some((ancestor, output)),
+            (None, None) => None,
+            _ => anyhow::bail!("--merge-output and --merge-ancestor go together"),
+        };
+        return diff_scenes(base, other, merge);
+    }
+
+    // Before anything that logs; the main loop would only set it up once the window opens
+    kajiya::logging::set_up_logging(log::LevelFilter::Info)?;
+
+    // Not fatal: the engine can still run, and the file it failed on will report its own error
+    if let Err(err) = first_run::prepare_working_directory(!opt.no_sample_scene) {
+        log::error!("Failed to set up the working directory: {:#}", err);
+    }
+
     let mut persisted: PersistedState = if opt.empty_scene || opt.reset || (opt.scene.is_none() && opt.mesh.is_none()) {
         PersistedState::default()
     } else {
@@ -129,6 +223,8 @@ fn main() -> anyhow::Result<()> {
         state.load_scene(scene)?;
     } else if let Some(mesh) = opt.mesh.as_ref() {
         state.add_standalone_mesh(mesh.clone(), opt.mesh_scale)?;
+    } else if !opt.empty_scene && !opt.reset {
+        state.load_startup_scene();
     }
 
     let state = state.run()?;
@@ -7,6 +7,13 @@ pub struct Aabb {
     pub max: Vec3,
 }
 
+/// Pairs of indices into `Aabb::corners` forming the twelve box edges
+pub const AABB_EDGES: [(usize, usize); 12] = [
+    (0, 1), (2, 3), (4, 5), (6, 7),
+    (0, 2), (1, 3), (4, 6), (5, 7),
+    (0, 4), (1, 5), (2, 6), (3, 7),
+];
+
 impl Aabb {
     pub fn new(min: Vec3, max: Vec3) -> Self {
         Self { min, max }
@@ -51,8 +58,21 @@ impl Aabb {
         self.size() * 0.5
     }
 
-    pub fn transform(&self, transform: &Mat4) -> Self {
-        let corners = [
+    /// Total area of the six faces, as used by SAH cost estimates. Inverted boxes count as empty.
+    pub fn surface_area(&self) -> f32 {
+        let size = self.size().max(Vec3::ZERO);
+        2.0 * (size.x * size.y + size.y * size.z + size.z * size.x)
+    }
+
+    /// Inverted boxes count as empty
+    pub fn volume(&self) -> f32 {
+        let size = self.size().max(Vec3::ZERO);
+        size.x * size.y * size.z
+    }
+
+    /// The eight corners; bit 0/1/2 of the index selects max over min on X/Y/Z
+    pub fn corners(&self) -> [Vec3; 8] {
+        [
             Vec3::new(self.min.x, self.min.y, self.min.z),
             Vec3::new(self.max.x, self.min.y, self.min.z),
             Vec3::new(self.min.x, self.max.y, self.min.z),
@@ -61,7 +81,11 @@ impl Aabb {
             Vec3::new(self.max.x, self.min.y, self.max.z),
             Vec3::new(self.min.x, self.max.y, self.max.z),
             Vec3::new(self.max.x, self.max.y, self.max.z),
-        ];
+        ]
+    }
+
+    pub fn transform(&self, transform: &Mat4) -> Self {
+        let corners = self.corners();
 
         let transformed_corners: Vec<Vec3> = corners
             .iter()
@@ -99,6 +123,33 @@ impl Aabb {
         self
     }
 
+    /// Distance along the ray to the first hit (slab test). `None` if missed or behind the origin;
+    /// 0.0 when the origin is inside the box.
+    pub fn ray_intersection(&self, origin: Vec3, direction: Vec3) -> Option<f32> {
+        let inv_dir = direction.recip();
+        let t0 = (self.min - origin) * inv_dir;
+        let t1 = (self.max - origin) * inv_dir;
+
+        let t_near = t0.min(t1).max_element();
+        let t_far = t0.max(t1).min_element();
+
+        if t_near > t_far || t_far < 0.0 {
+            None
+        } else {
+            Some(t_near.max(0.0))
+        }
+    }
+
+    /// Grow by `amount` on every side (shrink if negative). Shrinking stops at the center
+    /// rather than inverting the box.
+    pub fn inflate(&self, amount: Vec3) -> Self {
+        let center = self.center();
+        Self {
+            min: (self.min - amount).min(center),
+            max: (self.max + amount).max(center),
+        }
+    }
+
     pub fn union(&self, other: &Aabb) -> Self {
         Self {
             min: self.min.min(other.min),
@@ -115,3 +166,60 @@ impl Default for Aabb {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unit_cube_area_and_volume() {
+        let cube = Aabb::new(Vec3::ZERO, Vec3::ONE);
+        assert_eq!(cube.surface_area(), 6.0);
+        assert_eq!(cube.volume(), 1.0);
+
+        let scaled = Aabb::from_center_size(Vec3::splat(5.0), Vec3::new(1.0, 2.0, 3.0));
+        assert_eq!(scaled.surface_area(), 22.0);
+        assert_eq!(scaled.volume(), 6.0);
+    }
+
+    #[test]
+    fn test_inflate_grows_symmetrically() {
+        let aabb = Aabb::new(Vec3::new(-1.0, 0.0, 2.0), Vec3::new(3.0, 2.0, 4.0));
+        let inflated = aabb.inflate(Vec3::new(0.5, 1.0, 0.0));
+
+        assert_eq!(inflated.min, Vec3::new(-1.5, -1.0, 2.0));
+        assert_eq!(inflated.max, Vec3::new(3.5, 3.0, 4.0));
+        assert_eq!(inflated.center(), aabb.center());
+        assert_eq!(inflated.size(), aabb.size() + Vec3::new(1.0, 2.0, 0.0));
+    }
+
+    #[test]
+    fn test_inflate_negative_shrinks_to_center() {
+        let aabb = Aabb::from_center_size(Vec3::new(1.0, 1.0, 1.0), Vec3::new(4.0, 2.0, 6.0));
+
+        let shrunk = aabb.inflate(Vec3::splat(-0.5));
+        assert_eq!(shrunk.size(), Vec3::new(3.0, 1.0, 5.0));
+        assert_eq!(shrunk.center(), aabb.center());
+
+        // Shrinking past an axis' half size collapses it onto the center instead of inverting
+        let collapsed = aabb.inflate(Vec3::splat(-1.5));
+        assert_eq!(collapsed.size(), Vec3::new(1.0, 0.0, 3.0));
+        assert_eq!(collapsed.center(), aabb.center());
+    }
+
+    #[test]
+    fn test_degenerate_box_area_and_volume() {
+        // A flat quad still has area on both sides, but no volume
+        let flat = Aabb::new(Vec3::ZERO, Vec3::new(2.0, 0.0, 3.0));
+        assert_eq!(flat.surface_area(), 12.0);
+        assert_eq!(flat.volume(), 0.0);
+
+        let point = Aabb::new(Vec3::ONE, Vec3::ONE);
+        assert_eq!(point.surface_area(), 0.0);
+        assert_eq!(point.volume(), 0.0);
+
+        let inverted = Aabb::new(Vec3::ONE, Vec3::ZERO);
+        assert_eq!(inverted.surface_area(), 0.0);
+        assert_eq!(inverted.volume(), 0.0);
+    }
+}
@@ -82,6 +82,18 @@ impl Frustum {
         Self { planes }
     }
 
+    /// Replace the far plane with one `distance` along `forward` from `eye`, or one that
+    /// nothing is behind for an infinite `distance`.
+    /// Infinite projections, like the renderer's, don't yield a usable far plane of their own.
+    pub fn with_far_plane(mut self, eye: Vec3, forward: Vec3, distance: f32) -> Self {
+        self.planes[5] = if distance.is_finite() {
+            Plane::from_normal_and_point(-forward, eye + forward.normalize() * distance)
+        } else {
+            Plane::new(-forward.normalize(), f32::NEG_INFINITY)
+        };
+        self
+    }
+
     pub fn test_point(&self, point: Vec3) -> IntersectionResult {
         for plane in &self.planes {
             if !plane.is_point_in_front(point) {
@@ -2,8 +2,16 @@ pub mod frustum;
 pub mod aabb;
 pub mod occlusion;
 pub mod triangle_culling;
+pub mod sphere;
+pub mod pvs;
+pub mod screen;
+pub mod ray;
 
 pub use frustum::*;
 pub use aabb::*;
 pub use occlusion::*;
 pub use triangle_culling::*;
+pub use sphere::*;
+pub use pvs::*;
+pub use screen::*;
+pub use ray::*;
@@ -24,6 +24,14 @@ impl DepthBuffer {
         self.depths.fill(f32::INFINITY);
     }
 
+    /// Change the resolution, keeping the allocation when shrinking. Contents are cleared.
+    pub fn resize(&mut self, width: u32, height: u32) {
+        self.width = width;
+        self.height = height;
+        self.depths.clear();
+        self.depths.resize((width * height) as usize, f32::INFINITY);
+    }
+
     pub fn get_depth(&self, x: u32, y: u32) -> Option<f32> {
         if x < self.width && y < self.height {
             Some(self.depths[(y * self.width + x) as usize])
@@ -51,6 +59,35 @@ pub struct OcclusionCullingConfig {
     pub sample_count: u32,            // Number of samples per object for occlusion testing
     pub debug_visualize: bool,        // Visualize occlusion results
     pub max_test_distance: f32,       // Maximum distance for occlusion testing
+    /// Scale `depth_buffer_resolution` with frame time, within the min/max bounds below
+    #[serde(default)]
+    pub adaptive_resolution: bool,
+    #[serde(default = "default_min_adaptive_resolution")]
+    pub min_adaptive_resolution: u32,
+    #[serde(default = "default_max_adaptive_resolution")]
+    pub max_adaptive_resolution: u32,
+    /// Frame time the adaptive resolution aims to stay under
+    #[serde(default = "default_frame_time_budget_ms")]
+    pub frame_time_budget_ms: f32,
+    /// Grid cell size used when baking potentially visible sets
+    #[serde(default = "default_pvs_cell_size")]
+    pub pvs_cell_size: f32,
+}
+
+fn default_min_adaptive_resolution() -> u32 {
+    64
+}
+
+fn default_max_adaptive_resolution() -> u32 {
+    512
+}
+
+fn default_frame_time_budget_ms() -> f32 {
+    1000.0 / 60.0
+}
+
+fn default_pvs_cell_size() -> f32 {
+    4.0
 }
 
 impl Default for OcclusionCullingConfig {
@@ -62,6 +99,11 @@ impl Default for OcclusionCullingConfig {
             sample_count: 4, // Test 4 points per object
             debug_visualize: false,
             max_test_distance: 1000.0,
+            adaptive_resolution: false,
+            min_adaptive_resolution: default_min_adaptive_resolution(),
+            max_adaptive_resolution: default_max_adaptive_resolution(),
+            frame_time_budget_ms: default_frame_time_budget_ms(),
+            pvs_cell_size: default_pvs_cell_size(),
         }
     }
 }
@@ -83,11 +125,13 @@ impl OcclusionCuller {
         }
     }
 
-    /// Update configuration
+    /// Update configuration. A resolution change reallocates the depth buffer, dropping
+    /// the occluders rasterized at the old size.
     pub fn update_config(&mut self, config: OcclusionCullingConfig) {
-        if config.depth_buffer_resolution != self.config.depth_buffer_resolution {
-            let res = config.depth_buffer_resolution;
-            self.depth_buffer = DepthBuffer::new(res, res);
+        let res = config.depth_buffer_resolution;
+        if res != self.depth_buffer.width || res != self.depth_buffer.height {
+            self.depth_buffer.resize(res, res);
+            self.occluder_bounds.clear();
         }
         self.config = config;
     }
@@ -265,6 +309,56 @@ impl OcclusionCuller {
     }
 }
 
+// Over budget by this factor before lowering, under it by this factor before raising
+const ADAPTIVE_OVER_BUDGET: f32 = 1.1;
+const ADAPTIVE_UNDER_BUDGET: f32 = 0.8;
+// Resolution is scaled by this per step
+const ADAPTIVE_STEP: f32 = 1.25;
+// Frames to wait after a change, so the frame time reflects the new resolution
+const ADAPTIVE_COOLDOWN_FRAMES: u32 = 30;
+
+/// Picks the occlusion depth buffer resolution from recent frame times
+#[derive(Clone, Debug, Default)]
+pub struct AdaptiveOcclusionResolution {
+    resolution: Option<u32>,
+    smoothed_frame_time_ms: Option<f32>,
+    frames_since_change: u32,
+}
+
+impl AdaptiveOcclusionResolution {
+    /// Feed one frame's time; returns the resolution to use for the next one
+    pub fn update(&mut self, frame_time_ms: f32, config: &OcclusionCullingConfig) -> u32 {
+        let min = config.min_adaptive_resolution.max(1);
+        let max = config.max_adaptive_resolution.max(min);
+        let current = self
+            .resolution
+            .unwrap_or(config.depth_buffer_resolution)
+            .clamp(min, max);
+
+        let smoothed = match self.smoothed_frame_time_ms {
+            Some(prev) => prev + (frame_time_ms - prev) * 0.1,
+            None => frame_time_ms,
+        };
+        self.smoothed_frame_time_ms = Some(smoothed);
+        self.frames_since_change += 1;
+
+        let mut next = current;
+        if self.frames_since_change >= ADAPTIVE_COOLDOWN_FRAMES {
+            if smoothed > config.frame_time_budget_ms * ADAPTIVE_OVER_BUDGET {
+                next = ((current as f32 / ADAPTIVE_STEP) as u32).clamp(min, max);
+            } else if smoothed < config.frame_time_budget_ms * ADAPTIVE_UNDER_BUDGET {
+                next = ((current as f32 * ADAPTIVE_STEP).ceil() as u32).clamp(min, max);
+            }
+        }
+
+        if next != current {
+            self.frames_since_change = 0;
+        }
+        self.resolution = Some(next);
+        next
+    }
+}
+
 /// Statistics for occlusion culling debugging
 #[derive(Clone, Debug)]
 pub struct OcclusionCullingStatistics {
@@ -272,3 +366,84 @@ pub struct OcclusionCullingStatistics {
     pub depth_buffer_resolution: u32,
     pub depth_buffer_usage: f32, // Percentage of depth buffer filled
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn adaptive_config() -> OcclusionCullingConfig {
+        OcclusionCullingConfig {
+            depth_buffer_resolution: 256,
+            adaptive_resolution: true,
+            min_adaptive_resolution: 64,
+            max_adaptive_resolution: 512,
+            frame_time_budget_ms: 16.0,
+            ..Default::default()
+        }
+    }
+
+    fn run_frames(adaptive: &mut AdaptiveOcclusionResolution, frame_time_ms: f32, frames: u32) -> u32 {
+        let config = adaptive_config();
+        let mut resolution = 0;
+        for _ in 0..frames {
+            resolution = adaptive.update(frame_time_ms, &config);
+        }
+        resolution
+    }
+
+    #[test]
+    fn test_adaptive_resolution_drops_when_over_budget() {
+        let mut adaptive = AdaptiveOcclusionResolution::default();
+        assert_eq!(run_frames(&mut adaptive, 25.0, 1), 256);
+
+        // One step down per cooldown period, bottoming out at the minimum
+        let after_one_step = run_frames(&mut adaptive, 25.0, ADAPTIVE_COOLDOWN_FRAMES);
+        assert!(after_one_step < 256);
+        assert_eq!(run_frames(&mut adaptive, 25.0, 1000), 64);
+    }
+
+    #[test]
+    fn test_adaptive_resolution_rises_with_headroom() {
+        let mut adaptive = AdaptiveOcclusionResolution::default();
+        assert_eq!(run_frames(&mut adaptive, 5.0, 1000), 512);
+    }
+
+    #[test]
+    fn test_adaptive_resolution_holds_within_budget() {
+        let mut adaptive = AdaptiveOcclusionResolution::default();
+        assert_eq!(run_frames(&mut adaptive, 15.0, 1000), 256);
+    }
+
+    #[test]
+    fn test_depth_buffer_resize_clears() {
+        let mut buffer = DepthBuffer::new(4, 4);
+        buffer.set_depth(1, 1, 0.5);
+        buffer.resize(2, 2);
+        assert_eq!(buffer.depths.len(), 4);
+        assert_eq!(buffer.get_depth(1, 1), Some(f32::INFINITY));
+        assert_eq!(buffer.get_depth(2, 2), None);
+    }
+
+    #[test]
+    fn test_update_config_resizes_depth_buffer() {
+        let config = OcclusionCullingConfig {
+            depth_buffer_resolution: 128,
+            ..Default::default()
+        };
+        let mut culler = OcclusionCuller::new(config.clone());
+        culler.occluder_bounds.push(Aabb::new(Vec3::ZERO, Vec3::ONE));
+
+        // Same resolution: the frame's occluders stay
+        culler.update_config(config.clone());
+        assert_eq!(culler.occluder_bounds.len(), 1);
+
+        culler.update_config(OcclusionCullingConfig {
+            depth_buffer_resolution: 256,
+            ..config
+        });
+        assert_eq!(culler.depth_buffer.width, 256);
+        assert_eq!(culler.depth_buffer.height, 256);
+        assert_eq!(culler.depth_buffer.depths.len(), 256 * 256);
+        assert!(culler.occluder_bounds.is_empty());
+    }
+}
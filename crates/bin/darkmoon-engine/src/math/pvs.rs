@@ -0,0 +1,253 @@
+use kajiya_simple::Vec3;
+use serde::{Deserialize, Serialize};
+
+use super::Aabb;
+
+/// Grids larger than this get coarser cells instead, keeping bake time and file size in check
+const MAX_PVS_CELLS: usize = 32 * 1024;
+
+/// How far sample points are pulled in from box corners, so rays don't graze the box's own faces
+const SAMPLE_INSET: f32 = 0.1;
+
+/// Baked world bounds of a static element, used to tell whether it moved since the bake
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PvsElement {
+    pub id: u64,
+    pub world_bounds: Aabb,
+    /// Whether the element blocked sight lines during the bake
+    #[serde(default)]
+    pub occluder: bool,
+}
+
+/// Potentially visible sets of static elements, for each cell of a grid over the scene.
+///
+/// Visibility is sampled with rays between points of each cell and each element. Only elements
+/// marked as occluders block them, with their whole bounds, so only mark elements that fill
+/// their box (walls, floors, solid blocks). Gaps narrower than the sample spacing can be
+/// missed, so bake with smaller cells for scenes relying on them.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PotentiallyVisibleSet {
+    pub origin: Vec3,
+    pub cell_size: f32,
+    pub dims: [u32; 3],
+    /// Sorted by id
+    pub elements: Vec<PvsElement>,
+    /// Sorted ids of the elements visible from each cell, x-major
+    pub cells: Vec<Vec<u64>>,
+}
+
+impl PotentiallyVisibleSet {
+    /// Bake visibility between `static_elements` (id, world bounds and whether it's an
+    /// occluder) over a grid covering them, padded by one cell on each side
+    pub fn bake(static_elements: &[(u64, Aabb, bool)], cell_size: f32) -> Self {
+        let mut elements: Vec<PvsElement> = static_elements
+            .iter()
+            .map(|&(id, world_bounds, occluder)| PvsElement {
+                id,
+                world_bounds,
+                occluder,
+            })
+            .collect();
+        elements.sort_by_key(|elem| elem.id);
+
+        let bounds = elements
+            .iter()
+            .fold(None, |acc: Option<Aabb>, elem| {
+                Some(acc.map_or(elem.world_bounds, |acc| acc.union(&elem.world_bounds)))
+            })
+            .unwrap_or_else(|| Aabb::new(Vec3::ZERO, Vec3::ZERO));
+
+        let mut cell_size = cell_size.max(1e-3);
+        let mut dims = grid_dims(&bounds, cell_size);
+        while dims.iter().map(|&d| d as usize).product::<usize>() > MAX_PVS_CELLS {
+            cell_size *= 1.25;
+            dims = grid_dims(&bounds, cell_size);
+        }
+
+        let origin = bounds.min - Vec3::splat(cell_size);
+        let mut pvs = Self {
+            origin,
+            cell_size,
+            dims,
+            elements,
+            cells: Vec::new(),
+        };
+
+        let target_points: Vec<[Vec3; 9]> = pvs
+            .elements
+            .iter()
+            .map(|elem| sample_points(&elem.world_bounds))
+            .collect();
+
+        let cell_count = dims.iter().map(|&d| d as usize).product();
+        pvs.cells = (0..cell_count)
+            .map(|cell| {
+                let cell_bounds = pvs.cell_bounds(cell);
+                let cell_points = sample_points(&cell_bounds);
+
+                pvs.elements
+                    .iter()
+                    .zip(&target_points)
+                    .enumerate()
+                    .filter(|(target, (elem, points))| {
+                        cell_bounds.intersects(&elem.world_bounds)
+                            || cell_points.iter().any(|&from| {
+                                points
+                                    .iter()
+                                    .any(|&to| !pvs.is_segment_blocked(from, to, *target))
+                            })
+                    })
+                    .map(|(_, (elem, _))| elem.id)
+                    .collect()
+            })
+            .collect();
+
+        pvs
+    }
+
+    /// Index of the cell containing `position`, or `None` outside the baked grid
+    pub fn cell_index(&self, position: Vec3) -> Option<usize> {
+        let local = (position - self.origin) / self.cell_size;
+        if local.min_element() < 0.0 {
+            return None;
+        }
+
+        let [x, y, z] = [local.x as u32, local.y as u32, local.z as u32];
+        let [dx, dy, dz] = self.dims;
+        if x >= dx || y >= dy || z >= dz {
+            return None;
+        }
+
+        Some(((x * dy + y) * dz + z) as usize)
+    }
+
+    /// Sorted ids of the static elements potentially visible from `position`
+    pub fn visible_from(&self, position: Vec3) -> Option<&[u64]> {
+        self.cell_index(position).map(|cell| self.cells[cell].as_slice())
+    }
+
+    /// Baked visibility of element `id` from `camera_position`, if it was baked and hasn't
+    /// moved since. `None` means it has to be culled dynamically.
+    pub fn static_visibility(&self, id: u64, world_bounds: &Aabb, camera_position: Vec3) -> Option<bool> {
+        let baked = &self.elements[self.elements.binary_search_by_key(&id, |elem| elem.id).ok()?];
+
+        let tolerance = 1e-3 * (1.0 + baked.world_bounds.size().length());
+        if baked.world_bounds.min.distance(world_bounds.min) > tolerance
+            || baked.world_bounds.max.distance(world_bounds.max) > tolerance
+        {
+            return None;
+        }
+
+        let visible = self.visible_from(camera_position)?;
+        Some(visible.binary_search(&id).is_ok())
+    }
+
+    fn cell_bounds(&self, cell: usize) -> Aabb {
+        let [_, dy, dz] = self.dims;
+        let cell = cell as u32;
+        let coords = Vec3::new(
+            (cell / (dy * dz)) as f32,
+            ((cell / dz) % dy) as f32,
+            (cell % dz) as f32,
+        );
+
+        let min = self.origin + coords * self.cell_size;
+        Aabb::new(min, min + Vec3::splat(self.cell_size))
+    }
+
+    /// Whether any occluder other than `target` lies between `from` and `to`.
+    /// Occluders containing `from` don't block, so cells inside geometry still see out.
+    fn is_segment_blocked(&self, from: Vec3, to: Vec3, target: usize) -> bool {
+        let direction = to - from;
+        self.elements.iter().enumerate().any(|(i, elem)| {
+            i != target
+                && elem.occluder
+                && !elem.world_bounds.contains_point(from)
+                && elem
+                    .world_bounds
+                    .ray_intersection(from, direction)
+                    .map_or(false, |t| t < 1.0)
+        })
+    }
+}
+
+fn grid_dims(bounds: &Aabb, cell_size: f32) -> [u32; 3] {
+    let cells = (bounds.size() / cell_size).ceil() + Vec3::splat(2.0);
+    [cells.x.max(1.0) as u32, cells.y.max(1.0) as u32, cells.z.max(1.0) as u32]
+}
+
+/// The center and the eight corners, pulled in by `SAMPLE_INSET`
+fn sample_points(bounds: &Aabb) -> [Vec3; 9] {
+    let center = bounds.center();
+    let mut points = [center; 9];
+    for (point, corner) in points[1..].iter_mut().zip(bounds.corners()) {
+        *point = corner.lerp(center, SAMPLE_INSET);
+    }
+    points
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Two boxes on either side of a solid wall
+    fn walled_scene() -> Vec<(u64, Aabb, bool)> {
+        vec![
+            (1, Aabb::from_center_size(Vec3::new(-5.0, 0.5, 0.0), Vec3::ONE), false),
+            (2, Aabb::from_center_size(Vec3::new(5.0, 0.5, 0.0), Vec3::ONE), false),
+            (3, Aabb::new(Vec3::new(-0.5, 0.0, -10.0), Vec3::new(0.5, 4.0, 10.0)), true),
+        ]
+    }
+
+    #[test]
+    fn test_wall_hides_the_other_side() {
+        let pvs = PotentiallyVisibleSet::bake(&walled_scene(), 1.0);
+
+        let left = Vec3::new(-6.0, 1.0, 0.0);
+        assert_eq!(pvs.visible_from(left), Some(&[1, 3][..]));
+
+        let right = Vec3::new(6.0, 1.0, 0.0);
+        assert_eq!(pvs.visible_from(right), Some(&[2, 3][..]));
+
+        // Right above the wall everything is in view
+        let above = Vec3::new(0.0, 4.5, 0.0);
+        assert_eq!(pvs.visible_from(above), Some(&[1, 2, 3][..]));
+    }
+
+    #[test]
+    fn test_only_occluders_block() {
+        // The same wall, but not marked solid, say an archway whose box is mostly empty
+        let mut scene = walled_scene();
+        scene[2].2 = false;
+        let pvs = PotentiallyVisibleSet::bake(&scene, 1.0);
+
+        let left = Vec3::new(-6.0, 1.0, 0.0);
+        assert_eq!(pvs.visible_from(left), Some(&[1, 2, 3][..]));
+    }
+
+    #[test]
+    fn test_moved_or_unknown_elements_fall_back_to_dynamic_culling() {
+        let scene = walled_scene();
+        let pvs = PotentiallyVisibleSet::bake(&scene, 1.0);
+        let camera = Vec3::new(-6.0, 1.0, 0.0);
+
+        assert_eq!(pvs.static_visibility(2, &scene[1].1, camera), Some(false));
+        assert_eq!(pvs.static_visibility(1, &scene[0].1, camera), Some(true));
+
+        let moved = Aabb::from_center_size(Vec3::new(-3.0, 0.5, 0.0), Vec3::ONE);
+        assert_eq!(pvs.static_visibility(2, &moved, camera), None);
+        assert_eq!(pvs.static_visibility(42, &scene[0].1, camera), None);
+
+        // Outside the baked grid
+        assert_eq!(pvs.static_visibility(1, &scene[0].1, Vec3::splat(100.0)), None);
+    }
+
+    #[test]
+    fn test_large_scenes_get_coarser_cells() {
+        let scene = vec![(1, Aabb::new(Vec3::ZERO, Vec3::splat(1000.0)), true)];
+        let pvs = PotentiallyVisibleSet::bake(&scene, 1.0);
+
+        assert!(pvs.cells.len() <= MAX_PVS_CELLS);
+        assert!(pvs.cell_size > 1.0);
+    }
+}
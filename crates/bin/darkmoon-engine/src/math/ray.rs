@@ -0,0 +1,65 @@
+use kajiya_simple::{Mat4, Vec2, Vec3};
+
+/// A half-line from `origin`. `direction` is unit length, so distances along the ray are in world units.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Ray {
+    pub origin: Vec3,
+    pub direction: Vec3,
+}
+
+impl Ray {
+    pub fn new(origin: Vec3, direction: Vec3) -> Self {
+        Self {
+            origin,
+            direction: direction.normalize(),
+        }
+    }
+
+    pub fn at(&self, t: f32) -> Vec3 {
+        self.origin + self.direction * t
+    }
+
+    /// The ray from the eye through a point in normalized device coordinates (y up)
+    pub fn from_ndc(ndc: Vec2, clip_to_view: Mat4, view_to_world: Mat4) -> Self {
+        // Any depth between the clip planes is on the ray; halfway stays finite with reverse and infinite Z
+        let view_point = clip_to_view.project_point3(ndc.extend(0.5));
+        Self::new(
+            view_to_world.transform_point3(Vec3::ZERO),
+            view_to_world.transform_vector3(view_point),
+        )
+    }
+
+    /// Like `from_ndc`, from a position in window pixels (y down)
+    pub fn from_window_position(position: Vec2, window_size: Vec2, clip_to_view: Mat4, view_to_world: Mat4) -> Self {
+        let ndc = Vec2::new(
+            position.x / window_size.x * 2.0 - 1.0,
+            1.0 - position.y / window_size.y * 2.0,
+        );
+        Self::from_ndc(ndc, clip_to_view, view_to_world)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ray_through_screen_center_and_corner() {
+        let view_to_clip = Mat4::perspective_infinite_reverse_rh(90f32.to_radians(), 1.0, 0.1);
+        let view_to_world = Mat4::from_translation(Vec3::new(0.0, 2.0, 0.0));
+
+        let center = Ray::from_ndc(Vec2::ZERO, view_to_clip.inverse(), view_to_world);
+        assert!(center.origin.abs_diff_eq(Vec3::new(0.0, 2.0, 0.0), 1e-5));
+        assert!(center.direction.abs_diff_eq(-Vec3::Z, 1e-5));
+
+        // 90 degree FOV with a square aspect: the top-right corner is 45 degrees off in both axes
+        let corner = Ray::from_window_position(
+            Vec2::new(100.0, 0.0),
+            Vec2::new(100.0, 100.0),
+            view_to_clip.inverse(),
+            view_to_world,
+        );
+        assert!(corner.direction.abs_diff_eq(Vec3::new(1.0, 1.0, -1.0).normalize(), 1e-5));
+        assert!(corner.at(3f32.sqrt()).abs_diff_eq(Vec3::new(1.0, 3.0, -1.0), 1e-4));
+    }
+}
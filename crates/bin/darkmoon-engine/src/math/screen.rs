@@ -0,0 +1,241 @@
+use dolly::glam::{Vec2, Vec4};
+
+/// Keeps projected points away from the camera plane, where the divide by `w` blows up.
+/// Vertices closer to it than this are treated as behind the camera.
+pub const MIN_CLIP_W: f32 = 1e-3;
+
+/// Axis-aligned rectangle in screen space, usually normalized device coordinates
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScreenRect {
+    pub min: Vec2,
+    pub max: Vec2,
+}
+
+impl ScreenRect {
+    /// The visible part of NDC space
+    pub const NDC: Self = Self {
+        min: Vec2::new(-1.0, -1.0),
+        max: Vec2::new(1.0, 1.0),
+    };
+
+    pub fn new(min: Vec2, max: Vec2) -> Self {
+        Self { min, max }
+    }
+
+    /// Smallest rect containing `points`, or `None` if there are none
+    pub fn from_points(points: impl IntoIterator<Item = Vec2>) -> Option<Self> {
+        let mut points = points.into_iter();
+        let first = points.next()?;
+
+        Some(points.fold(Self::new(first, first), |rect, point| {
+            Self::new(rect.min.min(point), rect.max.max(point))
+        }))
+    }
+
+    pub fn size(&self) -> Vec2 {
+        (self.max - self.min).max(Vec2::ZERO)
+    }
+
+    pub fn area(&self) -> f32 {
+        let size = self.size();
+        size.x * size.y
+    }
+
+    /// Whether the rects overlap; touching edges count
+    pub fn intersects(&self, other: &ScreenRect) -> bool {
+        self.min.x <= other.max.x
+            && self.max.x >= other.min.x
+            && self.min.y <= other.max.y
+            && self.max.y >= other.min.y
+    }
+
+    /// NDC rect to pixels, with y pointing up like NDC
+    pub fn ndc_to_pixels(&self, viewport_size: Vec2) -> Self {
+        let to_pixels = |p: Vec2| (p * 0.5 + Vec2::splat(0.5)) * viewport_size;
+        Self::new(to_pixels(self.min), to_pixels(self.max))
+    }
+}
+
+/// Clip a convex polygon given in clip space (after the view-projection, before the w divide)
+/// to the visible volume, and return its vertices in NDC.
+///
+/// Clipping happens before the divide, so vertices behind the camera (w <= 0) are cut away
+/// rather than projected mirrored onto the screen. Depth isn't clipped; with reverse-Z and an
+/// infinite far plane, anything in front of the eye can be on screen.
+/// Returns an empty vec if nothing of the polygon is visible.
+pub fn clip_polygon_to_ndc(clip_vertices: &[Vec4]) -> Vec<Vec2> {
+    // The camera plane first, edge by edge. An edge's end is only added where it was cut,
+    // since otherwise it's the start of the next edge.
+    let mut polygon = Vec::with_capacity(clip_vertices.len() + 1);
+    for (i, &current) in clip_vertices.iter().enumerate() {
+        let next = clip_vertices[(i + 1) % clip_vertices.len()];
+        if let Some((start, end)) = clip_edge(current, next) {
+            polygon.push(start);
+            if end != next {
+                polygon.push(end);
+            }
+        }
+    }
+
+    // Signed distances to the screen edges; the polygon is kept where they're non-negative
+    let planes: [fn(Vec4) -> f32; 4] = [
+        |v| v.w + v.x,
+        |v| v.w - v.x,
+        |v| v.w + v.y,
+        |v| v.w - v.y,
+    ];
+
+    for plane in planes {
+        if polygon.is_empty() {
+            break;
+        }
+
+        let mut clipped = Vec::with_capacity(polygon.len() + 1);
+        for (i, &current) in polygon.iter().enumerate() {
+            let next = polygon[(i + 1) % polygon.len()];
+            let (d_current, d_next) = (plane(current), plane(next));
+
+            if d_current >= 0.0 {
+                clipped.push(current);
+            }
+            if (d_current >= 0.0) != (d_next >= 0.0) {
+                let t = d_current / (d_current - d_next);
+                clipped.push(current.lerp(next, t));
+            }
+        }
+        polygon = clipped;
+    }
+
+    polygon.iter().map(|v| v.truncate().truncate() / v.w).collect()
+}
+
+/// Clips a clip-space segment against the camera plane; `None` if it's entirely behind it
+pub fn clip_edge(mut p0: Vec4, mut p1: Vec4) -> Option<(Vec4, Vec4)> {
+    if p0.w < MIN_CLIP_W && p1.w < MIN_CLIP_W {
+        return None;
+    }
+
+    if p0.w < MIN_CLIP_W {
+        p0 = p0.lerp(p1, (MIN_CLIP_W - p0.w) / (p1.w - p0.w));
+    } else if p1.w < MIN_CLIP_W {
+        p1 = p1.lerp(p0, (MIN_CLIP_W - p1.w) / (p0.w - p1.w));
+    }
+
+    Some((p0, p1))
+}
+
+/// NDC bounds of the visible part of a clip-space polygon, see `clip_polygon_to_ndc`
+pub fn projected_ndc_rect(clip_vertices: &[Vec4]) -> Option<ScreenRect> {
+    ScreenRect::from_points(clip_polygon_to_ndc(clip_vertices))
+}
+
+/// Area of a simple polygon, regardless of winding
+pub fn polygon_area(points: &[Vec2]) -> f32 {
+    let twice_area: f32 = (0..points.len())
+        .map(|i| points[i].perp_dot(points[(i + 1) % points.len()]))
+        .sum();
+    twice_area.abs() * 0.5
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn clip(x: f32, y: f32, w: f32) -> Vec4 {
+        Vec4::new(x, y, 0.5, w)
+    }
+
+    #[test]
+    fn test_screen_rect_area_and_intersection() {
+        let a = ScreenRect::new(Vec2::new(0.0, 0.0), Vec2::new(2.0, 1.0));
+        let b = ScreenRect::new(Vec2::new(1.0, 0.5), Vec2::new(3.0, 3.0));
+        let c = ScreenRect::new(Vec2::new(2.5, -1.0), Vec2::new(4.0, 0.0));
+
+        assert_eq!(a.area(), 2.0);
+        assert!(a.intersects(&b) && b.intersects(&a));
+        assert!(!a.intersects(&c));
+        assert!(b.intersects(&c));
+
+        // Inverted rects are empty rather than negative
+        assert_eq!(ScreenRect::new(Vec2::ONE, Vec2::ZERO).area(), 0.0);
+
+        assert_eq!(ScreenRect::NDC.ndc_to_pixels(Vec2::new(1920.0, 1080.0)).area(), 1920.0 * 1080.0);
+        assert_eq!(ScreenRect::from_points(std::iter::empty()), None);
+    }
+
+    #[test]
+    fn test_clip_edge_keeps_front_segment() {
+        let p0 = Vec4::new(0.0, 0.0, 0.5, 1.0);
+        let p1 = Vec4::new(1.0, 1.0, 0.5, 2.0);
+        assert_eq!(clip_edge(p0, p1), Some((p0, p1)));
+    }
+
+    #[test]
+    fn test_clip_edge_clamps_to_camera_plane() {
+        let front = Vec4::new(1.0, 0.0, 0.5, 1.0);
+        let behind = Vec4::new(-1.0, 0.0, 0.5, -1.0);
+
+        let (p0, p1) = clip_edge(behind, front).unwrap();
+        assert!((p0.w - MIN_CLIP_W).abs() < 1e-6);
+        assert_eq!(p1, front);
+
+        assert_eq!(clip_edge(behind, behind), None);
+    }
+
+    #[test]
+    fn test_clip_inside_triangle_is_unchanged() {
+        let triangle = [clip(0.0, 0.0, 2.0), clip(1.0, 0.0, 2.0), clip(0.0, 1.0, 2.0)];
+        let ndc = clip_polygon_to_ndc(&triangle);
+
+        assert_eq!(ndc, vec![Vec2::new(0.0, 0.0), Vec2::new(0.5, 0.0), Vec2::new(0.0, 0.5)]);
+        assert_eq!(polygon_area(&ndc), 0.125);
+    }
+
+    #[test]
+    fn test_clip_outside_triangle_is_empty() {
+        let triangle = [clip(2.0, 0.0, 1.0), clip(3.0, 0.0, 1.0), clip(2.0, 1.0, 1.0)];
+        assert!(clip_polygon_to_ndc(&triangle).is_empty());
+        assert_eq!(projected_ndc_rect(&triangle), None);
+    }
+
+    #[test]
+    fn test_clip_triangle_crossing_screen_edge() {
+        // Half of this triangle sticks out past x = 1
+        let triangle = [clip(0.0, 0.0, 1.0), clip(2.0, 0.0, 1.0), clip(0.0, 1.0, 1.0)];
+        let ndc = clip_polygon_to_ndc(&triangle);
+
+        assert_eq!(ndc.len(), 4);
+        assert!((polygon_area(&ndc) - 0.75).abs() < 1e-5);
+
+        let rect = projected_ndc_rect(&triangle).unwrap();
+        assert_eq!(rect.max.x, 1.0);
+    }
+
+    #[test]
+    fn test_clip_triangle_behind_camera_is_empty() {
+        // Dividing these by w would put them on screen, mirrored
+        let triangle = [clip(0.0, 0.0, -1.0), clip(0.5, 0.0, -1.0), clip(0.0, 0.5, -1.0)];
+        assert!(clip_polygon_to_ndc(&triangle).is_empty());
+    }
+
+    #[test]
+    fn test_clip_triangle_straddling_eye_plane() {
+        // One vertex behind the camera: the visible part must stay finite and on screen
+        let triangle = [clip(0.0, 0.0, 1.0), clip(0.5, 0.0, 1.0), clip(0.2, 0.2, -1.0)];
+        let ndc = clip_polygon_to_ndc(&triangle);
+
+        assert!(!ndc.is_empty());
+        for p in &ndc {
+            assert!(p.is_finite());
+            assert!(p.x.abs() <= 1.0 + 1e-5 && p.y.abs() <= 1.0 + 1e-5);
+        }
+    }
+
+    #[test]
+    fn test_clip_quad_covering_screen() {
+        let quad = [clip(-3.0, -3.0, 1.0), clip(3.0, -3.0, 1.0), clip(3.0, 3.0, 1.0), clip(-3.0, 3.0, 1.0)];
+
+        assert!((polygon_area(&clip_polygon_to_ndc(&quad)) - 4.0).abs() < 1e-5);
+        assert_eq!(projected_ndc_rect(&quad), Some(ScreenRect::NDC));
+    }
+}
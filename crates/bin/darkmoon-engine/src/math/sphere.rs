@@ -0,0 +1,23 @@
+use kajiya_simple::Vec3;
+
+use super::Aabb;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BoundingSphere {
+    pub center: Vec3,
+    pub radius: f32,
+}
+
+impl BoundingSphere {
+    pub fn new(center: Vec3, radius: f32) -> Self {
+        Self { center, radius }
+    }
+
+    /// Sphere circumscribing the box
+    pub fn from_aabb(aabb: &Aabb) -> Self {
+        Self {
+            center: aabb.center(),
+            radius: aabb.half_size().length(),
+        }
+    }
+}
@@ -1,6 +1,10 @@
+use std::collections::VecDeque;
+
 use dolly::glam::{Vec2, Vec3, Vec4, Mat4};
 use serde::{Deserialize, Serialize};
 
+use super::{clip_polygon_to_ndc, polygon_area};
+
 /// Represents a triangle in 3D space with all necessary data
 #[derive(Debug, Clone, PartialEq)]
 pub struct Triangle {
@@ -86,6 +90,20 @@ pub struct TriangleCullingConfig {
     pub angle_threshold: f32,          // Angle threshold for view-dependent culling
     pub debug_logging: bool,           // Enable debug statistics
     pub log_interval_frames: u32,      // How often to log statistics
+    /// Cap on the triangles tested each frame; past it, meshes are sampled. 0 means no cap.
+    #[serde(default = "default_max_triangles_per_frame")]
+    pub max_triangles_per_frame: u32,
+    /// Frames averaged by `TriangleCuller::rolling_statistics`
+    #[serde(default = "default_stats_window_frames")]
+    pub stats_window_frames: u32,
+}
+
+fn default_max_triangles_per_frame() -> u32 {
+    50_000
+}
+
+fn default_stats_window_frames() -> u32 {
+    60
 }
 
 impl Default for TriangleCullingConfig {
@@ -105,6 +123,8 @@ impl Default for TriangleCullingConfig {
             angle_threshold: 0.1,          // ~5.7 degrees
             debug_logging: false,
             log_interval_frames: 60,
+            max_triangles_per_frame: default_max_triangles_per_frame(),
+            stats_window_frames: default_stats_window_frames(),
         }
     }
 }
@@ -135,11 +155,32 @@ impl TriangleCullingStats {
     }
 }
 
+/// Per-frame averages of `TriangleCullingStats` over recent frames
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct TriangleCullingAverage {
+    /// Frames in the window so far, up to `stats_window_frames`
+    pub frames: usize,
+    pub triangles_tested: f32,
+    pub triangles_rendered: f32,
+    pub total_culled: f32,
+    /// Culled share of all triangles tested in the window, rather than an average of per-frame
+    /// percentages, so nearly empty frames don't skew it
+    pub culling_efficiency: f32,
+}
+
 /// Triangle culler that operates at primitive level
 pub struct TriangleCuller {
     config: TriangleCullingConfig,
+    // The frame in progress; reset by `end_frame`
     statistics: TriangleCullingStats,
+    last_frame_statistics: TriangleCullingStats,
+    // Completed frames, newest last, at most `stats_window_frames` of them
+    history: VecDeque<TriangleCullingStats>,
     frame_count: u32,
+    // Render extent in pixels, which `min_triangle_area` is measured against
+    viewport_size: Vec2,
+    // Back-face and view-dependent tests are made from here
+    camera_position: Vec3,
 }
 
 impl TriangleCuller {
@@ -147,7 +188,11 @@ impl TriangleCuller {
         Self {
             config,
             statistics: Default::default(),
+            last_frame_statistics: Default::default(),
+            history: VecDeque::new(),
             frame_count: 0,
+            viewport_size: Vec2::new(1920.0, 1080.0),
+            camera_position: Vec3::new(0.0, 0.0, 5.0),
         }
     }
 
@@ -155,6 +200,28 @@ impl TriangleCuller {
         self.config = config;
     }
 
+    /// Set to the current render extent, so small-triangle culling works in real pixels
+    pub fn set_viewport_size(&mut self, viewport_size: Vec2) {
+        self.viewport_size = viewport_size;
+    }
+
+    /// Set to the current camera position before testing the frame's triangles
+    pub fn set_camera_position(&mut self, camera_position: Vec3) {
+        self.camera_position = camera_position;
+    }
+
+    /// Triangles that can still be tested this frame under `max_triangles_per_frame`
+    pub fn frame_budget(&self) -> usize {
+        match self.config.max_triangles_per_frame {
+            0 => usize::MAX,
+            max => max.saturating_sub(self.statistics.triangles_tested) as usize,
+        }
+    }
+
+    pub fn frame_count(&self) -> u32 {
+        self.frame_count
+    }
+
     /// Test if a triangle should be back-face culled
     pub fn is_backface(&self, triangle: &Triangle, camera_pos: Vec3) -> bool {
         if !self.config.methods.contains(&PrimitiveCullingMethod::BackFace) &&
@@ -176,22 +243,13 @@ impl TriangleCuller {
             return false;
         }
 
-        // Transform vertices to screen space
-        let screen_vertices: Vec<Vec2> = triangle.vertices.iter()
-            .map(|v| {
-                let clip = *view_proj_matrix * Vec4::new(v.x, v.y, v.z, 1.0);
-                let ndc = clip / clip.w;
-                Vec2::new(
-                    (ndc.x * 0.5 + 0.5) * viewport_size.x,
-                    (ndc.y * 0.5 + 0.5) * viewport_size.y,
-                )
-            })
-            .collect();
+        // Only the on-screen part counts; off-screen triangles have no area at all
+        let clip_vertices = triangle.vertices.map(|v| *view_proj_matrix * Vec4::new(v.x, v.y, v.z, 1.0));
+        let ndc = clip_polygon_to_ndc(&clip_vertices);
 
-        // Calculate screen space area
-        let edge1 = screen_vertices[1] - screen_vertices[0];
-        let edge2 = screen_vertices[2] - screen_vertices[0];
-        let screen_area = (edge1.x * edge2.y - edge1.y * edge2.x).abs() * 0.5;
+        // NDC spans two units across the viewport
+        let pixels_per_ndc = viewport_size * 0.5;
+        let screen_area = polygon_area(&ndc) * pixels_per_ndc.x * pixels_per_ndc.y;
 
         screen_area < self.config.min_triangle_area
     }
@@ -300,9 +358,8 @@ impl TriangleCuller {
             return;
         }
         
-        // Use default camera parameters for testing
-        let camera_pos = Vec3::new(0.0, 0.0, 5.0);
-        let viewport_size = Vec2::new(1920.0, 1080.0);
+        let camera_pos = self.camera_position;
+        let viewport_size = self.viewport_size;
         
         // If we have a view projection matrix, use it; otherwise use identity
         let view_proj = view_proj_matrix.cloned().unwrap_or(Mat4::IDENTITY);
@@ -310,7 +367,7 @@ impl TriangleCuller {
         let _ = self.should_cull_triangle(triangle, camera_pos, &view_proj, viewport_size);
     }
 
-    /// Update frame counter and potentially log statistics
+    /// Finish the frame: potentially log its statistics, then move them into the rolling window
     pub fn end_frame(&mut self) {
         self.frame_count += 1;
         
@@ -318,14 +375,14 @@ impl TriangleCuller {
            self.frame_count % self.config.log_interval_frames == 0 &&
            self.statistics.triangles_tested > 0 {
             
-            println!("Triangle Culling Stats: {}/{} triangles rendered ({:.1}% culled)",
+            log::info!("Triangle Culling Stats: {}/{} triangles rendered ({:.1}% culled)",
                 self.statistics.triangles_rendered,
                 self.statistics.triangles_tested,
                 self.statistics.culling_efficiency()
             );
             
             if self.statistics.total_culled > 0 {
-                println!("  Breakdown: {} backface, {} degenerate, {} small, {} view-dependent",
+                log::info!("  Breakdown: {} backface, {} degenerate, {} small, {} view-dependent",
                     self.statistics.backface_culled,
                     self.statistics.degenerate_culled,
                     self.statistics.small_triangle_culled,
@@ -333,18 +390,64 @@ impl TriangleCuller {
                 );
             }
         }
+
+        let window = self.config.stats_window_frames.max(1) as usize;
+        self.history.push_back(self.statistics.clone());
+        while self.history.len() > window {
+            self.history.pop_front();
+        }
+
+        self.last_frame_statistics = std::mem::take(&mut self.statistics);
     }
 
+    /// Statistics of the frame in progress
     pub fn get_statistics(&self) -> &TriangleCullingStats {
         &self.statistics
     }
 
+    /// Statistics of the last frame `end_frame` finished
+    pub fn last_frame_statistics(&self) -> &TriangleCullingStats {
+        &self.last_frame_statistics
+    }
+
+    /// Averages over the last `stats_window_frames` finished frames
+    pub fn rolling_statistics(&self) -> TriangleCullingAverage {
+        let frames = self.history.len();
+        if frames == 0 {
+            return Default::default();
+        }
+
+        let sum = |field: fn(&TriangleCullingStats) -> u32| -> f32 {
+            self.history.iter().map(|stats| field(stats) as f32).sum()
+        };
+        let tested = sum(|stats| stats.triangles_tested);
+        let culled = sum(|stats| stats.total_culled);
+
+        TriangleCullingAverage {
+            frames,
+            triangles_tested: tested / frames as f32,
+            triangles_rendered: sum(|stats| stats.triangles_rendered) / frames as f32,
+            total_culled: culled / frames as f32,
+            culling_efficiency: if tested > 0.0 { culled / tested * 100.0 } else { 0.0 },
+        }
+    }
+
     pub fn reset_statistics(&mut self) {
         self.statistics.reset();
+        self.last_frame_statistics.reset();
+        self.history.clear();
         self.frame_count = 0;
     }
 }
 
+/// Indices of an evenly spread subset of at most `budget` out of `len` triangles. The starting
+/// offset moves with `frame`, so over consecutive frames every triangle gets its turn.
+pub fn sample_triangles(len: usize, budget: usize, frame: u32) -> impl Iterator<Item = usize> {
+    let stride = if budget == 0 { 1 } else { ((len + budget - 1) / budget).max(1) };
+    let offset = frame as usize % stride;
+    (offset..len).step_by(stride).take(budget)
+}
+
 /// Helper function to extract triangles from mesh data
 /// This would be implemented based on your mesh format
 pub fn extract_triangles_from_mesh(
@@ -434,4 +537,102 @@ mod tests {
         
         assert!(culler.is_backface(&triangle, camera_pos));
     }
+
+    #[test]
+    fn test_small_triangle_culling_depends_on_resolution() {
+        let mut culler = TriangleCuller::new(TriangleCullingConfig {
+            methods: vec![PrimitiveCullingMethod::SmallTriangle],
+            ..Default::default()
+        });
+
+        // With an identity view-projection, positions are NDC: a sliver of the screen
+        let triangle = Triangle::new([
+            Vec3::new(0.0, 0.0, 0.5),
+            Vec3::new(0.004, 0.0, 0.5),
+            Vec3::new(0.0, 0.004, 0.5),
+        ]);
+        let view_proj = Mat4::IDENTITY;
+
+        // ~1.8 pixels at 720p, under the 4 pixel default
+        culler.set_viewport_size(Vec2::new(1280.0, 720.0));
+        culler.test_triangle(&triangle, Some(&view_proj));
+        assert_eq!(culler.get_statistics().small_triangle_culled, 1);
+
+        // ~16.6 pixels at 4K
+        culler.reset_statistics();
+        culler.set_viewport_size(Vec2::new(3840.0, 2160.0));
+        culler.test_triangle(&triangle, Some(&view_proj));
+        assert_eq!(culler.get_statistics().small_triangle_culled, 0);
+        assert_eq!(culler.get_statistics().triangles_rendered, 1);
+    }
+
+    #[test]
+    fn test_sampling_stays_in_budget_and_covers_mesh() {
+        assert_eq!(sample_triangles(5, 10, 0).collect::<Vec<_>>(), vec![0, 1, 2, 3, 4]);
+        assert_eq!(sample_triangles(5, 0, 0).count(), 0);
+
+        let mut seen = vec![false; 1000];
+        for frame in 0..4 {
+            let sampled: Vec<usize> = sample_triangles(1000, 300, frame).collect();
+            assert!(sampled.len() <= 300);
+            for i in sampled {
+                seen[i] = true;
+            }
+        }
+        assert!(seen.iter().all(|&seen| seen));
+    }
+
+    #[test]
+    fn test_statistics_reset_each_frame_and_average_over_window() {
+        let mut culler = TriangleCuller::new(TriangleCullingConfig {
+            methods: vec![PrimitiveCullingMethod::BackFace],
+            stats_window_frames: 2,
+            ..Default::default()
+        });
+        culler.set_camera_position(Vec3::new(0.0, 0.0, 5.0));
+        let front = Triangle::new([Vec3::ZERO, Vec3::X, Vec3::Y]);
+        let back = Triangle::new([Vec3::ZERO, Vec3::Y, Vec3::X]);
+
+        let mut run_frame = |front_count: usize, back_count: usize| {
+            for _ in 0..front_count {
+                culler.test_triangle(&front, None);
+            }
+            for _ in 0..back_count {
+                culler.test_triangle(&back, None);
+            }
+            culler.end_frame();
+        };
+
+        // Dropped from the two-frame window by the time the last frame ends
+        run_frame(10, 0);
+        run_frame(3, 1);
+        run_frame(1, 3);
+
+        assert_eq!(culler.get_statistics().triangles_tested, 0);
+        assert_eq!(culler.last_frame_statistics().triangles_tested, 4);
+        assert_eq!(culler.last_frame_statistics().backface_culled, 3);
+
+        let average = culler.rolling_statistics();
+        assert_eq!(average.frames, 2);
+        assert_eq!(average.triangles_tested, 4.0);
+        assert_eq!(average.triangles_rendered, 2.0);
+        assert_eq!(average.culling_efficiency, 50.0);
+    }
+
+    #[test]
+    fn test_frame_budget_resets_each_frame() {
+        let mut culler = TriangleCuller::new(TriangleCullingConfig {
+            max_triangles_per_frame: 3,
+            ..Default::default()
+        });
+        let triangle = Triangle::new([Vec3::ZERO, Vec3::X, Vec3::Y]);
+
+        culler.test_triangle(&triangle, None);
+        culler.test_triangle(&triangle, None);
+        assert_eq!(culler.frame_budget(), 1);
+
+        culler.end_frame();
+        assert_eq!(culler.frame_budget(), 3);
+        assert_eq!(culler.last_frame_statistics().triangles_tested, 2);
+    }
 }
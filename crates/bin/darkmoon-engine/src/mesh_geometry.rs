@@ -0,0 +1,98 @@
+use std::path::Path;
+
+use kajiya::asset::mesh::PackedTriMesh;
+use kajiya_simple::Vec3;
+
+use crate::math::Aabb;
+
+/// CPU-side copy of a baked mesh's geometry, for culling, statistics and bounds.
+///
+/// Positions are in mesh space, before the instance transform; `indices` form a triangle list.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MeshGeometry {
+    pub positions: Vec<Vec3>,
+    pub indices: Vec<u32>,
+}
+
+impl MeshGeometry {
+    /// Read a mesh baked by the asset pipeline, such as `/cache/<name>.mesh`.
+    ///
+    /// The file is memory-mapped through the same cache `WorldRenderer::add_baked_mesh` uses,
+    /// so meshes already in the scene aren't read from disk again.
+    pub fn load_baked(path: &Path) -> anyhow::Result<Self> {
+        let mesh = kajiya::mmap::mmapped_asset::<PackedTriMesh::Flat, _>(path)?;
+        Ok(Self::from_packed(mesh))
+    }
+
+    pub fn from_packed(mesh: &PackedTriMesh::Flat) -> Self {
+        Self {
+            positions: mesh.verts.iter().map(|vert| Vec3::from(vert.pos)).collect(),
+            indices: mesh.indices.as_slice().to_vec(),
+        }
+    }
+
+    pub fn vertex_count(&self) -> usize {
+        self.positions.len()
+    }
+
+    pub fn triangle_count(&self) -> usize {
+        self.indices.len() / 3
+    }
+
+    /// `None` for a mesh without vertices
+    pub fn bounding_box(&self) -> Option<Aabb> {
+        bounds_of(self.positions.iter().copied())
+    }
+}
+
+/// Mesh-space bounds of a baked mesh, read without copying its geometry.
+/// `None` for a mesh without vertices.
+pub fn load_baked_bounds(path: &Path) -> anyhow::Result<Option<Aabb>> {
+    let mesh = kajiya::mmap::mmapped_asset::<PackedTriMesh::Flat, _>(path)?;
+    Ok(bounds_of(mesh.verts.iter().map(|vert| Vec3::from(vert.pos))))
+}
+
+fn bounds_of(mut positions: impl Iterator<Item = Vec3>) -> Option<Aabb> {
+    let first = positions.next()?;
+    Some(positions.fold(Aabb::new(first, first), Aabb::expanded))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kajiya::asset::mesh::{pack_triangle_mesh, TriangleMesh};
+
+    #[test]
+    fn test_load_baked_mesh() {
+        let dir = std::env::temp_dir().join(format!("darkmoon_baked_mesh_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        // A quad: four vertices, two triangles
+        let quad = TriangleMesh {
+            positions: vec![[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [1.0, 1.0, 0.0], [0.0, 1.0, 0.0]],
+            normals: vec![[0.0, 0.0, 1.0]; 4],
+            indices: vec![0, 1, 2, 0, 2, 3],
+            material_ids: vec![0; 6],
+            ..Default::default()
+        };
+        pack_triangle_mesh(&quad).flatten_into(&mut std::fs::File::create(dir.join("quad.mesh")).unwrap());
+
+        kajiya::backend::set_vfs_mount_point("/test-baked-meshes", &dir);
+        let geometry = MeshGeometry::load_baked(Path::new("/test-baked-meshes/quad.mesh")).unwrap();
+
+        assert_eq!(geometry.vertex_count(), 4);
+        assert_eq!(geometry.triangle_count(), 2);
+        assert_eq!(geometry.positions[2], Vec3::new(1.0, 1.0, 0.0));
+        assert_eq!(
+            geometry.bounding_box(),
+            Some(Aabb::new(Vec3::ZERO, Vec3::new(1.0, 1.0, 0.0)))
+        );
+        assert_eq!(
+            load_baked_bounds(Path::new("/test-baked-meshes/quad.mesh")).unwrap(),
+            geometry.bounding_box()
+        );
+        assert_eq!(MeshGeometry::default().bounding_box(), None);
+
+        assert!(MeshGeometry::load_baked(Path::new("/test-baked-meshes/missing.mesh")).is_err());
+    }
+}
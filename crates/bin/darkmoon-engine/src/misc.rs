@@ -1,8 +1,85 @@
 #![allow(dead_code)]
 
+use kajiya_simple::{Quat, Vec3};
+
 pub fn smoothstep(edge0: f32, edge1: f32, mut x: f32) -> f32 {
     // Scale, bias and saturate x to 0..1 range
     x = ((x - edge0) / (edge1 - edge0)).clamp(0.0, 1.0);
     // Evaluate polynomial
     x * x * (3.0 - 2.0 * x)
 }
+
+/// Interpolates between two unit directions along the great arc joining them,
+/// so equal steps in `t` sweep equal angles (unlike `lerp` + `normalize`).
+pub fn slerp_direction(from: Vec3, to: Vec3, t: f32) -> Vec3 {
+    let rotation = Quat::from_rotation_arc(from, to);
+    (Quat::IDENTITY.slerp(rotation, t) * from).normalize()
+}
+
+/// Heading around +Y (0 along -Z, positive towards +X) and elevation above the horizon, in degrees
+pub fn direction_to_heading_elevation(dir: Vec3) -> (f32, f32) {
+    let dir = dir.normalize_or_zero();
+    (
+        dir.x.atan2(-dir.z).to_degrees(),
+        dir.y.clamp(-1.0, 1.0).asin().to_degrees(),
+    )
+}
+
+/// Inverse of `direction_to_heading_elevation`
+pub fn direction_from_heading_elevation(heading: f32, elevation: f32) -> Vec3 {
+    let (heading, elevation) = (heading.to_radians(), elevation.to_radians());
+    Vec3::new(
+        elevation.cos() * heading.sin(),
+        elevation.sin(),
+        -elevation.cos() * heading.cos(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn angle_between(a: Vec3, b: Vec3) -> f32 {
+        a.dot(b).clamp(-1.0, 1.0).acos()
+    }
+
+    #[test]
+    fn test_slerp_direction_sweeps_uniform_angles() {
+        let from = Vec3::X;
+        let to = Vec3::new(-1.0, 1.0, 0.0).normalize();
+        let total = angle_between(from, to);
+
+        for i in 0..=8 {
+            let t = i as f32 / 8.0;
+
+            let slerped = slerp_direction(from, to, t);
+            assert!((slerped.length() - 1.0).abs() < 1e-5);
+            assert!((angle_between(from, slerped) - total * t).abs() < 1e-4);
+        }
+
+        // Lerp + normalize bunches up around the middle of a wide arc
+        let lerped = from.lerp(to, 0.25).normalize();
+        let slerped = slerp_direction(from, to, 0.25);
+        assert!(angle_between(from, lerped) < angle_between(from, slerped) - 0.05);
+    }
+
+    #[test]
+    fn test_slerp_direction_endpoints() {
+        let from = Vec3::new(0.3, 0.8, -0.2).normalize();
+        let to = Vec3::new(-0.5, 0.1, 0.7).normalize();
+
+        assert!(slerp_direction(from, to, 0.0).abs_diff_eq(from, 1e-5));
+        assert!(slerp_direction(from, to, 1.0).abs_diff_eq(to, 1e-5));
+        assert!(slerp_direction(from, from, 0.5).abs_diff_eq(from, 1e-5));
+    }
+
+    #[test]
+    fn test_heading_elevation_round_trip() {
+        assert_eq!(direction_to_heading_elevation(-Vec3::Z), (0.0, 0.0));
+        assert!(direction_from_heading_elevation(90.0, 0.0).abs_diff_eq(Vec3::X, 1e-5));
+
+        let dir = Vec3::new(-0.4, 0.6, 0.3).normalize();
+        let (heading, elevation) = direction_to_heading_elevation(dir);
+        assert!(direction_from_heading_elevation(heading, elevation).abs_diff_eq(dir, 1e-5));
+    }
+}
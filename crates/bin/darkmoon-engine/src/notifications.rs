@@ -0,0 +1,61 @@
+use std::time::{Duration, Instant};
+
+const ERROR_TOAST_LIFETIME: Duration = Duration::from_secs(6);
+const INFO_TOAST_LIFETIME: Duration = Duration::from_secs(2);
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ToastKind {
+    Error,
+    Info,
+}
+
+pub struct Toast {
+    pub message: String,
+    pub kind: ToastKind,
+    created: Instant,
+}
+
+impl Toast {
+    fn lifetime(&self) -> Duration {
+        match self.kind {
+            ToastKind::Error => ERROR_TOAST_LIFETIME,
+            ToastKind::Info => INFO_TOAST_LIFETIME,
+        }
+    }
+
+    /// 1.0 when fresh, falling to 0.0 as the toast expires
+    pub fn remaining_fraction(&self) -> f32 {
+        1.0 - self.created.elapsed().as_secs_f32() / self.lifetime().as_secs_f32()
+    }
+}
+
+/// Short-lived messages shown in the corner of the viewport
+#[derive(Default)]
+pub struct Toasts {
+    toasts: Vec<Toast>,
+}
+
+impl Toasts {
+    pub fn error(&mut self, message: impl Into<String>) {
+        self.push(message.into(), ToastKind::Error);
+    }
+
+    pub fn info(&mut self, message: impl Into<String>) {
+        self.push(message.into(), ToastKind::Info);
+    }
+
+    fn push(&mut self, message: String, kind: ToastKind) {
+        self.toasts.push(Toast {
+            message,
+            kind,
+            created: Instant::now(),
+        });
+    }
+
+    /// Drop expired toasts and return the rest
+    pub fn active(&mut self) -> &[Toast] {
+        self.toasts
+            .retain(|toast| toast.created.elapsed() < toast.lifetime());
+        &self.toasts
+    }
+}
@@ -49,6 +49,23 @@ pub struct Opt {
     #[structopt(long)]
     pub reset: bool,
 
+    /// Don't write a sample scene when starting in a directory without scenes
+    #[structopt(long)]
+    pub no_sample_scene: bool,
+
+    /// Compare two .dmoon scenes (BASE OTHER), print the differences, and exit
+    #[structopt(long, number_of_values = 2, value_names = &["BASE", "OTHER"])]
+    pub diff_scenes: Vec<PathBuf>,
+
+    /// With --diff-scenes, also write the merged scene here
+    #[structopt(long)]
+    pub merge_output: Option<PathBuf>,
+
+    /// With --merge-output, the scene BASE and OTHER were both edited from. Elements both changed
+    /// differently keep BASE's version.
+    #[structopt(long)]
+    pub merge_ancestor: Option<PathBuf>,
+
     /// ray tracing?
     #[structopt(skip)]
     pub ray_tracing: bool,
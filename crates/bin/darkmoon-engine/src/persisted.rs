@@ -1,14 +1,27 @@
 use std::path::PathBuf;
 
 use kajiya::world_renderer::InstanceHandle;
-use kajiya_simple::{Affine3A, EulerRot, Mat2, Quat, Vec2, Vec3, Vec3Swizzles};
+use kajiya_simple::{Affine3A, EulerRot, Mat2, Mat4, Quat, Vec2, Vec3, Vec3Swizzles};
 
-use crate::{misc::smoothstep, sequence::Sequence, math::{Aabb, TriangleCullingConfig}, culling::FrustumCullingConfig};
+use crate::{layers::Layer, misc::smoothstep, placement::DropPlacement, sequence::Sequence, walk::WalkGround, math::{Aabb, BoundingSphere, TriangleCullingConfig}, culling::{default_true, FrustumCullingConfig}};
 
 #[derive(Clone, PartialEq, serde::Serialize, serde::Deserialize)]
 pub struct SunState {
     pub controller: SunController,
     pub size_multiplier: f32,
+    /// Tint of the sun and sky; multiplied with `intensity`
+    #[serde(default = "default_sun_color")]
+    pub color: Vec3,
+    #[serde(default = "default_sun_intensity")]
+    pub intensity: f32,
+}
+
+fn default_sun_color() -> Vec3 {
+    Vec3::ONE
+}
+
+fn default_sun_intensity() -> f32 {
+    1.0
 }
 
 impl Default for SunState {
@@ -16,6 +29,8 @@ impl Default for SunState {
         Self {
             controller: SunController::default(),
             size_multiplier: 1.0,
+            color: default_sun_color(),
+            intensity: default_sun_intensity(),
         }
     }
 }
@@ -142,11 +157,19 @@ pub trait ShouldResetPathTracer {
     }
 }
 
+fn default_z_far() -> f32 {
+    f32::INFINITY
+}
+
 #[derive(Clone, serde::Serialize, serde::Deserialize)]
 pub struct CameraState {
     pub position: Vec3,
     pub rotation: Quat,
     pub vertical_fov: f32,
+    /// Far plane of the culling frustum. Infinite by default; refitted to the scene on load when
+    /// `FarPlaneConfig::auto_fit` is set.
+    #[serde(default = "default_z_far")]
+    pub z_far: f32,
 }
 
 impl Default for CameraState {
@@ -155,15 +178,80 @@ impl Default for CameraState {
             position: Vec3::ONE,
             rotation: Quat::IDENTITY,
             vertical_fov: 62.0,
+            z_far: default_z_far(),
+        }
+    }
+}
+
+/// Resource streaming options; the cache size and worker count apply the next time streaming starts
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct StreamingSettings {
+    /// Start the streaming system with the engine
+    pub enabled: bool,
+    /// 0 sizes the cache from system memory, see `streaming_integration::cache_size`
+    pub cache_size_mb: u32,
+    /// 0 uses half the CPU cores, between 2 and 8
+    pub worker_threads: u8,
+    /// What the cache evicts when it's full, and what its maintenance removes
+    pub eviction_policy: resource_streaming::EvictionPolicy,
+}
+
+impl Default for StreamingSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            cache_size_mb: 0,
+            worker_threads: 0,
+            eviction_policy: Default::default(),
         }
     }
 }
 
+/// How `CameraState::z_far` follows the scene
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct FarPlaneConfig {
+    /// Fit the far plane to the scene bounds whenever a scene loads
+    pub auto_fit: bool,
+    /// Distance added beyond the scene bounds
+    pub margin: f32,
+}
+
+impl Default for FarPlaneConfig {
+    fn default() -> Self {
+        Self {
+            auto_fit: false,
+            margin: 10.0,
+        }
+    }
+}
+
+impl FarPlaneConfig {
+    /// A far plane that keeps all of `bounds` in view from anywhere inside it, and from `eye`
+    /// if that's outside. The camera can then move around the scene without it being refitted.
+    pub fn fit(&self, bounds: &Aabb, eye: Vec3) -> f32 {
+        let farthest_from_eye = bounds
+            .corners()
+            .iter()
+            .map(|corner| corner.distance(eye))
+            .fold(0.0, f32::max);
+        bounds.size().length().max(farthest_from_eye) + self.margin.max(0.0)
+    }
+}
+
+impl CameraState {
+    /// RON for this camera, suitable for pasting back into a persisted state or bookmark list
+    pub fn to_ron_bookmark(&self) -> anyhow::Result<String> {
+        Ok(ron::ser::to_string_pretty(self, Default::default())?)
+    }
+}
+
 impl ShouldResetPathTracer for CameraState {
     fn should_reset_path_tracer(&self, other: &Self) -> bool {
         !self.position.abs_diff_eq(other.position, 1e-5)
             || !self.rotation.abs_diff_eq(other.rotation, 1e-5)
             || self.vertical_fov != other.vertical_fov
+            || self.z_far != other.z_far
     }
 }
 
@@ -201,11 +289,32 @@ impl ShouldResetPathTracer for LightState {
     }
 }
 
+fn default_eye_height() -> f32 {
+    1.7
+}
+
+fn default_collision_radius() -> f32 {
+    0.3
+}
+
 #[derive(Clone, serde::Serialize, serde::Deserialize)]
 pub struct MovementState {
     pub camera_speed: f32,
     pub camera_smoothness: f32,
     pub sun_rotation_smoothness: f32,
+    /// Reference mode normally disables camera and sun smoothing so accumulation isn't smeared;
+    /// enable this to keep smoothing, e.g. when recording path-traced flythroughs
+    #[serde(default)]
+    pub smoothing_in_reference_mode: bool,
+    /// Keep the camera at `eye_height` above the ground instead of flying freely
+    #[serde(default)]
+    pub walk_mode: bool,
+    #[serde(default)]
+    pub walk_ground: WalkGround,
+    #[serde(default = "default_eye_height")]
+    pub eye_height: f32,
+    #[serde(default = "default_collision_radius")]
+    pub collision_radius: f32,
 }
 
 impl Default for MovementState {
@@ -214,6 +323,11 @@ impl Default for MovementState {
             camera_speed: 2.5,
             camera_smoothness: 1.0,
             sun_rotation_smoothness: 0.0,
+            smoothing_in_reference_mode: false,
+            walk_mode: false,
+            walk_ground: WalkGround::default(),
+            eye_height: default_eye_height(),
+            collision_radius: default_collision_radius(),
         }
     }
 }
@@ -254,11 +368,110 @@ impl Default for ExposureState {
 
 impl ShouldResetPathTracer for ExposureState {}
 
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct EditingState {
+    /// World units moved per nudge key press
+    pub nudge_step: f32,
+    /// Step multiplier while the coarse nudge modifier is held
+    pub nudge_coarse_multiplier: f32,
+    /// Step multiplier while the fine nudge modifier is held
+    pub nudge_fine_multiplier: f32,
+    /// Unit lengths are shown and edited in; they are always stored in meters
+    #[serde(default)]
+    pub display_unit: LengthUnit,
+    #[serde(default)]
+    pub transform_space: TransformSpace,
+    /// Where dropped meshes go; holding Alt while dropping puts them at the origin instead
+    #[serde(default)]
+    pub drop_placement: DropPlacement,
+    /// Distance from the camera for `DropPlacement::InFrontOfCamera`
+    #[serde(default = "default_drop_distance")]
+    pub drop_distance: f32,
+    /// How far duplicates are moved from the original, so they don't overlap it
+    #[serde(default = "default_duplicate_offset")]
+    pub duplicate_offset: Vec3,
+}
+
+fn default_drop_distance() -> f32 {
+    5.0
+}
+
+fn default_duplicate_offset() -> Vec3 {
+    Vec3::new(0.5, 0.0, 0.5)
+}
+
+impl Default for EditingState {
+    fn default() -> Self {
+        Self {
+            nudge_step: 0.1,
+            nudge_coarse_multiplier: 10.0,
+            nudge_fine_multiplier: 0.1,
+            display_unit: LengthUnit::default(),
+            transform_space: TransformSpace::default(),
+            drop_placement: DropPlacement::default(),
+            drop_distance: default_drop_distance(),
+            duplicate_offset: default_duplicate_offset(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum LengthUnit {
+    Meters,
+    Centimeters,
+}
+
+impl Default for LengthUnit {
+    fn default() -> Self {
+        Self::Meters
+    }
+}
+
+impl LengthUnit {
+    pub fn per_meter(self) -> f32 {
+        match self {
+            Self::Meters => 1.0,
+            Self::Centimeters => 100.0,
+        }
+    }
+
+    pub fn suffix(self) -> &'static str {
+        match self {
+            Self::Meters => "m",
+            Self::Centimeters => "cm",
+        }
+    }
+
+    /// imgui format string for a length in this unit
+    pub fn drag_format(self) -> &'static str {
+        match self {
+            Self::Meters => "%.3f m",
+            Self::Centimeters => "%.1f cm",
+        }
+    }
+}
+
+/// Whether the Attributes window shows positions relative to the parent or in world space
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum TransformSpace {
+    World,
+    Local,
+}
+
+impl Default for TransformSpace {
+    fn default() -> Self {
+        Self::Local
+    }
+}
+
 #[derive(Clone, Debug, Default, serde::Serialize, serde::Deserialize, PartialEq)]
 pub struct SceneElementTransform {
     pub position: Vec3,
     pub rotation_euler_degrees: Vec3,
     pub scale: Vec3,
+    /// Point in mesh space that `position` places, and that rotation and scale happen about
+    #[serde(default)]
+    pub pivot: Vec3,
 }
 
 impl SceneElementTransform {
@@ -266,6 +479,7 @@ impl SceneElementTransform {
         position: Vec3::ZERO,
         rotation_euler_degrees: Vec3::ZERO,
         scale: Vec3::ONE,
+        pivot: Vec3::ZERO,
     };
 
     pub fn affine_transform(&self) -> Affine3A {
@@ -278,7 +492,27 @@ impl SceneElementTransform {
                 self.rotation_euler_degrees.z.to_radians(),
             ),
             self.position,
-        )
+        ) * Affine3A::from_translation(-self.pivot)
+    }
+
+    /// Move the pivot to `pivot`, compensating `position` so the mesh stays put in the world
+    pub fn set_pivot_preserving_placement(&mut self, pivot: Vec3) {
+        self.position += self.affine_transform().transform_vector3(pivot - self.pivot);
+        self.pivot = pivot;
+    }
+
+    /// Apply the edit that turned `before` into `after` on top of this transform: position and
+    /// rotation shift by the same amount, scale changes by the same factor. Used to edit several
+    /// elements at once without snapping them all to the same values.
+    pub fn with_edit(&self, before: &Self, after: &Self) -> Self {
+        let scale_factor = Vec3::select(before.scale.cmpeq(Vec3::ZERO), Vec3::ONE, after.scale / before.scale);
+        Self {
+            position: self.position + (after.position - before.position),
+            rotation_euler_degrees: self.rotation_euler_degrees
+                + (after.rotation_euler_degrees - before.rotation_euler_degrees),
+            scale: (self.scale * scale_factor).max(Vec3::splat(0.001)),
+            pivot: self.pivot,
+        }
     }
 }
 
@@ -293,30 +527,233 @@ pub struct SceneElement {
     #[serde(skip)]
     pub instance: InstanceHandle,
 
+    /// Stable across saves; elements persisted before IDs existed get a fresh one
+    #[serde(default = "crate::scene::new_element_id")]
+    pub id: u64,
+
+    /// Label set by the user; without one the element is listed by its first node or source
+    #[serde(default)]
+    pub name: Option<String>,
+
     pub source: MeshSource,
     pub transform: SceneElementTransform,
+
+    /// Free-form labels for organizing and filtering, normalized by `tags::add_tag`
+    #[serde(default)]
+    pub tags: Vec<String>,
+
+    /// What was last handed to the renderer; differs from `transform` while culled
+    #[serde(skip)]
+    pub render_transform: Option<SceneElementTransform>,
     
     #[serde(skip)]
     pub bounding_box: Option<Aabb>,
+
+    /// Local-space sphere around `bounding_box`, with the box it was derived from
+    #[serde(skip)]
+    pub bounding_sphere_cache: Option<(Aabb, BoundingSphere)>,
     
     // For GLTF files with multiple nodes/meshes
     pub mesh_nodes: Vec<MeshNode>,
     
     // Indicates if this element represents a single mesh or a collection
     pub is_compound: bool,
+
+    /// Drawn as one renderer instance per mesh node instead of the file baked as a whole, so
+    /// nodes can be moved within the element. Set when a node is first edited.
+    #[serde(default)]
+    pub split_nodes: bool,
+
+    /// The per-node instances, in `mesh_nodes` order, while `split_nodes` is set
+    #[serde(skip)]
+    pub node_instances: Vec<InstanceHandle>,
+
+    /// Off for geometry like sky domes that would otherwise shadow the scene
+    #[serde(default = "default_true")]
+    pub casts_shadow: bool,
+
+    /// Toggled from the Outliner; hidden elements go through the same hiding path as culled ones
+    #[serde(default = "default_true")]
+    pub visible: bool,
+
+    /// Fills its bounding box, so baked visibility treats the box as blocking sight
+    #[serde(default)]
+    pub occluder: bool,
 }
 
-#[derive(Clone, Default, serde::Serialize, serde::Deserialize)]
+impl SceneElement {
+    /// A visible, shadow-casting element of `source` with no renderer instance yet and nothing
+    /// known about its mesh
+    pub fn new(id: u64, source: MeshSource, transform: SceneElementTransform) -> Self {
+        Self {
+            instance: InstanceHandle::INVALID,
+            id,
+            name: None,
+            source,
+            transform,
+            tags: Vec::new(),
+            render_transform: None,
+            bounding_box: None,
+            bounding_sphere_cache: None,
+            mesh_nodes: Vec::new(),
+            is_compound: false,
+            split_nodes: false,
+            node_instances: Vec::new(),
+            casts_shadow: true,
+            visible: true,
+            occluder: false,
+        }
+    }
+
+    /// The renderer instances drawing the element: its node instances once split, else `instance`
+    pub fn instances(&self) -> &[InstanceHandle] {
+        if self.split_nodes {
+            &self.node_instances
+        } else {
+            std::slice::from_ref(&self.instance)
+        }
+    }
+
+    /// Where each of `instances` goes when the element is placed by `transform`
+    pub fn instance_transforms(&self, transform: &SceneElementTransform) -> Vec<Affine3A> {
+        let affine = transform.affine_transform();
+        if self.split_nodes {
+            self.mesh_nodes
+                .iter()
+                .map(|node| affine * node.local_transform.affine_transform())
+                .collect()
+        } else {
+            vec![affine]
+        }
+    }
+
+    /// Whether every mesh node knows which glTF node to bake on its own, so the element can be
+    /// split. Nodes analyzed before that was recorded need analyzing again.
+    pub fn can_split_nodes(&self) -> bool {
+        matches!(self.source, MeshSource::File(_))
+            && !self.mesh_nodes.is_empty()
+            && self.mesh_nodes.iter().all(|node| node.gltf_node.is_some())
+    }
+
+    /// Mesh-space bounds: the element's own box, else the union of its node boxes
+    pub fn local_bounds(&self, default_object_size: f32) -> Aabb {
+        if let Some(aabb) = self.bounding_box {
+            return aabb;
+        }
+
+        self.mesh_nodes
+            .iter()
+            .filter_map(|node| {
+                let aabb = node.bounding_box?;
+                Some(aabb.transform(&Mat4::from(node.local_transform.affine_transform())))
+            })
+            .reduce(|a, b| a.union(&b))
+            .unwrap_or_else(|| Aabb::from_center_size(Vec3::ZERO, Vec3::splat(default_object_size)))
+    }
+
+    /// Whether `other` has the same authoring data: everything saved with the scene, but not
+    /// the renderer state (`instance`, `render_transform`) that culling rewrites every frame
+    pub fn same_authoring_data(&self, other: &Self) -> bool {
+        self.id == other.id
+            && self.name == other.name
+            && self.source == other.source
+            && self.transform == other.transform
+            && self.tags == other.tags
+            && self.bounding_box == other.bounding_box
+            && self.mesh_nodes == other.mesh_nodes
+            && self.is_compound == other.is_compound
+            && self.split_nodes == other.split_nodes
+            && self.casts_shadow == other.casts_shadow
+            && self.visible == other.visible
+            && self.occluder == other.occluder
+    }
+
+    /// A copy moved by `offset`, with a fresh ID and no renderer instance yet. The bounding box
+    /// is dropped too: whether it was guessed is tracked by ID, so it gets worked out again.
+    pub fn duplicate(&self, offset: Vec3) -> Self {
+        let mut copy = self.clone();
+        copy.id = crate::scene::new_element_id();
+        copy.instance = InstanceHandle::INVALID;
+        copy.node_instances.clear();
+        copy.transform.position += offset;
+        copy.render_transform = None;
+        copy.bounding_box = None;
+        copy.bounding_sphere_cache = None;
+        copy
+    }
+
+    /// `local_bounds` placed in the world by the authoring transform
+    pub fn world_bounds(&self, default_object_size: f32) -> Aabb {
+        self.local_bounds(default_object_size)
+            .transform(&Mat4::from(self.transform.affine_transform()))
+    }
+
+    /// Local-space sphere around `bounding_box`, recomputed only when the box changes
+    pub fn local_bounding_sphere(&mut self) -> Option<BoundingSphere> {
+        let aabb = self.bounding_box?;
+
+        match self.bounding_sphere_cache {
+            Some((cached_aabb, sphere)) if cached_aabb == aabb => Some(sphere),
+            _ => {
+                let sphere = BoundingSphere::from_aabb(&aabb);
+                self.bounding_sphere_cache = Some((aabb, sphere));
+                Some(sphere)
+            }
+        }
+    }
+
+    /// Put the pivot at the center of the geometry, without moving the element
+    pub fn center_pivot(&mut self, default_object_size: f32) {
+        let center = self.local_bounds(default_object_size).center();
+        self.transform.set_pivot_preserving_placement(center);
+    }
+}
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
 pub struct SceneState {
     pub elements: Vec<SceneElement>,
 
     #[serde(default)]
     pub ibl: Option<PathBuf>,
+
+    #[serde(default)]
+    pub layers: Vec<Layer>,
+
+    /// Seeds `RuntimeState::rng`; saved with the scene
+    #[serde(default)]
+    pub seed: u64,
+
+    /// Cull with the bounds computed from each mesh, guessing `default_object_size` only for
+    /// meshes whose geometry can't be read. When off, every element gets the guessed size.
+    #[serde(default = "default_true")]
+    pub auto_bounds: bool,
+}
+
+impl Default for SceneState {
+    fn default() -> Self {
+        Self {
+            elements: Vec::new(),
+            ibl: None,
+            layers: Vec::new(),
+            seed: 0,
+            auto_bounds: true,
+        }
+    }
+}
+
+impl SceneState {
+    /// Union of the world bounds of all elements; `None` for an empty scene
+    pub fn bounds(&self, default_object_size: f32) -> Option<Aabb> {
+        self.elements
+            .iter()
+            .map(|elem| elem.world_bounds(default_object_size))
+            .reduce(|a, b| a.union(&b))
+    }
 }
 
 impl ShouldResetPathTracer for SceneState {
     fn should_reset_path_tracer(&self, other: &Self) -> bool {
-        self.elements != other.elements
+        self.elements != other.elements || self.layers != other.layers
     }
 }
 
@@ -330,11 +767,20 @@ pub struct PersistedState {
     #[serde(default)]
     pub scene: SceneState,
     #[serde(default)]
-    pub frustum_culling: FrustumCullingConfig,
-    #[serde(default)]
-    pub occlusion_culling: crate::math::OcclusionCullingConfig,
-    #[serde(default)]
-    pub triangle_culling: crate::math::TriangleCullingConfig,
+    pub streaming: StreamingSettings,
+
+    // Editor preferences used to be stored with the view state. They now live in the app config;
+    // these are only read from older files so `AppConfig::migrate_legacy_preferences` can move them.
+    #[serde(default, rename = "frustum_culling", skip_serializing)]
+    pub legacy_frustum_culling: Option<FrustumCullingConfig>,
+    #[serde(default, rename = "occlusion_culling", skip_serializing)]
+    pub legacy_occlusion_culling: Option<crate::math::OcclusionCullingConfig>,
+    #[serde(default, rename = "triangle_culling", skip_serializing)]
+    pub legacy_triangle_culling: Option<TriangleCullingConfig>,
+    #[serde(default, rename = "selection_highlight", skip_serializing)]
+    pub legacy_selection_highlight: Option<crate::selection::SelectionHighlightConfig>,
+    #[serde(default, rename = "editing", skip_serializing)]
+    pub legacy_editing: Option<EditingState>,
 }
 
 impl ShouldResetPathTracer for PersistedState {
@@ -352,6 +798,9 @@ pub struct MeshNode {
     pub name: Option<String>,
     pub local_transform: SceneElementTransform,
     pub bounding_box: Option<Aabb>,
+    /// Index of the node in the glTF file, for baking it on its own
+    #[serde(default)]
+    pub gltf_node: Option<usize>,
 }
 
 impl Default for MeshNode {
@@ -360,6 +809,241 @@ impl Default for MeshNode {
             name: None,
             local_transform: SceneElementTransform::IDENTITY,
             bounding_box: None,
+            gltf_node: None,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cached_bounding_sphere_matches_aabb() {
+        let mut elem = SceneElement {
+            bounding_box: Some(Aabb::new(Vec3::new(-1.0, 0.0, 2.0), Vec3::new(3.0, 2.0, 4.0))),
+            ..SceneElement::new(1, MeshSource::File(PathBuf::from("test.gltf")), SceneElementTransform::IDENTITY)
+        };
+
+        let sphere = elem.local_bounding_sphere().unwrap();
+        assert_eq!(sphere.center, Vec3::new(1.0, 1.0, 3.0));
+        assert!((sphere.radius - Vec3::new(2.0, 1.0, 1.0).length()).abs() < 1e-6);
+        assert_eq!(elem.local_bounding_sphere(), Some(sphere));
+
+        // Changing the box invalidates the cache
+        let new_aabb = Aabb::from_center_size(Vec3::ZERO, Vec3::splat(2.0));
+        elem.bounding_box = Some(new_aabb);
+        assert_eq!(elem.local_bounding_sphere(), Some(BoundingSphere::from_aabb(&new_aabb)));
+
+        elem.bounding_box = None;
+        assert_eq!(elem.local_bounding_sphere(), None);
+    }
+
+    #[test]
+    fn test_far_plane_fits_scene_bounds() {
+        let element = |position: Vec3| SceneElement {
+            bounding_box: Some(Aabb::from_center_size(Vec3::ZERO, Vec3::splat(2.0))),
+            ..SceneElement::new(
+                1,
+                MeshSource::File(PathBuf::from("test.gltf")),
+                SceneElementTransform {
+                    position,
+                    ..SceneElementTransform::IDENTITY
+                },
+            )
+        };
+
+        let mut scene = SceneState::default();
+        assert!(scene.bounds(1.0).is_none());
+
+        scene.elements.push(element(Vec3::new(-100.0, 0.0, 0.0)));
+        scene.elements.push(element(Vec3::new(100.0, 0.0, 0.0)));
+        let bounds = scene.bounds(1.0).unwrap();
+        assert_eq!(bounds, Aabb::new(Vec3::new(-101.0, -1.0, -1.0), Vec3::new(101.0, 1.0, 1.0)));
+
+        let config = FarPlaneConfig {
+            auto_fit: true,
+            margin: 5.0,
+        };
+        // From inside, the whole diagonal, so moving within the scene never clips it
+        let diagonal = bounds.size().length() + 5.0;
+        assert!((config.fit(&bounds, Vec3::ZERO) - diagonal).abs() < 1e-3);
+        assert!((config.fit(&bounds, Vec3::new(90.0, 0.0, 0.0)) - diagonal).abs() < 1e-3);
+        // From far outside, the whole scene still fits
+        let eye = Vec3::new(-1000.0, 0.0, 0.0);
+        let from_outside = Vec3::new(1101.0, 1.0, 1.0).length() + 5.0;
+        assert!((config.fit(&bounds, eye) - from_outside).abs() < 1e-3);
+        assert!(CameraState::default().z_far.is_infinite());
+    }
+
+    #[test]
+    fn test_center_pivot_preserves_world_vertices() {
+        let mut transform = SceneElementTransform {
+            position: Vec3::new(4.0, -1.0, 2.5),
+            rotation_euler_degrees: Vec3::new(30.0, 45.0, -10.0),
+            scale: Vec3::new(2.0, 0.5, 1.5),
+            pivot: Vec3::ZERO,
+        };
+
+        // Geometry sitting well away from its origin
+        let vertices = [
+            Vec3::new(10.0, 0.0, 10.0),
+            Vec3::new(12.0, 3.0, 10.0),
+            Vec3::new(11.0, 1.0, 14.0),
+        ];
+        let world_before = vertices.map(|v| transform.affine_transform().transform_point3(v));
+
+        let center = Aabb::from_points(&vertices).center();
+        transform.set_pivot_preserving_placement(center);
+        assert_eq!(transform.pivot, center);
+
+        for (v, before) in vertices.iter().zip(world_before) {
+            let after = transform.affine_transform().transform_point3(*v);
+            assert!((after - before).length() < 1e-4, "{} moved to {}", before, after);
+        }
+
+        // The pivot is now where `position` puts it
+        let pivot_world = transform.affine_transform().transform_point3(center);
+        assert!((pivot_world - transform.position).length() < 1e-4);
+    }
+
+    #[test]
+    fn test_batch_edit_applies_relative_change() {
+        let before = SceneElementTransform {
+            position: Vec3::new(1.0, 0.0, 0.0),
+            rotation_euler_degrees: Vec3::new(0.0, 10.0, 0.0),
+            scale: Vec3::splat(2.0),
+            pivot: Vec3::ZERO,
+        };
+        let after = SceneElementTransform {
+            position: Vec3::new(1.5, 0.0, 0.0),
+            rotation_euler_degrees: Vec3::new(0.0, 40.0, 0.0),
+            scale: Vec3::new(4.0, 2.0, 2.0),
+            ..before.clone()
+        };
+
+        let other = SceneElementTransform {
+            position: Vec3::new(-3.0, 2.0, 0.0),
+            rotation_euler_degrees: Vec3::new(90.0, -20.0, 0.0),
+            scale: Vec3::new(1.0, 3.0, 0.5),
+            pivot: Vec3::Y,
+        };
+        let edited = other.with_edit(&before, &after);
+
+        assert_eq!(edited.position, Vec3::new(-2.5, 2.0, 0.0));
+        assert_eq!(edited.rotation_euler_degrees, Vec3::new(90.0, 10.0, 0.0));
+        assert_eq!(edited.scale, Vec3::new(2.0, 3.0, 0.5));
+        assert_eq!(edited.pivot, Vec3::Y);
+    }
+
+    #[test]
+    fn test_render_state_is_not_authoring_data() {
+        let elem = SceneElement::new(
+            1,
+            MeshSource::File(PathBuf::from("rock.gltf")),
+            SceneElementTransform::IDENTITY,
+        );
+
+        let culled = SceneElement {
+            instance: InstanceHandle(3),
+            render_transform: Some(SceneElementTransform {
+                scale: Vec3::ZERO,
+                ..SceneElementTransform::IDENTITY
+            }),
+            ..elem.clone()
+        };
+        assert!(elem.same_authoring_data(&culled));
+
+        let moved = SceneElement {
+            transform: SceneElementTransform {
+                position: Vec3::X,
+                ..SceneElementTransform::IDENTITY
+            },
+            ..elem.clone()
+        };
+        assert!(!elem.same_authoring_data(&moved));
+
+        let retargeted = SceneElement {
+            source: MeshSource::File(PathBuf::from("tree.gltf")),
+            ..elem.clone()
+        };
+        assert!(!elem.same_authoring_data(&retargeted));
+    }
+
+    #[test]
+    fn test_duplicate_keeps_compound_nodes() {
+        let node = MeshNode {
+            name: Some("Wheel".to_string()),
+            local_transform: SceneElementTransform::IDENTITY,
+            bounding_box: Some(Aabb::new(Vec3::ZERO, Vec3::ONE)),
+            gltf_node: Some(3),
+        };
+        let original = SceneElement {
+            instance: InstanceHandle(4),
+            tags: vec!["vehicle".to_string()],
+            render_transform: Some(SceneElementTransform::IDENTITY),
+            bounding_box: Some(Aabb::new(Vec3::ZERO, Vec3::ONE)),
+            mesh_nodes: vec![node.clone(), MeshNode::default()],
+            is_compound: true,
+            ..SceneElement::new(
+                1,
+                MeshSource::File(PathBuf::from("car.gltf")),
+                SceneElementTransform {
+                    position: Vec3::new(1.0, 2.0, 3.0),
+                    ..SceneElementTransform::IDENTITY
+                },
+            )
+        };
+
+        let copy = original.duplicate(Vec3::X);
+        assert_ne!(copy.id, original.id);
+        assert!(!copy.instance.is_valid());
+        assert_eq!(copy.transform.position, Vec3::new(2.0, 2.0, 3.0));
+        assert!(copy.render_transform.is_none());
+        assert!(copy.bounding_box.is_none());
+        assert!(copy.is_compound);
+        assert_eq!(copy.mesh_nodes.len(), 2);
+        assert_eq!(copy.mesh_nodes[0].name, node.name);
+        assert_eq!(copy.tags, original.tags);
+    }
+
+    #[test]
+    fn test_split_element_places_an_instance_per_node() {
+        let node = |x: f32| MeshNode {
+            local_transform: SceneElementTransform {
+                position: Vec3::new(x, 0.0, 0.0),
+                ..SceneElementTransform::IDENTITY
+            },
+            gltf_node: Some(x as usize),
+            ..Default::default()
+        };
+        let mut elem = SceneElement {
+            instance: InstanceHandle(4),
+            mesh_nodes: vec![node(1.0), node(2.0)],
+            is_compound: true,
+            ..SceneElement::new(1, MeshSource::File(PathBuf::from("car.gltf")), SceneElementTransform::IDENTITY)
+        };
+        let placed = SceneElementTransform {
+            position: Vec3::new(0.0, 5.0, 0.0),
+            ..SceneElementTransform::IDENTITY
+        };
+
+        assert!(elem.can_split_nodes());
+        assert_eq!(elem.instances(), &[InstanceHandle(4)]);
+        assert_eq!(elem.instance_transforms(&placed).len(), 1);
+
+        elem.split_nodes = true;
+        elem.node_instances = vec![InstanceHandle(5), InstanceHandle(6)];
+        assert_eq!(elem.instances(), &[InstanceHandle(5), InstanceHandle(6)]);
+        let origins: Vec<Vec3> = elem
+            .instance_transforms(&placed)
+            .iter()
+            .map(|affine| affine.translation.into())
+            .collect();
+        assert_eq!(origins, vec![Vec3::new(1.0, 5.0, 0.0), Vec3::new(2.0, 5.0, 0.0)]);
+
+        // Nodes analyzed before their glTF index was recorded can't be baked on their own
+        elem.mesh_nodes.push(MeshNode::default());
+        assert!(!elem.can_split_nodes());
+    }
+}
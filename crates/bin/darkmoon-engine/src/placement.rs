@@ -0,0 +1,86 @@
+use kajiya_simple::Vec3;
+use serde::{Deserialize, Serialize};
+
+use crate::math::Aabb;
+
+/// Where meshes dropped onto the window are placed
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DropPlacement {
+    /// At the world origin
+    Origin,
+    /// On the camera's view axis, `drop_distance` away
+    InFrontOfCamera,
+    /// Where the view axis hits scene bounds or the y=0 plane, falling back to in front of the camera
+    GroundHit,
+}
+
+impl Default for DropPlacement {
+    fn default() -> Self {
+        Self::InFrontOfCamera
+    }
+}
+
+/// Hits further than this are ignored, so looking at the horizon doesn't throw meshes miles away
+const MAX_GROUND_HIT_DISTANCE: f32 = 1000.0;
+
+/// Position for a new element, given the camera and the world-space bounds of the scene
+pub fn drop_position(
+    placement: DropPlacement,
+    camera_position: Vec3,
+    camera_forward: Vec3,
+    distance: f32,
+    world_bounds: impl Iterator<Item = Aabb>,
+) -> Vec3 {
+    let in_front = camera_position + camera_forward * distance;
+
+    match placement {
+        DropPlacement::Origin => Vec3::ZERO,
+        DropPlacement::InFrontOfCamera => in_front,
+        DropPlacement::GroundHit => {
+            // Ray-plane hit with y=0, only when looking down at it
+            let plane_hit = (camera_forward.y < -1e-4)
+                .then(|| -camera_position.y / camera_forward.y)
+                .filter(|t| *t >= 0.0);
+
+            world_bounds
+                .filter(|aabb| !aabb.contains_point(camera_position))
+                .filter_map(|aabb| aabb.ray_intersection(camera_position, camera_forward))
+                .chain(plane_hit)
+                .filter(|t| *t <= MAX_GROUND_HIT_DISTANCE)
+                .min_by(|a, b| a.total_cmp(b))
+                .map_or(in_front, |t| camera_position + camera_forward * t)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_in_front_of_camera() {
+        let pos = drop_position(DropPlacement::InFrontOfCamera, Vec3::Y, -Vec3::Z, 5.0, std::iter::empty());
+        assert_eq!(pos, Vec3::new(0.0, 1.0, -5.0));
+    }
+
+    #[test]
+    fn test_ground_hit_prefers_nearest_surface() {
+        let forward = Vec3::new(0.0, -1.0, -1.0).normalize();
+        let camera = Vec3::new(0.0, 4.0, 0.0);
+
+        // Just the ground plane
+        let pos = drop_position(DropPlacement::GroundHit, camera, forward, 5.0, std::iter::empty());
+        assert!((pos - Vec3::new(0.0, 0.0, -4.0)).length() < 1e-4);
+
+        // A box in the way is hit first
+        let block = Aabb::new(Vec3::new(-1.0, 0.0, -3.0), Vec3::new(1.0, 2.0, -1.0));
+        let pos = drop_position(DropPlacement::GroundHit, camera, forward, 5.0, std::iter::once(block));
+        assert!((pos - Vec3::new(0.0, 2.0, -2.0)).length() < 1e-4);
+    }
+
+    #[test]
+    fn test_ground_hit_falls_back_when_looking_up() {
+        let pos = drop_position(DropPlacement::GroundHit, Vec3::Y, Vec3::Y, 5.0, std::iter::empty());
+        assert_eq!(pos, Vec3::new(0.0, 6.0, 0.0));
+    }
+}
@@ -0,0 +1,316 @@
+use std::{
+    fmt::Write as _,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Context;
+use kajiya_simple::{Mat4, Vec3};
+
+/// Where generated primitives are written, under the meshes mount so scenes can reference
+/// them like any other mesh
+pub const PRIMITIVES_DIR: &str = "/meshes/primitives";
+
+/// `PRIMITIVES_DIR` on disk, wherever `/meshes` is mounted
+pub fn primitives_dir() -> anyhow::Result<PathBuf> {
+    kajiya::backend::mounted_path_from_vfs(PRIMITIVES_DIR)
+}
+
+/// Built-in meshes for blocking out scenes without external assets. All are one unit across,
+/// centered on the origin.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Primitive {
+    Cube,
+    Sphere,
+    Plane,
+}
+
+impl Primitive {
+    pub const ALL: [Primitive; 3] = [Primitive::Cube, Primitive::Sphere, Primitive::Plane];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Primitive::Cube => "Cube",
+            Primitive::Sphere => "Sphere",
+            Primitive::Plane => "Plane",
+        }
+    }
+
+    pub fn file_stem(self) -> &'static str {
+        match self {
+            Primitive::Cube => "cube",
+            Primitive::Sphere => "sphere",
+            Primitive::Plane => "plane",
+        }
+    }
+
+    pub fn generate(self) -> ProceduralMesh {
+        match self {
+            Primitive::Cube => ProceduralMesh::cube(),
+            Primitive::Sphere => ProceduralMesh::uv_sphere(32, 16),
+            Primitive::Plane => ProceduralMesh::plane(),
+        }
+    }
+
+    /// VFS path of the primitive's glTF file, as scenes reference it
+    pub fn vfs_path(self) -> String {
+        format!("{}/{}.gltf", PRIMITIVES_DIR, self.file_stem())
+    }
+
+    /// The primitive's glTF file in `dir`, written first if it isn't there yet
+    pub fn ensure_file(self, dir: &Path) -> anyhow::Result<PathBuf> {
+        let path = dir.join(format!("{}.gltf", self.file_stem()));
+        if !path.exists() {
+            std::fs::create_dir_all(dir).with_context(|| format!("Creating {:?}", dir))?;
+            self.generate()
+                .write_gltf(self.name(), &path)
+                .with_context(|| format!("Writing primitive {:?}", path))?;
+        }
+
+        Ok(path)
+    }
+}
+
+/// Triangle list with per-vertex normals and UVs; front faces wind counter-clockwise like glTF
+#[derive(Clone, Debug, Default)]
+pub struct ProceduralMesh {
+    pub positions: Vec<Vec3>,
+    pub normals: Vec<Vec3>,
+    pub uvs: Vec<[f32; 2]>,
+    pub indices: Vec<u32>,
+}
+
+impl ProceduralMesh {
+    pub fn cube() -> Self {
+        let mut mesh = Self::default();
+        let faces = [
+            (Vec3::X, -Vec3::Z),
+            (-Vec3::X, Vec3::Z),
+            (Vec3::Y, Vec3::X),
+            (-Vec3::Y, Vec3::X),
+            (Vec3::Z, Vec3::X),
+            (-Vec3::Z, -Vec3::X),
+        ];
+        for (normal, u) in faces {
+            mesh.add_quad(normal * 0.5, normal, u);
+        }
+        mesh
+    }
+
+    /// A unit square on the XZ plane, facing up
+    pub fn plane() -> Self {
+        let mut mesh = Self::default();
+        mesh.add_quad(Vec3::ZERO, Vec3::Y, Vec3::X);
+        mesh
+    }
+
+    /// Sphere of `segments` around the Y axis and `rings` from pole to pole
+    pub fn uv_sphere(segments: u32, rings: u32) -> Self {
+        let mut mesh = Self::default();
+
+        for ring in 0..=rings {
+            let theta = std::f32::consts::PI * ring as f32 / rings as f32;
+            for segment in 0..=segments {
+                let phi = std::f32::consts::TAU * segment as f32 / segments as f32;
+                let normal = Vec3::new(theta.sin() * phi.cos(), theta.cos(), theta.sin() * phi.sin());
+
+                mesh.positions.push(normal * 0.5);
+                mesh.normals.push(normal);
+                mesh.uvs.push([segment as f32 / segments as f32, ring as f32 / rings as f32]);
+            }
+        }
+
+        let stride = segments + 1;
+        for ring in 0..rings {
+            for segment in 0..segments {
+                let a = ring * stride + segment;
+                let b = a + stride;
+
+                // The triangles touching the poles would be degenerate
+                if ring != 0 {
+                    mesh.indices.extend_from_slice(&[a, a + 1, b]);
+                }
+                if ring != rings - 1 {
+                    mesh.indices.extend_from_slice(&[a + 1, b + 1, b]);
+                }
+            }
+        }
+
+        mesh
+    }
+
+    /// A unit quad centered at `center`, facing `normal`, with `u` along its first edge
+    fn add_quad(&mut self, center: Vec3, normal: Vec3, u: Vec3) {
+        let v = normal.cross(u);
+        let base = self.positions.len() as u32;
+
+        for (du, dv) in [(-0.5, -0.5), (0.5, -0.5), (0.5, 0.5), (-0.5, 0.5)] {
+            self.positions.push(center + u * du + v * dv);
+            self.normals.push(normal);
+            self.uvs.push([du + 0.5, 0.5 - dv]);
+        }
+
+        self.indices
+            .extend_from_slice(&[base, base + 1, base + 2, base, base + 2, base + 3]);
+    }
+
+    /// Writes a glTF file with a single mesh, and its buffer next to it as `<stem>.bin`
+    pub fn write_gltf(&self, name: &str, path: &Path) -> anyhow::Result<()> {
+        self.write_gltf_with_transform(name, path, Mat4::IDENTITY)
+    }
+
+    /// Like `write_gltf`, with `transform` as the matrix of the mesh's node
+    pub fn write_gltf_with_transform(&self, name: &str, path: &Path, transform: Mat4) -> anyhow::Result<()> {
+        let bin_path = path.with_extension("bin");
+        let bin_uri = bin_path
+            .file_name()
+            .context("glTF path has no file name")?
+            .to_string_lossy()
+            .into_owned();
+
+        let mut bin = Vec::new();
+        let mut views = Vec::new();
+        let mut push_view = |bytes: Vec<u8>, target: u32| {
+            views.push((bin.len(), bytes.len(), target));
+            bin.extend_from_slice(&bytes);
+        };
+
+        const ARRAY_BUFFER: u32 = 34962;
+        const ELEMENT_ARRAY_BUFFER: u32 = 34963;
+        push_view(vec3_bytes(&self.positions), ARRAY_BUFFER);
+        push_view(vec3_bytes(&self.normals), ARRAY_BUFFER);
+        push_view(self.uvs.iter().flatten().flat_map(|x| x.to_le_bytes()).collect(), ARRAY_BUFFER);
+        push_view(self.indices.iter().flat_map(|i| i.to_le_bytes()).collect(), ELEMENT_ARRAY_BUFFER);
+
+        let (min, max) = self.positions.iter().fold(
+            (Vec3::splat(f32::MAX), Vec3::splat(f32::MIN)),
+            |(min, max), p| (min.min(*p), max.max(*p)),
+        );
+
+        let mut buffer_views = String::new();
+        for (i, (offset, length, target)) in views.iter().enumerate() {
+            if i > 0 {
+                buffer_views.push(',');
+            }
+            write!(
+                buffer_views,
+                r#"{{"buffer":0,"byteOffset":{},"byteLength":{},"target":{}}}"#,
+                offset, length, target
+            )?;
+        }
+
+        // Element names and file names can hold quotes or backslashes
+        let name = serde_json::to_string(name)?;
+        let bin_uri = serde_json::to_string(&bin_uri)?;
+
+        let node_matrix = if transform == Mat4::IDENTITY {
+            String::new()
+        } else {
+            let columns: Vec<String> = transform.to_cols_array().iter().map(|x| x.to_string()).collect();
+            format!(r#", "matrix": [{}]"#, columns.join(", "))
+        };
+
+        const FLOAT: u32 = 5126;
+        const UNSIGNED_INT: u32 = 5125;
+        let vertex_count = self.positions.len();
+        let gltf = format!(
+            r#"{{
+  "asset": {{"version": "2.0", "generator": "darkmoon-engine"}},
+  "scene": 0,
+  "scenes": [{{"nodes": [0]}}],
+  "nodes": [{{"mesh": 0, "name": {name}{node_matrix}}}],
+  "meshes": [{{"name": {name}, "primitives": [{{"attributes": {{"POSITION": 0, "NORMAL": 1, "TEXCOORD_0": 2}}, "indices": 3, "material": 0}}]}}],
+  "materials": [{{"name": "Default", "pbrMetallicRoughness": {{"baseColorFactor": [0.8, 0.8, 0.8, 1.0], "metallicFactor": 0.0, "roughnessFactor": 0.5}}}}],
+  "buffers": [{{"uri": {bin_uri}, "byteLength": {bin_len}}}],
+  "bufferViews": [{buffer_views}],
+  "accessors": [
+    {{"bufferView": 0, "componentType": {FLOAT}, "count": {vertex_count}, "type": "VEC3", "min": [{}, {}, {}], "max": [{}, {}, {}]}},
+    {{"bufferView": 1, "componentType": {FLOAT}, "count": {vertex_count}, "type": "VEC3"}},
+    {{"bufferView": 2, "componentType": {FLOAT}, "count": {vertex_count}, "type": "VEC2"}},
+    {{"bufferView": 3, "componentType": {UNSIGNED_INT}, "count": {index_count}, "type": "SCALAR"}}
+  ]
+}}
+"#,
+            min.x,
+            min.y,
+            min.z,
+            max.x,
+            max.y,
+            max.z,
+            bin_len = bin.len(),
+            index_count = self.indices.len(),
+        );
+
+        std::fs::write(&bin_path, &bin).with_context(|| format!("Writing {:?}", bin_path))?;
+        std::fs::write(path, gltf).with_context(|| format!("Writing {:?}", path))?;
+        Ok(())
+    }
+}
+
+fn vec3_bytes(vectors: &[Vec3]) -> Vec<u8> {
+    vectors
+        .iter()
+        .flat_map(|v| v.to_array())
+        .flat_map(|x| x.to_le_bytes())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_primitives_are_closed_and_face_outward() {
+        for primitive in [Primitive::Cube, Primitive::Sphere] {
+            let mesh = primitive.generate();
+            assert_eq!(mesh.indices.len() % 3, 0);
+
+            for tri in mesh.indices.chunks_exact(3) {
+                let [a, b, c] = [0, 1, 2].map(|i| mesh.positions[tri[i] as usize]);
+                let face_normal = (b - a).cross(c - a);
+                assert!(face_normal.length() > 0.0, "{:?} has a degenerate triangle", primitive);
+
+                // Centered on the origin, so outward means away from it
+                assert!(face_normal.dot(a + b + c) > 0.0, "{:?} has an inward-facing triangle", primitive);
+            }
+        }
+
+        let cube = Primitive::Cube.generate();
+        assert_eq!((cube.positions.len(), cube.indices.len()), (24, 36));
+        assert_eq!(Primitive::Plane.generate().indices.len(), 6);
+    }
+
+    #[test]
+    fn test_primitive_gltf_loads() {
+        let dir = std::env::temp_dir().join(format!("darkmoon_primitives_{}", std::process::id()));
+
+        for primitive in Primitive::ALL {
+            let path = primitive.ensure_file(&dir).unwrap();
+            let mesh = primitive.generate();
+
+            let (document, buffers, _) = gltf::import(&path).unwrap();
+            let prim = document.meshes().next().unwrap().primitives().next().unwrap();
+            let reader = prim.reader(|buffer| Some(&buffers[buffer.index()]));
+
+            assert_eq!(reader.read_positions().unwrap().count(), mesh.positions.len());
+            assert_eq!(reader.read_indices().unwrap().into_u32().collect::<Vec<_>>(), mesh.indices);
+        }
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_gltf_name_is_escaped() {
+        let dir = std::env::temp_dir().join(format!("darkmoon_primitives_escape_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let name = r#"Crate "B" \ copy"#;
+        let path = dir.join("crate.gltf");
+        Primitive::Cube.generate().write_gltf(name, &path).unwrap();
+
+        let (document, _, _) = gltf::import(&path).unwrap();
+        assert_eq!(document.nodes().next().unwrap().name(), Some(name));
+        assert_eq!(document.meshes().next().unwrap().name(), Some(name));
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
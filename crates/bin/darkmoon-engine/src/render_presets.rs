@@ -0,0 +1,140 @@
+use kajiya::world_renderer::WorldRenderer;
+use serde::{Deserialize, Serialize};
+
+use crate::app_config::AppConfig;
+
+/// A coherent group of renderer and culling settings, applied in one go
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RenderPreset {
+    pub name: String,
+    pub ray_tracing: bool,
+    pub gi_spatial_reuse_passes: u32,
+    pub raytraced_reservoir_visibility: bool,
+    pub reuse_gi_rays_for_reflections: bool,
+    pub scroll_irradiance_cache: bool,
+    pub frustum_culling: bool,
+    pub occlusion_culling: bool,
+    pub triangle_culling: bool,
+    /// DLSS upscaling; only has an effect in builds with the `dlss` feature
+    #[serde(default)]
+    pub dlss: bool,
+}
+
+impl RenderPreset {
+    /// Performance, Balanced and Quality, from fastest to best looking
+    pub fn built_in() -> [RenderPreset; 3] {
+        [
+            RenderPreset {
+                name: "Performance".to_owned(),
+                ray_tracing: false,
+                gi_spatial_reuse_passes: 1,
+                raytraced_reservoir_visibility: false,
+                reuse_gi_rays_for_reflections: true,
+                scroll_irradiance_cache: true,
+                frustum_culling: true,
+                occlusion_culling: true,
+                triangle_culling: true,
+                dlss: true,
+            },
+            RenderPreset {
+                name: "Balanced".to_owned(),
+                ray_tracing: true,
+                gi_spatial_reuse_passes: 2,
+                raytraced_reservoir_visibility: false,
+                reuse_gi_rays_for_reflections: true,
+                scroll_irradiance_cache: true,
+                frustum_culling: true,
+                occlusion_culling: false,
+                triangle_culling: false,
+                dlss: true,
+            },
+            RenderPreset {
+                name: "Quality".to_owned(),
+                ray_tracing: true,
+                gi_spatial_reuse_passes: 3,
+                raytraced_reservoir_visibility: true,
+                reuse_gi_rays_for_reflections: false,
+                scroll_irradiance_cache: true,
+                frustum_culling: true,
+                occlusion_culling: false,
+                triangle_culling: false,
+                dlss: false,
+            },
+        ]
+    }
+
+    /// Snapshot the current settings, e.g. to save them as a custom preset
+    pub fn capture(name: String, world_renderer: &WorldRenderer, app_config: &AppConfig) -> Self {
+        Self {
+            name,
+            ray_tracing: world_renderer.is_ray_tracing_enabled(),
+            gi_spatial_reuse_passes: world_renderer.rtdgi.spatial_reuse_pass_count,
+            raytraced_reservoir_visibility: world_renderer.rtdgi.use_raytraced_reservoir_visibility,
+            reuse_gi_rays_for_reflections: world_renderer.rtr.reuse_rtdgi_rays,
+            scroll_irradiance_cache: world_renderer.ircache.enable_scroll,
+            frustum_culling: app_config.frustum_culling.enabled,
+            occlusion_culling: app_config.occlusion_culling.enabled,
+            triangle_culling: app_config.triangle_culling.enabled,
+            #[cfg(feature = "dlss")]
+            dlss: world_renderer.use_dlss,
+            #[cfg(not(feature = "dlss"))]
+            dlss: false,
+        }
+    }
+
+    pub fn apply(&self, world_renderer: &mut WorldRenderer, app_config: &mut AppConfig) {
+        world_renderer.set_ray_tracing_enabled(self.ray_tracing);
+        world_renderer.rtdgi.spatial_reuse_pass_count = self.gi_spatial_reuse_passes.clamp(1, 3);
+        world_renderer.rtdgi.use_raytraced_reservoir_visibility = self.raytraced_reservoir_visibility;
+        world_renderer.rtr.reuse_rtdgi_rays = self.reuse_gi_rays_for_reflections;
+        world_renderer.ircache.enable_scroll = self.scroll_irradiance_cache;
+        #[cfg(feature = "dlss")]
+        {
+            world_renderer.use_dlss = self.dlss;
+        }
+        self.apply_culling(app_config);
+    }
+
+    fn apply_culling(&self, app_config: &mut AppConfig) {
+        app_config.frustum_culling.enabled = self.frustum_culling;
+        app_config.occlusion_culling.enabled = self.occlusion_culling;
+        app_config.triangle_culling.enabled = self.triangle_culling;
+    }
+}
+
+/// First free "Custom N" name among the saved presets
+pub fn next_custom_preset_name(presets: &[RenderPreset]) -> String {
+    (1..)
+        .map(|i| format!("Custom {}", i))
+        .find(|name| presets.iter().all(|preset| &preset.name != name))
+        .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply_culling_sets_all_passes() {
+        let mut config = AppConfig::default();
+        let [performance, _, quality] = RenderPreset::built_in();
+
+        performance.apply_culling(&mut config);
+        assert!(config.frustum_culling.enabled);
+        assert!(config.occlusion_culling.enabled);
+        assert!(config.triangle_culling.enabled);
+
+        quality.apply_culling(&mut config);
+        assert!(!config.occlusion_culling.enabled);
+        assert!(!config.triangle_culling.enabled);
+    }
+
+    #[test]
+    fn test_next_custom_preset_name_skips_taken_names() {
+        let mut preset = RenderPreset::built_in()[0].clone();
+        assert_eq!(next_custom_preset_name(&[]), "Custom 1");
+
+        preset.name = "Custom 1".to_owned();
+        assert_eq!(next_custom_preset_name(&[preset]), "Custom 2");
+    }
+}
@@ -0,0 +1,58 @@
+/// splitmix64 finalizer: scrambles `x` so that neighboring inputs give unrelated outputs
+pub fn mix64(mut x: u64) -> u64 {
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d049bb133111eb);
+    x ^ (x >> 31)
+}
+
+/// Random numbers for editor tools. Seeded from the scene, so randomized edits come out
+/// the same every time they're repeated from the same seed, across sessions and machines.
+#[derive(Clone, Debug)]
+pub struct EditorRng {
+    state: u64,
+}
+
+impl EditorRng {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e3779b97f4a7c15);
+        mix64(self.state)
+    }
+
+    /// Uniform in `[0, 1)`
+    pub fn next_f32(&mut self) -> f32 {
+        (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    /// Uniform in `[min, max)`
+    pub fn range(&mut self, min: f32, max: f32) -> f32 {
+        min + (max - min) * self.next_f32()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_same_seed_same_sequence() {
+        let mut a = EditorRng::new(1234);
+        let mut b = EditorRng::new(1234);
+        let mut c = EditorRng::new(1235);
+
+        let a_values: Vec<u64> = (0..16).map(|_| a.next_u64()).collect();
+        let b_values: Vec<u64> = (0..16).map(|_| b.next_u64()).collect();
+        let c_values: Vec<u64> = (0..16).map(|_| c.next_u64()).collect();
+
+        assert_eq!(a_values, b_values);
+        assert_ne!(a_values, c_values);
+
+        for _ in 0..1000 {
+            let x = a.range(-2.0, 3.0);
+            assert!((-2.0..3.0).contains(&x));
+        }
+    }
+}
@@ -4,10 +4,10 @@ use anyhow::Context;
 
 use dolly::prelude::*;
 use gltf;
-use dolly::glam::{Mat4, Vec3};
+use dolly::glam::{Mat4, Vec2, Vec3};
 use kajiya::{
     rg::GraphDebugHook,
-    world_renderer::{AddMeshOptions, MeshHandle, WorldRenderer},
+    world_renderer::{AddMeshOptions, InstanceHandle, MeshHandle, WorldRenderer},
 };
 use kajiya_simple::*;
 use gilrs::Gilrs;
@@ -15,20 +15,34 @@ use gilrs::Gilrs;
 use crate::{
     opt::Opt,
     persisted::{MeshSource, SceneElement, SceneElementTransform, MeshNode, ShouldResetPathTracer as _},
-    scene::{SceneDesc, SceneInstanceDesc},
-    sequence::{CameraPlaybackSequence, MemOption, SequenceValue},
+    scene::{new_element_id, SceneDesc, SceneInstanceDesc},
+    sequence::{CameraPlaybackSequence, MemOption, SequenceFullValue, SequenceValue},
     PersistedState,
-    math::{Aabb, Frustum, OcclusionCuller, TriangleCuller},
-    culling::CullingMethod,
+    math::{
+        extract_triangles_from_mesh, sample_triangles, Aabb, AdaptiveOcclusionResolution, BoundingSphere, Frustum,
+        OcclusionCuller, PotentiallyVisibleSet, Ray, Triangle, TriangleCuller,
+    },
+    history::{EditAction, EditHistory},
+    misc::slerp_direction,
+    placement::DropPlacement,
+    mesh_geometry::MeshGeometry,
+    rng::EditorRng,
+    selection::SelectionTarget,
+    culling::{
+        CachedVisibility, CullingCache, CullingFrameStats, CullingKey, CullingPasses, CullingStatsLogger,
+        CullingView,
+    },
+    streaming_integration::PersistedStateStreamingExt as _,
 };
 
-use crate::keymap::KeymapConfig;
+use crate::{app_config::CameraKeyAction, keymap::{KeymapConfig, KeymapWatcher}};
 use log::{info, warn};
 use std::{
-    collections::{hash_map::DefaultHasher, HashMap},
+    collections::{hash_map::DefaultHasher, HashMap, HashSet},
     fs::File,
     hash::{Hash, Hasher},
-    path::PathBuf,
+    path::{Component, Path, PathBuf},
+    sync::Arc,
 };
 
 pub const MAX_FPS_LIMIT: u32 = 256;
@@ -37,7 +51,19 @@ pub struct UiWindowsState {
     pub show_asset_browser: bool,
     pub show_hierarchy: bool,
     pub show_debug: bool,
+    pub show_preferences: bool,
+    pub show_layers: bool,
+    pub show_scatter: bool,
     pub asset_browser: Option<crate::asset_browser::AssetBrowser>,
+    /// Tag query typed into the Outliner, see `tags::TagQuery`
+    pub outliner_tag_filter: String,
+    /// Outliner name search; empty shows everything
+    pub outliner_search: String,
+    // Text box contents for adding a tag in the Attributes window
+    pub new_tag: String,
+    // Text box contents for creating a layer in the Layers window
+    pub new_layer: String,
+    pub scatter: crate::scatter::ScatterParams,
 }
 
 impl Default for UiWindowsState {
@@ -46,7 +72,15 @@ impl Default for UiWindowsState {
             show_asset_browser: true,
             show_hierarchy: true,
             show_debug: true,
+            show_preferences: false,
+            show_layers: false,
+            show_scatter: false,
             asset_browser: None,
+            outliner_tag_filter: String::new(),
+            outliner_search: String::new(),
+            new_tag: String::new(),
+            new_layer: String::new(),
+            scatter: Default::default(),
         }
     }
 }
@@ -60,26 +94,89 @@ pub struct RuntimeState {
     pub keymap_config: KeymapConfig,
     pub movement_map: KeyboardMap,
     pub gamepad_movement_map: GamepadMap,
+    keymap_path: Option<PathBuf>,
+    keymap_watcher: KeymapWatcher,
 
     pub show_gui: bool,
+    /// Debug view: outline every element in its own color
+    pub show_element_debug_colors: bool,
+    /// Elements culled last frame, by index; only tracked while culling preview is on
+    pub culled_elements: Vec<usize>,
+    /// Result of the last scene/renderer instance check in the Debug window
+    pub instance_check: Option<crate::scene_validation::InstanceMismatch>,
+    // Set by `do_gui` when an imgui widget has keyboard focus or takes text input
+    pub gui_wants_keyboard: bool,
+    // Set by `do_gui` when the pointer is over (or dragging) an imgui window
+    pub gui_wants_mouse: bool,
     pub sun_direction_interp: Vec3,
     pub left_click_edit_mode: LeftClickEditMode,
 
     pub max_fps: u32,
+    // Tracked from winit focus events, for the idle frame rate cap
+    pub window_focused: bool,
+    // Last window input or scene change, for the power saver frame rate cap
+    last_activity: std::time::Instant,
+    // When this frame's work started, after the frame rate cap's sleep
+    frame_work_start: Option<std::time::Instant>,
+    // Time from one frame's work starting to the next frame's cap sleep, so it excludes the sleep
+    frame_work_ms: Option<f32>,
+    /// Camera, sun and object updates are frozen to study a frame; edits apply once unpaused
+    pub paused: bool,
+    // Set when the scene is replaced, so the crash snapshot and duplicate check are refreshed next frame
+    crash_snapshot_stale: bool,
+    // Set when a scene loads; the far plane is fitted once its bounds are known
+    far_plane_fit_pending: bool,
+    // Whether the scene was over the large scene cap when last checked, to warn once per crossing
+    large_scene: bool,
+    /// Outliner selection, and the node of the selected compound element if one is picked
+    pub selected_element: Option<SelectionTarget>,
+    pub selected_node: Option<usize>,
+    /// Every selected element, sorted; `selected_element` is the one shown in Attributes.
+    /// Holds more than one after Ctrl or Shift clicks in the Outliner.
+    pub selected_elements: Vec<usize>,
+    /// Set from the Window menu; windows move back to their default layout for a frame
+    pub reset_window_positions: bool,
+    /// Edits since the scene was last saved
+    pub scene_dirty: bool,
+    pub edit_history: EditHistory,
+    /// Elements stacked on an identical copy, flagged in the Outliner
+    pub duplicate_elements: Vec<crate::scene_validation::DuplicateElement>,
     pub locked_rg_debug_hook: Option<GraphDebugHook>,
     pub grab_cursor_pos: winit::dpi::PhysicalPosition<f64>,
 
     pub reset_path_tracer: bool,
 
+    /// Randomness for editor tools, seeded from `SceneState::seed`
+    pub rng: EditorRng,
+
     pub active_camera_key: Option<usize>,
+    /// While soloing the active key, the view is pinned to this copy of it. Edits go here
+    /// and reach the sequence only through `commit_solo_camera_key`.
+    pub solo_camera_key: Option<SequenceFullValue>,
     sequence_playback_state: SequencePlaybackState,
     pub sequence_playback_speed: f32,
 
     known_meshes: HashMap<PathBuf, MeshHandle>,
+    /// CPU-side data read from baked meshes, keyed like `known_meshes`
+    mesh_cache: HashMap<PathBuf, CachedMesh>,
+    /// Elements whose `bounding_box` is the `default_object_size` guess rather than their mesh's bounds
+    guessed_bounds: HashSet<u64>,
     occlusion_culler: OcclusionCuller,
+    occlusion_resolution: AdaptiveOcclusionResolution,
+    /// Baked visibility of the current scene's static elements, saved with the scene
+    pvs: Option<PotentiallyVisibleSet>,
+    /// Bake running in the background, see `start_pvs_bake`
+    pvs_bake: Option<std::sync::mpsc::Receiver<PotentiallyVisibleSet>>,
     triangle_culler: TriangleCuller,
+    culling_logger: CullingStatsLogger,
+    /// Visibility reused between culling updates, see `FrustumCullingConfig::update_interval_frames`
+    culling_cache: CullingCache,
     pub streaming_integration: crate::streaming_integration::StreamingIntegration,
     pub ui_windows: UiWindowsState,
+    pub toasts: crate::notifications::Toasts,
+    /// Set by the camera key; the GUI copies the camera to the clipboard on its next frame
+    pub pending_camera_copy: bool,
+    pub app_config: crate::app_config::AppConfig,
     // Currently loaded scene file path for saving changes
     pub current_scene_path: Option<PathBuf>,
 }
@@ -120,6 +217,14 @@ impl RuntimeState {
 
         let sun_direction_interp = persisted.light.sun.controller.towards_sun();
 
+        let mut app_config = crate::app_config::AppConfig::load();
+        if app_config.migrate_legacy_preferences(persisted) {
+            log::info!("Moved editor preferences from the view state to the app config");
+            if let Err(err) = app_config.save() {
+                log::error!("Failed to save app config: {:#}", err);
+            }
+        }
+
         let mut res = Self {
             camera,
             mouse,
@@ -132,39 +237,71 @@ impl RuntimeState {
             keymap_config: keymap_config.clone(),
             movement_map: keymap_config.movement.clone().into(),
             gamepad_movement_map: keymap_config.movement.into(),
-
-            show_gui: true,
+            keymap_path: opt.keymap.clone(),
+            keymap_watcher: KeymapWatcher::new(KeymapConfig::path(&opt.keymap)),
+
+            show_gui: app_config.gui.visible,
+            show_element_debug_colors: false,
+            culled_elements: Vec::new(),
+            instance_check: None,
+            gui_wants_keyboard: false,
+            gui_wants_mouse: false,
             sun_direction_interp,
             left_click_edit_mode: LeftClickEditMode::MoveSun,
 
             max_fps: MAX_FPS_LIMIT,
+            window_focused: true,
+            last_activity: std::time::Instant::now(),
+            frame_work_start: None,
+            frame_work_ms: None,
+            paused: false,
+            crash_snapshot_stale: true,
+            far_plane_fit_pending: false,
+            large_scene: false,
+            selected_element: None,
+            selected_node: None,
+            selected_elements: Vec::new(),
+            reset_window_positions: false,
+            scene_dirty: false,
+            edit_history: EditHistory::default(),
+            duplicate_elements: Vec::new(),
             locked_rg_debug_hook: None,
             grab_cursor_pos: Default::default(),
 
             reset_path_tracer: false,
 
+            rng: EditorRng::new(persisted.scene.seed),
+
             active_camera_key: None,
+            solo_camera_key: None,
             sequence_playback_state: SequencePlaybackState::NotPlaying,
             sequence_playback_speed: 1.0,
 
             known_meshes: Default::default(),
-            occlusion_culler: OcclusionCuller::new(persisted.occlusion_culling.clone()),
-            triangle_culler: TriangleCuller::new(persisted.triangle_culling.clone()),
+            mesh_cache: Default::default(),
+            guessed_bounds: Default::default(),
+            occlusion_culler: OcclusionCuller::new(app_config.occlusion_culling.clone()),
+            occlusion_resolution: Default::default(),
+            pvs: None,
+            pvs_bake: None,
+            triangle_culler: TriangleCuller::new(app_config.triangle_culling.clone()),
+            culling_logger: CullingStatsLogger::default(),
+            culling_cache: CullingCache::default(),
             streaming_integration: crate::streaming_integration::StreamingIntegration::new(),
             ui_windows: UiWindowsState::default(),
+            toasts: Default::default(),
+            pending_camera_copy: false,
+            app_config,
             current_scene_path: None,
         };
 
         // Load meshes that the persisted scene was referring to
         persisted.scene.elements.retain_mut(|elem| {
-            match res.load_mesh(world_renderer, &elem.source) {
-                Ok(mesh) => {
-                    elem.instance =
-                        world_renderer.add_instance(mesh, elem.transform.affine_transform());
-                    true
-                }
+            match res.add_element_instances(world_renderer, elem) {
+                Ok(()) => true,
                 Err(err) => {
                     log::error!("Failed to load mesh {:?}: {:#}", elem.source, err);
+                    res.toasts.error(format!("Failed to load mesh {:?}", elem.source));
                     false
                 }
             }
@@ -177,9 +314,11 @@ impl RuntimeState {
             }
         }
 
-        // Initialize streaming system automatically
-        res.streaming_integration.request_initialization();
-        log::info!("Resource streaming system initialized automatically at startup");
+        // Initialize streaming system automatically, unless it was turned off
+        if persisted.get_streaming_enabled() {
+            res.streaming_integration.request_initialization();
+            log::info!("Resource streaming system initialized automatically at startup");
+        }
 
         res
     }
@@ -190,8 +329,59 @@ impl RuntimeState {
         world_renderer: &mut WorldRenderer,
     ) {
         for elem in persisted.scene.elements.drain(..) {
-            world_renderer.remove_instance(elem.instance);
+            for &instance in elem.instances() {
+                world_renderer.remove_instance(instance);
+            }
+        }
+        persisted.scene.layers.clear();
+        self.pvs = None;
+        self.pvs_bake = None;
+        self.edit_history.clear();
+        self.guessed_bounds.clear();
+    }
+
+    /// Brings the renderer back in line with the scene: orphan instances are removed, and dead
+    /// elements get a fresh instance, or are dropped if their mesh no longer loads. Returns
+    /// what was found.
+    pub(crate) fn reconcile_instances(
+        &mut self,
+        persisted: &mut PersistedState,
+        world_renderer: &mut WorldRenderer,
+    ) -> crate::scene_validation::InstanceMismatch {
+        let mismatch = crate::scene_validation::find_instance_mismatches(
+            &persisted.scene.elements,
+            world_renderer.instance_handles(),
+        );
+
+        for &instance in &mismatch.orphan_instances {
+            world_renderer.remove_instance(instance);
+        }
+
+        let mut dropped = false;
+        for &idx in mismatch.dead_elements.iter().rev() {
+            let elem = &mut persisted.scene.elements[idx];
+            if let Err(err) = self.add_element_instances(world_renderer, elem) {
+                log::error!("Dropping element {:?} with no instance: {:#}", elem.source, err);
+                persisted.scene.elements.remove(idx);
+                self.on_element_removed(idx);
+                dropped = true;
+            }
+        }
+
+        if dropped {
+            // Recorded edits refer to elements by index
+            self.edit_history.clear();
         }
+        if !mismatch.is_empty() {
+            log::warn!(
+                "Reconciled renderer instances: removed {} orphan(s), fixed {} element(s)",
+                mismatch.orphan_instances.len(),
+                mismatch.dead_elements.len()
+            );
+            self.crash_snapshot_stale = true;
+        }
+
+        mismatch
     }
 
     /// Convenience method for clearing scene from GUI (takes FrameContext)
@@ -201,8 +391,15 @@ impl RuntimeState {
         ctx: &mut FrameContext,
     ) {
         for elem in persisted.scene.elements.drain(..) {
-            ctx.world_renderer.remove_instance(elem.instance);
+            for &instance in elem.instances() {
+                ctx.world_renderer.remove_instance(instance);
+            }
         }
+        persisted.scene.layers.clear();
+        self.pvs = None;
+        self.pvs_bake = None;
+        self.edit_history.clear();
+        self.guessed_bounds.clear();
     }
 
     pub fn load_scene(
@@ -219,36 +416,49 @@ impl RuntimeState {
 
         self.clear_scene(persisted, world_renderer);
 
-        for instance in scene_desc.instances {
-            let mesh_path = canonical_path_from_vfs(&instance.mesh)
-                .with_context(|| format!("Mesh path: {:?}", instance.mesh))
-                .expect("valid mesh path");
-
-            let mesh = self
-                .load_mesh(world_renderer, &MeshSource::File(mesh_path.clone()))
-                .with_context(|| format!("Mesh path: {:?}", instance.mesh))
-                .expect("valid mesh");
-
-            let transform = SceneElementTransform {
-                position: instance.position.into(),
-                rotation_euler_degrees: instance.rotation.into(),
-                scale: instance.scale.into(),
-            };
+        // Warm the streaming cache with everything the scene references
+        self.streaming_integration.prefetch_scene(&scene_desc);
 
-            let render_instance = world_renderer.add_instance(mesh, transform.affine_transform());
-
-            persisted.scene.elements.push(SceneElement {
-                source: MeshSource::File(mesh_path),
-                instance: render_instance,
-                transform,
-                bounding_box: None, // Will be calculated later when mesh data is available
-                mesh_nodes: Vec::new(),
-                is_compound: false,
+        for instance in scene_desc.instances {
+            // A single bad mesh skips its element instead of failing the whole scene
+            let mesh = instance.mesh.clone();
+            let loaded = if instance.cached {
+                Ok(MeshSource::Cache(PathBuf::from(&instance.mesh)))
+            } else {
+                canonical_path_from_vfs(&instance.mesh).map(MeshSource::File)
+            }
+            .and_then(|source| {
+                let mut element = instance.into_element(source, InstanceHandle::INVALID);
+                self.add_element_instances(world_renderer, &mut element)?;
+                Ok(element)
             });
+
+            match loaded {
+                Ok(element) => persisted.scene.elements.push(element),
+                Err(err) => {
+                    log::error!("Skipping scene element with mesh {:?}: {:#}", mesh, err);
+                    self.toasts.error(format!("Failed to load mesh {}", mesh));
+                }
+            }
+        }
+        self.pvs = scene_desc.pvs;
+        self.pvs_bake = None;
+        persisted.scene.layers = scene_desc.layers;
+        persisted.scene.auto_bounds = scene_desc.auto_bounds;
+        self.set_scene_seed(persisted, scene_desc.seed);
+
+        // Remember it for "last scene" startup
+        if self.app_config.last_scene.as_ref() != Some(&scene_path) {
+            self.app_config.last_scene = Some(scene_path.clone());
+            if let Err(err) = self.app_config.save() {
+                log::error!("Failed to save app config: {:#}", err);
+            }
         }
 
         // Store the scene path for saving changes later
         self.current_scene_path = Some(scene_path);
+        self.crash_snapshot_stale = true;
+        self.far_plane_fit_pending = self.app_config.far_plane.auto_fit;
 
         Ok(())
     }
@@ -263,6 +473,115 @@ impl RuntimeState {
         self.load_scene(persisted, &mut ctx.world_renderer, path)
     }
 
+    /// The scene as it would be saved to a .dmoon file
+    pub fn scene_desc(&self, persisted: &PersistedState) -> SceneDesc {
+        let instances = persisted
+            .scene
+            .elements
+            .iter()
+            .map(|elem| {
+                let mut instance = SceneInstanceDesc::from_element(elem);
+                // Guesses are redone on load, possibly with a different default size
+                if self.guessed_bounds.contains(&elem.id) {
+                    instance.bounding_box = None;
+                }
+                instance
+            })
+            .collect();
+
+        SceneDesc {
+            instances,
+            pvs: self.pvs.clone(),
+            layers: persisted.scene.layers.clone(),
+            seed: persisted.scene.seed,
+            auto_bounds: persisted.scene.auto_bounds,
+        }
+    }
+
+    /// Change the scene's seed, and restart the editor tools' random sequence from it
+    pub fn set_scene_seed(&mut self, persisted: &mut PersistedState, seed: u64) {
+        persisted.scene.seed = seed;
+        self.rng = EditorRng::new(seed);
+    }
+
+    pub fn pvs(&self) -> Option<&PotentiallyVisibleSet> {
+        self.pvs.as_ref()
+    }
+
+    /// Start baking potentially visible sets for the current placement of all elements, on a
+    /// background thread so large scenes don't freeze the editor. Elements moved or added
+    /// afterwards are culled dynamically until the next bake.
+    pub fn start_pvs_bake(&mut self, persisted: &PersistedState) {
+        if self.pvs_bake.is_some() {
+            return;
+        }
+
+        let default_object_size = self.app_config.frustum_culling.default_object_size;
+        let static_elements: Vec<(u64, Aabb, bool)> = persisted
+            .scene
+            .elements
+            .iter()
+            .map(|elem| (elem.id, elem.world_bounds(default_object_size), elem.occluder))
+            .collect();
+        let cell_size = self.app_config.occlusion_culling.pvs_cell_size;
+
+        let (sender, receiver) = std::sync::mpsc::channel();
+        std::thread::spawn(move || {
+            let pvs = PotentiallyVisibleSet::bake(&static_elements, cell_size);
+            info!(
+                "Baked PVS for {} elements into {} cells of {:.2}m",
+                static_elements.len(),
+                pvs.cells.len(),
+                pvs.cell_size
+            );
+            let _ = sender.send(pvs);
+        });
+        self.pvs_bake = Some(receiver);
+    }
+
+    pub fn is_baking_pvs(&self) -> bool {
+        self.pvs_bake.is_some()
+    }
+
+    /// Pick up a finished bake. Bakes of a scene that has since been replaced were dropped
+    /// along with their receiver.
+    fn poll_pvs_bake(&mut self) {
+        if let Some(receiver) = &self.pvs_bake {
+            match receiver.try_recv() {
+                Ok(pvs) => {
+                    self.pvs = Some(pvs);
+                    self.pvs_bake = None;
+                    self.crash_snapshot_stale = true;
+                    self.mark_unsaved_changes();
+                }
+                Err(std::sync::mpsc::TryRecvError::Empty) => {}
+                Err(std::sync::mpsc::TryRecvError::Disconnected) => self.pvs_bake = None,
+            }
+        }
+    }
+
+    pub fn clear_pvs(&mut self) {
+        self.pvs = None;
+        self.pvs_bake = None;
+        self.crash_snapshot_stale = true;
+    }
+
+    /// Move the far plane to just past the scene bounds, see `FarPlaneConfig::fit`. Empty scenes
+    /// and an infinite far plane are left alone.
+    pub fn fit_far_plane(&self, persisted: &mut PersistedState) {
+        if persisted.camera.z_far.is_infinite() {
+            return;
+        }
+
+        let default_object_size = self.app_config.frustum_culling.default_object_size;
+        if let Some(bounds) = persisted.scene.bounds(default_object_size) {
+            persisted.camera.z_far = self
+                .app_config
+                .far_plane
+                .fit(&bounds, self.camera.final_transform.position);
+        }
+    }
+
     /// Save the current scene to a .dmoon file
     pub fn save_scene_to_path(
         &self,
@@ -270,48 +589,7 @@ impl RuntimeState {
         path: impl Into<PathBuf>,
     ) -> anyhow::Result<()> {
         let path = path.into();
-        
-        // Convert persisted scene elements back to SceneDesc format
-        let instances: Vec<SceneInstanceDesc> = persisted.scene.elements.iter().map(|elem| {
-            // Extract mesh path from the source
-            let mesh_path = match &elem.source {
-                MeshSource::File(file_path) => {
-                    // Convert to VFS format (always starts with /)
-                    let path_str = file_path.to_string_lossy();
-                    
-                    // Handle absolute paths that contain "assets/"
-                    if let Some(assets_pos) = path_str.find("assets/") {
-                        // Extract everything after "assets/"
-                        let relative_path = &path_str[assets_pos + 7..]; // Skip "assets/"
-                        format!("/{}", relative_path)
-                    } 
-                    // Handle relative paths starting with "assets/"
-                    else if path_str.starts_with("assets/") {
-                        format!("/{}", &path_str[7..]) // Skip "assets/"
-                    }
-                    // Handle paths already in VFS format (starting with /)
-                    else if path_str.starts_with("/") {
-                        path_str.to_string()
-                    }
-                    // Fallback for other cases
-                    else {
-                        format!("/{}", path_str)
-                    }
-                },
-                MeshSource::Cache(cache_path) => {
-                    format!("/cache/{}", cache_path.file_name().unwrap().to_string_lossy())
-                }
-            };
-
-            SceneInstanceDesc {
-                position: [elem.transform.position.x, elem.transform.position.y, elem.transform.position.z],
-                scale: [elem.transform.scale.x, elem.transform.scale.y, elem.transform.scale.z],
-                rotation: [elem.transform.rotation_euler_degrees.x, elem.transform.rotation_euler_degrees.y, elem.transform.rotation_euler_degrees.z],
-                mesh: mesh_path,
-            }
-        }).collect();
-
-        let scene_desc = SceneDesc { instances };
+        let scene_desc = self.scene_desc(persisted);
 
         // Write to file with pretty formatting
         let file = File::create(&path)
@@ -340,7 +618,9 @@ impl RuntimeState {
 
     fn update_camera(&mut self, persisted: &mut PersistedState, ctx: &FrameContext) {
         let smooth = self.camera.driver_mut::<Smooth>();
-        if ctx.world_renderer.get_render_mode() == RenderMode::Reference {
+        if ctx.world_renderer.get_render_mode() == RenderMode::Reference
+            && !persisted.movement.smoothing_in_reference_mode
+        {
             smooth.position_smoothness = 0.0;
             smooth.rotation_smoothness = 0.0;
         } else {
@@ -349,7 +629,7 @@ impl RuntimeState {
         }
 
         // When starting camera rotation, hide the mouse cursor, and capture it to the window.
-        if (self.mouse.buttons_pressed & (1 << 2)) != 0 {
+        if (self.mouse.buttons_pressed & (1 << 2)) != 0 && !self.gui_wants_mouse {
             let _ = ctx.window.set_cursor_grab(winit::window::CursorGrabMode::Confined);
             self.grab_cursor_pos = self.mouse.physical_position;
             ctx.window.set_cursor_visible(false);
@@ -362,6 +642,14 @@ impl RuntimeState {
         }
 
         let mut input = self.movement_map.map(&self.keyboard, ctx.dt_filtered);
+
+        // Keys typed into a text field shouldn't fly the camera around
+        if self.gui_wants_keyboard {
+            for value in input.values_mut() {
+                *value = 0.0;
+            }
+        }
+
         let gamepad_input = self.gamepad_movement_map.map(&self.gamepad, ctx.dt_filtered);
         
         for (axis, value) in gamepad_input {
@@ -373,12 +661,26 @@ impl RuntimeState {
             *value = value.clamp(-1.0, 1.0);
         }
         
-        let move_vec = self.camera.final_transform.rotation
+        if self.hotkey_pressed(self.keymap_config.camera.toggle_walk_mode) {
+            persisted.movement.walk_mode = !persisted.movement.walk_mode;
+            log::info!("Camera walk mode: {}", persisted.movement.walk_mode);
+        }
+
+        if self.hotkey_pressed(self.keymap_config.camera.look_at_selected) {
+            self.look_at_selected(persisted);
+        }
+
+        let mut move_vec = self.camera.final_transform.rotation
             * Vec3::new(input["move_right"], input["move_up"], -input["move_fwd"])
                 .clamp_length_max(1.0)
             * 4.0f32.powf(input["boost"]);
 
-        if (self.mouse.buttons_held & (1 << 2)) != 0 {
+        // Walking keeps the camera level; the height comes from the ground below
+        if persisted.movement.walk_mode {
+            move_vec.y = 0.0;
+        }
+
+        if (self.mouse.buttons_held & (1 << 2)) != 0 && !self.gui_wants_mouse {
             // While we're rotating, the cursor should not move, so that upon revealing it,
             // it will be where we started the rotation motion at.
             let _ = ctx
@@ -413,6 +715,23 @@ impl RuntimeState {
             .driver_mut::<Position>()
             .translate(move_vec * ctx.dt_filtered * persisted.movement.camera_speed);
 
+        if persisted.movement.walk_mode && !self.is_sequence_playing() {
+            let default_object_size = self.app_config.frustum_culling.default_object_size;
+            let position = self.camera.driver::<Position>().position;
+            let ground = crate::walk::walk_ground_height(
+                persisted.movement.walk_ground,
+                position,
+                persisted.movement.eye_height,
+                persisted.movement.collision_radius,
+                persisted.scene.elements.iter().map(|elem| {
+                    elem.local_bounds(default_object_size)
+                        .transform(&Mat4::from(elem.transform.affine_transform()))
+                }),
+            );
+
+            self.camera.driver_mut::<Position>().position.y = ground + persisted.movement.eye_height;
+        }
+
         if let SequencePlaybackState::Playing { t, sequence } = &mut self.sequence_playback_state {
             let smooth = self.camera.driver_mut::<Smooth>();
             if *t <= 0.0 {
@@ -435,6 +754,12 @@ impl RuntimeState {
                     .sun
                     .controller
                     .set_towards_sun(value.towards_sun);
+                if let Some(intensity) = value.sun_intensity {
+                    persisted.light.sun.intensity = intensity;
+                }
+                if let Some(color) = value.sun_color {
+                    persisted.light.sun.color = color;
+                }
 
                 *t += ctx.dt_filtered * self.sequence_playback_speed;
             } else {
@@ -442,26 +767,35 @@ impl RuntimeState {
             }
         }
 
+        if let Some(value) = self.solo_camera_key {
+            let smooth = self.camera.driver_mut::<Smooth>();
+            smooth.position_smoothness = 0.0;
+            smooth.rotation_smoothness = 0.0;
+            self.set_camera_view(persisted, &value);
+        }
+
         self.camera.update(ctx.dt_filtered);
 
         persisted.camera.position = self.camera.final_transform.position;
         persisted.camera.rotation = self.camera.final_transform.rotation;
 
-        if self
-            .keyboard
-            .was_just_pressed(self.keymap_config.misc.print_camera_transform)
-        {
-            println!(
-                "position: {}, look_at: {}",
-                persisted.camera.position,
-                persisted.camera.position + persisted.camera.rotation * -Vec3::Z,
-            );
+        if self.hotkey_pressed(self.keymap_config.misc.print_camera_transform) {
+            match self.app_config.camera_key_action {
+                CameraKeyAction::CopyToClipboard if self.show_gui => {
+                    self.pending_camera_copy = true;
+                }
+                CameraKeyAction::AddKeyframe => {
+                    self.add_sequence_keyframe(persisted);
+                    log::info!("Added the camera as a sequence keyframe");
+                }
+                // Without the GUI there's no clipboard access, so fall back to printing
+                CameraKeyAction::Print | CameraKeyAction::CopyToClipboard => {
+                    self.print_camera_transform(persisted);
+                }
+            }
         }
 
-        if self
-            .keyboard
-            .was_just_pressed(self.keymap_config.misc.save_scene)
-        {
+        if self.hotkey_pressed(self.keymap_config.misc.save_scene) {
             if let Err(err) = self.save_current_scene(persisted) {
                 log::error!("Failed to save scene (Ctrl+S): {:#}", err);
             } else {
@@ -470,8 +804,319 @@ impl RuntimeState {
         }
     }
 
+    /// Turn the camera in place towards the selected element's center; the `Smooth` driver eases it in
+    fn look_at_selected(&mut self, persisted: &PersistedState) {
+        let elem = match self.selected_element_index()
+            .and_then(|idx| persisted.scene.elements.get(idx))
+        {
+            Some(elem) => elem,
+            None => return,
+        };
+
+        let center = elem
+            .transform
+            .affine_transform()
+            .transform_point3(elem.local_bounds(self.app_config.frustum_culling.default_object_size).center());
+        let direction = center - self.camera.driver::<Position>().position;
+
+        if direction.length_squared() > 1e-6 {
+            self.camera
+                .driver_mut::<YawPitch>()
+                .set_rotation_quat(dolly::util::look_at::<dolly::handedness::RightHanded>(
+                    direction.normalize(),
+                ));
+        }
+    }
+
+    /// Re-read the keymap file, keeping the current bindings if it doesn't parse or validate
+    fn reload_keymap(&mut self) {
+        match KeymapConfig::load(&self.keymap_path) {
+            Ok(keymap) => {
+                self.movement_map = keymap.movement.clone().into();
+                self.gamepad_movement_map = keymap.movement.clone().into();
+                self.keymap_config = keymap;
+                log::info!("Reloaded keymap");
+            }
+            Err(err) => {
+                log::error!("Keeping the current keymap: {:#}", err);
+                self.toasts.error(format!("Keymap reload failed: {:#}", err));
+            }
+        }
+    }
+
+    fn update_window_focus(&mut self, events: &[winit::event::Event<()>]) {
+        for event in events {
+            if let winit::event::Event::WindowEvent { event, .. } = event {
+                // Any window event (keys, mouse, resizing) counts as activity
+                self.last_activity = std::time::Instant::now();
+
+                if let WindowEvent::Focused(focused) = event {
+                    self.window_focused = *focused;
+                }
+            }
+        }
+    }
+
+    /// `max_fps`, lowered to the idle cap while the window is unfocused, or to the power saver
+    /// cap while nothing happens
+    fn effective_max_fps(&self, render_mode: RenderMode) -> u32 {
+        self.app_config.idle_throttle.cap_fps(
+            self.max_fps,
+            self.window_focused,
+            self.last_activity.elapsed(),
+            render_mode == RenderMode::Reference,
+        )
+    }
+
+    /// Feed the camera and its frustum to the streaming system, so assets in view load first
+    fn update_streaming(&mut self, persisted: &PersistedState, ctx: &FrameContext) {
+        if !self.streaming_integration.is_enabled() {
+            return;
+        }
+
+        let lens = CameraLens {
+            aspect_ratio: ctx.aspect_ratio(),
+            vertical_fov: persisted.camera.vertical_fov,
+            ..Default::default()
+        };
+        let camera_matrices = self
+            .camera
+            .final_transform
+            .into_position_rotation()
+            .through(&lens);
+        let frustum = Frustum::from_view_projection_matrix(
+            camera_matrices.view_to_clip * camera_matrices.world_to_view,
+        )
+        .with_far_plane(
+            self.camera.final_transform.position,
+            self.camera.final_transform.forward(),
+            persisted.camera.z_far,
+        );
+
+        self.streaming_integration.update_view(
+            self.camera.final_transform.position,
+            self.camera.final_transform.forward(),
+            &frustum,
+            &persisted.scene.elements,
+        );
+    }
+
+    /// Whether `key` was just pressed and isn't being typed into an imgui text field
+    pub(crate) fn hotkey_pressed(&self, key: VirtualKeyCode) -> bool {
+        !self.gui_wants_keyboard && self.keyboard.was_just_pressed(key)
+    }
+
+    /// Move the selected elements with the nudge keys, by a step scaled by the modifier keys
+    fn update_selection_nudge(&mut self, persisted: &mut PersistedState) {
+        if self.gui_wants_keyboard || self.selected_elements.is_empty() {
+            return;
+        }
+
+        let keys = &self.keymap_config.editing;
+        let mut direction = Vec3::ZERO;
+
+        for (key, dir) in [
+            (keys.nudge_left, -Vec3::X),
+            (keys.nudge_right, Vec3::X),
+            (keys.nudge_forward, -Vec3::Z),
+            (keys.nudge_backward, Vec3::Z),
+            (keys.nudge_up, Vec3::Y),
+            (keys.nudge_down, -Vec3::Y),
+        ] {
+            if self.keyboard.was_just_pressed(key) {
+                direction += dir;
+            }
+        }
+
+        if direction == Vec3::ZERO {
+            return;
+        }
+
+        let mut step = self.app_config.editing.nudge_step;
+        if self.keyboard.is_down(keys.nudge_coarse) {
+            step *= self.app_config.editing.nudge_coarse_multiplier;
+        }
+        if self.keyboard.is_down(keys.nudge_fine) {
+            step *= self.app_config.editing.nudge_fine_multiplier;
+        }
+
+        let mut changes = Vec::new();
+        for &idx in &self.selected_elements {
+            if let Some(elem) = persisted.scene.elements.get_mut(idx) {
+                let old = elem.transform.clone();
+                elem.transform.position += direction * step;
+                changes.push(EditAction::TransformChanged {
+                    idx,
+                    old,
+                    new: elem.transform.clone(),
+                });
+            }
+        }
+        self.edit_history.push(EditAction::Batch(changes));
+        self.mark_unsaved_changes();
+    }
+
+    /// Ctrl plus the undo, redo or duplicate key
+    fn update_edit_keys(&mut self, persisted: &mut PersistedState, world_renderer: &mut WorldRenderer) {
+        if self.gui_wants_keyboard
+            || !(self.keyboard.is_down(VirtualKeyCode::LControl) || self.keyboard.is_down(VirtualKeyCode::RControl))
+        {
+            return;
+        }
+
+        if self.keyboard.was_just_pressed(self.keymap_config.editing.undo) {
+            self.undo_edit(persisted, world_renderer);
+        } else if self.keyboard.was_just_pressed(self.keymap_config.editing.redo) {
+            self.redo_edit(persisted, world_renderer);
+        } else if self.keyboard.was_just_pressed(self.keymap_config.editing.duplicate) {
+            self.duplicate_selected(persisted, world_renderer);
+        }
+    }
+
+    pub(crate) fn undo_edit(&mut self, persisted: &mut PersistedState, world_renderer: &mut WorldRenderer) {
+        if let Some(action) = self.edit_history.pop_undo() {
+            match self.revert_edit(persisted, world_renderer, action) {
+                Ok(redo) => {
+                    self.edit_history.undone(redo);
+                    self.mark_unsaved_changes();
+                }
+                Err(err) => {
+                    log::error!("Undo failed: {:#}", err);
+                    self.toasts.error("Undo failed");
+                }
+            }
+        }
+    }
+
+    pub(crate) fn redo_edit(&mut self, persisted: &mut PersistedState, world_renderer: &mut WorldRenderer) {
+        if let Some(action) = self.edit_history.pop_redo() {
+            match self.revert_edit(persisted, world_renderer, action) {
+                Ok(undo) => {
+                    self.edit_history.redone(undo);
+                    self.mark_unsaved_changes();
+                }
+                Err(err) => {
+                    log::error!("Redo failed: {:#}", err);
+                    self.toasts.error("Redo failed");
+                }
+            }
+        }
+    }
+
+    /// Undo `action` in the scene and the renderer, returning the action that reverts that
+    fn revert_edit(
+        &mut self,
+        persisted: &mut PersistedState,
+        world_renderer: &mut WorldRenderer,
+        action: EditAction,
+    ) -> anyhow::Result<EditAction> {
+        match action {
+            EditAction::TransformChanged { idx, old, new } => {
+                let elem = persisted
+                    .scene
+                    .elements
+                    .get_mut(idx)
+                    .context("The edited element no longer exists")?;
+
+                // Only the authoring transform is set; `update_objects` hands it to the renderer
+                // later this frame, the same way Attributes edits are applied
+                elem.transform = old.clone();
+                Ok(EditAction::TransformChanged { idx, old: new, new: old })
+            }
+            EditAction::NodeTransformChanged { idx, node, old, new } => {
+                let elem = persisted
+                    .scene
+                    .elements
+                    .get_mut(idx)
+                    .context("The edited element no longer exists")?;
+                elem.mesh_nodes
+                    .get_mut(node)
+                    .context("The edited node no longer exists")?
+                    .local_transform = old.clone();
+
+                // Re-analyzing the nodes merges the element back into one instance
+                self.split_element_nodes(world_renderer, elem)?;
+                Ok(EditAction::NodeTransformChanged { idx, node, old: new, new: old })
+            }
+            EditAction::ElementAdded { idx } => {
+                if idx >= persisted.scene.elements.len() {
+                    anyhow::bail!("The added element no longer exists");
+                }
+
+                let element = persisted.scene.elements.remove(idx);
+                for &instance in element.instances() {
+                    world_renderer.remove_instance(instance);
+                }
+                self.on_element_removed(idx);
+                Ok(EditAction::ElementRemoved { element, idx })
+            }
+            EditAction::ElementRemoved { mut element, idx } => {
+                self.add_element_instances(world_renderer, &mut element)?;
+
+                let idx = idx.min(persisted.scene.elements.len());
+                persisted.scene.elements.insert(idx, element);
+                self.select_only(idx);
+                Ok(EditAction::ElementAdded { idx })
+            }
+            EditAction::Batch(actions) => {
+                // Back to front; reverting the result replays them front to back
+                let mut reverts = Vec::with_capacity(actions.len());
+                for action in actions.into_iter().rev() {
+                    reverts.push(self.revert_edit(persisted, world_renderer, action)?);
+                }
+                Ok(EditAction::Batch(reverts))
+            }
+        }
+    }
+
+    /// Select the element under the cursor on left click, or clear the selection if there's none
+    fn update_mouse_pick(&mut self, persisted: &PersistedState, ctx: &FrameContext) {
+        if self.left_click_edit_mode != LeftClickEditMode::Select
+            || self.mouse.buttons_pressed & 1 == 0
+            || self.gui_wants_mouse
+        {
+            return;
+        }
+
+        let lens = CameraLens {
+            aspect_ratio: ctx.aspect_ratio(),
+            vertical_fov: persisted.camera.vertical_fov,
+            ..Default::default()
+        };
+        let camera_matrices = self
+            .camera
+            .final_transform
+            .into_position_rotation()
+            .through(&lens);
+
+        let window_size = ctx.window.inner_size();
+        let ray = Ray::from_window_position(
+            Vec2::new(
+                self.mouse.physical_position.x as f32,
+                self.mouse.physical_position.y as f32,
+            ),
+            Vec2::new(window_size.width as f32, window_size.height as f32),
+            camera_matrices.clip_to_view,
+            camera_matrices.view_to_world,
+        );
+
+        let hit = crate::selection::pick_element(
+            &ray,
+            &persisted.scene.elements,
+            &persisted.scene.layers,
+            self.app_config.frustum_culling.default_object_size,
+        );
+        match hit {
+            Some((idx, node)) => {
+                self.select_only(idx);
+                self.selected_node = node;
+            }
+            None => self.clear_selection(),
+        }
+    }
+
     fn update_sun(&mut self, persisted: &mut PersistedState, ctx: &mut FrameContext) {
-        if self.mouse.buttons_held & 1 != 0 {
+        if self.mouse.buttons_held & 1 != 0 && !self.gui_wants_mouse {
             let delta_x =
                 (self.mouse.delta.x / ctx.render_extent[0] as f32) * std::f32::consts::TAU;
             let delta_y = (self.mouse.delta.y / ctx.render_extent[1] as f32) * std::f32::consts::PI;
@@ -491,7 +1136,8 @@ impl RuntimeState {
                         .sun
                         .controller
                         .view_space_rotate(&ref_frame, delta_x, delta_y);
-                } /*LeftClickEditMode::MoveLocalLights => {
+                }
+                LeftClickEditMode::Select => {} /*LeftClickEditMode::MoveLocalLights => {
                       persisted.light.lights.theta += theta_delta;
                       persisted.light.lights.phi += phi_delta;
                   }*/
@@ -506,20 +1152,28 @@ impl RuntimeState {
             self.reset_path_tracer = true;
         }
 
-        let sun_interp_t = if ctx.world_renderer.get_render_mode() == RenderMode::Reference {
+        let sun_interp_t = if ctx.world_renderer.get_render_mode() == RenderMode::Reference
+            && !persisted.movement.smoothing_in_reference_mode
+        {
             1.0
         } else {
             (-1.0 * persisted.movement.sun_rotation_smoothness).exp2()
         };
 
         self.sun_direction_interp =
-            Vec3::lerp(self.sun_direction_interp, sun_direction, sun_interp_t).normalize();
+            slerp_direction(self.sun_direction_interp, sun_direction, sun_interp_t);
 
         ctx.world_renderer.sun_size_multiplier = persisted.light.sun.size_multiplier;
+
+        let sun_color_multiplier = persisted.light.sun.color * persisted.light.sun.intensity;
+        if sun_color_multiplier != ctx.world_renderer.sun_color_multiplier {
+            ctx.world_renderer.sun_color_multiplier = sun_color_multiplier;
+            self.reset_path_tracer = true;
+        }
     }
 
     fn update_lights(&mut self, persisted: &mut PersistedState, ctx: &mut FrameContext) {
-        if self.keyboard.was_just_pressed(
+        if self.hotkey_pressed(
             self.keymap_config
                 .rendering
                 .switch_to_reference_path_tracing,
@@ -536,10 +1190,7 @@ impl RuntimeState {
             };
         }
 
-        if self
-            .keyboard
-            .was_just_pressed(self.keymap_config.rendering.light_enable_emissive)
-        {
+        if self.hotkey_pressed(self.keymap_config.rendering.light_enable_emissive) {
             persisted.light.enable_emissive = !persisted.light.enable_emissive;
         }
 
@@ -596,20 +1247,45 @@ impl RuntimeState {
         let mut frustum_culled = 0;
         let mut occlusion_culled = 0;
         let total_elements = persisted.scene.elements.len();
-        let frustum_culling_enabled = persisted.frustum_culling.enabled;
-        let occlusion_culling_enabled = persisted.occlusion_culling.enabled;
-        let triangle_culling_enabled = persisted.triangle_culling.enabled;
+        let frustum_culling = self.app_config.frustum_culling.clone();
+        let triangle_culling = self.app_config.triangle_culling.clone();
+        let passes = CullingPasses::for_frame(
+            &frustum_culling,
+            self.app_config.occlusion_culling.enabled,
+            triangle_culling.enabled,
+            ctx.world_renderer.get_render_mode(),
+        );
+        let frustum_culling_enabled = passes.frustum;
+        let occlusion_culling_enabled = passes.occlusion;
+        // Per-triangle work is the first thing dropped on very large scenes
+        let triangle_culling_enabled =
+            passes.triangle && !self.app_config.large_scene.is_large(total_elements);
 
         // Update occlusion culler config if changed
-        self.occlusion_culler.update_config(persisted.occlusion_culling.clone());
+        let mut occlusion_config = self.app_config.occlusion_culling.clone();
+        // Adapted to the frame's own work, not `dt_filtered`, which includes the frame rate cap's sleep
+        if !occlusion_config.adaptive_resolution {
+            self.occlusion_resolution = Default::default();
+        } else if let Some(frame_work_ms) = self.frame_work_ms {
+            occlusion_config.depth_buffer_resolution = self
+                .occlusion_resolution
+                .update(frame_work_ms, &occlusion_config);
+        }
+        self.occlusion_culler.update_config(occlusion_config);
         
         // Update triangle culler config if changed
-        self.triangle_culler.update_config(persisted.triangle_culling.clone());
+        self.triangle_culler.update_config(triangle_culling.clone());
+        self.triangle_culler.set_viewport_size(Vec2::new(
+            ctx.render_extent[0] as f32,
+            ctx.render_extent[1] as f32,
+        ));
+        self.triangle_culler.set_camera_position(self.camera.final_transform.position);
 
         // Only create frustum if culling is enabled
+        let aspect_ratio = ctx.aspect_ratio();
         let (frustum, view_proj_matrix) = if frustum_culling_enabled || occlusion_culling_enabled {
             let lens = CameraLens {
-                aspect_ratio: ctx.aspect_ratio(),
+                aspect_ratio,
                 vertical_fov: persisted.camera.vertical_fov,
                 ..Default::default()
             };
@@ -621,20 +1297,56 @@ impl RuntimeState {
                 .through(&lens);
 
             let view_proj = camera_matrices.view_to_clip * camera_matrices.world_to_view;
-            let frustum = Frustum::from_view_projection_matrix(view_proj);
+            let frustum = Frustum::from_view_projection_matrix(view_proj).with_far_plane(
+                self.camera.final_transform.position,
+                self.camera.final_transform.forward(),
+                persisted.camera.z_far,
+            );
             (Some(frustum), Some(view_proj))
         } else {
             (None, None)
         };
 
+        let camera_position = self.camera.final_transform.position;
+        let vertical_fov = persisted.camera.vertical_fov;
+
+        // Between culling updates, elements keep their last results. Any change to the shown
+        // elements forces an update, since the changed element may also have been an occluder.
+        let cache_culling = passes.any_visibility() && CullingCache::reuses_results(&frustum_culling);
+        let full_culling_update = if cache_culling {
+            let layers = &persisted.scene.layers;
+            let scene_changed = self.culling_cache.scene_changed(
+                persisted
+                    .scene
+                    .elements
+                    .iter()
+                    .filter(|elem| elem.visible && !crate::layers::hidden_by_layers(&elem.tags, layers)),
+            );
+            let view = CullingView {
+                position: camera_position,
+                forward: self.camera.final_transform.forward(),
+                vertical_fov,
+                aspect_ratio,
+                z_far: persisted.camera.z_far,
+            };
+            self.culling_cache.begin_frame(&frustum_culling, passes, view, scene_changed)
+        } else {
+            self.culling_cache.clear();
+            true
+        };
+
         // Prepare occlusion culler for new frame
-        if occlusion_culling_enabled {
+        if occlusion_culling_enabled && full_culling_update {
             self.occlusion_culler.prepare_frame();
         }
 
         // PASS 1: Add visible objects as potential occluders
-        if occlusion_culling_enabled {
+        if occlusion_culling_enabled && full_culling_update {
             for elem in persisted.scene.elements.iter() {
+                if !elem.visible || crate::layers::hidden_by_layers(&elem.tags, &persisted.scene.layers) {
+                    continue;
+                }
+
                 if let Some(bounding_box) = &elem.bounding_box {
                     let world_aabb = bounding_box.transform(&Mat4::from(elem.transform.affine_transform()));
                     if let Some(ref view_proj) = view_proj_matrix {
@@ -644,18 +1356,62 @@ impl RuntimeState {
             }
         }
 
+        self.culled_elements.clear();
+        // Visible elements for triangle culling, with their approximate screen size
+        let mut triangle_culling_candidates: Vec<(f32, usize)> = Vec::new();
+
         // PASS 2: Test all objects for visibility
-        for elem in persisted.scene.elements.iter_mut() {
+        for (elem_idx, elem) in persisted.scene.elements.iter_mut().enumerate() {
             // Analyze GLTF files to extract nodes if not already done
-            if elem.is_compound && elem.mesh_nodes.is_empty() {
+            if self.app_config.auto_analyze_gltf && elem.is_compound && elem.mesh_nodes.is_empty() {
                 if let Err(e) = self.analyze_gltf_nodes(elem, ctx.world_renderer) {
                     println!("Warning: Failed to analyze GLTF nodes: {}", e);
                 }
             }
 
             let mut element_is_visible = true;
-            
-            if frustum_culling_enabled || occlusion_culling_enabled {
+
+            // Baked visibility stands in for the occlusion test of static elements;
+            // moved or unbaked ones go through the dynamic occlusion culler
+            let pvs_visibility = match &self.pvs {
+                Some(pvs) if occlusion_culling_enabled => pvs.static_visibility(
+                    elem.id,
+                    &elem.world_bounds(frustum_culling.default_object_size),
+                    camera_position,
+                ),
+                _ => None,
+            };
+            let dynamic_occlusion = occlusion_culling_enabled && pvs_visibility.is_none();
+
+            // Checked before the culling tests, so hidden elements stay hidden with culling off
+            let hidden = !elem.visible || crate::layers::hidden_by_layers(&elem.tags, &persisted.scene.layers);
+            let counters_before = (total_sub_objects, visible_objects, frustum_culled, occlusion_culled);
+            let mut reused = false;
+            if hidden {
+                // Hidden elements and layers go through the same hiding path as culled elements
+                element_is_visible = false;
+                total_sub_objects += if elem.is_compound {
+                    elem.mesh_nodes.len().max(1)
+                } else {
+                    1
+                };
+            } else if let Some(cached) = self.culling_cache.get(elem) {
+                reused = true;
+                element_is_visible = cached.visible;
+                total_sub_objects += cached.sub_objects;
+                visible_objects += cached.visible_sub_objects;
+                frustum_culled += cached.frustum_culled;
+                occlusion_culled += cached.occlusion_culled;
+            } else if pvs_visibility == Some(false) {
+                element_is_visible = false;
+                let sub_objects = if elem.is_compound {
+                    elem.mesh_nodes.len().max(1)
+                } else {
+                    1
+                };
+                total_sub_objects += sub_objects;
+                occlusion_culled += sub_objects;
+            } else if frustum_culling_enabled || occlusion_culling_enabled {
                 if elem.is_compound && !elem.mesh_nodes.is_empty() {
                     // For compound objects (GLTF with multiple nodes), test each node
                     let mut any_node_visible = false;
@@ -672,12 +1428,19 @@ impl RuntimeState {
                             // Test frustum culling first
                             if frustum_culling_enabled {
                                 if let Some(ref frustum) = frustum {
-                                    node_visible = if persisted.frustum_culling.use_sphere_culling {
-                                        let sphere_center = world_aabb.center();
-                                        let sphere_radius = world_aabb.half_size().length();
-                                        frustum.is_visible_sphere(sphere_center, sphere_radius)
+                                    let sphere_center = world_aabb.center();
+                                    let sphere_radius = world_aabb.half_size().length();
+                                    let use_sphere = frustum_culling.use_sphere_test(
+                                        sphere_radius,
+                                        sphere_center.distance(camera_position),
+                                        vertical_fov,
+                                    );
+
+                                    let margin = frustum_culling.frustum_margin;
+                                    node_visible = if use_sphere {
+                                        frustum.is_visible_sphere(sphere_center, (sphere_radius + margin).max(0.0))
                                     } else {
-                                        frustum.is_visible_aabb(&world_aabb)
+                                        frustum.is_visible_aabb(&world_aabb.inflate(Vec3::splat(margin)))
                                     };
                                     
                                     if !node_visible {
@@ -687,7 +1450,7 @@ impl RuntimeState {
                             }
                             
                             // Test occlusion culling if still visible after frustum test
-                            if node_visible && occlusion_culling_enabled {
+                            if node_visible && dynamic_occlusion {
                                 if let Some(ref view_proj) = view_proj_matrix {
                                     if self.occlusion_culler.is_occluded(&world_aabb, view_proj) {
                                         node_visible = false;
@@ -712,25 +1475,32 @@ impl RuntimeState {
                     // For simple objects, use the element's bounding box
                     total_sub_objects += 1;
                     
-                    // Calculate world-space bounding box if not cached
-                    if elem.bounding_box.is_none() {
-                        let default_size = Vec3::splat(persisted.frustum_culling.default_object_size);
-                        elem.bounding_box = Some(Aabb::from_center_size(Vec3::ZERO, default_size));
-                    }
-
-                    if let Some(local_aabb) = &elem.bounding_box {
-                        let world_aabb = local_aabb.transform(&Mat4::from(elem.transform.affine_transform()));
+                    // `update_bounding_boxes` gave every element a box earlier this frame
+                    if let (Some(local_aabb), Some(local_sphere)) =
+                        (elem.bounding_box, elem.local_bounding_sphere())
+                    {
+                        let affine = elem.transform.affine_transform();
+                        let world_aabb = local_aabb.transform(&Mat4::from(affine));
                         
                         // Test frustum culling first
                         if frustum_culling_enabled {
                             if let Some(ref frustum) = frustum {
-                                element_is_visible = if persisted.frustum_culling.use_sphere_culling {
-                                    let world_center = elem.transform.position;
-                                    let world_scale = elem.transform.scale.max_element();
-                                    let sphere_radius = local_aabb.half_size().length() * world_scale;
-                                    frustum.is_visible_sphere(world_center, sphere_radius)
+                                // Only the center needs transforming; the local sphere is cached
+                                let world_center = affine.transform_point3(local_sphere.center);
+                                let world_scale = elem.transform.scale.max_element();
+                                let sphere_radius = local_sphere.radius * world_scale;
+                                let use_sphere = !frustum_culling.is_non_uniform_scale(elem.transform.scale)
+                                    && frustum_culling.use_sphere_test(
+                                        sphere_radius,
+                                        world_center.distance(camera_position),
+                                        vertical_fov,
+                                    );
+
+                                let margin = frustum_culling.frustum_margin;
+                                element_is_visible = if use_sphere {
+                                    frustum.is_visible_sphere(world_center, (sphere_radius + margin).max(0.0))
                                 } else {
-                                    frustum.is_visible_aabb(&world_aabb)
+                                    frustum.is_visible_aabb(&world_aabb.inflate(Vec3::splat(margin)))
                                 };
                                 
                                 if !element_is_visible {
@@ -740,7 +1510,7 @@ impl RuntimeState {
                         }
                         
                         // Test occlusion culling if still visible after frustum test
-                        if element_is_visible && occlusion_culling_enabled {
+                        if element_is_visible && dynamic_occlusion {
                             if let Some(ref view_proj) = view_proj_matrix {
                                 if self.occlusion_culler.is_occluded(&world_aabb, view_proj) {
                                     element_is_visible = false;
@@ -765,81 +1535,108 @@ impl RuntimeState {
                 }
             }
 
-            // Apply visibility results
-            if element_is_visible {
-                // Update instance parameters and transform only for visible objects
-                ctx.world_renderer
-                    .get_instance_dynamic_parameters_mut(elem.instance)
-                    .emissive_multiplier = persisted.light.emissive_multiplier * emissive_toggle_mult;
+            if !hidden && !reused && cache_culling {
+                self.culling_cache.insert(
+                    elem.id,
+                    CachedVisibility {
+                        key: CullingKey::of(elem),
+                        visible: element_is_visible,
+                        sub_objects: total_sub_objects - counters_before.0,
+                        visible_sub_objects: visible_objects - counters_before.1,
+                        frustum_culled: frustum_culled - counters_before.2,
+                        occlusion_culled: occlusion_culled - counters_before.3,
+                    },
+                );
+            }
+
+            // In preview, culled elements stay on screen and the GUI outlines them instead
+            let culling_previewed = frustum_culling.preview && !element_is_visible && !hidden;
+            if culling_previewed {
+                self.culled_elements.push(elem_idx);
+            }
+            let rendered = element_is_visible || culling_previewed;
+
+            // Apply visibility results. The render transform is derived from the authoring
+            // transform every frame; culling never writes to `elem.transform`.
+            let render_transform = frustum_culling
+                .culling_method
+                .render_transform(&elem.transform, rendered);
+            for (&instance, transform) in elem.instances().iter().zip(elem.instance_transforms(&render_transform)) {
+                ctx.world_renderer.set_instance_transform(instance, transform);
                 ctx.world_renderer
-                    .set_instance_transform(elem.instance, elem.transform.affine_transform());
+                    .get_instance_dynamic_parameters_mut(instance)
+                    .casts_shadow = elem.casts_shadow;
+            }
+            elem.render_transform = Some(render_transform);
+
+            if rendered {
+                // Fade emissive out before the far plane, rather than popping at it
+                let emissive_fade = if frustum_culling_enabled && frustum_culling.emissive_fade_band > 0.0 {
+                    let sphere = BoundingSphere::from_aabb(&elem.world_bounds(frustum_culling.default_object_size));
+                    let depth = (sphere.center - camera_position).dot(self.camera.final_transform.forward()) - sphere.radius;
+                    frustum_culling.emissive_fade(depth, persisted.camera.z_far)
+                } else {
+                    1.0
+                };
+
+                for &instance in elem.instances() {
+                    ctx.world_renderer
+                        .get_instance_dynamic_parameters_mut(instance)
+                        .emissive_multiplier = persisted.light.emissive_multiplier * emissive_toggle_mult * emissive_fade;
+                }
                 
-                // Perform triangle culling analysis for visible objects
-                if triangle_culling_enabled {
-                    self.analyze_triangle_culling(elem, &persisted.triangle_culling, view_proj_matrix.as_ref());
+                // Triangle culling runs after this pass, once all visible elements are known
+                if triangle_culling_enabled && element_is_visible {
+                    let sphere = BoundingSphere::from_aabb(&elem.world_bounds(frustum_culling.default_object_size));
+                    let screen_size = crate::culling::projected_sphere_size(
+                        sphere.radius,
+                        sphere.center.distance(camera_position),
+                        vertical_fov,
+                    )
+                    .unwrap_or(f32::INFINITY);
+                    triangle_culling_candidates.push((screen_size, elem_idx));
                 }
             } else {
-                // Apply culling based on the chosen method
-                match persisted.frustum_culling.culling_method {
-                    CullingMethod::EmissiveMultiplier => {
-                        // Make objects invisible by setting emissive to 0
-                        ctx.world_renderer
-                            .get_instance_dynamic_parameters_mut(elem.instance)
-                            .emissive_multiplier = 0.0;
-                    }
-                    CullingMethod::MoveAway => {
-                        // Move objects far away (more effective for GPU culling)
-                        ctx.world_renderer
-                            .get_instance_dynamic_parameters_mut(elem.instance)
-                            .emissive_multiplier = 0.0;
-                        
-                        let mut culled_transform = elem.transform.clone();
-                        culled_transform.position = Vec3::new(1000000.0, 1000000.0, 1000000.0);
-                        ctx.world_renderer
-                            .set_instance_transform(elem.instance, culled_transform.affine_transform());
-                    }
-                    CullingMethod::ScaleToZero => {
-                        // Scale objects to zero size (effective for GPU culling)
-                        ctx.world_renderer
-                            .get_instance_dynamic_parameters_mut(elem.instance)
-                            .emissive_multiplier = 0.0;
-                        
-                        let mut culled_transform = elem.transform.clone();
-                        culled_transform.scale = Vec3::ZERO;
-                        ctx.world_renderer
-                            .set_instance_transform(elem.instance, culled_transform.affine_transform());
-                    }
+                // Culled objects are hidden by zeroing emissive, on top of the transform
+                // change of the chosen method.
+                for &instance in elem.instances() {
+                    ctx.world_renderer
+                        .get_instance_dynamic_parameters_mut(instance)
+                        .emissive_multiplier = 0.0;
                 }
             }
         }
 
+        // The per-frame triangle budget goes to the largest elements on screen first
+        triangle_culling_candidates.sort_by(|a, b| b.0.total_cmp(&a.0));
+        for (_, elem_idx) in triangle_culling_candidates {
+            self.analyze_triangle_culling(
+                &persisted.scene.elements[elem_idx],
+                &triangle_culling,
+                view_proj_matrix.as_ref(),
+            );
+        }
+
         // Optional: Log culling statistics
-        if (frustum_culling_enabled || occlusion_culling_enabled) && persisted.frustum_culling.debug_logging {
-            static mut FRAME_COUNTER: u32 = 0;
-            unsafe {
-                FRAME_COUNTER += 1;
-                if FRAME_COUNTER % persisted.frustum_culling.log_interval_frames == 0 {
-                    let mut log_msg = format!("Culling Stats: {}/{} sub-objects visible from {} elements", 
-                        visible_objects, total_sub_objects, total_elements);
-                    
-                    if frustum_culling_enabled && occlusion_culling_enabled {
-                        log_msg += &format!(" (Frustum: {} culled, Occlusion: {} culled)", frustum_culled, occlusion_culled);
-                    } else if frustum_culling_enabled {
-                        log_msg += &format!(" (Frustum culling only)");
-                    } else if occlusion_culling_enabled {
-                        log_msg += &format!(" (Occlusion culling only)");
-                    }
-                    
-                    println!("{}", log_msg);
-                    
-                    // Show occlusion culling statistics
-                    if occlusion_culling_enabled {
-                        let stats = self.occlusion_culler.get_statistics();
-                        println!("  Occlusion Stats: {} occluders, {:.1}% depth buffer usage", 
-                            stats.total_occluders, stats.depth_buffer_usage);
-                    }
-                }
-            }
+        if (frustum_culling_enabled || occlusion_culling_enabled) && frustum_culling.debug_logging {
+            let triangle_stats = self.triangle_culler.get_statistics();
+            let stats = CullingFrameStats {
+                visible: visible_objects,
+                total: total_sub_objects,
+                elements: total_elements,
+                frustum_culled,
+                occlusion_culled,
+                occluders: if occlusion_culling_enabled {
+                    self.occlusion_culler.get_statistics().total_occluders
+                } else {
+                    0
+                },
+                triangles_tested: triangle_stats.triangles_tested,
+                triangles_rendered: triangle_stats.triangles_rendered,
+                triangles_culled: triangle_stats.total_culled,
+            };
+
+            self.culling_logger.end_frame(&frustum_culling, &stats);
         }
         
         // Update triangle culling frame counter and potentially log statistics
@@ -853,18 +1650,27 @@ impl RuntimeState {
         mut ctx: FrameContext,
         persisted: &mut PersistedState,
     ) -> WorldFrameDesc {
+        self.update_window_focus(ctx.events);
+
+        if let Some(start) = self.frame_work_start {
+            self.frame_work_ms = Some(start.elapsed().as_secs_f32() * 1000.0);
+        }
+
         // Limit framerate. Not particularly precise.
-        if self.max_fps != MAX_FPS_LIMIT {
+        let max_fps = self.effective_max_fps(ctx.world_renderer.get_render_mode());
+        if max_fps != MAX_FPS_LIMIT {
             std::thread::sleep(std::time::Duration::from_micros(
-                1_000_000 / self.max_fps as u64,
+                1_000_000 / max_fps as u64,
             ));
         }
+        self.frame_work_start = Some(std::time::Instant::now());
 
         self.keyboard.update(ctx.events);
         self.mouse.update(ctx.events);
         self.gamepad.update_from_gilrs(&mut self.gilrs);
         self.gamepad.update_ticks();
         self.handle_file_drop_events(persisted, ctx.world_renderer, ctx.events);
+        self.poll_pvs_bake();
 
         let orig_persisted_state = persisted.clone();
         let orig_render_overrides = ctx.world_renderer.render_overrides;
@@ -872,24 +1678,33 @@ impl RuntimeState {
         self.do_gui(persisted, &mut ctx);
         
         // Procesar inicialización pendiente del streaming
-        if let Err(e) = futures::executor::block_on(
-            self.streaming_integration.process_pending_initialization()
-        ) {
+        if let Err(e) = self.streaming_integration.process_pending_initialization(persisted) {
             log::error!("Error procesando inicialización de streaming: {}", e);
         }
         
-        self.update_lights(persisted, &mut ctx);
-        self.update_objects(persisted, &mut ctx);
-        self.update_sun(persisted, &mut ctx);
+        self.update_selection_nudge(persisted);
+        self.update_edit_keys(persisted, ctx.world_renderer);
+        self.update_mouse_pick(persisted, &ctx);
 
-        // Update bounding boxes for new objects
-        self.update_bounding_boxes(persisted, ctx.world_renderer);
+        if self.hotkey_pressed(self.keymap_config.misc.pause) {
+            self.set_paused(!self.paused, &ctx);
+        }
+
+        // Before culling in `update_objects`, which falls back to a default box for elements without one
+        self.update_bounding_boxes(persisted);
+
+        if !self.paused {
+            self.update_lights(persisted, &mut ctx);
+            self.update_objects(persisted, &mut ctx);
+            self.update_sun(persisted, &mut ctx);
+        }
         
-        // Analyze GLTF files for compound objects
+        // Analyze GLTF files for compound objects. Analysis always leaves nodes behind (a
+        // fallback one on failure), so each element is only parsed once.
         let mut elements_to_analyze = Vec::new();
         
         for (index, elem) in persisted.scene.elements.iter().enumerate() {
-            if !elem.is_compound {
+            if self.app_config.auto_analyze_gltf && !elem.is_compound && elem.mesh_nodes.is_empty() {
                 if let MeshSource::File(path) = &elem.source {
                     let extension = path.extension()
                         .and_then(|ext| ext.to_str())
@@ -912,20 +1727,29 @@ impl RuntimeState {
             }
         }
 
-        self.update_camera(persisted, &ctx);
+        // Node analysis above refines the bounds, so fitting waits until after it
+        if self.far_plane_fit_pending {
+            self.fit_far_plane(persisted);
+            self.far_plane_fit_pending = false;
+        }
+
+        if !self.paused {
+            self.update_camera(persisted, &ctx);
+        }
+        self.update_streaming(persisted, &ctx);
 
-        if self
-            .keyboard
-            .was_just_pressed(self.keymap_config.sequencer.add_keyframe)
-            || (self.mouse.buttons_pressed & (1 << 1)) != 0
+        if self.hotkey_pressed(self.keymap_config.misc.reload_keymap) || self.keymap_watcher.poll()
         {
-            self.add_sequence_keyframe(persisted);
+            self.reload_keymap();
         }
 
-        if self
-            .keyboard
-            .was_just_pressed(self.keymap_config.sequencer.play)
+        if self.hotkey_pressed(self.keymap_config.sequencer.add_keyframe)
+            || ((self.mouse.buttons_pressed & (1 << 1)) != 0 && !self.gui_wants_mouse)
         {
+            self.add_sequence_keyframe(persisted);
+        }
+
+        if self.hotkey_pressed(self.keymap_config.sequencer.play) {
             match self.sequence_playback_state {
                 SequencePlaybackState::NotPlaying => {
                     self.play_sequence(persisted);
@@ -938,6 +1762,9 @@ impl RuntimeState {
 
         ctx.world_renderer.ev_shift = persisted.exposure.ev_shift;
         ctx.world_renderer.contrast = persisted.exposure.contrast;
+        if persisted.exposure.use_dynamic_adaptation && !ctx.world_renderer.dynamic_exposure.enabled {
+            ctx.world_renderer.dynamic_exposure.reset();
+        }
         ctx.world_renderer.dynamic_exposure.enabled = persisted.exposure.use_dynamic_adaptation;
         ctx.world_renderer.dynamic_exposure.speed_log2 =
             persisted.exposure.dynamic_adaptation_speed;
@@ -950,13 +1777,54 @@ impl RuntimeState {
             || ctx.world_renderer.render_overrides != orig_render_overrides
         {
             self.reset_path_tracer = true;
+            self.last_activity = std::time::Instant::now();
+        }
+
+        // Gamepad sticks and sequence playback keep the scene moving without window events
+        if self.gamepad.has_input()
+            || matches!(self.sequence_playback_state, SequencePlaybackState::Playing { .. })
+        {
+            self.last_activity = std::time::Instant::now();
+        }
+
+        // Keep the copy the panic hook saves, and the duplicate check, in sync with the scene.
+        // Both only depend on authoring data, which culling doesn't touch every frame.
+        let authoring_changed = persisted.scene.elements.len() != orig_persisted_state.scene.elements.len()
+            || persisted
+                .scene
+                .elements
+                .iter()
+                .zip(&orig_persisted_state.scene.elements)
+                .any(|(elem, orig)| !elem.same_authoring_data(orig));
+        if self.crash_snapshot_stale || authoring_changed {
+            crate::crash::update_snapshot(
+                self.scene_desc(persisted),
+                crate::crash::crash_file_path(self.current_scene_path.as_ref()),
+            );
+            self.duplicate_elements = crate::scene_validation::find_duplicates(
+                &persisted.scene.elements,
+                self.app_config.frustum_culling.default_object_size,
+            );
+
+            let large_scene = self.app_config.large_scene.is_large(persisted.scene.elements.len());
+            if large_scene && !self.large_scene {
+                warn!(
+                    "Scene has {} elements, over the soft cap of {}; disabling triangle culling and per-element overlays",
+                    persisted.scene.elements.len(),
+                    self.app_config.large_scene.element_soft_cap
+                );
+                self.toasts.error(format!(
+                    "Large scene ({} elements): some editor features are off",
+                    persisted.scene.elements.len()
+                ));
+            }
+            self.large_scene = large_scene;
+            self.crash_snapshot_stale = false;
         }
 
         // Reset accumulation of the path tracer whenever the camera moves
         if (self.reset_path_tracer
-            || self
-                .keyboard
-                .was_just_pressed(self.keymap_config.rendering.reset_path_tracer))
+            || self.hotkey_pressed(self.keymap_config.rendering.reset_path_tracer))
             && ctx.world_renderer.get_render_mode() == RenderMode::Reference
         {
             ctx.world_renderer.reset_reference_accumulation = true;
@@ -980,6 +1848,17 @@ impl RuntimeState {
         }
     }
 
+    /// Freeze or resume scene updates. Releases the cursor, since a mouse-look drag in progress
+    /// won't see its button release while paused.
+    pub fn set_paused(&mut self, paused: bool, ctx: &FrameContext) {
+        if paused && !self.paused {
+            let _ = ctx.window.set_cursor_grab(winit::window::CursorGrabMode::None);
+            ctx.window.set_cursor_visible(true);
+        }
+        self.paused = paused;
+        self.last_activity = std::time::Instant::now();
+    }
+
     pub fn is_sequence_playing(&self) -> bool {
         matches!(
             &self.sequence_playback_state,
@@ -995,9 +1874,11 @@ impl RuntimeState {
         // Allow some time at the start of the playback before the camera starts moving
         const PLAYBACK_WARMUP_DURATION: f32 = 0.5;
 
+        self.solo_camera_key = None;
+
         let t = self
             .active_camera_key
-            .and_then(|i| Some(persisted.sequence.get_item(i)?.t))
+            .and_then(|i| persisted.sequence.playback_time(i))
             .unwrap_or(-PLAYBACK_WARMUP_DURATION);
 
         self.sequence_playback_state = SequencePlaybackState::Playing {
@@ -1006,6 +1887,14 @@ impl RuntimeState {
         };
     }
 
+    pub fn print_camera_transform(&self, persisted: &PersistedState) {
+        println!(
+            "position: {}, look_at: {}",
+            persisted.camera.position,
+            persisted.camera.position + persisted.camera.rotation * -Vec3::Z,
+        );
+    }
+
     pub fn add_sequence_keyframe(&mut self, persisted: &mut PersistedState) {
         persisted.sequence.add_keyframe(
             self.active_camera_key,
@@ -1013,6 +1902,8 @@ impl RuntimeState {
                 camera_position: MemOption::new(persisted.camera.position),
                 camera_direction: MemOption::new(persisted.camera.rotation * -Vec3::Z),
                 towards_sun: MemOption::new(persisted.light.sun.controller.towards_sun()),
+                sun_intensity: MemOption::new(persisted.light.sun.intensity),
+                sun_color: MemOption::new(persisted.light.sun.color),
             },
         );
 
@@ -1022,37 +1913,82 @@ impl RuntimeState {
     }
 
     pub fn jump_to_sequence_key(&mut self, persisted: &mut PersistedState, idx: usize) {
-        let exact_item = if let Some(item) = persisted.sequence.get_item(idx) {
-            item.clone()
-        } else {
+        if persisted.sequence.get_item(idx).is_none() {
             return;
-        };
+        }
+
+        let value = persisted.sequence.resolved_value(idx);
+        if let Some(value) = &value {
+            self.set_camera_view(persisted, value);
+            self.camera.update(1e10);
+        }
+
+        self.active_camera_key = Some(idx);
+        self.sequence_playback_state = SequencePlaybackState::NotPlaying;
+
+        // Soloing follows the active key
+        if self.solo_camera_key.is_some() {
+            self.solo_camera_key = value;
+        }
+    }
 
-        if let Some(value) = persisted.sequence.to_playback().sample(exact_item.t) {
-            self.camera.driver_mut::<Position>().position = exact_item
-                .value
-                .camera_position
-                .unwrap_or(value.camera_position);
+    fn set_camera_view(&mut self, persisted: &mut PersistedState, value: &SequenceFullValue) {
+        self.camera.driver_mut::<Position>().position = value.camera_position;
+        if value.camera_direction.length_squared() > 0.0 {
             self.camera
                 .driver_mut::<YawPitch>()
                 .set_rotation_quat(dolly::util::look_at::<dolly::handedness::RightHanded>(
-                    exact_item
-                        .value
-                        .camera_direction
-                        .unwrap_or(value.camera_direction),
+                    value.camera_direction.normalize(),
                 ));
+        }
 
-            self.camera.update(1e10);
+        persisted
+            .light
+            .sun
+            .controller
+            .set_towards_sun(value.towards_sun.normalize_or_zero());
+        if let Some(intensity) = value.sun_intensity {
+            persisted.light.sun.intensity = intensity;
+        }
+        if let Some(color) = value.sun_color {
+            persisted.light.sun.color = color;
+        }
+    }
+
+    /// Pin the view to the active key, see `solo_camera_key`. Returns whether there was a key to solo.
+    pub fn start_solo_camera_key(&mut self, persisted: &mut PersistedState) -> bool {
+        self.solo_camera_key = self
+            .active_camera_key
+            .and_then(|idx| persisted.sequence.resolved_value(idx));
 
-            persisted
-                .light
-                .sun
-                .controller
-                .set_towards_sun(exact_item.value.towards_sun.unwrap_or(value.towards_sun));
+        if self.solo_camera_key.is_some() {
+            self.sequence_playback_state = SequencePlaybackState::NotPlaying;
         }
+        self.solo_camera_key.is_some()
+    }
 
-        self.active_camera_key = Some(idx);
-        self.sequence_playback_state = SequencePlaybackState::NotPlaying;
+    /// Write the soloed view into the active key, setting all of its values
+    pub fn commit_solo_camera_key(&mut self, persisted: &mut PersistedState) {
+        let (idx, value) = match (self.active_camera_key, self.solo_camera_key) {
+            (Some(idx), Some(value)) => (idx, value),
+            _ => return,
+        };
+
+        persisted.sequence.each_key(|i, item| {
+            if idx != i {
+                return;
+            }
+
+            item.value.camera_position = MemOption::new(value.camera_position);
+            item.value.camera_direction = MemOption::new(value.camera_direction.normalize_or_zero());
+            item.value.towards_sun = MemOption::new(value.towards_sun.normalize_or_zero());
+            if let Some(intensity) = value.sun_intensity {
+                item.value.sun_intensity = MemOption::new(intensity);
+            }
+            if let Some(color) = value.sun_color {
+                item.value.sun_color = MemOption::new(color);
+            }
+        })
     }
 
     pub fn replace_camera_sequence_key(&mut self, persisted: &mut PersistedState, idx: usize) {
@@ -1064,6 +2000,8 @@ impl RuntimeState {
             item.value.camera_position = MemOption::new(persisted.camera.position);
             item.value.camera_direction = MemOption::new(persisted.camera.rotation * -Vec3::Z);
             item.value.towards_sun = MemOption::new(persisted.light.sun.controller.towards_sun());
+            item.value.sun_intensity = MemOption::new(persisted.light.sun.intensity);
+            item.value.sun_color = MemOption::new(persisted.light.sun.color);
         })
     }
 
@@ -1071,6 +2009,7 @@ impl RuntimeState {
         persisted.sequence.delete_key(idx);
 
         self.active_camera_key = None;
+        self.solo_camera_key = None;
     }
 
     pub(crate) fn load_mesh(
@@ -1080,42 +2019,187 @@ impl RuntimeState {
     ) -> anyhow::Result<MeshHandle> {
         log::info!("Loading a mesh from {:?}", source);
 
-        let path = match source {
-            MeshSource::File(path) => {
-                fn calculate_hash(t: &PathBuf) -> u64 {
-                    let mut s = DefaultHasher::new();
-                    t.hash(&mut s);
-                    s.finish()
-                }
+        let path = baked_mesh_path(source);
+        if let MeshSource::File(source_path) = source {
+            if !canonical_path_from_vfs(&path).map_or(false, |path| path.exists()) {
+                kajiya_asset_pipe::process_mesh_asset(
+                    kajiya_asset_pipe::MeshAssetProcessParams {
+                        path: source_path.clone(),
+                        output_name: baked_mesh_name(source_path),
+                        scale: 1.0,
+                    },
+                )
+                .with_context(|| format!("Processing mesh asset {:?}", source_path))?;
+            }
+        }
 
-                let path_hash = match path.canonicalize() {
-                    Ok(canonical) => calculate_hash(&canonical),
-                    Err(_) => calculate_hash(path),
-                };
+        get_or_add_mesh(&mut self.known_meshes, path, |path| {
+            world_renderer
+                .add_baked_mesh(path.clone(), AddMeshOptions::new())
+                .with_context(|| format!("Adding baked mesh {:?}", path))
+        })
+    }
 
-                let cached_mesh_name = format!("{:8.8x}", path_hash);
-                let cached_mesh_path = PathBuf::from(format!("/cache/{}.mesh", cached_mesh_name));
-
-                if !canonical_path_from_vfs(&cached_mesh_path).map_or(false, |path| path.exists()) {
-                    kajiya_asset_pipe::process_mesh_asset(
-                        kajiya_asset_pipe::MeshAssetProcessParams {
-                            path: path.clone(),
-                            output_name: cached_mesh_name,
-                            scale: 1.0,
-                        },
-                    )?;
-                }
+    /// The baked mesh of glTF node `gltf_node` alone, in the node's own space. Baked on first
+    /// use, like `load_mesh`.
+    fn load_node_mesh(
+        &mut self,
+        world_renderer: &mut WorldRenderer,
+        source_path: &Path,
+        gltf_node: usize,
+    ) -> anyhow::Result<MeshHandle> {
+        let output_name = format!("{}-node{}", baked_mesh_name(source_path), gltf_node);
+        let path = PathBuf::from(format!("/cache/{}.mesh", output_name));
+        if !canonical_path_from_vfs(&path).map_or(false, |path| path.exists()) {
+            kajiya_asset_pipe::process_mesh_asset(
+                kajiya_asset_pipe::MeshAssetProcessParams {
+                    path: source_path.to_path_buf(),
+                    output_name,
+                    scale: 1.0,
+                    node: Some(gltf_node),
+                },
+            )
+            .with_context(|| format!("Processing node {} of mesh asset {:?}", gltf_node, source_path))?;
+        }
 
-                cached_mesh_path
+        get_or_add_mesh(&mut self.known_meshes, path, |path| {
+            world_renderer
+                .add_baked_mesh(path.clone(), AddMeshOptions::new())
+                .with_context(|| format!("Adding baked mesh {:?}", path))
+        })
+    }
+
+    /// Gives `elem` new renderer instances, replacing its handles: one per mesh node if it's
+    /// split, else one for the whole mesh
+    pub(crate) fn add_element_instances(
+        &mut self,
+        world_renderer: &mut WorldRenderer,
+        elem: &mut SceneElement,
+    ) -> anyhow::Result<()> {
+        if elem.split_nodes {
+            let source_path = match &elem.source {
+                MeshSource::File(path) if elem.can_split_nodes() => path.clone(),
+                _ => anyhow::bail!("{:?} can't be drawn per node; analyze its glTF nodes again", elem.source),
+            };
+
+            // All node meshes load before any instance is added, so failing leaves none behind
+            let mut meshes = Vec::with_capacity(elem.mesh_nodes.len());
+            for node in &elem.mesh_nodes {
+                meshes.push(self.load_node_mesh(world_renderer, &source_path, node.gltf_node.unwrap())?);
             }
-            MeshSource::Cache(path) => path.clone(),
+
+            elem.node_instances = meshes
+                .into_iter()
+                .zip(elem.instance_transforms(&elem.transform))
+                .map(|(mesh, transform)| world_renderer.add_instance(mesh, transform))
+                .collect();
+            elem.instance = InstanceHandle::INVALID;
+        } else {
+            let mesh = self.load_mesh(world_renderer, &elem.source)?;
+            elem.instance = world_renderer.add_instance(mesh, elem.transform.affine_transform());
+            elem.node_instances.clear();
+        }
+
+        elem.render_transform = None;
+        Ok(())
+    }
+
+    /// Draws `elem` as one instance per mesh node from now on, so its nodes can be moved.
+    /// Does nothing if it already is.
+    pub(crate) fn split_element_nodes(
+        &mut self,
+        world_renderer: &mut WorldRenderer,
+        elem: &mut SceneElement,
+    ) -> anyhow::Result<()> {
+        self.set_nodes_split(world_renderer, elem, true)
+    }
+
+    /// Undoes `split_element_nodes`, back to a single instance of the whole mesh
+    pub(crate) fn merge_element_nodes(
+        &mut self,
+        world_renderer: &mut WorldRenderer,
+        elem: &mut SceneElement,
+    ) -> anyhow::Result<()> {
+        self.set_nodes_split(world_renderer, elem, false)
+    }
+
+    fn set_nodes_split(
+        &mut self,
+        world_renderer: &mut WorldRenderer,
+        elem: &mut SceneElement,
+        split: bool,
+    ) -> anyhow::Result<()> {
+        if elem.split_nodes == split {
+            return Ok(());
+        }
+
+        // The old instances stay until the new ones are in, so a failed bake changes nothing
+        let old_instances = elem.instances().to_vec();
+        elem.split_nodes = split;
+        if let Err(err) = self.add_element_instances(world_renderer, elem) {
+            elem.split_nodes = !split;
+            return Err(err);
+        }
+        for instance in old_instances {
+            world_renderer.remove_instance(instance);
+        }
+
+        self.mark_unsaved_changes();
+        Ok(())
+    }
+
+    /// Vertex positions and indices of a mesh's baked version, read on first use and then
+    /// cached. The mesh must have been baked already, e.g. by `load_mesh`.
+    pub(crate) fn mesh_geometry(&mut self, source: &MeshSource) -> anyhow::Result<Arc<MeshGeometry>> {
+        let path = baked_mesh_path(source);
+        let cached = self.mesh_cache.entry(path.clone()).or_default();
+        if let Some(geometry) = &cached.geometry {
+            return Ok(geometry.clone());
+        }
+
+        let geometry = Arc::new(
+            MeshGeometry::load_baked(&path).with_context(|| format!("Reading baked mesh {:?}", path))?,
+        );
+        cached.geometry = Some(geometry.clone());
+        Ok(geometry)
+    }
+
+    /// Write an element's mesh and transform to a glTF file in `gltf_export::EXPORT_DIR`,
+    /// named after its source and ID. Returns the written file.
+    pub(crate) fn export_element_gltf(&self, elem: &SceneElement) -> anyhow::Result<PathBuf> {
+        let source_path = match &elem.source {
+            MeshSource::File(path) | MeshSource::Cache(path) => path,
         };
+        let name = source_path
+            .file_stem()
+            .map_or_else(|| "mesh".to_string(), |stem| stem.to_string_lossy().into_owned());
 
-        Ok(*self.known_meshes.entry(path.clone()).or_insert_with(|| {
-            world_renderer
-                .add_baked_mesh(path, AddMeshOptions::new())
-                .unwrap()
-        }))
+        let export_dir = Path::new(crate::gltf_export::EXPORT_DIR);
+        std::fs::create_dir_all(export_dir).with_context(|| format!("Creating {:?}", export_dir))?;
+
+        let path = export_dir.join(format!("{}-{:x}.gltf", name, elem.id));
+        crate::gltf_export::export_baked_mesh(&baked_mesh_path(&elem.source), &name, &elem.transform, &path)?;
+
+        info!("Exported {:?} to {:?}", elem.source, path);
+        Ok(path)
+    }
+
+    /// Mesh-space triangles of a mesh's baked version, extracted from `mesh_geometry` on first use
+    fn mesh_triangles(&mut self, source: &MeshSource) -> anyhow::Result<Arc<Vec<Triangle>>> {
+        let path = baked_mesh_path(source);
+        if let Some(triangles) = self.mesh_cache.get(&path).and_then(|cached| cached.triangles.clone()) {
+            return Ok(triangles);
+        }
+
+        let geometry = self.mesh_geometry(source)?;
+        let triangles = Arc::new(extract_triangles_from_mesh(
+            &geometry.positions,
+            &geometry.indices,
+            None,
+            None,
+        ));
+        self.mesh_cache.entry(path).or_default().triangles = Some(triangles.clone());
+        Ok(triangles)
     }
 
     pub(crate) fn add_mesh_instance(
@@ -1128,18 +2212,106 @@ impl RuntimeState {
         let mesh = self.load_mesh(world_renderer, &source)?;
         let inst = world_renderer.add_instance(mesh, transform.affine_transform());
 
+        // The bounding box is calculated later, when mesh data is available
         persisted.scene.elements.push(SceneElement {
-            source,
             instance: inst,
-            transform,
-            bounding_box: None, // Will be calculated later when mesh data is available
-            mesh_nodes: Vec::new(),
-            is_compound: false,
+            ..SceneElement::new(new_element_id(), source, transform)
+        });
+        self.edit_history.push(EditAction::ElementAdded {
+            idx: persisted.scene.elements.len() - 1,
         });
 
         Ok(())
     }
 
+    /// Copies the selected elements, moved by the duplicate offset, and selects the copies.
+    /// The copies share the originals' meshes, which are already loaded.
+    pub(crate) fn duplicate_selected(&mut self, persisted: &mut PersistedState, world_renderer: &mut WorldRenderer) {
+        let offset = self.app_config.editing.duplicate_offset;
+        let mut added = Vec::new();
+
+        for idx in self.selected_elements.clone() {
+            let mut copy = match persisted.scene.elements.get(idx) {
+                Some(elem) => elem.duplicate(offset),
+                None => continue,
+            };
+
+            match self.add_element_instances(world_renderer, &mut copy) {
+                Ok(()) => {
+                    persisted.scene.elements.push(copy);
+                    added.push(persisted.scene.elements.len() - 1);
+                }
+                Err(err) => {
+                    log::error!("Failed to duplicate {:?}: {:#}", copy.source, err);
+                    self.toasts.error(format!("Failed to duplicate {:?}", copy.source));
+                }
+            }
+        }
+
+        if added.is_empty() {
+            return;
+        }
+
+        self.edit_history.push(EditAction::Batch(
+            added.iter().map(|&idx| EditAction::ElementAdded { idx }).collect(),
+        ));
+        self.selected_element = added.first().copied().map(SelectionTarget::Element);
+        self.selected_node = None;
+        self.selected_elements = added;
+        self.mark_unsaved_changes();
+    }
+
+    /// Adds a built-in primitive where a dropped mesh would go, generating its file on first use
+    pub(crate) fn add_primitive(
+        &mut self,
+        persisted: &mut PersistedState,
+        world_renderer: &mut WorldRenderer,
+        primitive: crate::primitives::Primitive,
+    ) -> anyhow::Result<()> {
+        primitive.ensure_file(&crate::primitives::primitives_dir()?)?;
+        // The same path the element gets when the scene is loaded again
+        let path = canonical_path_from_vfs(primitive.vfs_path())?;
+        let transform = self.dropped_mesh_transform(persisted);
+        self.add_mesh_instance(persisted, world_renderer, MeshSource::File(path), transform)
+    }
+
+    /// Adds copies of the selected element as laid out by `scatter::scatter_transforms`, drawing from
+    /// `rng`, undone in one step. Copies keep everything but the transform, like duplicates.
+    /// Returns how many were added.
+    pub(crate) fn scatter_selected(
+        &mut self,
+        persisted: &mut PersistedState,
+        world_renderer: &mut WorldRenderer,
+        params: &crate::scatter::ScatterParams,
+    ) -> anyhow::Result<usize> {
+        let source_elem = self.selected_element_index()
+            .and_then(|idx| persisted.scene.elements.get(idx))
+            .context("No element selected")?
+            .clone();
+
+        let transforms = crate::scatter::scatter_transforms(params, &source_elem.transform, &mut self.rng);
+        let mut added = Vec::with_capacity(transforms.len());
+        for transform in transforms {
+            let mut copy = SceneElement {
+                transform,
+                ..source_elem.duplicate(Vec3::ZERO)
+            };
+            // Only the first copy can fail; the rest reuse the meshes it loaded
+            self.add_element_instances(world_renderer, &mut copy)?;
+            persisted.scene.elements.push(copy);
+            added.push(persisted.scene.elements.len() - 1);
+        }
+
+        let count = added.len();
+        if count > 0 {
+            self.edit_history.push(EditAction::Batch(
+                added.into_iter().map(|idx| EditAction::ElementAdded { idx }).collect(),
+            ));
+        }
+
+        Ok(count)
+    }
+
     fn handle_file_drop_events(
         &mut self,
         persisted: &mut PersistedState,
@@ -1172,17 +2344,20 @@ impl RuntimeState {
                             // Scene
                             if let Err(err) = self.load_scene(persisted, world_renderer, path) {
                                 log::error!("Failed to load scene: {:#}", err);
+                                self.toasts.error(format!("Failed to load scene {:?}", path));
                             }
                         }
                         "gltf" | "glb" => {
                             // Mesh
+                            let transform = self.dropped_mesh_transform(persisted);
                             if let Err(err) = self.add_mesh_instance(
                                 persisted,
                                 world_renderer,
                                 MeshSource::File(path.clone()),
-                                SceneElementTransform::IDENTITY,
+                                transform,
                             ) {
                                 log::error!("{:#}", err);
+                                self.toasts.error(format!("Failed to load mesh {:?}", path));
                             }
                         }
                         _ => {}
@@ -1193,44 +2368,93 @@ impl RuntimeState {
         }
     }
 
-    /// Calculate a more accurate bounding box for a mesh instance
-    pub fn calculate_mesh_bounding_box(
-        &self,
-        _world_renderer: &WorldRenderer, // Prefixed with _ to suppress unused warning
-        mesh_handle: MeshHandle,
-    ) -> Option<Aabb> {
-        // In a real implementation, you would:
-        // 1. Get the mesh data from world_renderer
-        // 2. Calculate the actual AABB from vertex positions
-        // 3. Cache the result
-        
-        // For now, return a default based on mesh handle ID
-        let handle_id = mesh_handle.0; // Assuming MeshHandle has a numeric ID
-        let base_size = 1.0 + (handle_id % 5) as f32; // Vary size based on mesh ID
-        
-        Some(Aabb::from_center_size(
-            Vec3::ZERO,
-            Vec3::splat(base_size)
-        ))
+    /// Placement for a dropped mesh, per the editing preferences
+    fn dropped_mesh_transform(&self, persisted: &PersistedState) -> SceneElementTransform {
+        let editing = &self.app_config.editing;
+        let placement = if self.keyboard.is_down(VirtualKeyCode::LAlt) || self.keyboard.is_down(VirtualKeyCode::RAlt) {
+            DropPlacement::Origin
+        } else {
+            editing.drop_placement
+        };
+
+        let default_object_size = self.app_config.frustum_culling.default_object_size;
+        let position = crate::placement::drop_position(
+            placement,
+            self.camera.final_transform.position,
+            self.camera.final_transform.forward(),
+            editing.drop_distance,
+            persisted.scene.elements.iter().map(|elem| {
+                elem.local_bounds(default_object_size)
+                    .transform(&Mat4::from(elem.transform.affine_transform()))
+            }),
+        );
+
+        SceneElementTransform {
+            position,
+            ..SceneElementTransform::IDENTITY
+        }
     }
 
-    /// Update bounding boxes for all scene elements that don't have them
-    pub fn update_bounding_boxes(
-        &self,
-        persisted: &mut PersistedState,
-        _world_renderer: &WorldRenderer, // Prefixed with _ to suppress unused warning
-    ) {
+    /// Mesh-space bounds of a mesh's baked version, from its vertex positions. Computed once
+    /// per mesh and then cached. `None` for a mesh without vertices, or if it can't be read.
+    pub fn calculate_mesh_bounding_box(&mut self, source: &MeshSource) -> Option<Aabb> {
+        let path = baked_mesh_path(source);
+        let cached = self.mesh_cache.entry(path.clone()).or_default();
+        if let Some(aabb) = cached.bounds {
+            return aabb;
+        }
+
+        let aabb = match crate::mesh_geometry::load_baked_bounds(&path) {
+            Ok(aabb) => aabb,
+            Err(err) => {
+                log::warn!("Failed to read the bounds of {:?}: {:#}", path, err);
+                None
+            }
+        };
+        cached.bounds = Some(aabb);
+        aabb
+    }
+
+    /// Give every element a bounding box: its mesh's bounds with `SceneState::auto_bounds`, else
+    /// (or if the geometry can't be read) a `default_object_size` cube. Guesses are redone each
+    /// frame, so they follow the default size.
+    pub fn update_bounding_boxes(&mut self, persisted: &mut PersistedState) {
+        let default_size = Vec3::splat(self.app_config.frustum_culling.default_object_size);
+        let auto_bounds = persisted.scene.auto_bounds;
+
         for elem in persisted.scene.elements.iter_mut() {
-            if elem.bounding_box.is_none() {
-                // Try to get the mesh handle from the instance
-                // This is a simplified version - in practice you'd need to access the mesh data
-                if let Some(aabb) = self.calculate_mesh_bounding_box(_world_renderer, MeshHandle(0)) {
+            if auto_bounds && elem.bounding_box.is_some() && !self.guessed_bounds.contains(&elem.id) {
+                continue;
+            }
+
+            let computed = if auto_bounds {
+                self.calculate_mesh_bounding_box(&elem.source)
+            } else {
+                None
+            };
+            match computed {
+                Some(aabb) => {
                     elem.bounding_box = Some(aabb);
+                    self.guessed_bounds.remove(&elem.id);
+                }
+                None => {
+                    elem.bounding_box = Some(Aabb::from_center_size(Vec3::ZERO, default_size));
+                    self.guessed_bounds.insert(elem.id);
                 }
             }
         }
     }
 
+    /// How many elements are culled with guessed bounds, see `update_bounding_boxes`
+    pub fn guessed_bounds_count(&self, persisted: &PersistedState) -> usize {
+        persisted
+            .scene
+            .elements
+            .iter()
+            .filter(|elem| self.guessed_bounds.contains(&elem.id))
+            .count()
+    }
+
     /// Analyze a GLTF file and extract individual mesh nodes for better culling
     pub fn analyze_gltf_nodes(
         &self,
@@ -1265,6 +2489,7 @@ impl RuntimeState {
                                 name: Some("Fallback_Node".to_string()),
                                 local_transform: SceneElementTransform::IDENTITY,
                                 bounding_box: Some(Aabb::from_center_size(Vec3::ZERO, Vec3::splat(1.0))),
+                                gltf_node: None,
                             },
                         ];
                         elem.is_compound = false;
@@ -1300,6 +2525,7 @@ impl RuntimeState {
                                     name: Some("Fallback_Dmoon_Node".to_string()),
                                     local_transform: SceneElementTransform::IDENTITY,
                                     bounding_box: Some(Aabb::from_center_size(Vec3::ZERO, Vec3::splat(2.0))),
+                                    gltf_node: None,
                                 },
                             ];
                             elem.is_compound = false;
@@ -1330,9 +2556,10 @@ impl RuntimeState {
                             if start < end {
                                 let mesh_path = &line[start+1..end];
                                 
-                                // Remove leading slash if present and construct full path
-                                let mesh_path = mesh_path.trim_start_matches('/');
-                                let full_path = std::path::Path::new("assets").join(mesh_path);
+                                let full_path = self
+                                    .app_config
+                                    .asset_search_paths
+                                    .resolve(std::path::Path::new(mesh_path))?;
                                 
                                 println!("Extracted GLTF path from dmoon: {}", full_path.display());
                                 return Some(full_path);
@@ -1351,12 +2578,12 @@ impl RuntimeState {
         use std::fs::File;
         use std::io::BufReader;
         
-        // Resolve the full path (GLTF files are typically in assets/)
-        let full_path = if path.is_absolute() {
-            path.to_path_buf()
-        } else {
-            std::path::Path::new("assets").join(path)
-        };
+        // Resolve the full path against the asset search directories
+        let full_path = self
+            .app_config
+            .asset_search_paths
+            .resolve(path)
+            .with_context(|| format!("GLTF file not found in any asset directory: {}", path.display()))?;
 
         println!("Attempting to load GLTF from: {}", full_path.display());
 
@@ -1439,8 +2666,10 @@ impl RuntimeState {
                     position: translation,
                     rotation_euler_degrees: rotation_degrees,
                     scale,
+                    pivot: Vec3::ZERO,
                 },
                 bounding_box: Some(Aabb::from_center_size(translation, bounding_size)),
+                gltf_node: Some(node.index()),
             };
 
             mesh_nodes.push(mesh_node);
@@ -1466,74 +2695,176 @@ impl RuntimeState {
         Ok(())
     }
 
-    /// Analyze triangle culling for a given scene element
+    /// Run an element's mesh triangles through the triangle culler, in world space. Once the
+    /// frame's `max_triangles_per_frame` budget runs low, meshes are sampled instead; elements
+    /// are passed in from the largest on screen, so the smallest are the ones left out.
     fn analyze_triangle_culling(
         &mut self,
         elem: &SceneElement,
         _config: &crate::math::triangle_culling::TriangleCullingConfig,
         view_proj_matrix: Option<&Mat4>,
     ) {
-        // For now, we'll generate some example triangles for demonstration
-        // In a real implementation, you would extract actual triangles from the mesh data
-        let example_triangles = self.generate_example_triangles_for_element(elem);
-        
-        for triangle in example_triangles {
-            self.triangle_culler.test_triangle(&triangle, view_proj_matrix);
+        let budget = self.triangle_culler.frame_budget();
+        if budget == 0 {
+            return;
         }
-    }
-    
-    /// Generate example triangles for demonstration purposes
-    /// In a real implementation, this would extract actual triangles from mesh data
-    fn generate_example_triangles_for_element(&self, elem: &SceneElement) -> Vec<crate::math::Triangle> {
-        let mut triangles = Vec::new();
-        
-        // Transform to world space using element transform
-        let transform = Mat4::from(elem.transform.affine_transform());
-        
-        if elem.is_compound {
-            // For compound objects, generate triangles for each mesh node
-            for node in &elem.mesh_nodes {
-                if let Some(aabb) = &node.bounding_box {
-                    let combined_transform = transform * Mat4::from(node.local_transform.affine_transform());
-                    triangles.extend(self.triangles_from_aabb(aabb, &combined_transform));
-                }
-            }
-        } else {
-            // For simple objects, generate triangles from the element's bounding box
-            if let Some(aabb) = &elem.bounding_box {
-                triangles.extend(self.triangles_from_aabb(aabb, &transform));
+
+        // Compound meshes are baked with their node transforms applied, so the element's is enough
+        let triangles = match self.mesh_triangles(&elem.source) {
+            Ok(triangles) => triangles,
+            Err(err) => {
+                log::debug!("No triangles for {:?}: {:#}", elem.source, err);
+                return;
             }
+        };
+
+        let transform = Mat4::from(elem.transform.affine_transform());
+        for idx in sample_triangles(triangles.len(), budget, self.triangle_culler.frame_count()) {
+            self.triangle_culler
+                .test_triangle(&triangles[idx].transform(&transform), view_proj_matrix);
         }
-        
-        triangles
     }
-    /// Generate triangles representing the faces of an AABB transformed by a given matrix
-    fn triangles_from_aabb(&self, aabb: &crate::math::Aabb, transform: &Mat4) -> Vec<crate::math::Triangle> {
-        let min_point = aabb.min;
-        let max_point = aabb.max;
-        
-        // Create two triangles for one face of the AABB as an example
-        let v0 = transform.transform_point3(Vec3::new(min_point.x, min_point.y, min_point.z));
-        let v1 = transform.transform_point3(Vec3::new(max_point.x, min_point.y, min_point.z));
-        let v2 = transform.transform_point3(Vec3::new(max_point.x, max_point.y, min_point.z));
-        let v3 = transform.transform_point3(Vec3::new(min_point.x, max_point.y, min_point.z));
-        
-        vec![
-            crate::math::Triangle::new([v0, v1, v2]),
-            crate::math::Triangle::new([v0, v2, v3]),
-        ]
+
+    /// Get occlusion culling statistics, including the depth buffer resolution in use
+    pub fn get_occlusion_culling_statistics(&self) -> crate::math::OcclusionCullingStatistics {
+        self.occlusion_culler.get_statistics()
     }
 
-    /// Get triangle culling statistics
+    /// Triangle culling statistics of the last finished frame
     pub fn get_triangle_culling_statistics(&self) -> &crate::math::triangle_culling::TriangleCullingStats {
-        self.triangle_culler.get_statistics()
+        self.triangle_culler.last_frame_statistics()
+    }
+
+    /// Triangle culling statistics averaged over the configured window of recent frames
+    pub fn get_triangle_culling_average(&self) -> crate::math::triangle_culling::TriangleCullingAverage {
+        self.triangle_culler.rolling_statistics()
     }
 
     //...existing code...
 }
 
+/// One spelling per source file: VFS mounts resolved, then canonicalized. Files that can't be
+/// canonicalized (e.g. missing) are still made absolute and cleaned up lexically.
+fn normalize_mesh_path(path: &Path) -> PathBuf {
+    let resolved = canonical_path_from_vfs(path).unwrap_or_else(|_| path.to_owned());
+    if let Ok(canonical) = resolved.canonicalize() {
+        return canonical;
+    }
+
+    let absolute = std::env::current_dir()
+        .map(|cwd| cwd.join(&resolved))
+        .unwrap_or(resolved);
+
+    let mut normalized = PathBuf::new();
+    for component in absolute.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            other => normalized.push(other),
+        }
+    }
+    normalized
+}
+
+/// Name of the baked mesh in `/cache`. Hashes the normalized path, so every spelling of a file
+/// shares one cache entry and one renderer mesh.
+fn baked_mesh_name(path: &Path) -> String {
+    let mut hasher = DefaultHasher::new();
+    normalize_mesh_path(path).hash(&mut hasher);
+    format!("{:8.8x}", hasher.finish())
+}
+
+/// VFS path of the baked version of `source`
+fn baked_mesh_path(source: &MeshSource) -> PathBuf {
+    match source {
+        MeshSource::File(path) => PathBuf::from(format!("/cache/{}.mesh", baked_mesh_name(path))),
+        MeshSource::Cache(path) => path.clone(),
+    }
+}
+
+/// Look up a baked mesh, adding it with `add` on first use. Failures aren't cached, so a later load can retry.
+fn get_or_add_mesh(
+    known_meshes: &mut HashMap<PathBuf, MeshHandle>,
+    path: PathBuf,
+    add: impl FnOnce(&PathBuf) -> anyhow::Result<MeshHandle>,
+) -> anyhow::Result<MeshHandle> {
+    if let Some(mesh) = known_meshes.get(&path) {
+        return Ok(*mesh);
+    }
+
+    let mesh = add(&path)?;
+    known_meshes.insert(path, mesh);
+    Ok(mesh)
+}
+
+/// What has been read from one baked mesh so far; each part is filled in on first use
+#[derive(Default)]
+struct CachedMesh {
+    geometry: Option<Arc<MeshGeometry>>,
+    /// Mesh-space triangles from `geometry`, for triangle culling
+    triangles: Option<Arc<Vec<Triangle>>>,
+    /// Mesh-space bounds; `Some(None)` for a mesh without vertices
+    bounds: Option<Option<Aabb>>,
+}
+
 #[derive(PartialEq, Eq)]
 pub enum LeftClickEditMode {
     MoveSun,
+    /// Click an element in the viewport to select it
+    Select,
     //MoveLocalLights,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mesh_bake_failure_returns_err() {
+        let mut known_meshes = HashMap::new();
+        let path = PathBuf::from("/cache/deadbeef.mesh");
+
+        let res = get_or_add_mesh(&mut known_meshes, path.clone(), |_| {
+            anyhow::bail!("corrupt baked mesh")
+        });
+        assert!(res.is_err());
+        assert!(known_meshes.is_empty());
+
+        // The failure isn't cached; a later successful bake is, and gets reused
+        let mesh = get_or_add_mesh(&mut known_meshes, path.clone(), |_| Ok(MeshHandle(7))).unwrap();
+        assert_eq!(mesh.0, 7);
+        let mesh = get_or_add_mesh(&mut known_meshes, path, |_| panic!("should be cached")).unwrap();
+        assert_eq!(mesh.0, 7);
+    }
+
+    #[test]
+    fn test_path_spellings_share_one_mesh() {
+        let dir = std::env::temp_dir().join(format!("darkmoon_mesh_paths_{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("textures")).unwrap();
+        std::fs::write(dir.join("model.gltf"), "{}").unwrap();
+
+        let plain = dir.join("model.gltf");
+        let roundabout = dir.join(".").join("textures").join("..").join("model.gltf");
+        assert_eq!(normalize_mesh_path(&plain), normalize_mesh_path(&roundabout));
+
+        let mut known_meshes = HashMap::new();
+        let cache_path = |path: &Path| PathBuf::from(format!("/cache/{}.mesh", baked_mesh_name(path)));
+        let first = get_or_add_mesh(&mut known_meshes, cache_path(&plain), |_| Ok(MeshHandle(3))).unwrap();
+        let second =
+            get_or_add_mesh(&mut known_meshes, cache_path(&roundabout), |_| panic!("should share the first mesh"))
+                .unwrap();
+        assert_eq!(first.0, second.0);
+        assert_eq!(known_meshes.len(), 1);
+
+        // Missing files still normalize lexically
+        let missing = dir.join("missing.gltf");
+        assert_eq!(
+            normalize_mesh_path(&missing),
+            normalize_mesh_path(&dir.join("textures").join("..").join("missing.gltf"))
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
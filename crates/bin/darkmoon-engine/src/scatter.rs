@@ -0,0 +1,193 @@
+use std::collections::HashMap;
+
+use kajiya_simple::Vec3;
+
+use crate::{math::Aabb, persisted::SceneElementTransform, rng::EditorRng};
+
+/// Where scattered copies go within the scatter box
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ScatterArea {
+    /// Anywhere inside the box
+    Volume,
+    /// On the box's bottom face
+    Ground,
+}
+
+#[derive(Clone, Debug)]
+pub struct ScatterParams {
+    pub count: u32,
+    pub area: ScatterArea,
+    pub bounds: Aabb,
+    /// Uniform scale factor range, applied on top of the source element's scale
+    pub scale_range: [f32; 2],
+    /// Random rotation of up to this many degrees either way per axis, on top of the source's
+    pub rotation_range: Vec3,
+    /// Copies are kept at least this far apart; 0 allows overlap
+    pub min_spacing: f32,
+}
+
+impl Default for ScatterParams {
+    fn default() -> Self {
+        Self {
+            count: 20,
+            area: ScatterArea::Ground,
+            bounds: Aabb::from_center_size(Vec3::ZERO, Vec3::new(20.0, 4.0, 20.0)),
+            scale_range: [0.8, 1.2],
+            rotation_range: Vec3::new(0.0, 180.0, 0.0),
+            min_spacing: 1.0,
+        }
+    }
+}
+
+/// Candidate positions tried per requested copy before giving up on `min_spacing`
+const MAX_ATTEMPTS_PER_COPY: u32 = 30;
+
+/// Placed positions bucketed into cells `min_spacing` wide, so a candidate is only checked
+/// against the copies in its own and neighboring cells
+struct SpacingGrid {
+    min_spacing: f32,
+    cells: HashMap<[i32; 3], Vec<Vec3>>,
+}
+
+impl SpacingGrid {
+    fn new(min_spacing: f32) -> Self {
+        Self {
+            min_spacing,
+            cells: HashMap::new(),
+        }
+    }
+
+    fn cell(&self, position: Vec3) -> [i32; 3] {
+        let cell = (position / self.min_spacing).floor();
+        [cell.x as i32, cell.y as i32, cell.z as i32]
+    }
+
+    fn has_room(&self, position: Vec3) -> bool {
+        if self.min_spacing <= 0.0 {
+            return true;
+        }
+
+        let [x, y, z] = self.cell(position);
+        let min_spacing_sq = self.min_spacing * self.min_spacing;
+        (-1..=1).all(|dx| {
+            (-1..=1).all(|dy| {
+                (-1..=1).all(|dz| {
+                    self.cells.get(&[x + dx, y + dy, z + dz]).map_or(true, |placed| {
+                        placed
+                            .iter()
+                            .all(|other| other.distance_squared(position) >= min_spacing_sq)
+                    })
+                })
+            })
+        })
+    }
+
+    fn insert(&mut self, position: Vec3) {
+        if self.min_spacing > 0.0 {
+            let cell = self.cell(position);
+            self.cells.entry(cell).or_default().push(position);
+        }
+    }
+}
+
+/// Transforms for copies of an element with transform `base`, randomized from `rng`.
+///
+/// May return fewer than `count` copies when `min_spacing` doesn't leave room for them all.
+pub fn scatter_transforms(
+    params: &ScatterParams,
+    base: &SceneElementTransform,
+    rng: &mut EditorRng,
+) -> Vec<SceneElementTransform> {
+    let bounds = params.bounds;
+    let [min_scale, max_scale] = params.scale_range;
+    let mut spacing = SpacingGrid::new(params.min_spacing);
+
+    let mut res: Vec<SceneElementTransform> = Vec::with_capacity(params.count as usize);
+    let mut attempts = params.count.saturating_mul(MAX_ATTEMPTS_PER_COPY);
+
+    while res.len() < params.count as usize && attempts > 0 {
+        attempts -= 1;
+
+        let position = Vec3::new(
+            rng.range(bounds.min.x, bounds.max.x),
+            match params.area {
+                ScatterArea::Volume => rng.range(bounds.min.y, bounds.max.y),
+                ScatterArea::Ground => bounds.min.y,
+            },
+            rng.range(bounds.min.z, bounds.max.z),
+        );
+
+        if !spacing.has_room(position) {
+            continue;
+        }
+        spacing.insert(position);
+
+        let rotation = Vec3::new(
+            rng.range(-1.0, 1.0),
+            rng.range(-1.0, 1.0),
+            rng.range(-1.0, 1.0),
+        ) * params.rotation_range;
+        let scale = rng.range(min_scale, max_scale.max(min_scale));
+
+        res.push(SceneElementTransform {
+            position,
+            rotation_euler_degrees: base.rotation_euler_degrees + rotation,
+            scale: base.scale * scale,
+            pivot: base.pivot,
+        });
+    }
+
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scatter_count_within_bounds() {
+        let params = ScatterParams {
+            count: 50,
+            area: ScatterArea::Volume,
+            bounds: Aabb::new(Vec3::new(-10.0, 0.0, -10.0), Vec3::new(10.0, 5.0, 10.0)),
+            scale_range: [0.5, 2.0],
+            rotation_range: Vec3::new(0.0, 90.0, 0.0),
+            min_spacing: 1.0,
+        };
+
+        let copies = scatter_transforms(&params, &SceneElementTransform::IDENTITY, &mut EditorRng::new(3));
+        assert_eq!(copies.len(), 50);
+
+        for (i, copy) in copies.iter().enumerate() {
+            assert!(params.bounds.contains_point(copy.position));
+            assert!((0.5..=2.0).contains(&copy.scale.x));
+            assert_eq!(copy.rotation_euler_degrees.x, 0.0);
+            assert!(copy.rotation_euler_degrees.y.abs() <= 90.0);
+
+            for other in &copies[i + 1..] {
+                assert!(copy.position.distance(other.position) >= 1.0);
+            }
+        }
+
+        // Seeded, so the same scatter comes out again
+        let again = scatter_transforms(&params, &SceneElementTransform::IDENTITY, &mut EditorRng::new(3));
+        assert_eq!(again, copies);
+    }
+
+    #[test]
+    fn test_scatter_on_ground_and_out_of_room() {
+        let params = ScatterParams {
+            count: 100,
+            area: ScatterArea::Ground,
+            bounds: Aabb::new(Vec3::new(0.0, 2.0, 0.0), Vec3::new(2.0, 4.0, 2.0)),
+            min_spacing: 1.5,
+            ..Default::default()
+        };
+
+        let copies = scatter_transforms(&params, &SceneElementTransform::IDENTITY, &mut EditorRng::new(0));
+
+        // Only a few copies fit 1.5 apart on a 2x2 patch
+        assert!(!copies.is_empty() && copies.len() < 10);
+        assert!(copies.iter().all(|copy| copy.position.y == 2.0));
+    }
+}
@@ -1,18 +1,310 @@
-#[derive(serde::Serialize, serde::Deserialize)]
+use std::{
+    collections::hash_map::RandomState,
+    hash::{BuildHasher, Hash, Hasher},
+    sync::atomic::{AtomicU64, Ordering},
+    time::SystemTime,
+};
+
+use kajiya::world_renderer::InstanceHandle;
+
+use crate::{
+    culling::default_true,
+    layers::Layer,
+    math::{Aabb, PotentiallyVisibleSet},
+    persisted::{MeshNode, MeshSource, SceneElement, SceneElementTransform},
+};
+
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
 pub struct SceneDesc {
     pub instances: Vec<SceneInstanceDesc>,
+    /// Baked visibility of the static elements, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pvs: Option<PotentiallyVisibleSet>,
+    /// Visibility groups; membership is by tag
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub layers: Vec<Layer>,
+    /// Seed for randomized editor tools, see `EditorRng`
+    #[serde(default)]
+    pub seed: u64,
+    /// See `SceneState::auto_bounds`
+    #[serde(default = "default_true", skip_serializing_if = "is_true")]
+    pub auto_bounds: bool,
 }
 
 fn default_instance_scale() -> [f32; 3] {
     [1.0, 1.0, 1.0]
 }
 
-#[derive(serde::Serialize, serde::Deserialize)]
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
 pub struct SceneInstanceDesc {
+    /// Stable element ID, used to match elements across versions of a scene
+    #[serde(default)]
+    pub id: Option<u64>,
     pub position: [f32; 3],
     #[serde(default = "default_instance_scale")]
     pub scale: [f32; 3],
     #[serde(default)]
     pub rotation: [f32; 3],
+    #[serde(default)]
+    pub pivot: [f32; 3],
     pub mesh: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(default = "default_true", skip_serializing_if = "is_true")]
+    pub casts_shadow: bool,
+    /// `mesh` is an already baked mesh in the VFS cache rather than a source asset
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub cached: bool,
+    /// The glTF node hierarchy found when the element was analyzed, so loading doesn't redo it
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub is_compound: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bounding_box: Option<Aabb>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub mesh_nodes: Vec<MeshNode>,
+    /// See `SceneElement::split_nodes`
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub split_nodes: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default = "default_true", skip_serializing_if = "is_true")]
+    pub visible: bool,
+    /// See `SceneElement::occluder`
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub occluder: bool,
+}
+
+impl SceneInstanceDesc {
+    /// How `element` is written to a .dmoon file
+    pub fn from_element(element: &SceneElement) -> Self {
+        let transform = &element.transform;
+        Self {
+            id: Some(element.id),
+            position: transform.position.into(),
+            scale: transform.scale.into(),
+            rotation: transform.rotation_euler_degrees.into(),
+            pivot: transform.pivot.into(),
+            mesh: vfs_mesh_path(&element.source),
+            tags: element.tags.clone(),
+            casts_shadow: element.casts_shadow,
+            cached: matches!(element.source, MeshSource::Cache(_)),
+            is_compound: element.is_compound,
+            bounding_box: element.bounding_box,
+            mesh_nodes: element.mesh_nodes.clone(),
+            split_nodes: element.split_nodes,
+            name: element.name.clone(),
+            visible: element.visible,
+            occluder: element.occluder,
+        }
+    }
+
+    pub fn transform(&self) -> SceneElementTransform {
+        SceneElementTransform {
+            position: self.position.into(),
+            rotation_euler_degrees: self.rotation.into(),
+            scale: self.scale.into(),
+            pivot: self.pivot.into(),
+        }
+    }
+
+    /// The element this describes, once its mesh has been resolved to `source` and added to
+    /// the renderer as `instance`
+    pub fn into_element(self, source: MeshSource, instance: InstanceHandle) -> SceneElement {
+        SceneElement {
+            id: self.id.unwrap_or_else(new_element_id),
+            name: self.name,
+            transform: self.transform(),
+            source,
+            instance,
+            tags: self.tags,
+            render_transform: None,
+            bounding_box: self.bounding_box,
+            bounding_sphere_cache: None,
+            mesh_nodes: self.mesh_nodes,
+            is_compound: self.is_compound,
+            split_nodes: self.split_nodes,
+            node_instances: Vec::new(),
+            casts_shadow: self.casts_shadow,
+            visible: self.visible,
+            occluder: self.occluder,
+        }
+    }
+}
+
+/// `source` as a VFS path (always starting with `/`), relative to the assets mount
+fn vfs_mesh_path(source: &MeshSource) -> String {
+    match source {
+        MeshSource::File(file_path) => {
+            let path_str = file_path.to_string_lossy();
+
+            // Absolute paths that contain "assets/", and relative ones starting with it
+            if let Some(assets_pos) = path_str.find("assets/") {
+                format!("/{}", &path_str[assets_pos + "assets/".len()..])
+            } else if path_str.starts_with('/') {
+                path_str.to_string()
+            } else {
+                format!("/{}", path_str)
+            }
+        }
+        MeshSource::Cache(cache_path) => {
+            format!("/cache/{}", cache_path.file_name().unwrap().to_string_lossy())
+        }
+    }
+}
+
+fn is_true(value: &bool) -> bool {
+    *value
+}
+
+fn is_false(value: &bool) -> bool {
+    !*value
+}
+
+/// A fresh element ID; unique within the process, and practically unique across sessions
+pub fn new_element_id() -> u64 {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+    let mut hasher = RandomState::new().build_hasher();
+    SystemTime::now().hash(&mut hasher);
+    std::process::id().hash(&mut hasher);
+    COUNTER.fetch_add(1, Ordering::Relaxed).hash(&mut hasher);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tags_round_trip() {
+        let scene = SceneDesc {
+            instances: vec![
+                SceneInstanceDesc {
+                    id: Some(1),
+                    position: [0.0, 1.0, 2.0],
+                    scale: [1.0, 1.0, 1.0],
+                    rotation: [0.0, 90.0, 0.0],
+                    pivot: [0.0, 0.0, 0.0],
+                    mesh: "/meshes/car/scene.gltf".to_string(),
+                    tags: vec!["hero".to_string(), "collision".to_string()],
+                    casts_shadow: true,
+                    visible: true,
+                    occluder: false,
+                    cached: false,
+                    is_compound: false,
+                    bounding_box: None,
+                    name: None,
+                    mesh_nodes: Vec::new(),
+                    split_nodes: false,
+                },
+                SceneInstanceDesc {
+                    id: Some(2),
+                    position: [0.0, 0.0, 0.0],
+                    scale: [1.0, 1.0, 1.0],
+                    rotation: [0.0, 0.0, 0.0],
+                    pivot: [0.0, 0.0, 0.0],
+                    mesh: "/meshes/floor/scene.gltf".to_string(),
+                    tags: Vec::new(),
+                    casts_shadow: false,
+                    visible: true,
+                    occluder: false,
+                    cached: false,
+                    is_compound: false,
+                    bounding_box: None,
+                    name: None,
+                    mesh_nodes: Vec::new(),
+                    split_nodes: false,
+                },
+            ],
+            pvs: None,
+            layers: vec![Layer::new("hero")],
+            seed: 7,
+            auto_bounds: true,
+        };
+
+        let ron = ron::ser::to_string_pretty(&scene, Default::default()).unwrap();
+        let read_back: SceneDesc = ron::de::from_str(&ron).unwrap();
+        assert_eq!(read_back, scene);
+
+        // Untagged instances don't write the field, and scenes from before tags still load
+        assert_eq!(ron.matches("tags").count(), 1);
+        let old: SceneDesc =
+            ron::de::from_str(r#"(instances: [(position: (0, 0, 0), mesh: "/meshes/rock/scene.gltf")])"#).unwrap();
+        assert!(old.instances[0].tags.is_empty());
+        assert!(old.instances[0].name.is_none());
+
+        // Only the exception to casting shadows is written
+        assert_eq!(ron.matches("casts_shadow").count(), 1);
+        assert!(old.instances[0].casts_shadow);
+
+        // Computed bounds are the default, and only turning them off is written
+        assert!(old.auto_bounds);
+        assert!(!ron.contains("auto_bounds"));
+    }
+
+    #[test]
+    fn test_saved_elements_reload_with_their_transforms() {
+        use kajiya_simple::Vec3;
+
+        let element = SceneElement {
+            tags: vec!["hero".to_string()],
+            bounding_box: Some(Aabb::new(Vec3::splat(-1.0), Vec3::splat(1.0))),
+            name: Some("Hero car".to_string()),
+            mesh_nodes: vec![
+                MeshNode {
+                    name: Some("Body".to_string()),
+                    ..Default::default()
+                },
+                MeshNode {
+                    name: Some("Wheel".to_string()),
+                    local_transform: SceneElementTransform {
+                        position: Vec3::new(0.8, 0.3, 1.2),
+                        ..SceneElementTransform::IDENTITY
+                    },
+                    bounding_box: Some(Aabb::from_center_size(Vec3::ZERO, Vec3::splat(0.6))),
+                    gltf_node: Some(2),
+                },
+            ],
+            is_compound: true,
+            split_nodes: true,
+            visible: false,
+            ..SceneElement::new(
+                42,
+                MeshSource::File("assets/meshes/car/scene.gltf".into()),
+                SceneElementTransform {
+                    position: Vec3::new(1.0, 2.0, 3.0),
+                    rotation_euler_degrees: Vec3::new(15.0, 90.0, -30.0),
+                    scale: Vec3::new(2.0, 0.5, 1.5),
+                    pivot: Vec3::new(0.0, -1.0, 0.0),
+                },
+            )
+        };
+
+        let path = std::env::temp_dir().join(format!("darkmoon_scene_round_trip_{}.dmoon", std::process::id()));
+        let scene = SceneDesc {
+            instances: vec![SceneInstanceDesc::from_element(&element)],
+            pvs: None,
+            layers: Vec::new(),
+            seed: 0,
+            auto_bounds: true,
+        };
+        ron::ser::to_writer_pretty(std::fs::File::create(&path).unwrap(), &scene, Default::default()).unwrap();
+        let read_back: SceneDesc = ron::de::from_reader(std::fs::File::open(&path).unwrap()).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let instance = read_back.instances.into_iter().next().unwrap();
+        assert_eq!(instance.mesh, "/meshes/car/scene.gltf");
+        assert!(!instance.cached);
+
+        let reloaded = instance.into_element(element.source.clone(), InstanceHandle::INVALID);
+        assert_eq!(reloaded.id, element.id);
+        assert_eq!(reloaded.name.as_deref(), Some("Hero car"));
+        assert_eq!(reloaded.transform, element.transform);
+        assert_eq!(reloaded.transform.affine_transform(), element.transform.affine_transform());
+        assert_eq!(reloaded.bounding_box, element.bounding_box);
+        assert_eq!(reloaded.mesh_nodes, element.mesh_nodes);
+        assert!(reloaded.is_compound);
+        assert!(reloaded.split_nodes);
+        assert!(!reloaded.visible);
+    }
 }
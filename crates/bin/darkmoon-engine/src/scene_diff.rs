@@ -0,0 +1,430 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+    fs::File,
+    path::Path,
+};
+
+use anyhow::Context;
+
+use crate::scene::{SceneDesc, SceneInstanceDesc};
+
+// Transform components closer than this are considered unchanged (RON round-trips aren't exact)
+const TRANSFORM_EPSILON: f32 = 1e-5;
+
+/// How an instance is matched between two scenes
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ElementKey {
+    Id(u64),
+    /// Instances saved before stable IDs existed: the n-th ID-less instance of a mesh
+    Unnamed { mesh: String, occurrence: usize },
+}
+
+impl fmt::Display for ElementKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Id(id) => write!(f, "#{:016x}", id),
+            Self::Unnamed { mesh, occurrence } => write!(f, "{} [{}]", mesh, occurrence),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ElementChange {
+    pub key: ElementKey,
+    pub before: SceneInstanceDesc,
+    pub after: SceneInstanceDesc,
+}
+
+/// Differences going from a base scene to another one
+#[derive(Clone, Debug, Default)]
+pub struct SceneDiff {
+    pub added: Vec<(ElementKey, SceneInstanceDesc)>,
+    pub removed: Vec<(ElementKey, SceneInstanceDesc)>,
+    pub changed: Vec<ElementChange>,
+}
+
+impl SceneDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+impl fmt::Display for SceneDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{} added, {} removed, {} changed",
+            self.added.len(),
+            self.removed.len(),
+            self.changed.len()
+        )?;
+
+        for (key, inst) in &self.added {
+            writeln!(f, "  + {} {}", key, inst.mesh)?;
+        }
+        for (key, inst) in &self.removed {
+            writeln!(f, "  - {} {}", key, inst.mesh)?;
+        }
+        for change in &self.changed {
+            writeln!(f, "  ~ {} {}", change.key, change.after.mesh)?;
+            if change.before.mesh != change.after.mesh {
+                writeln!(f, "      mesh: {} -> {}", change.before.mesh, change.after.mesh)?;
+            }
+            if change.before.tags != change.after.tags {
+                writeln!(f, "      tags: {:?} -> {:?}", change.before.tags, change.after.tags)?;
+            }
+            if change.before.casts_shadow != change.after.casts_shadow {
+                writeln!(f, "      casts_shadow: {} -> {}", change.before.casts_shadow, change.after.casts_shadow)?;
+            }
+            if change.before.visible != change.after.visible {
+                writeln!(f, "      visible: {} -> {}", change.before.visible, change.after.visible)?;
+            }
+            if change.before.occluder != change.after.occluder {
+                writeln!(f, "      occluder: {} -> {}", change.before.occluder, change.after.occluder)?;
+            }
+            if change.before.split_nodes != change.after.split_nodes || change.before.mesh_nodes != change.after.mesh_nodes {
+                writeln!(f, "      mesh nodes changed")?;
+            }
+            for (name, before, after) in [
+                ("position", change.before.position, change.after.position),
+                ("rotation", change.before.rotation, change.after.rotation),
+                ("scale", change.before.scale, change.after.scale),
+                ("pivot", change.before.pivot, change.after.pivot),
+            ] {
+                if !approx_eq(before, after) {
+                    writeln!(f, "      {}: {:?} -> {:?}", name, before, after)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// An element both sides of a merge changed, differently. `None` means that side removed it.
+#[derive(Clone, Debug)]
+pub struct MergeConflict {
+    pub key: ElementKey,
+    pub ours: Option<SceneInstanceDesc>,
+    pub theirs: Option<SceneInstanceDesc>,
+}
+
+/// Result of merging two scenes; conflicting elements keep `ours`'s version
+pub struct SceneMerge {
+    pub scene: SceneDesc,
+    pub conflicts: Vec<MergeConflict>,
+}
+
+pub fn load_scene_desc(path: &Path) -> anyhow::Result<SceneDesc> {
+    let file = File::open(path).with_context(|| format!("Opening scene file {:?}", path))?;
+    ron::de::from_reader(file).with_context(|| format!("Parsing scene file {:?}", path))
+}
+
+pub fn diff_scenes(base: &SceneDesc, other: &SceneDesc) -> SceneDiff {
+    let base_keyed = keyed_instances(base);
+    let other_keyed = keyed_instances(other);
+    let other_by_key: HashMap<&ElementKey, &SceneInstanceDesc> =
+        other_keyed.iter().map(|(key, inst)| (key, *inst)).collect();
+    let base_by_key: HashMap<&ElementKey, &SceneInstanceDesc> =
+        base_keyed.iter().map(|(key, inst)| (key, *inst)).collect();
+
+    let mut diff = SceneDiff::default();
+
+    for (key, before) in &base_keyed {
+        match other_by_key.get(key) {
+            Some(after) if !same_instance(before, after) => diff.changed.push(ElementChange {
+                key: key.clone(),
+                before: (*before).clone(),
+                after: (*after).clone(),
+            }),
+            Some(_) => {}
+            None => diff.removed.push((key.clone(), (*before).clone())),
+        }
+    }
+
+    for (key, after) in &other_keyed {
+        if !base_by_key.contains_key(key) {
+            diff.added.push((key.clone(), (*after).clone()));
+        }
+    }
+
+    diff
+}
+
+/// Three-way merge of `ours` and `theirs`, both edited from `ancestor`. Additions, changes and
+/// removals made on one side only are applied; elements both sides changed differently are
+/// conflicts, and keep `ours`'s version.
+pub fn merge_scenes(ancestor: &SceneDesc, ours: &SceneDesc, theirs: &SceneDesc) -> SceneMerge {
+    let our_diff = diff_scenes(ancestor, ours);
+    let their_diff = diff_scenes(ancestor, theirs);
+
+    let our_changes: HashMap<&ElementKey, &SceneInstanceDesc> =
+        our_diff.changed.iter().map(|change| (&change.key, &change.after)).collect();
+    let our_removals: HashSet<&ElementKey> = our_diff.removed.iter().map(|(key, _)| key).collect();
+    let our_additions: HashMap<&ElementKey, &SceneInstanceDesc> =
+        our_diff.added.iter().map(|(key, inst)| (key, inst)).collect();
+
+    let mut conflicts = Vec::new();
+    let mut replaced: HashMap<ElementKey, SceneInstanceDesc> = HashMap::new();
+    let mut removed: HashSet<ElementKey> = HashSet::new();
+
+    for change in their_diff.changed {
+        if let Some(ours) = our_changes.get(&change.key) {
+            if !same_instance(ours, &change.after) {
+                conflicts.push(MergeConflict {
+                    key: change.key,
+                    ours: Some((*ours).clone()),
+                    theirs: Some(change.after),
+                });
+            }
+        } else if our_removals.contains(&change.key) {
+            conflicts.push(MergeConflict {
+                key: change.key,
+                ours: None,
+                theirs: Some(change.after),
+            });
+        } else {
+            replaced.insert(change.key, change.after);
+        }
+    }
+
+    for (key, _) in their_diff.removed {
+        match our_changes.get(&key) {
+            Some(ours) => conflicts.push(MergeConflict {
+                key,
+                ours: Some((*ours).clone()),
+                theirs: None,
+            }),
+            None => {
+                removed.insert(key);
+            }
+        }
+    }
+
+    let mut instances: Vec<SceneInstanceDesc> = keyed_instances(ours)
+        .into_iter()
+        .filter(|(key, _)| !removed.contains(key))
+        .map(|(key, inst)| replaced.remove(&key).unwrap_or_else(|| inst.clone()))
+        .collect();
+
+    for (key, inst) in their_diff.added {
+        match our_additions.get(&key) {
+            Some(ours) if same_instance(ours, &inst) => {}
+            Some(ours) => conflicts.push(MergeConflict {
+                key,
+                ours: Some((*ours).clone()),
+                theirs: Some(inst),
+            }),
+            None => instances.push(inst),
+        }
+    }
+
+    let mut layers = ours.layers.clone();
+    for layer in &theirs.layers {
+        crate::layers::add_layer(&mut layers, layer.clone());
+    }
+
+    SceneMerge {
+        // Elements added or moved by `theirs` don't match the baked set and get culled dynamically
+        scene: SceneDesc {
+            instances,
+            pvs: ours.pvs.clone(),
+            layers,
+            seed: ours.seed,
+            auto_bounds: ours.auto_bounds,
+        },
+        conflicts,
+    }
+}
+
+fn keyed_instances(scene: &SceneDesc) -> Vec<(ElementKey, &SceneInstanceDesc)> {
+    let mut occurrences: HashMap<&str, usize> = HashMap::new();
+
+    scene
+        .instances
+        .iter()
+        .map(|inst| {
+            let key = match inst.id {
+                Some(id) => ElementKey::Id(id),
+                None => {
+                    let occurrence = occurrences.entry(inst.mesh.as_str()).or_default();
+                    let key = ElementKey::Unnamed {
+                        mesh: inst.mesh.clone(),
+                        occurrence: *occurrence,
+                    };
+                    *occurrence += 1;
+                    key
+                }
+            };
+            (key, inst)
+        })
+        .collect()
+}
+
+fn same_instance(a: &SceneInstanceDesc, b: &SceneInstanceDesc) -> bool {
+    a.mesh == b.mesh
+        && a.tags == b.tags
+        && a.casts_shadow == b.casts_shadow
+        && a.visible == b.visible
+        && a.occluder == b.occluder
+        && a.split_nodes == b.split_nodes
+        && a.mesh_nodes == b.mesh_nodes
+        && approx_eq(a.position, b.position)
+        && approx_eq(a.rotation, b.rotation)
+        && approx_eq(a.scale, b.scale)
+        && approx_eq(a.pivot, b.pivot)
+}
+
+fn approx_eq(a: [f32; 3], b: [f32; 3]) -> bool {
+    a.iter()
+        .zip(b.iter())
+        .all(|(a, b)| (a - b).abs() <= TRANSFORM_EPSILON)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn instance(id: Option<u64>, mesh: &str, position: [f32; 3]) -> SceneInstanceDesc {
+        SceneInstanceDesc {
+            id,
+            position,
+            scale: [1.0, 1.0, 1.0],
+            rotation: [0.0, 0.0, 0.0],
+            pivot: [0.0, 0.0, 0.0],
+            mesh: mesh.to_string(),
+            tags: Vec::new(),
+            casts_shadow: true,
+            visible: true,
+            occluder: false,
+            cached: false,
+            is_compound: false,
+            bounding_box: None,
+            name: None,
+            mesh_nodes: Vec::new(),
+            split_nodes: false,
+        }
+    }
+
+    #[test]
+    fn test_diff_and_merge_small_scenes() {
+        let base = SceneDesc {
+            instances: vec![
+                instance(Some(1), "/meshes/floor/scene.gltf", [0.0, 0.0, 0.0]),
+                instance(Some(2), "/meshes/car/scene.gltf", [1.0, 0.0, 0.0]),
+                instance(Some(3), "/meshes/lamp/scene.gltf", [5.0, 0.0, 0.0]),
+                instance(None, "/meshes/rock/scene.gltf", [0.0, 0.0, 2.0]),
+            ],
+            pvs: None,
+            layers: Vec::new(),
+            seed: 0,
+            auto_bounds: true,
+        };
+        let other = SceneDesc {
+            instances: vec![
+                instance(Some(1), "/meshes/floor/scene.gltf", [0.0, 0.0, 0.000001]),
+                instance(Some(2), "/meshes/car/scene.gltf", [3.0, 0.0, 0.0]),
+                instance(Some(4), "/meshes/tree/scene.gltf", [0.0, 0.0, -4.0]),
+                instance(None, "/meshes/rock/scene.gltf", [0.0, 0.0, 2.0]),
+            ],
+            pvs: None,
+            layers: Vec::new(),
+            seed: 0,
+            auto_bounds: true,
+        };
+
+        let diff = diff_scenes(&base, &other);
+        assert_eq!(
+            diff.added.iter().map(|(key, _)| key.clone()).collect::<Vec<_>>(),
+            vec![ElementKey::Id(4)]
+        );
+        assert_eq!(
+            diff.removed.iter().map(|(key, _)| key.clone()).collect::<Vec<_>>(),
+            vec![ElementKey::Id(3)]
+        );
+        // Element 1 only moved by float noise, and the ID-less rock matches by mesh
+        assert_eq!(diff.changed.len(), 1);
+        assert_eq!(diff.changed[0].key, ElementKey::Id(2));
+        assert_eq!(diff.changed[0].after.position, [3.0, 0.0, 0.0]);
+
+        assert!(diff_scenes(&base, &base).is_empty());
+    }
+
+    fn scene(instances: Vec<SceneInstanceDesc>) -> SceneDesc {
+        SceneDesc {
+            instances,
+            pvs: None,
+            layers: Vec::new(),
+            seed: 0,
+            auto_bounds: true,
+        }
+    }
+
+    #[test]
+    fn test_merge_applies_edits_from_both_sides() {
+        let ancestor = scene(vec![
+            instance(Some(1), "/meshes/floor/scene.gltf", [0.0, 0.0, 0.0]),
+            instance(Some(2), "/meshes/car/scene.gltf", [1.0, 0.0, 0.0]),
+            instance(Some(3), "/meshes/lamp/scene.gltf", [5.0, 0.0, 0.0]),
+            instance(Some(4), "/meshes/rock/scene.gltf", [0.0, 0.0, 2.0]),
+        ]);
+        // We move the car and remove the lamp
+        let ours = scene(vec![
+            instance(Some(1), "/meshes/floor/scene.gltf", [0.0, 0.0, 0.0]),
+            instance(Some(2), "/meshes/car/scene.gltf", [3.0, 0.0, 0.0]),
+            instance(Some(4), "/meshes/rock/scene.gltf", [0.0, 0.0, 2.0]),
+        ]);
+        // They move the floor, remove the rock and add a tree
+        let theirs = scene(vec![
+            instance(Some(1), "/meshes/floor/scene.gltf", [0.0, -1.0, 0.0]),
+            instance(Some(2), "/meshes/car/scene.gltf", [1.0, 0.0, 0.0]),
+            instance(Some(3), "/meshes/lamp/scene.gltf", [5.0, 0.0, 0.0]),
+            instance(Some(5), "/meshes/tree/scene.gltf", [0.0, 0.0, -4.0]),
+        ]);
+
+        let merge = merge_scenes(&ancestor, &ours, &theirs);
+        assert!(merge.conflicts.is_empty());
+
+        let merged: Vec<_> = merge.scene.instances.iter().map(|inst| (inst.id, inst.position)).collect();
+        assert_eq!(
+            merged,
+            vec![
+                (Some(1), [0.0, -1.0, 0.0]),
+                (Some(2), [3.0, 0.0, 0.0]),
+                (Some(5), [0.0, 0.0, -4.0]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_merge_conflicts_keep_ours() {
+        let ancestor = scene(vec![
+            instance(Some(1), "/meshes/floor/scene.gltf", [0.0, 0.0, 0.0]),
+            instance(Some(2), "/meshes/car/scene.gltf", [1.0, 0.0, 0.0]),
+            instance(Some(3), "/meshes/lamp/scene.gltf", [5.0, 0.0, 0.0]),
+        ]);
+        // Both move the floor, differently; we move the car they remove; we remove the lamp they move
+        let ours = scene(vec![
+            instance(Some(1), "/meshes/floor/scene.gltf", [0.0, 1.0, 0.0]),
+            instance(Some(2), "/meshes/car/scene.gltf", [2.0, 0.0, 0.0]),
+        ]);
+        let theirs = scene(vec![
+            instance(Some(1), "/meshes/floor/scene.gltf", [0.0, -1.0, 0.0]),
+            instance(Some(3), "/meshes/lamp/scene.gltf", [6.0, 0.0, 0.0]),
+        ]);
+
+        let merge = merge_scenes(&ancestor, &ours, &theirs);
+        let conflicts: Vec<_> = merge.conflicts.iter().map(|c| c.key.clone()).collect();
+        assert_eq!(conflicts.len(), 3);
+        for id in 1..=3 {
+            assert!(conflicts.contains(&ElementKey::Id(id)));
+        }
+
+        let merged: Vec<_> = merge.scene.instances.iter().map(|inst| (inst.id, inst.position)).collect();
+        assert_eq!(merged, vec![(Some(1), [0.0, 1.0, 0.0]), (Some(2), [2.0, 0.0, 0.0])]);
+
+        // The same edit on both sides isn't a conflict
+        assert!(merge_scenes(&ancestor, &ours, &ours).conflicts.is_empty());
+    }
+}
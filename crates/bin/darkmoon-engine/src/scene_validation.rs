@@ -0,0 +1,230 @@
+use std::collections::HashSet;
+
+use kajiya::world_renderer::InstanceHandle;
+use kajiya_simple::Mat4;
+
+use crate::{math::Aabb, persisted::SceneElement};
+
+/// How far apart matching box corners may be, relative to the box diagonal, for two elements
+/// to count as placed in the same spot
+const DUPLICATE_TOLERANCE: f32 = 0.01;
+
+/// An element that repeats an earlier one: same source, near-identical placement
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DuplicateElement {
+    pub id: u64,
+    /// The first element it duplicates, which is the one to keep
+    pub duplicate_of: u64,
+}
+
+/// Finds elements stacked on top of an identical one, e.g. a model dropped twice at the origin
+pub fn find_duplicates(elements: &[SceneElement], default_object_size: f32) -> Vec<DuplicateElement> {
+    let world_bounds: Vec<Aabb> = elements
+        .iter()
+        .map(|elem| {
+            elem.local_bounds(default_object_size)
+                .transform(&Mat4::from(elem.transform.affine_transform()))
+        })
+        .collect();
+
+    let mut duplicates: Vec<DuplicateElement> = Vec::new();
+    for (j, elem) in elements.iter().enumerate() {
+        let original = (0..j).find(|&i| {
+            // Chains of duplicates all point at the first element
+            let is_original = duplicates.iter().all(|dup| dup.id != elements[i].id);
+            is_original
+                && elements[i].source == elem.source
+                && boxes_coincide(&world_bounds[i], &world_bounds[j])
+        });
+
+        if let Some(i) = original {
+            duplicates.push(DuplicateElement {
+                id: elem.id,
+                duplicate_of: elements[i].id,
+            });
+        }
+    }
+
+    duplicates
+}
+
+fn boxes_coincide(a: &Aabb, b: &Aabb) -> bool {
+    let tolerance = a.size().length().max(b.size().length()) * DUPLICATE_TOLERANCE;
+    a.intersects(b) && a.min.distance(b.min) <= tolerance && a.max.distance(b.max) <= tolerance
+}
+
+/// Where the scene's elements and the renderer's instances disagree, e.g. after a load that
+/// failed partway
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct InstanceMismatch {
+    /// Renderer instances no intact element refers to; they're drawn but can't be selected or
+    /// removed
+    pub orphan_instances: Vec<InstanceHandle>,
+    /// Indices of elements missing any of their instances from the renderer, or sharing one
+    /// with an earlier element; editing or removing them would touch the wrong instance or panic
+    pub dead_elements: Vec<usize>,
+}
+
+impl InstanceMismatch {
+    pub fn is_empty(&self) -> bool {
+        self.orphan_instances.is_empty() && self.dead_elements.is_empty()
+    }
+}
+
+/// Compares the elements' instance handles against the renderer's `live_instances`
+pub fn find_instance_mismatches(elements: &[SceneElement], live_instances: &[InstanceHandle]) -> InstanceMismatch {
+    let live: HashSet<InstanceHandle> = live_instances.iter().copied().collect();
+    let mut claimed: HashSet<InstanceHandle> = HashSet::new();
+
+    let dead_elements = elements
+        .iter()
+        .enumerate()
+        .filter(|(_, elem)| {
+            let instances = elem.instances();
+            let dead = instances.is_empty()
+                || instances.iter().any(|instance| !live.contains(instance) || claimed.contains(instance));
+            // The live instances of a dead element are left unclaimed, and so removed as orphans
+            // before it gets new ones
+            if !dead {
+                claimed.extend(instances.iter().copied());
+            }
+            dead
+        })
+        .map(|(idx, _)| idx)
+        .collect();
+
+    let orphan_instances = live_instances
+        .iter()
+        .copied()
+        .filter(|instance| !claimed.contains(instance))
+        .collect();
+
+    InstanceMismatch {
+        orphan_instances,
+        dead_elements,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{
+        persisted::MeshSource,
+        scene::SceneDesc,
+    };
+
+    // Two copies of the same model dropped at the origin, a copy placed elsewhere,
+    // and a different model in the same spot
+    const DUPLICATE_SCENE: &str = r#"(
+        instances: [
+            (id: Some(1), position: (0, 0, 0), mesh: "/meshes/car/scene.gltf"),
+            (id: Some(2), position: (0, 0.001, 0), mesh: "/meshes/car/scene.gltf"),
+            (id: Some(3), position: (5, 0, 0), mesh: "/meshes/car/scene.gltf"),
+            (id: Some(4), position: (0, 0, 0), mesh: "/meshes/tree/scene.gltf"),
+        ]
+    )"#;
+
+    fn elements_from_scene(scene: &SceneDesc) -> Vec<SceneElement> {
+        scene
+            .instances
+            .iter()
+            .map(|instance| SceneElement {
+                tags: instance.tags.clone(),
+                casts_shadow: instance.casts_shadow,
+                visible: instance.visible,
+                ..SceneElement::new(
+                    instance.id.unwrap(),
+                    MeshSource::File(instance.mesh.clone().into()),
+                    instance.transform(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_finds_duplicate_pair() {
+        let scene: SceneDesc = ron::de::from_str(DUPLICATE_SCENE).unwrap();
+        let elements = elements_from_scene(&scene);
+
+        assert_eq!(
+            find_duplicates(&elements, 1.0),
+            vec![DuplicateElement {
+                id: 2,
+                duplicate_of: 1
+            }]
+        );
+    }
+
+    #[test]
+    fn test_triple_points_at_first_copy() {
+        let scene: SceneDesc = ron::de::from_str(DUPLICATE_SCENE).unwrap();
+        let mut elements = elements_from_scene(&scene);
+        let mut third = elements[0].clone();
+        third.id = 5;
+        elements.push(third);
+
+        let duplicates = find_duplicates(&elements, 1.0);
+        assert_eq!(duplicates.len(), 2);
+        assert!(duplicates.iter().all(|dup| dup.duplicate_of == 1));
+    }
+
+    #[test]
+    fn test_instance_mismatch_finds_orphans_and_dead_elements() {
+        let scene: SceneDesc = ron::de::from_str(DUPLICATE_SCENE).unwrap();
+        let mut elements = elements_from_scene(&scene);
+        for (i, elem) in elements.iter_mut().enumerate() {
+            elem.instance = InstanceHandle(i);
+        }
+        let live: Vec<InstanceHandle> = (0..elements.len()).map(InstanceHandle).collect();
+        assert!(find_instance_mismatches(&elements, &live).is_empty());
+
+        // A load that failed after adding instance 7, and an element left pointing at a removed
+        // instance and another sharing one
+        let mut live = live;
+        live.push(InstanceHandle(7));
+        live.retain(|&instance| instance != InstanceHandle(1));
+        elements[3].instance = InstanceHandle(2);
+
+        assert_eq!(
+            find_instance_mismatches(&elements, &live),
+            InstanceMismatch {
+                orphan_instances: vec![InstanceHandle(3), InstanceHandle(7)],
+                dead_elements: vec![1, 3],
+            }
+        );
+    }
+
+    #[test]
+    fn test_split_elements_claim_their_node_instances() {
+        let scene: SceneDesc = ron::de::from_str(DUPLICATE_SCENE).unwrap();
+        let mut elements = elements_from_scene(&scene);
+        elements.truncate(2);
+        elements[0].instance = InstanceHandle(0);
+        elements[1].split_nodes = true;
+        elements[1].node_instances = vec![InstanceHandle(1), InstanceHandle(2)];
+        let live: Vec<InstanceHandle> = (0..3).map(InstanceHandle).collect();
+        assert!(find_instance_mismatches(&elements, &live).is_empty());
+
+        // Split, but its node instances weren't added yet
+        let mut unsplit = elements.clone();
+        unsplit[1].node_instances.clear();
+        assert_eq!(
+            find_instance_mismatches(&unsplit, &live),
+            InstanceMismatch {
+                orphan_instances: vec![InstanceHandle(1), InstanceHandle(2)],
+                dead_elements: vec![1],
+            }
+        );
+
+        // One node instance was removed; the other goes too, so the node instances can be redone
+        let live = vec![InstanceHandle(0), InstanceHandle(2)];
+        assert_eq!(
+            find_instance_mismatches(&elements, &live),
+            InstanceMismatch {
+                orphan_instances: vec![InstanceHandle(2)],
+                dead_elements: vec![1],
+            }
+        );
+    }
+}
@@ -0,0 +1,442 @@
+use imgui::{DrawListMut, ImColor32};
+use kajiya_simple::{Mat4, Vec2, Vec3, Vec4};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    layers::{hidden_by_layers, Layer},
+    math::{clip_edge, Aabb, Ray, AABB_EDGES, MIN_CLIP_W},
+    persisted::SceneElement,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SelectionHighlightStyle {
+    /// Outline of the element's bounding box
+    Wireframe,
+    /// Translucent fill of the bounding box faces, plus the outline
+    Tint,
+}
+
+impl Default for SelectionHighlightStyle {
+    fn default() -> Self {
+        Self::Wireframe
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SelectionHighlightConfig {
+    pub enabled: bool,
+    pub style: SelectionHighlightStyle,
+    pub color: [f32; 4],
+    pub tint_opacity: f32,
+}
+
+impl Default for SelectionHighlightConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            style: SelectionHighlightStyle::default(),
+            color: [1.0, 0.6, 0.1, 1.0],
+            tint_opacity: 0.2,
+        }
+    }
+}
+
+/// What's selected in the Outliner and shown in the Attributes window
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SelectionTarget {
+    Sun,
+    /// Index into `SceneState::elements`
+    Element(usize),
+}
+
+impl SelectionTarget {
+    pub fn element_index(self) -> Option<usize> {
+        match self {
+            SelectionTarget::Sun => None,
+            SelectionTarget::Element(idx) => Some(idx),
+        }
+    }
+
+    /// The same selection once the element at `removed` is gone: later elements move down
+    /// an index, and the removed element itself can't stay selected
+    pub fn after_removal(self, removed: usize) -> Option<Self> {
+        match self {
+            SelectionTarget::Element(idx) if idx == removed => None,
+            SelectionTarget::Element(idx) if idx > removed => Some(SelectionTarget::Element(idx - 1)),
+            other => Some(other),
+        }
+    }
+}
+
+/// Add `idx` to a sorted multi-selection, or take it out if it's already in
+pub fn toggle_in_selection(selection: &mut Vec<usize>, idx: usize) {
+    match selection.binary_search(&idx) {
+        Ok(pos) => {
+            selection.remove(pos);
+        }
+        Err(pos) => selection.insert(pos, idx),
+    }
+}
+
+/// The rows between `anchor` and `clicked` inclusive, in the order they're listed, as a sorted
+/// selection. Just `clicked` if the anchor isn't listed, e.g. because it's filtered out.
+pub fn range_selection(rows: &[usize], anchor: usize, clicked: usize) -> Vec<usize> {
+    let mut selection = match (
+        rows.iter().position(|&row| row == anchor),
+        rows.iter().position(|&row| row == clicked),
+    ) {
+        (Some(a), Some(b)) => rows[a.min(b)..=a.max(b)].to_vec(),
+        _ => vec![clicked],
+    };
+    selection.sort_unstable();
+    selection
+}
+
+/// Update a sorted multi-selection for an Outliner click on `idx`: Ctrl toggles it, Shift takes
+/// the `rows` from the `active` element to it, and a plain click selects only it. Returns the new
+/// active element.
+pub fn click_selection(
+    selection: &mut Vec<usize>,
+    active: Option<usize>,
+    rows: &[usize],
+    idx: usize,
+    ctrl: bool,
+    shift: bool,
+) -> Option<usize> {
+    if ctrl {
+        toggle_in_selection(selection, idx);
+        if selection.contains(&idx) {
+            Some(idx)
+        } else {
+            active.filter(|active| selection.contains(active)).or_else(|| selection.last().copied())
+        }
+    } else if let (true, Some(anchor)) = (shift, active) {
+        // The anchor stays active, so further Shift clicks extend from the same row
+        *selection = range_selection(rows, anchor, idx);
+        Some(if selection.contains(&anchor) { anchor } else { idx })
+    } else {
+        *selection = vec![idx];
+        Some(idx)
+    }
+}
+
+/// Byte range of the first case-insensitive occurrence of `query` in an Outliner label
+pub fn find_ignore_case(label: &str, query: &str) -> Option<std::ops::Range<usize>> {
+    label.char_indices().map(|(start, _)| start).chain([label.len()]).find_map(|start| {
+        let mut end = start;
+        let mut rest = label[start..].chars();
+        for q in query.chars() {
+            let c = rest.next().filter(|c| c.to_lowercase().eq(q.to_lowercase()))?;
+            end += c.len_utf8();
+        }
+        Some(start..end)
+    })
+}
+
+/// `selection` once the element at `removed` is gone, like `SelectionTarget::after_removal`
+pub fn selection_after_removal(selection: &mut Vec<usize>, removed: usize) {
+    selection.retain(|&idx| idx != removed);
+    for idx in selection.iter_mut() {
+        if *idx > removed {
+            *idx -= 1;
+        }
+    }
+}
+
+/// Index of the element whose box `ray` hits first, and for compound elements the node that was
+/// hit, so clicking in the viewport can select sub-objects. Elements in hidden layers are skipped.
+pub fn pick_element(
+    ray: &Ray,
+    elements: &[SceneElement],
+    layers: &[Layer],
+    default_object_size: f32,
+) -> Option<(usize, Option<usize>)> {
+    let mut nearest: Option<(f32, usize, Option<usize>)> = None;
+
+    for (idx, elem) in elements.iter().enumerate() {
+        if !elem.visible || hidden_by_layers(&elem.tags, layers) {
+            continue;
+        }
+
+        let elem_transform = elem.transform.affine_transform();
+        let hit = if elem.is_compound && elem.mesh_nodes.iter().any(|node| node.bounding_box.is_some()) {
+            elem.mesh_nodes
+                .iter()
+                .enumerate()
+                .filter_map(|(nidx, node)| {
+                    let transform = elem_transform * node.local_transform.affine_transform();
+                    let world_aabb = node.bounding_box?.transform(&Mat4::from(transform));
+                    Some((world_aabb.ray_intersection(ray.origin, ray.direction)?, Some(nidx)))
+                })
+                .min_by(|a, b| a.0.total_cmp(&b.0))
+        } else {
+            elem.world_bounds(default_object_size)
+                .ray_intersection(ray.origin, ray.direction)
+                .map(|t| (t, None))
+        };
+
+        if let Some((t, node)) = hit {
+            if nearest.map_or(true, |(nearest_t, _, _)| t < nearest_t) {
+                nearest = Some((t, idx, node));
+            }
+        }
+    }
+
+    nearest.map(|(_, idx, node)| (idx, node))
+}
+
+/// A stable, saturated color for an element, for telling instances apart at a glance
+pub fn debug_color_for_id(id: u64) -> [f32; 4] {
+    // Mixed, so neighboring IDs land on unrelated hues
+    let x = crate::rng::mix64(id.wrapping_add(0x9e3779b97f4a7c15));
+
+    let hue = (x >> 40) as f32 / (1u64 << 24) as f32;
+    let [r, g, b] = hsv_to_rgb(hue, 0.75, 1.0);
+    [r, g, b, 1.0]
+}
+
+fn hsv_to_rgb(h: f32, s: f32, v: f32) -> [f32; 3] {
+    let h = h.fract() * 6.0;
+    let c = v * s;
+    let x = c * (1.0 - ((h % 2.0) - 1.0).abs());
+    let m = v - c;
+
+    let [r, g, b] = match h as u32 {
+        0 => [c, x, 0.0],
+        1 => [x, c, 0.0],
+        2 => [0.0, c, x],
+        3 => [0.0, x, c],
+        4 => [x, 0.0, c],
+        _ => [c, 0.0, x],
+    };
+    [r + m, g + m, b + m]
+}
+
+// Box faces as indices into `Aabb::corners`, wound consistently
+const AABB_FACES: [[usize; 4]; 6] = [
+    [0, 1, 3, 2],
+    [4, 6, 7, 5],
+    [0, 4, 5, 1],
+    [2, 3, 7, 6],
+    [0, 2, 6, 4],
+    [1, 5, 7, 3],
+];
+
+/// Draws `local_aabb`, placed in the world by `transform`, on top of the viewport
+pub fn draw_selection_highlight(
+    draw_list: &DrawListMut,
+    config: &SelectionHighlightConfig,
+    local_aabb: &Aabb,
+    transform: &Mat4,
+    view_proj: &Mat4,
+    display_size: [f32; 2],
+) {
+    let display_size = Vec2::from(display_size);
+    let clip_corners = local_aabb
+        .corners()
+        .map(|corner| *view_proj * transform.transform_point3(corner).extend(1.0));
+
+    let [r, g, b, a] = config.color;
+    let line_color = ImColor32::from_rgba_f32s(r, g, b, a);
+
+    if config.style == SelectionHighlightStyle::Tint {
+        let fill_color = ImColor32::from_rgba_f32s(r, g, b, a * config.tint_opacity);
+
+        for face in AABB_FACES {
+            // Faces crossing the camera plane are skipped rather than clipped
+            if face.iter().any(|&i| clip_corners[i].w < MIN_CLIP_W) {
+                continue;
+            }
+
+            let [p0, p1, p2, p3] = face.map(|i| clip_to_screen(clip_corners[i], display_size));
+            draw_list
+                .add_triangle(p0, p1, p2, fill_color)
+                .filled(true)
+                .build();
+            draw_list
+                .add_triangle(p0, p2, p3, fill_color)
+                .filled(true)
+                .build();
+        }
+    }
+
+    for (i0, i1) in AABB_EDGES {
+        if let Some((p0, p1)) = clip_edge(clip_corners[i0], clip_corners[i1]) {
+            draw_list
+                .add_line(
+                    clip_to_screen(p0, display_size),
+                    clip_to_screen(p1, display_size),
+                    line_color,
+                )
+                .thickness(2.0)
+                .build();
+        }
+    }
+}
+
+/// Draws a world-space line strip on top of the viewport
+pub fn draw_world_polyline(
+    draw_list: &DrawListMut,
+    points: &[Vec3],
+    color: [f32; 4],
+    thickness: f32,
+    view_proj: &Mat4,
+    display_size: [f32; 2],
+) {
+    let display_size = Vec2::from(display_size);
+    let color = ImColor32::from_rgba_f32s(color[0], color[1], color[2], color[3]);
+
+    for segment in points.windows(2) {
+        let clip0 = *view_proj * segment[0].extend(1.0);
+        let clip1 = *view_proj * segment[1].extend(1.0);
+        if let Some((p0, p1)) = clip_edge(clip0, clip1) {
+            draw_list
+                .add_line(
+                    clip_to_screen(p0, display_size),
+                    clip_to_screen(p1, display_size),
+                    color,
+                )
+                .thickness(thickness)
+                .build();
+        }
+    }
+}
+
+/// Draws a filled circle at a world-space point, unless it's behind the camera
+pub fn draw_world_marker(
+    draw_list: &DrawListMut,
+    point: Vec3,
+    radius: f32,
+    color: [f32; 4],
+    view_proj: &Mat4,
+    display_size: [f32; 2],
+) {
+    let clip = *view_proj * point.extend(1.0);
+    if clip.w < MIN_CLIP_W {
+        return;
+    }
+
+    let color = ImColor32::from_rgba_f32s(color[0], color[1], color[2], color[3]);
+    draw_list
+        .add_circle(clip_to_screen(clip, Vec2::from(display_size)), radius, color)
+        .filled(true)
+        .build();
+}
+
+fn clip_to_screen(clip: Vec4, display_size: Vec2) -> [f32; 2] {
+    let ndc = clip / clip.w;
+    [
+        (ndc.x + 1.0) * 0.5 * display_size.x,
+        (1.0 - ndc.y) * 0.5 * display_size.y, // Flip Y
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_debug_color_is_stable_per_id() {
+        assert_eq!(debug_color_for_id(42), debug_color_for_id(42));
+        assert_ne!(debug_color_for_id(1), debug_color_for_id(2));
+
+        for id in 0..64 {
+            let color = debug_color_for_id(id);
+            assert!(color.iter().all(|c| (0.0..=1.0).contains(c)));
+            assert_eq!(color[3], 1.0);
+        }
+    }
+
+    #[test]
+    fn test_multi_selection_toggle_and_range() {
+        let mut selection = vec![2];
+        toggle_in_selection(&mut selection, 0);
+        toggle_in_selection(&mut selection, 5);
+        assert_eq!(selection, vec![0, 2, 5]);
+        toggle_in_selection(&mut selection, 2);
+        assert_eq!(selection, vec![0, 5]);
+
+        // Rows as listed in a filtered Outliner
+        let rows = [7, 1, 4, 3, 9];
+        assert_eq!(range_selection(&rows, 4, 9), vec![3, 4, 9]);
+        assert_eq!(range_selection(&rows, 4, 7), vec![1, 4, 7]);
+        assert_eq!(range_selection(&rows, 2, 3), vec![3]);
+
+        let mut selection = vec![4];
+        assert_eq!(click_selection(&mut selection, Some(4), &rows, 9, false, true), Some(4));
+        assert_eq!(selection, vec![3, 4, 9]);
+        assert_eq!(click_selection(&mut selection, Some(4), &rows, 4, true, false), Some(9));
+        assert_eq!(selection, vec![3, 9]);
+        assert_eq!(click_selection(&mut selection, Some(9), &rows, 1, false, false), Some(1));
+        assert_eq!(selection, vec![1]);
+
+        let mut selection = vec![1, 3, 4, 8];
+        selection_after_removal(&mut selection, 3);
+        assert_eq!(selection, vec![1, 3, 7]);
+    }
+
+    #[test]
+    fn test_outliner_search_ignores_case() {
+        assert_eq!(find_ignore_case("Sponza_Curtain", "curt"), Some(7..11));
+        assert_eq!(find_ignore_case("Árbol grande", "áRBOL"), Some(0..6));
+        assert_eq!(find_ignore_case("Lamp", ""), Some(0..0));
+        assert_eq!(find_ignore_case("Lamp", "lamps"), None);
+    }
+
+    #[test]
+    fn test_pick_nearest_element_and_node() {
+        use crate::persisted::{MeshNode, MeshSource, SceneElementTransform};
+
+        let element = |x: f32| SceneElement {
+            bounding_box: Some(Aabb::from_center_size(Vec3::ZERO, Vec3::splat(2.0))),
+            ..SceneElement::new(
+                1,
+                MeshSource::File("test.gltf".into()),
+                SceneElementTransform {
+                    position: Vec3::new(x, 0.0, 0.0),
+                    ..SceneElementTransform::IDENTITY
+                },
+            )
+        };
+
+        let mut compound = element(-10.0);
+        compound.bounding_box = None;
+        compound.is_compound = true;
+        compound.mesh_nodes = [0.0, 0.0, 3.0]
+            .into_iter()
+            .map(|z| MeshNode {
+                local_transform: SceneElementTransform {
+                    position: Vec3::new(0.0, 0.0, z),
+                    ..SceneElementTransform::IDENTITY
+                },
+                bounding_box: Some(Aabb::from_center_size(Vec3::ZERO, Vec3::ONE)),
+                ..Default::default()
+            })
+            .collect();
+
+        let elements = vec![element(10.0), element(5.0), compound];
+        let along_x = Ray::new(Vec3::ZERO, Vec3::X);
+        assert_eq!(pick_element(&along_x, &elements, &[], 1.0), Some((1, None)));
+
+        // The first node with a box is hit first; the one moved along z isn't on the ray
+        let along_neg_x = Ray::new(Vec3::ZERO, -Vec3::X);
+        assert_eq!(pick_element(&along_neg_x, &elements, &[], 1.0), Some((2, Some(0))));
+
+        assert_eq!(pick_element(&Ray::new(Vec3::ZERO, Vec3::Y), &elements, &[], 1.0), None);
+
+        let mut hidden = elements.clone();
+        hidden[1].tags.push("hidden".to_string());
+        let mut layer = Layer::new("hidden");
+        layer.visible = false;
+        assert_eq!(pick_element(&along_x, &hidden, &[layer], 1.0), Some((0, None)));
+    }
+
+    #[test]
+    fn test_selection_follows_element_removal() {
+        let selected = SelectionTarget::Element(3);
+        assert_eq!(selected.after_removal(5), Some(SelectionTarget::Element(3)));
+        assert_eq!(selected.after_removal(1), Some(SelectionTarget::Element(2)));
+        assert_eq!(selected.after_removal(3), None);
+        assert_eq!(SelectionTarget::Sun.after_removal(0), Some(SelectionTarget::Sun));
+    }
+}
@@ -1,8 +1,34 @@
 use kajiya_simple::Vec3;
 
-#[derive(Default, Clone, serde::Serialize, serde::Deserialize)]
+use crate::misc::slerp_direction;
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
 pub struct Sequence {
     items: Vec<SequenceItem>,
+    /// Seconds of playback per second of key time; 2 plays the whole sequence twice as slow
+    #[serde(default = "default_time_scale")]
+    pub time_scale: f32,
+    /// Duration given to keys when they're added
+    #[serde(default = "default_key_duration")]
+    pub default_key_duration: f32,
+}
+
+impl Default for Sequence {
+    fn default() -> Self {
+        Self {
+            items: Vec::new(),
+            time_scale: default_time_scale(),
+            default_key_duration: default_key_duration(),
+        }
+    }
+}
+
+fn default_time_scale() -> f32 {
+    1.0
+}
+
+fn default_key_duration() -> f32 {
+    1.0
 }
 
 #[derive(Debug, Default, Clone, serde::Serialize, serde::Deserialize)]
@@ -43,13 +69,20 @@ pub struct SequenceValue {
     pub camera_position: MemOption<Vec3>,
     pub camera_direction: MemOption<Vec3>,
     pub towards_sun: MemOption<Vec3>,
+    #[serde(default)]
+    pub sun_intensity: MemOption<f32>,
+    #[serde(default)]
+    pub sun_color: MemOption<Vec3>,
 }
 
-#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
 pub struct SequenceFullValue {
     pub camera_position: Vec3,
     pub camera_direction: Vec3,
     pub towards_sun: Vec3,
+    /// `None` when no key sets it, so playback leaves the current value alone
+    pub sun_intensity: Option<f32>,
+    pub sun_color: Option<Vec3>,
 }
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
@@ -73,7 +106,7 @@ impl Sequence {
     pub fn add_keyframe(&mut self, after: Option<usize>, value: SequenceValue) {
         let idx = after.map_or_else(|| self.items.len(), |idx| idx + 1);
 
-        let t_delta = 1.0;
+        let t_delta = self.default_key_duration.max(0.01);
         let prev_t = self
             .items
             .get(idx.saturating_sub(1))
@@ -91,79 +124,119 @@ impl Sequence {
         }
     }
 
+    /// Where key `i` falls in playback, in seconds
+    pub fn playback_time(&self, i: usize) -> Option<f32> {
+        Some(self.items.get(i)?.t * self.time_scale.max(0.01))
+    }
+
     pub fn to_playback(&self) -> CameraPlaybackSequence {
+        let time_scale = self.time_scale.max(0.01);
+
         CameraPlaybackSequence {
-            duration: self.items.last().map_or(0.0, |item| item.t),
+            duration: self.items.last().map_or(0.0, |item| item.t * time_scale),
             camera_position_x_spline: splines::Spline::from_iter(self.items.iter().filter_map(|k| {
                 Some(splines::Key::new(
-                    k.t,
+                    k.t * time_scale,
                     k.value.camera_position.as_option()?.x,
                     splines::Interpolation::CatmullRom,
                 ))
             })),
             camera_position_y_spline: splines::Spline::from_iter(self.items.iter().filter_map(|k| {
                 Some(splines::Key::new(
-                    k.t,
+                    k.t * time_scale,
                     k.value.camera_position.as_option()?.y,
                     splines::Interpolation::CatmullRom,
                 ))
             })),
             camera_position_z_spline: splines::Spline::from_iter(self.items.iter().filter_map(|k| {
                 Some(splines::Key::new(
-                    k.t,
+                    k.t * time_scale,
                     k.value.camera_position.as_option()?.z,
                     splines::Interpolation::CatmullRom,
                 ))
             })),
             camera_direction_x_spline: splines::Spline::from_iter(self.items.iter().filter_map(|k| {
                 Some(splines::Key::new(
-                    k.t,
+                    k.t * time_scale,
                     k.value.camera_direction.as_option()?.x,
                     splines::Interpolation::CatmullRom,
                 ))
             })),
             camera_direction_y_spline: splines::Spline::from_iter(self.items.iter().filter_map(|k| {
                 Some(splines::Key::new(
-                    k.t,
+                    k.t * time_scale,
                     k.value.camera_direction.as_option()?.y,
                     splines::Interpolation::CatmullRom,
                 ))
             })),
             camera_direction_z_spline: splines::Spline::from_iter(self.items.iter().filter_map(|k| {
                 Some(splines::Key::new(
-                    k.t,
+                    k.t * time_scale,
                     k.value.camera_direction.as_option()?.z,
                     splines::Interpolation::CatmullRom,
                 ))
             })),
-            towards_sun_x_spline: splines::Spline::from_iter(self.items.iter().filter_map(|k| {
-                Some(splines::Key::new(
-                    k.t,
-                    k.value.towards_sun.as_option()?.x,
-                    splines::Interpolation::CatmullRom,
-                ))
-            })),
-            towards_sun_y_spline: splines::Spline::from_iter(self.items.iter().filter_map(|k| {
-                Some(splines::Key::new(
-                    k.t,
-                    k.value.towards_sun.as_option()?.y,
-                    splines::Interpolation::CatmullRom,
-                ))
-            })),
-            towards_sun_z_spline: splines::Spline::from_iter(self.items.iter().filter_map(|k| {
-                Some(splines::Key::new(
-                    k.t,
-                    k.value.towards_sun.as_option()?.z,
-                    splines::Interpolation::CatmullRom,
-                ))
-            })),
+            towards_sun_keys: self
+                .items
+                .iter()
+                .filter_map(|k| Some((k.t * time_scale, k.value.towards_sun.as_option()?.normalize())))
+                .collect(),
+            sun_intensity_keys: self
+                .items
+                .iter()
+                .filter_map(|k| Some((k.t * time_scale, k.value.sun_intensity.as_option()?)))
+                .collect(),
+            sun_color_keys: self
+                .items
+                .iter()
+                .filter_map(|k| Some((k.t * time_scale, k.value.sun_color.as_option()?)))
+                .collect(),
         }
     }
 
+    /// Camera positions along the playback curve, for previewing the path in the viewport
+    pub fn sample_camera_path(&self, samples_per_second: f32, max_samples: usize) -> Vec<Vec3> {
+        let mut playback = self.to_playback();
+        if playback.duration <= 0.0 {
+            return Vec::new();
+        }
+
+        let sample_count = ((playback.duration * samples_per_second).ceil() as usize).clamp(2, max_samples);
+        (0..sample_count)
+            .filter_map(|i| {
+                let t = playback.duration * i as f32 / (sample_count - 1) as f32;
+                Some(playback.sample(t)?.camera_position)
+            })
+            .collect()
+    }
+
+    /// Keys that set the camera position, with their index
+    pub fn camera_position_keys(&self) -> impl Iterator<Item = (usize, Vec3)> + '_ {
+        self.items
+            .iter()
+            .enumerate()
+            .filter_map(|(i, k)| Some((i, k.value.camera_position.as_option()?)))
+    }
+
     pub fn get_item(&self, i: usize) -> Option<&SequenceItem> {
         self.items.get(i)
     }
 
+    /// What the view looks like at key `i`: its own values, with the ones it doesn't set
+    /// taken from the curve at its time
+    pub fn resolved_value(&self, i: usize) -> Option<SequenceFullValue> {
+        let item = self.items.get(i)?;
+        let curve = self.to_playback().sample(self.playback_time(i)?)?;
+
+        Some(SequenceFullValue {
+            camera_position: item.value.camera_position.unwrap_or(curve.camera_position),
+            camera_direction: item.value.camera_direction.unwrap_or(curve.camera_direction),
+            towards_sun: item.value.towards_sun.unwrap_or(curve.towards_sun),
+            sun_intensity: item.value.sun_intensity.as_option().or(curve.sun_intensity),
+            sun_color: item.value.sun_color.as_option().or(curve.sun_color),
+        })
+    }
+
     pub fn delete_key(&mut self, i: usize) {
         let t_delta = self
             .items
@@ -181,7 +254,7 @@ impl Sequence {
             let duration = self
                 .items
                 .get(i + 1)
-                .map_or(1.0, |p_next| p_next.t - self.items[i].t);
+                .map_or(self.default_key_duration, |p_next| p_next.t - self.items[i].t);
 
             let item = self.items.get_mut(i).unwrap();
 
@@ -210,12 +283,19 @@ pub struct CameraPlaybackSequence {
     camera_direction_x_spline: splines::Spline<f32, f32>,
     camera_direction_y_spline: splines::Spline<f32, f32>,
     camera_direction_z_spline: splines::Spline<f32, f32>,
-    towards_sun_x_spline: splines::Spline<f32, f32>,
-    towards_sun_y_spline: splines::Spline<f32, f32>,
-    towards_sun_z_spline: splines::Spline<f32, f32>,
+    // The sun is slerped between keys rather than splined per component, so it sweeps evenly across the sky
+    towards_sun_keys: Vec<(f32, Vec3)>,
+    // Lerped, since a spline could overshoot into negative light
+    sun_intensity_keys: Vec<(f32, f32)>,
+    sun_color_keys: Vec<(f32, Vec3)>,
 }
 
 impl CameraPlaybackSequence {
+    /// Seconds from the first key to the last
+    pub fn duration(&self) -> f32 {
+        self.duration
+    }
+
     pub fn sample(&mut self, t: f32) -> Option<SequenceFullValue> {
         if t > self.duration {
             return None;
@@ -230,18 +310,137 @@ impl CameraPlaybackSequence {
         let dir_y = self.camera_direction_y_spline.clamped_sample(t)?;
         let dir_z = self.camera_direction_z_spline.clamped_sample(t)?;
         
-        let sun_x = self.towards_sun_x_spline.clamped_sample(t)?;
-        let sun_y = self.towards_sun_y_spline.clamped_sample(t)?;
-        let sun_z = self.towards_sun_z_spline.clamped_sample(t)?;
+        let towards_sun = sample_keys(&self.towards_sun_keys, t, slerp_direction)?;
 
         let camera_position = Vec3::new(pos_x, pos_y, pos_z);
         let camera_direction = Vec3::new(dir_x, dir_y, dir_z);
-        let towards_sun = Vec3::new(sun_x, sun_y, sun_z);
 
         Some(SequenceFullValue {
             camera_position,
             camera_direction,
             towards_sun,
+            sun_intensity: sample_keys(&self.sun_intensity_keys, t, |a, b, t| a + (b - a) * t),
+            sun_color: sample_keys(&self.sun_color_keys, t, Vec3::lerp),
         })
     }
 }
+
+/// Interpolates between the keys around `t`, holding the first and last values beyond them
+fn sample_keys<T: Copy>(keys: &[(f32, T)], t: f32, interpolate: impl Fn(T, T, f32) -> T) -> Option<T> {
+    let next = keys.partition_point(|(key_t, _)| *key_t <= t);
+
+    match (next.checked_sub(1).map(|i| keys[i]), keys.get(next)) {
+        (Some((t0, from)), Some(&(t1, to))) => Some(interpolate(from, to, (t - t0) / (t1 - t0).max(1e-5))),
+        (Some((_, value)), None) | (None, Some(&(_, value))) => Some(value),
+        (None, None) => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn position_key(position: Vec3) -> SequenceValue {
+        SequenceValue {
+            camera_position: MemOption::new(position),
+            camera_direction: MemOption::new(Vec3::Z),
+            towards_sun: MemOption::new(Vec3::Y),
+            sun_intensity: MemOption::default(),
+            sun_color: MemOption::default(),
+        }
+    }
+
+    #[test]
+    fn test_empty_sequence_has_no_path() {
+        assert!(Sequence::default().sample_camera_path(16.0, 256).is_empty());
+    }
+
+    #[test]
+    fn test_camera_path_follows_keys() {
+        let mut sequence = Sequence::default();
+        for x in 0..4 {
+            sequence.add_keyframe(None, position_key(Vec3::new(x as f32, 0.0, 0.0)));
+        }
+
+        let path = sequence.sample_camera_path(16.0, 256);
+        assert!(!path.is_empty());
+        for point in path {
+            // Keys are collinear, so the curve stays on the line between them
+            assert!(point.y.abs() < 1e-4 && point.z.abs() < 1e-4);
+            assert!((-1e-4..=3.0 + 1e-4).contains(&point.x));
+        }
+
+        assert_eq!(sequence.camera_position_keys().count(), 4);
+    }
+
+    #[test]
+    fn test_resolved_value_falls_back_to_curve() {
+        let mut sequence = Sequence::default();
+        for x in 0..5 {
+            sequence.add_keyframe(None, position_key(Vec3::new(x as f32, 0.0, 0.0)));
+        }
+
+        sequence.each_key(|i, item| {
+            if i == 2 {
+                item.value.camera_position.is_some = false;
+                item.value.towards_sun = MemOption::new(Vec3::X);
+            }
+        });
+
+        // The curve through the remaining keys is symmetric about the middle one
+        let value = sequence.resolved_value(2).unwrap();
+        assert!(value.camera_position.abs_diff_eq(Vec3::new(2.0, 0.0, 0.0), 1e-4));
+        assert_eq!(value.camera_direction, Vec3::Z);
+        assert_eq!(value.towards_sun, Vec3::X);
+
+        assert!(sequence.resolved_value(5).is_none());
+    }
+
+    #[test]
+    fn test_sun_intensity_interpolates_between_keys() {
+        let mut sequence = Sequence::default();
+        for x in 0..4 {
+            sequence.add_keyframe(None, position_key(Vec3::new(x as f32, 0.0, 0.0)));
+        }
+
+        let mut playback = sequence.to_playback();
+        assert_eq!(playback.sample(1.0).unwrap().sun_intensity, None);
+
+        // Keys 1 and 3 set the intensity; key 2 in between doesn't
+        sequence.each_key(|i, item| match i {
+            1 => item.value.sun_intensity = MemOption::new(1.0),
+            3 => item.value.sun_intensity = MemOption::new(5.0),
+            _ => {}
+        });
+
+        let mut playback = sequence.to_playback();
+        let mut intensity = |t| playback.sample(t).unwrap().sun_intensity.unwrap();
+        assert_eq!(intensity(1.0), 1.0);
+        assert_eq!(intensity(1.5), 2.0);
+        // Held before the first key that sets it
+        assert_eq!(intensity(0.0), 1.0);
+        assert_eq!(intensity(3.0), 5.0);
+    }
+
+    #[test]
+    fn test_time_scale_stretches_playback() {
+        let mut sequence = Sequence {
+            default_key_duration: 2.0,
+            ..Default::default()
+        };
+        for x in 0..4 {
+            sequence.add_keyframe(None, position_key(Vec3::new(x as f32, 0.0, 0.0)));
+        }
+        assert_eq!(sequence.to_playback().duration(), 6.0);
+
+        sequence.time_scale = 0.5;
+        let mut playback = sequence.to_playback();
+        assert_eq!(playback.duration(), 3.0);
+        assert_eq!(sequence.playback_time(2), Some(2.0));
+
+        // The curve passes the same keys, just sooner
+        let at_key = playback.sample(2.0).unwrap().camera_position;
+        assert!(at_key.abs_diff_eq(Vec3::new(2.0, 0.0, 0.0), 1e-4));
+        assert!(playback.sample(3.5).is_none());
+    }
+}
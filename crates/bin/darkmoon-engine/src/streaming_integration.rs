@@ -1,9 +1,18 @@
-use resource_streaming::{ResourceStreamingManager, StreamingConfig, LoadPriority};
-use crate::PersistedState;
+use resource_streaming::{AssetMetadata, EvictionPolicy, FormatInfo, ResourceStreamingManager, SelfTestReport, StreamingConfig, LoadPriority, ResourceType};
+use crate::{
+    math::{Aabb, BoundingSphere, Frustum},
+    persisted::{MeshSource, SceneElement},
+    scene::SceneDesc,
+    PersistedState,
+};
+use kajiya_simple::{Mat4, Vec3};
+use resource_streaming::resource_manager::{ResourceBounds, ResourceInfo, ResourceState};
 use anyhow::Result;
 use log::{info, debug, error};
 use std::sync::Arc;
 use parking_lot::RwLock;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
 
 /// Estado de inicialización del streaming
 #[derive(Debug, Clone, PartialEq)]
@@ -14,12 +23,75 @@ pub enum StreamingInitState {
     Failed(String),
 }
 
+const MB: u64 = 1024 * 1024;
+
+/// Share of total system memory the cache gets by default
+pub const CACHE_MEMORY_FRACTION: f64 = 0.25;
+pub const MIN_CACHE_SIZE: u64 = 256 * MB;
+pub const MAX_CACHE_SIZE: u64 = 16 * 1024 * MB;
+/// Used when the system memory can't be determined
+pub const FALLBACK_CACHE_SIZE: u64 = 2 * 1024 * MB;
+
+/// Size of the streaming cache in bytes: `size_mb` if it isn't 0, otherwise a share of the
+/// machine's `total_memory` bytes (0 if unknown), so low-memory machines don't run out and
+/// large ones aren't left mostly idle.
+pub fn cache_size(size_mb: u32, total_memory: u64) -> u64 {
+    if size_mb > 0 {
+        return size_mb as u64 * MB;
+    }
+
+    if total_memory == 0 {
+        return FALLBACK_CACHE_SIZE;
+    }
+
+    ((total_memory as f64 * CACHE_MEMORY_FRACTION) as u64).clamp(MIN_CACHE_SIZE, MAX_CACHE_SIZE)
+}
+
 /// Resource streaming system integration with Darkmoon Engine
 pub struct StreamingIntegration {
     manager: Option<ResourceStreamingManager>,
     enabled: bool,
     init_state: StreamingInitState,
     init_requested: bool,
+    /// Prefetches requested before the manager exists, sent once it's initialized
+    pending_prefetch: Vec<(Vec<(String, ResourceType)>, LoadPriority)>,
+    /// Path filter of the resource inspector
+    inspector_filter: String,
+    /// Column the resource inspector is sorted by, and whether it's ascending
+    inspector_sort: (ResourceColumn, bool),
+    /// Self-test running in the background, see `start_self_test`
+    self_test_result: Option<std::sync::mpsc::Receiver<SelfTestReport>>,
+    last_self_test: Option<SelfTestReport>,
+}
+
+/// Columns of the resource inspector
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResourceColumn {
+    Path,
+    State,
+    Priority,
+    Memory,
+    LastAccessed,
+}
+
+impl ResourceColumn {
+    const ALL: [ResourceColumn; 5] = [
+        ResourceColumn::Path,
+        ResourceColumn::State,
+        ResourceColumn::Priority,
+        ResourceColumn::Memory,
+        ResourceColumn::LastAccessed,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            ResourceColumn::Path => "Path",
+            ResourceColumn::State => "State",
+            ResourceColumn::Priority => "Priority",
+            ResourceColumn::Memory => "Memory",
+            ResourceColumn::LastAccessed => "Last accessed",
+        }
+    }
 }
 
 impl StreamingIntegration {
@@ -29,11 +101,16 @@ impl StreamingIntegration {
             enabled: false,
             init_state: StreamingInitState::NotInitialized,
             init_requested: false,
+            pending_prefetch: Vec::new(),
+            inspector_filter: String::new(),
+            inspector_sort: (ResourceColumn::Path, true),
+            self_test_result: None,
+            last_self_test: None,
         }
     }
     
-    /// Inicializa el sistema de streaming
-    pub async fn initialize(&mut self, persisted: &PersistedState) -> Result<()> {
+    /// Inicializa el sistema de streaming en el hilo actual, con las opciones guardadas en `persisted`
+    pub fn initialize(&mut self, persisted: &PersistedState) -> Result<()> {
         if self.enabled {
             info!("Streaming system already initialized");
             return Ok(());
@@ -42,19 +119,27 @@ impl StreamingIntegration {
         info!("Initializing resource streaming system...");
         
         let config = StreamingConfig {
-            max_cache_size: self.calculate_cache_size(),
-            worker_threads: self.calculate_worker_threads(),
+            max_cache_size: Self::calculate_cache_size(persisted.get_streaming_cache_size_mb()),
+            worker_threads: Self::calculate_worker_threads(persisted.get_streaming_worker_threads()),
             high_quality_distance: 50.0,
             medium_quality_distance: 150.0,
             low_quality_distance: 500.0,
             enable_predictive_loading: true,
+            lookahead_seconds: 1.0,
             asset_base_path: "assets".to_string(),
+            max_load_retries: 3,
+            load_retry_base_delay_ms: 100,
+            maintenance_interval_ms: 1000,
+            eviction_policy: persisted.get_streaming_eviction_policy(),
+            default_resource_distance: 100.0,
+            lod_hysteresis_distance: 5.0,
         };
         
         match resource_streaming::initialize_streaming(config) {
             Ok(manager) => {
                 self.manager = Some(manager);
                 self.enabled = true;
+                self.flush_pending_prefetch();
                 info!("Streaming system initialized successfully");
                 Ok(())
             }
@@ -72,16 +157,105 @@ impl StreamingIntegration {
         }
     }
     
+    /// Per-frame update with the camera frustum: resources used by elements in view
+    /// are prioritized over equally distant ones off screen
+    pub fn update_view(
+        &self,
+        camera_position: Vec3,
+        camera_direction: Vec3,
+        frustum: &Frustum,
+        elements: &[SceneElement],
+    ) {
+        let manager = match self.manager {
+            Some(ref manager) => manager,
+            None => return,
+        };
+
+        // Every instance of a mesh counts, so bound them all together
+        let mut mesh_bounds: HashMap<String, Aabb> = HashMap::new();
+        for elem in elements {
+            if let (Some(path), Some(bounding_box)) = (resource_path(&elem.source), &elem.bounding_box) {
+                let world_aabb = bounding_box.transform(&Mat4::from(elem.transform.affine_transform()));
+                mesh_bounds
+                    .entry(path)
+                    .and_modify(|aabb| *aabb = aabb.union(&world_aabb))
+                    .or_insert(world_aabb);
+            }
+        }
+
+        for (path, aabb) in mesh_bounds {
+            let sphere = BoundingSphere::from_aabb(&aabb);
+            manager.set_resource_bounds(
+                &path,
+                ResourceBounds {
+                    center: sphere.center.into(),
+                    radius: sphere.radius,
+                },
+            );
+        }
+
+        manager.update_with_visibility(&camera_position.into(), &camera_direction.into(), |bounds| {
+            frustum.is_visible_sphere(Vec3::from(bounds.center), bounds.radius)
+        });
+    }
+    
     /// Solicita la carga de un recurso
-    pub fn request_resource(&self, path: &str, priority: LoadPriority) -> Option<u64> {
+    pub fn request_resource(&self, path: &str, priority: LoadPriority, resource_type: ResourceType) -> Option<u64> {
         if let Some(ref manager) = self.manager {
-            Some(manager.request_resource(path, priority))
+            Some(manager.request_resource(path, priority, resource_type))
         } else {
             debug!("Streaming system not initialized, ignoring request: {}", path);
             None
         }
     }
     
+    /// Registers `resources` so `update` loads them by priority; queued until the streaming system is initialized
+    pub fn prefetch(&mut self, resources: Vec<(String, ResourceType)>, priority: LoadPriority) {
+        if resources.is_empty() {
+            return;
+        }
+
+        if let Some(ref manager) = self.manager {
+            manager.prefetch(resources, priority);
+        } else {
+            debug!("Streaming system not initialized, deferring prefetch of {} resources", resources.len());
+            self.pending_prefetch.push((resources, priority));
+        }
+    }
+
+    /// Prefetches a scene's meshes and the files they reference, see `scene_dependencies`
+    pub fn prefetch_scene(&mut self, scene: &SceneDesc) {
+        let mut by_priority: Vec<(LoadPriority, Vec<(String, ResourceType)>)> = Vec::new();
+        for (path, priority, resource_type) in scene_dependencies(scene, Path::new("assets")) {
+            match by_priority.iter_mut().find(|(p, _)| *p == priority) {
+                Some((_, resources)) => resources.push((path, resource_type)),
+                None => by_priority.push((priority, vec![(path, resource_type)])),
+            }
+        }
+
+        for (priority, resources) in by_priority {
+            self.prefetch(resources, priority);
+        }
+    }
+
+    fn flush_pending_prefetch(&mut self) {
+        if let Some(ref manager) = self.manager {
+            for (resources, priority) in self.pending_prefetch.drain(..) {
+                manager.prefetch(resources, priority);
+            }
+        }
+    }
+    
+    /// Reloads a resource at `Critical` priority; `false` if unknown or streaming isn't initialized
+    pub fn force_load(&self, handle: u64) -> bool {
+        self.manager.as_ref().map_or(false, |manager| manager.force_load(handle))
+    }
+
+    /// Drops a resource from the streaming cache; `false` if unknown or streaming isn't initialized
+    pub fn evict(&self, handle: u64) -> bool {
+        self.manager.as_ref().map_or(false, |manager| manager.evict(handle))
+    }
+    
     /// Obtiene el estado de un recurso
     pub fn get_resource_state(&self, handle: u64) -> Option<resource_streaming::resource_manager::ResourceState> {
         if let Some(ref manager) = self.manager {
@@ -107,14 +281,49 @@ impl StreamingIntegration {
         }
     }
     
+    /// Runs `ResourceStreamingManager::self_test` on its own thread, so a stuck worker
+    /// doesn't freeze the editor; the report shows up in the streaming GUI
+    pub fn start_self_test(&mut self) {
+        let manager = match self.manager {
+            Some(ref manager) => manager.clone(),
+            None => return,
+        };
+        if self.self_test_result.is_some() {
+            return;
+        }
+
+        let (sender, receiver) = std::sync::mpsc::channel();
+        std::thread::spawn(move || {
+            let _ = sender.send(manager.self_test(SELF_TEST_TIMEOUT));
+        });
+        self.self_test_result = Some(receiver);
+    }
+
+    fn poll_self_test(&mut self) {
+        if let Some(receiver) = &self.self_test_result {
+            match receiver.try_recv() {
+                Ok(report) => {
+                    self.last_self_test = Some(report);
+                    self.self_test_result = None;
+                }
+                Err(std::sync::mpsc::TryRecvError::Empty) => {}
+                Err(std::sync::mpsc::TryRecvError::Disconnected) => self.self_test_result = None,
+            }
+        }
+    }
+    
     /// Verifica si el sistema de streaming está habilitado
     pub fn is_enabled(&self) -> bool {
         self.enabled && self.manager.is_some()
     }
     
     /// Renderiza la GUI del sistema de streaming
-    pub fn render_gui(&mut self, ui: &imgui::Ui) {
+    pub fn render_gui(&mut self, ui: &imgui::Ui, persisted: &mut PersistedState) {
         let mut initialize_clicked = false;
+        let mut self_test_clicked = false;
+        self.poll_self_test();
+
+        render_settings(ui, persisted);
         
         if let Some(ref manager) = self.manager {
             let stats = manager.get_stats();
@@ -183,6 +392,35 @@ impl StreamingIntegration {
                     info!("Garbage collection executed manually");
                 }
             }
+
+            ui.same_line();
+            let self_test_running = self.self_test_result.is_some();
+            {
+                let _disabled = ui.begin_disabled(self_test_running);
+                self_test_clicked = ui.button("Self-test");
+            }
+            if ui.is_item_hovered() {
+                ui.tooltip_text("Load a test file through the whole streaming pipeline\nto check the asset path and the worker thread");
+            }
+
+            if self_test_running {
+                ui.text("Self-test running...");
+            } else if let Some(report) = &self.last_self_test {
+                render_self_test_report(ui, report);
+            }
+
+            // Resources
+            ui.separator();
+            ui.text("Resources");
+            ui.separator();
+
+            ui.input_text("Filter", &mut self.inspector_filter)
+                .hint("Path contains...")
+                .build();
+
+            let mut resources = manager.resources_snapshot();
+            resources.retain(|info| resource_matches_filter(info, &self.inspector_filter));
+            self.render_resource_table(ui, &mut resources);
         } else {
             match &self.init_state {
                 StreamingInitState::NotInitialized => {
@@ -226,6 +464,93 @@ impl StreamingIntegration {
             info!("Requesting streaming initialization from GUI");
             self.request_initialization();
         }
+        if self_test_clicked {
+            self.start_self_test();
+        }
+    }
+
+    /// Sortable table with a row per resource; failed loads show their error on hover.
+    /// Each row can force-load or evict its resource, to test streaming or recover stuck loads.
+    fn render_resource_table(&mut self, ui: &imgui::Ui, resources: &mut [ResourceInfo]) {
+        let [path, state, priority, memory, last_accessed] =
+            ResourceColumn::ALL.map(|column| imgui::TableColumnSetup::new(column.name()));
+        let mut actions = imgui::TableColumnSetup::new("Actions");
+        actions.flags = imgui::TableColumnFlags::NO_SORT;
+        let columns = [path, state, priority, memory, last_accessed, actions];
+        let flags = imgui::TableFlags::SORTABLE
+            | imgui::TableFlags::RESIZABLE
+            | imgui::TableFlags::ROW_BG
+            | imgui::TableFlags::BORDERS
+            | imgui::TableFlags::SCROLL_Y;
+
+        let _table = match ui.begin_table_header_with_sizing("streaming_resources", columns, flags, [0.0, 300.0], 0.0) {
+            Some(table) => table,
+            None => return,
+        };
+
+        if let Some(mut sort_specs) = ui.table_sort_specs_mut() {
+            let sort = &mut self.inspector_sort;
+            sort_specs.conditional_sort(|specs| {
+                if let Some(spec) = specs.iter().next() {
+                    sort.0 = ResourceColumn::ALL[spec.column_idx().min(ResourceColumn::ALL.len() - 1)];
+                    sort.1 = !matches!(spec.sort_direction(), Some(imgui::TableSortDirection::Descending));
+                }
+            });
+        }
+        sort_resources(resources, self.inspector_sort.0, self.inspector_sort.1);
+
+        let now = std::time::Instant::now();
+        for (idx, info) in resources.iter().enumerate() {
+            ui.table_next_column();
+            ui.text(&info.path);
+            if let Some(metadata) = &info.metadata {
+                if ui.is_item_hovered() {
+                    ui.tooltip_text(metadata_label(metadata));
+                }
+            }
+
+            ui.table_next_column();
+            match &info.state {
+                ResourceState::Failed(error) => {
+                    ui.text_colored([1.0, 0.3, 0.3, 1.0], "Failed");
+                    if ui.is_item_hovered() {
+                        ui.tooltip_text(error);
+                    }
+                }
+                state => ui.text(state_label(state)),
+            }
+            if info.retry_count > 0 && ui.is_item_hovered() {
+                ui.tooltip_text(format!("Retries: {}", info.retry_count));
+            }
+
+            ui.table_next_column();
+            ui.text(format!("{:?}", info.priority));
+
+            ui.table_next_column();
+            ui.text(format!("{:.1} KB", info.memory_usage as f32 / 1024.0));
+
+            ui.table_next_column();
+            ui.text(format!("{:.1}s ago", now.duration_since(info.last_accessed).as_secs_f32()));
+
+            ui.table_next_column();
+            if self.is_enabled() {
+                let id_token = ui.push_id_usize(idx);
+                if ui.small_button("Load") {
+                    self.force_load(info.handle);
+                }
+                if ui.is_item_hovered() {
+                    ui.tooltip_text("Reload now at Critical priority");
+                }
+                ui.same_line();
+                if ui.small_button("Evict") {
+                    self.evict(info.handle);
+                }
+                if ui.is_item_hovered() {
+                    ui.tooltip_text("Drop from the cache");
+                }
+                id_token.pop();
+            }
+        }
     }
 
     /// Solicita la inicialización del streaming (llamado desde GUI)
@@ -238,11 +563,11 @@ impl StreamingIntegration {
     }
     
     /// Verifica si hay una solicitud de inicialización pendiente y la procesa
-    pub async fn process_pending_initialization(&mut self) -> Result<()> {
+    pub fn process_pending_initialization(&mut self, persisted: &PersistedState) -> Result<()> {
         if self.init_requested && self.init_state == StreamingInitState::Initializing {
             self.init_requested = false;
             
-            match self.initialize_internal().await {
+            match self.initialize(persisted) {
                 Ok(()) => {
                     self.init_state = StreamingInitState::Initialized;
                     info!("Streaming system initialized successfully from GUI");
@@ -257,48 +582,33 @@ impl StreamingIntegration {
         Ok(())
     }
     
-    /// Inicialización interna del sistema de streaming
-    async fn initialize_internal(&mut self) -> Result<()> {
-        if self.enabled {
-            info!("Streaming system already initialized");
-            return Ok(());
-        }
-        
-        info!("Initializing resource streaming system...");
-        
-        let config = StreamingConfig {
-            max_cache_size: self.calculate_cache_size(),
-            worker_threads: self.calculate_worker_threads(),
-            high_quality_distance: 50.0,
-            medium_quality_distance: 150.0,
-            low_quality_distance: 500.0,
-            enable_predictive_loading: true,
-            asset_base_path: "assets".to_string(),
-        };
-        
-        match resource_streaming::initialize_streaming(config) {
-            Ok(manager) => {
-                self.manager = Some(manager);
-                self.enabled = true;
-                info!("Streaming system initialized successfully");
-                Ok(())
-            }
-            Err(e) => {
-                error!("Error initializing streaming system: {}", e);
-                Err(e)
-            }
-        }
-    }
-
     // Métodos privados para configuración
     
-    fn calculate_cache_size(&self) -> u64 {
-        // Calculate cache size based on available system memory
-        // For now, use a fixed value of 2GB
-        2 * 1024 * 1024 * 1024
+    /// `size_mb` de las opciones guardadas, o si es 0, una parte de la memoria del sistema
+    fn calculate_cache_size(size_mb: u32) -> u64 {
+        if size_mb > 0 {
+            info!("Streaming cache size: {} MB (persisted setting)", size_mb);
+            return cache_size(size_mb, 0);
+        }
+
+        let mut system = sysinfo::System::new();
+        system.refresh_memory();
+        let total_memory = system.total_memory();
+
+        let size = cache_size(0, total_memory);
+        info!(
+            "Streaming cache size: {} MB (system memory: {} MB)",
+            size / MB,
+            total_memory / MB
+        );
+        size
     }
     
-    fn calculate_worker_threads(&self) -> usize {
+    fn calculate_worker_threads(threads: u8) -> usize {
+        if threads > 0 {
+            return threads as usize;
+        }
+
         // Use half of available cores for streaming
         (num_cpus::get() / 2).max(2).min(8)
     }
@@ -310,6 +620,210 @@ impl Default for StreamingIntegration {
     }
 }
 
+/// Files a scene needs, relative to the asset directory: meshes and the buffers
+/// they reference (`LoadPriority::High`), and their textures (`LoadPriority::Medium`)
+pub fn scene_dependencies(scene: &SceneDesc, asset_base: &Path) -> Vec<(String, LoadPriority, ResourceType)> {
+    let mut dependencies = Vec::new();
+    let mut seen = HashSet::new();
+
+    for instance in &scene.instances {
+        // Scene meshes are VFS paths, where "/" is the asset directory
+        let mesh = instance.mesh.trim_start_matches('/').to_string();
+        if !seen.insert(mesh.clone()) {
+            continue;
+        }
+
+        let is_gltf = mesh.ends_with(".gltf") || mesh.ends_with(".glb");
+        dependencies.push((mesh.clone(), LoadPriority::High, ResourceType::Mesh));
+
+        if is_gltf {
+            match gltf_external_files(&asset_base.join(&mesh)) {
+                Ok(files) => {
+                    let mesh_dir = Path::new(&mesh).parent().unwrap_or_else(|| Path::new(""));
+                    for (uri, priority, resource_type) in files {
+                        let path = mesh_dir.join(uri).to_string_lossy().replace('\\', "/");
+                        if seen.insert(path.clone()) {
+                            dependencies.push((path, priority, resource_type));
+                        }
+                    }
+                }
+                Err(err) => debug!("Not prefetching dependencies of {}: {:#}", mesh, err),
+            }
+        }
+    }
+
+    dependencies
+}
+
+const SELF_TEST_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+
+fn render_self_test_report(ui: &imgui::Ui, report: &SelfTestReport) {
+    match &report.error {
+        None => ui.text_colored(
+            [0.3, 0.8, 0.3, 1.0],
+            format!(
+                "Self-test passed: loaded in {:.1} ms, {:.1} ms total",
+                report.load_time.unwrap_or_default().as_secs_f32() * 1000.0,
+                report.total_time.as_secs_f32() * 1000.0
+            ),
+        ),
+        Some(error) => {
+            ui.text_colored(
+                [1.0, 0.3, 0.3, 1.0],
+                format!("Self-test failed after {:.1} ms:", report.total_time.as_secs_f32() * 1000.0),
+            );
+            ui.text_wrapped(error);
+        }
+    }
+
+    for warning in &report.warnings {
+        ui.text_colored([0.8, 0.8, 0.3, 1.0], "Warning:");
+        ui.same_line();
+        ui.text_wrapped(warning);
+    }
+}
+
+fn state_label(state: &ResourceState) -> String {
+    match state {
+        ResourceState::NotLoaded => "Not loaded".to_string(),
+        ResourceState::Loading => "Loading".to_string(),
+        ResourceState::Loaded(lod) => format!("Loaded ({:?})", lod),
+        ResourceState::Failed(_) => "Failed".to_string(),
+    }
+}
+
+fn metadata_label(metadata: &AssetMetadata) -> String {
+    let details = match metadata.format_info {
+        FormatInfo::Image { width, height } => format!(", {}x{}", width, height),
+        FormatInfo::Mesh { node_count, mesh_count } => format!(", {} nodes, {} meshes", node_count, mesh_count),
+        FormatInfo::None => String::new(),
+    };
+    format!(
+        "{}{}\n{:.1} KB on disk, {:.1} KB decoded",
+        metadata.format,
+        details,
+        metadata.original_size as f32 / 1024.0,
+        metadata.logical_size as f32 / 1024.0
+    )
+}
+
+/// Case-insensitive path match; an empty filter matches everything
+fn resource_matches_filter(info: &ResourceInfo, filter: &str) -> bool {
+    let filter = filter.trim();
+    filter.is_empty() || info.path.to_lowercase().contains(&filter.to_lowercase())
+}
+
+/// Sort the inspector rows by `column`, breaking ties by path so rows don't jump around between frames
+fn sort_resources(resources: &mut [ResourceInfo], column: ResourceColumn, ascending: bool) {
+    resources.sort_by(|a, b| {
+        let ord = match column {
+            ResourceColumn::Path => std::cmp::Ordering::Equal,
+            ResourceColumn::State => state_label(&a.state).cmp(&state_label(&b.state)),
+            ResourceColumn::Priority => a.priority.cmp(&b.priority),
+            ResourceColumn::Memory => a.memory_usage.cmp(&b.memory_usage),
+            // Most recently accessed first when ascending, matching the "N s ago" column text
+            ResourceColumn::LastAccessed => b.last_accessed.cmp(&a.last_accessed),
+        };
+        let ord = ord.then_with(|| a.path.cmp(&b.path));
+        if ascending {
+            ord
+        } else {
+            ord.reverse()
+        }
+    });
+}
+
+/// Streaming path of a mesh, relative to the asset directory like the ones `scene_dependencies` returns
+fn resource_path(source: &MeshSource) -> Option<String> {
+    match source {
+        MeshSource::File(path) => {
+            let path = path.to_string_lossy().replace('\\', "/");
+            let relative = match path.find("assets/") {
+                Some(assets_pos) => &path[assets_pos + "assets/".len()..],
+                None => path.trim_start_matches('/'),
+            };
+            Some(relative.to_string())
+        }
+        MeshSource::Cache(_) => None,
+    }
+}
+
+/// Buffers and images a glTF file references by URI (embedded data is skipped)
+fn gltf_external_files(path: &Path) -> Result<Vec<(String, LoadPriority, ResourceType)>> {
+    let gltf = gltf::Gltf::open(path)?;
+    let is_external = |uri: &str| !uri.starts_with("data:");
+
+    let buffers = gltf.buffers().filter_map(|buffer| match buffer.source() {
+        gltf::buffer::Source::Uri(uri) if is_external(uri) => Some((uri.to_string(), LoadPriority::High, ResourceType::Mesh)),
+        _ => None,
+    });
+    let images = gltf.images().filter_map(|image| match image.source() {
+        gltf::image::Source::Uri { uri, .. } if is_external(uri) => Some((uri.to_string(), LoadPriority::Medium, ResourceType::Texture)),
+        _ => None,
+    });
+
+    Ok(buffers.chain(images).collect())
+}
+
+/// Opciones de streaming guardadas con el estado persistido. El tamaño del cache y el número de
+/// workers se leen al arrancar el streaming, así que sus cambios se aplican la próxima vez.
+fn render_settings(ui: &imgui::Ui, persisted: &mut PersistedState) {
+    ui.separator();
+    ui.text("Settings");
+    ui.separator();
+
+    let mut enabled = persisted.get_streaming_enabled();
+    if ui.checkbox("Start with the engine", &mut enabled) {
+        persisted.set_streaming_enabled(enabled);
+    }
+
+    let mut cache_size_mb = persisted.get_streaming_cache_size_mb();
+    if ui.slider("Cache size (MB)", 0, 16384, &mut cache_size_mb) {
+        persisted.set_streaming_cache_size_mb(cache_size_mb);
+    }
+    if ui.is_item_hovered() {
+        ui.tooltip_text("0 uses a quarter of system memory, between 256 MB and 16 GB.\nApplies the next time streaming starts.");
+    }
+
+    let mut worker_threads = persisted.get_streaming_worker_threads();
+    if ui.slider("Worker threads", 0, 16, &mut worker_threads) {
+        persisted.set_streaming_worker_threads(worker_threads);
+    }
+    if ui.is_item_hovered() {
+        ui.tooltip_text("0 uses half the CPU cores.\nApplies the next time streaming starts.");
+    }
+
+    let policy = persisted.get_streaming_eviction_policy();
+    let ttl = match policy {
+        EvictionPolicy::TimeToLive(ttl) => Some(ttl),
+        _ => None,
+    };
+    ui.text("Eviction:");
+    for (label, option) in [
+        ("LRU", EvictionPolicy::LeastRecentlyUsed),
+        ("LFU", EvictionPolicy::LeastFrequentlyUsed),
+        ("Priority", EvictionPolicy::Priority),
+        ("Time to live", EvictionPolicy::TimeToLive(ttl.unwrap_or(DEFAULT_EVICTION_TTL))),
+    ] {
+        ui.same_line();
+        if ui.radio_button_bool(label, std::mem::discriminant(&policy) == std::mem::discriminant(&option)) {
+            persisted.set_streaming_eviction_policy(option);
+        }
+    }
+    if let Some(ttl) = ttl {
+        let mut seconds = ttl.as_secs() as u32;
+        if ui.slider("Time to live (s)", 1, 3600, &mut seconds) {
+            persisted.set_streaming_eviction_policy(EvictionPolicy::TimeToLive(std::time::Duration::from_secs(seconds as u64)));
+        }
+        if ui.is_item_hovered() {
+            ui.tooltip_text("Cached resources are dropped this long after loading.\nApplies the next time streaming starts.");
+        }
+    }
+}
+
+/// Vida de los recursos en el cache al elegir la política de desalojo por tiempo
+const DEFAULT_EVICTION_TTL: std::time::Duration = std::time::Duration::from_secs(300);
+
 /// Extensions for persisted state to include streaming configuration
 pub trait PersistedStateStreamingExt {
     fn get_streaming_enabled(&self) -> bool;
@@ -318,36 +832,135 @@ pub trait PersistedStateStreamingExt {
     fn set_streaming_cache_size_mb(&mut self, size_mb: u32);
     fn get_streaming_worker_threads(&self) -> u8;
     fn set_streaming_worker_threads(&mut self, threads: u8);
+    fn get_streaming_eviction_policy(&self) -> EvictionPolicy;
+    fn set_streaming_eviction_policy(&mut self, policy: EvictionPolicy);
 }
 
-// Default implementation for PersistedState
-#[allow(unused)]
 impl PersistedStateStreamingExt for PersistedState {
     fn get_streaming_enabled(&self) -> bool {
-        // Default enabled
-        true
+        self.streaming.enabled
     }
-    
+
     fn set_streaming_enabled(&mut self, enabled: bool) {
-        // Implement when fields are added to PersistedState
-        debug!("Setting streaming enabled: {}", enabled);
+        self.streaming.enabled = enabled;
     }
-    
+
     fn get_streaming_cache_size_mb(&self) -> u32 {
-        // Default 2GB
-        2048
+        self.streaming.cache_size_mb
     }
-    
+
     fn set_streaming_cache_size_mb(&mut self, size_mb: u32) {
-        debug!("Setting cache size: {} MB", size_mb);
+        self.streaming.cache_size_mb = size_mb;
     }
-    
+
     fn get_streaming_worker_threads(&self) -> u8 {
-        // Default 4 threads
-        4
+        self.streaming.worker_threads
     }
-    
+
     fn set_streaming_worker_threads(&mut self, threads: u8) {
-        debug!("Setting worker threads: {}", threads);
+        self.streaming.worker_threads = threads;
+    }
+
+    fn get_streaming_eviction_policy(&self) -> EvictionPolicy {
+        self.streaming.eviction_policy.clone()
+    }
+
+    fn set_streaming_eviction_policy(&mut self, policy: EvictionPolicy) {
+        self.streaming.eviction_policy = policy;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use resource_streaming::StreamingPriority;
+
+    fn resource(path: &str, priority: StreamingPriority, memory_usage: u64) -> ResourceInfo {
+        ResourceInfo {
+            id: path.to_string(),
+            handle: 0,
+            path: path.to_string(),
+            resource_type: ResourceType::from_path(path),
+            state: ResourceState::Loading,
+            priority,
+            last_accessed: std::time::Instant::now(),
+            memory_usage,
+            retry_count: 0,
+            bounds: None,
+            world_position: None,
+            metadata: None,
+            cancelled: false,
+            pending_lod: None,
+            evicted: None,
+        }
+    }
+
+    #[test]
+    fn test_resource_inspector_sort_and_filter() {
+        let mut resources = vec![
+            resource("meshes/b.gltf", StreamingPriority::Low, 300),
+            resource("textures/a.png", StreamingPriority::Critical, 100),
+            resource("meshes/a.gltf", StreamingPriority::High, 200),
+        ];
+        let paths = |resources: &[ResourceInfo]| resources.iter().map(|r| r.path.as_str()).collect::<Vec<_>>();
+
+        sort_resources(&mut resources, ResourceColumn::Path, true);
+        assert_eq!(paths(&resources), ["meshes/a.gltf", "meshes/b.gltf", "textures/a.png"]);
+
+        sort_resources(&mut resources, ResourceColumn::Priority, false);
+        assert_eq!(paths(&resources), ["textures/a.png", "meshes/a.gltf", "meshes/b.gltf"]);
+
+        sort_resources(&mut resources, ResourceColumn::Memory, true);
+        assert_eq!(paths(&resources), ["textures/a.png", "meshes/a.gltf", "meshes/b.gltf"]);
+
+        resources.retain(|info| resource_matches_filter(info, " MESHES/ "));
+        assert_eq!(resources.len(), 2);
+        assert!(resource_matches_filter(&resources[0], ""));
+    }
+
+    #[test]
+    fn test_cache_size_from_system_memory() {
+        let gb = 1024 * MB;
+
+        assert_eq!(cache_size(0, 16 * gb), 4 * gb);
+        assert_eq!(cache_size(0, 512 * MB), MIN_CACHE_SIZE);
+        assert_eq!(cache_size(0, 512 * gb), MAX_CACHE_SIZE);
+        assert_eq!(cache_size(0, 0), FALLBACK_CACHE_SIZE);
+
+        assert_eq!(cache_size(100, 16 * gb), 100 * MB);
+    }
+
+    #[test]
+    fn test_streaming_settings_persist() {
+        let mut persisted = PersistedState::default();
+        assert!(persisted.get_streaming_enabled());
+
+        persisted.set_streaming_enabled(false);
+        persisted.set_streaming_cache_size_mb(512);
+        persisted.set_streaming_worker_threads(3);
+        persisted.set_streaming_eviction_policy(EvictionPolicy::TimeToLive(std::time::Duration::from_secs(60)));
+
+        let saved: PersistedState = ron::from_str(&ron::to_string(&persisted).unwrap()).unwrap();
+        assert_eq!(saved.streaming, persisted.streaming);
+        assert_eq!(saved.get_streaming_cache_size_mb(), 512);
+        assert_eq!(saved.get_streaming_worker_threads(), 3);
+        assert_eq!(
+            saved.get_streaming_eviction_policy(),
+            EvictionPolicy::TimeToLive(std::time::Duration::from_secs(60))
+        );
+    }
+
+    #[test]
+    fn test_requested_initialization_completes() {
+        let mut persisted = PersistedState::default();
+        persisted.set_streaming_cache_size_mb(64);
+        let mut streaming = StreamingIntegration::new();
+        assert!(!streaming.is_enabled());
+
+        streaming.request_initialization();
+        streaming.process_pending_initialization(&persisted).unwrap();
+
+        assert_eq!(streaming.init_state, StreamingInitState::Initialized);
+        assert!(streaming.is_enabled());
     }
 }
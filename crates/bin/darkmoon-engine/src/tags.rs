@@ -0,0 +1,78 @@
+/// Tags are compared case-insensitively and without surrounding whitespace
+pub fn normalize_tag(tag: &str) -> String {
+    tag.trim().to_lowercase()
+}
+
+/// Add `tag` unless it's empty or already there. Returns whether it was added.
+pub fn add_tag(tags: &mut Vec<String>, tag: &str) -> bool {
+    let tag = normalize_tag(tag);
+    if tag.is_empty() || tags.contains(&tag) {
+        return false;
+    }
+
+    tags.push(tag);
+    true
+}
+
+/// A tag filter as typed in the Outliner: whitespace-separated tags that must all be present,
+/// with a leading `!` for tags that must be absent. An empty query matches everything.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TagQuery {
+    pub required: Vec<String>,
+    pub excluded: Vec<String>,
+}
+
+impl TagQuery {
+    pub fn parse(query: &str) -> Self {
+        let mut res = Self::default();
+
+        for term in query.split_whitespace() {
+            match term.strip_prefix('!') {
+                Some(tag) if !tag.is_empty() => res.excluded.push(normalize_tag(tag)),
+                Some(_) => {}
+                None => res.required.push(normalize_tag(term)),
+            }
+        }
+
+        res
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.required.is_empty() && self.excluded.is_empty()
+    }
+
+    pub fn matches(&self, tags: &[String]) -> bool {
+        self.required.iter().all(|tag| tags.contains(tag))
+            && !self.excluded.iter().any(|tag| tags.contains(tag))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_add_tag_normalizes_and_dedups() {
+        let mut tags = Vec::new();
+        assert!(add_tag(&mut tags, " Hero "));
+        assert!(!add_tag(&mut tags, "hero"));
+        assert!(!add_tag(&mut tags, "   "));
+        assert!(add_tag(&mut tags, "prop"));
+        assert_eq!(tags, vec!["hero".to_string(), "prop".to_string()]);
+    }
+
+    #[test]
+    fn test_tag_query() {
+        let tags = vec!["prop".to_string(), "collision".to_string()];
+
+        assert!(TagQuery::parse("").matches(&tags));
+        assert!(TagQuery::parse("Prop").matches(&tags));
+        assert!(TagQuery::parse("prop collision").matches(&tags));
+        assert!(!TagQuery::parse("prop hero").matches(&tags));
+        assert!(TagQuery::parse("prop !hero").matches(&tags));
+        assert!(!TagQuery::parse("!collision").matches(&tags));
+
+        // A lone `!` is ignored rather than excluding an empty tag
+        assert!(TagQuery::parse("!").is_empty());
+    }
+}
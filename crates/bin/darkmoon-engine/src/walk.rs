@@ -0,0 +1,80 @@
+use kajiya_simple::Vec3;
+use serde::{Deserialize, Serialize};
+
+use crate::math::Aabb;
+
+/// Obstacles whose top is at most this far above the feet can be stepped onto
+const WALK_STEP_HEIGHT: f32 = 0.5;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WalkGround {
+    /// Walk on the y=0 plane
+    GroundPlane,
+    /// Walk on top of scene element bounds, falling back to the y=0 plane
+    SceneBounds,
+}
+
+impl Default for WalkGround {
+    fn default() -> Self {
+        Self::GroundPlane
+    }
+}
+
+/// Height of the ground under a walking camera at `eye`.
+///
+/// A downward ray is cast from step height above the feet against the world-space bounds,
+/// each widened horizontally by `collision_radius` so the camera can't slip off edges.
+pub fn walk_ground_height(
+    ground: WalkGround,
+    eye: Vec3,
+    eye_height: f32,
+    collision_radius: f32,
+    world_bounds: impl Iterator<Item = Aabb>,
+) -> f32 {
+    let mut height = 0.0f32;
+
+    if ground == WalkGround::SceneBounds {
+        let origin = Vec3::new(eye.x, eye.y - eye_height + WALK_STEP_HEIGHT, eye.z);
+        let padding = Vec3::new(collision_radius, 0.0, collision_radius);
+
+        for aabb in world_bounds {
+            let aabb = Aabb::new(aabb.min - padding, aabb.max + padding);
+
+            // Boxes reaching above the ray origin are obstacles (or enclose us), not ground
+            if aabb.max.y > origin.y {
+                continue;
+            }
+
+            if let Some(t) = aabb.ray_intersection(origin, -Vec3::Y) {
+                height = height.max(origin.y - t);
+            }
+        }
+    }
+
+    height
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_walk_ground_height() {
+        let eye_height = 1.7;
+        let step = Aabb::new(Vec3::new(0.0, 0.0, 0.0), Vec3::new(2.0, 0.3, 2.0));
+        let wall = Aabb::new(Vec3::new(5.0, 0.0, 0.0), Vec3::new(6.0, 3.0, 6.0));
+        let bounds = || [step, wall].into_iter();
+
+        let on_step = Vec3::new(1.0, 2.0, 1.0);
+        assert_eq!(walk_ground_height(WalkGround::GroundPlane, on_step, eye_height, 0.3, bounds()), 0.0);
+        assert!((walk_ground_height(WalkGround::SceneBounds, on_step, eye_height, 0.3, bounds()) - 0.3).abs() < 1e-6);
+
+        // Just off the edge, but within the collision radius
+        let near_edge = Vec3::new(2.2, 2.0, 1.0);
+        assert!((walk_ground_height(WalkGround::SceneBounds, near_edge, eye_height, 0.3, bounds()) - 0.3).abs() < 1e-6);
+
+        // Too tall to step onto
+        let at_wall = Vec3::new(5.5, 1.7, 1.0);
+        assert_eq!(walk_ground_height(WalkGround::SceneBounds, at_wall, eye_height, 0.3, bounds()), 0.0);
+    }
+}
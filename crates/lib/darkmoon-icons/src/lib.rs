@@ -8,12 +8,28 @@ pub use font_awesome::{
     ICON_SUN,  // Add sun icon for the Outliner
     ICON_SHAPES, ICON_OBJECT_GROUP, ICON_TREE,  // Add more icons for different element types
     ICON_FLOPPY_DISK, ICON_CHECK,  // Add save and check icons for GUI
+    ICON_TRIANGLE_EXCLAMATION,  // Warnings
+    ICON_SQUARE,  // Debug color swatches
+    ICON_TRASH,  // Deleting presets
+    ICON_XMARK,  // Removing tags
+    ICON_EYE, ICON_EYE_SLASH,  // Layer visibility
+    ICON_CIRCLE_INFO,  // Notifications
+    ICON_FILE_EXPORT,  // Mesh export
+    ICON_MAGNIFYING_GLASS,  // Outliner search
+    ICON_PAUSE,  // Paused scene indicator
+    ICON_CLONE,  // Duplicating elements
     FONT_ICON_FILE_NAME_FAS, FONT_ICON_FILE_NAME_FAR
 };
 pub use font_awesome_brands::*;
+
+/// Darkmoon scenes (.dmoon). `ICON_FILM` is left to video files, so the two can't be confused.
+pub const ICON_DMOON_SCENE: char = font_awesome::ICON_MOUNTAIN_SUN;
+
 pub fn get_file_icon(extension: &str) -> char {
     match extension.to_lowercase().as_str() {
-        "dmoon" => ICON_FILM,
+        "dmoon" => ICON_DMOON_SCENE,
+
+        "mp4" | "mov" | "avi" | "mkv" | "webm" => ICON_FILM,
         
         "gltf" | "glb" | "obj" | "fbx" | "dae" | "3ds" | "blend" => ICON_CUBE,
         
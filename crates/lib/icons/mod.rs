@@ -16,7 +16,7 @@ pub const ICON_FA_FOLDER: &str = "\u{f07b}";
 pub const ICON_FA_FOLDER_OPEN: &str = "\u{f07c}";
 
 // Assets específicos del motor
-pub const ICON_FA_SCENE: &str = "\u{f008}";      // Film - Escenas .dmoon
+pub const ICON_FA_SCENE: &str = "\u{e52f}";      // Mountain sun - Escenas .dmoon (film queda para vídeo)
 pub const ICON_FA_MODEL: &str = "\u{f1b2}";      // Cube - Modelos 3D (.gltf, .glb)
 pub const ICON_FA_TEXTURE: &str = "\u{f03e}";    // Image - Texturas e imágenes
 pub const ICON_FA_SHADER: &str = "\u{f0e7}";     // Bolt - Shaders (.hlsl, .glsl, .wgsl)
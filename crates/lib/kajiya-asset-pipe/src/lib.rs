@@ -14,6 +14,8 @@ pub struct MeshAssetProcessParams {
     pub path: PathBuf,
     pub output_name: String,
     pub scale: f32,
+    /// Bake just this glTF node, without its transform, instead of the whole scene
+    pub node: Option<usize>,
 }
 
 pub fn process_mesh_asset(opt: MeshAssetProcessParams) -> Result<()> {
@@ -29,6 +31,7 @@ pub fn process_mesh_asset(opt: MeshAssetProcessParams) -> Result<()> {
             scale: opt.scale,
             //rotation: Quat::from_rotation_y(std::f32::consts::FRAC_PI_2),
             rotation: Quat::IDENTITY,
+            node: opt.node,
         }
         .into_lazy();
 
@@ -273,6 +273,8 @@ pub struct LoadGltfScene {
     pub path: PathBuf,
     pub scale: f32,
     pub rotation: Quat,
+    /// Only load the mesh of the node with this index, in the node's own space
+    pub node: Option<usize>,
 }
 
 impl Hash for LoadGltfScene {
@@ -283,6 +285,7 @@ impl Hash for LoadGltfScene {
         self.rotation.y.to_ne_bytes().hash(state);
         self.rotation.z.to_ne_bytes().hash(state);
         self.rotation.w.to_ne_bytes().hash(state);
+        self.node.hash(state);
     }
 }
 
@@ -446,8 +449,16 @@ impl LazyWorker for LoadGltfScene {
                 self.rotation,
                 Vec3::ZERO,
             );
-            for node in scene.nodes() {
-                iter_gltf_node_tree(&node, xform, &mut process_node);
+            if let Some(node) = self.node {
+                let node = gltf
+                    .nodes()
+                    .nth(node)
+                    .with_context(|| format!("No node {} in {:?}", node, self.path))?;
+                process_node(&node, xform);
+            } else {
+                for node in scene.nodes() {
+                    iter_gltf_node_tree(&node, xform, &mut process_node);
+                }
             }
 
             Ok(res)
@@ -105,6 +105,27 @@ pub fn normalized_path_from_vfs(path: impl Into<PathBuf>) -> anyhow::Result<Path
     Ok(path)
 }
 
+/// Like `normalized_path_from_vfs`, but the path doesn't need to exist yet
+pub fn mounted_path_from_vfs(path: impl Into<PathBuf>) -> anyhow::Result<PathBuf> {
+    let path = path.into();
+
+    for (mount_point, mounted_path) in VFS_MOUNT_POINTS.lock().iter() {
+        if let Ok(rel_path) = path.strip_prefix(mount_point) {
+            return Ok(mounted_path.join(rel_path));
+        }
+    }
+
+    if path.strip_prefix("/").is_ok() {
+        anyhow::bail!(
+            "No vfs mount point for {:?}. Current mount points: {:#?}",
+            path,
+            VFS_MOUNT_POINTS.lock()
+        );
+    }
+
+    Ok(path)
+}
+
 #[derive(Clone, Hash)]
 pub struct LoadFile {
     path: PathBuf,
@@ -43,6 +43,8 @@ pub struct RayTracingInstanceDesc {
     pub blas: Arc<RayTracingAcceleration>,
     pub transformation: Affine3A,
     pub mesh_index: u32,
+    /// Rays only hit this instance if their cull mask shares a bit with this
+    pub mask: u8,
 }
 
 #[derive(Clone)]
@@ -208,7 +210,7 @@ impl Device {
                 GeometryInstance::new(
                     transform,
                     desc.mesh_index, /* instance id */
-                    0xff,
+                    desc.mask,
                     0,
                     /*ash::vk::GeometryInstanceFlagsKHR::TRIANGLE_FACING_CULL_DISABLE
                     | */
@@ -440,7 +442,7 @@ impl Device {
             GeometryInstance::new(
                 transform,
                 desc.mesh_index, /* instance id */
-                0xff,
+                desc.mask,
                 0,
                 /*ash::vk::GeometryInstanceFlagsKHR::TRIANGLE_FACING_CULL_DISABLE
                 | */
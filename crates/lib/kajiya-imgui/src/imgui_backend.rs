@@ -27,6 +27,12 @@ pub struct ImGuiBackend {
     inner: Arc<Mutex<ImGuiBackendInner>>,
     device: Arc<Device>,
     imgui_platform: WinitPlatform,
+    /// Scale the fonts were rasterized at
+    font_scale: f32,
+    /// The style before any UI scaling, so rescaling doesn't compound rounding
+    base_style: imgui::Style,
+    /// Last scale passed to `set_ui_scale`; `None` until the first call
+    ui_scale: Option<f32>,
 }
 
 impl ImGuiBackend {
@@ -36,14 +42,15 @@ impl ImGuiBackend {
         imgui: &mut imgui::Context,
     ) -> Self {
         setup_imgui_style(imgui);
+        let base_style = *imgui.style();
 
         let mut imgui_platform = WinitPlatform::init(imgui);
         imgui_platform.attach_window(imgui.io_mut(), window, HiDpiMode::Locked(1.0));
 
+        let hidpi_factor = window.scale_factor();
         {
             use imgui::{FontConfig, FontGlyphRanges, FontSource};
 
-            let hidpi_factor = window.scale_factor();
             let font_size = (13.0 * hidpi_factor) as f32;
             let icon_font_size = font_size * 2.0 / 3.0; // Font Awesome needs to be smaller
             
@@ -91,6 +98,9 @@ impl ImGuiBackend {
         Self {
             device,
             imgui_platform,
+            font_scale: hidpi_factor as f32,
+            base_style,
+            ui_scale: None,
             inner: Arc::new(Mutex::new(ImGuiBackendInner {
                 imgui_renderer,
                 gfx: None,
@@ -98,6 +108,21 @@ impl ImGuiBackend {
         }
     }
 
+    /// Scale the whole UI, 1.0 being unscaled at 96 DPI. Fonts, the icon font included, are
+    /// scaled from the size `new` rasterized them at, so they stay crisp near the window's
+    /// own scale factor.
+    pub fn set_ui_scale(&mut self, imgui: &mut imgui::Context, scale: f32) {
+        if self.ui_scale == Some(scale) || scale <= 0.0 {
+            return;
+        }
+
+        let style = imgui.style_mut();
+        *style = self.base_style;
+        style.scale_all_sizes(scale);
+        imgui.io_mut().font_global_scale = scale / self.font_scale;
+        self.ui_scale = Some(scale);
+    }
+
     pub fn create_graphics_resources(&mut self, surface_resolution: [u32; 2]) {
         self.inner
             .lock()
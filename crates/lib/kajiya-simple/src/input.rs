@@ -113,6 +113,11 @@ impl GamepadState {
         self.axes.get(&axis).copied().unwrap_or(0.0)
     }
 
+    /// Whether any button is held or any axis is outside its deadzone
+    pub fn has_input(&self) -> bool {
+        !self.buttons_down.is_empty() || self.axes.values().any(|&value| value != 0.0)
+    }
+
     pub fn set_button(&mut self, button: GamepadButton, pressed: bool, value: f32) {
         if pressed {
             self.buttons_down.entry(button).or_insert(GamepadButtonState { ticks: 0, value: 0.0 }).value = value;
@@ -48,6 +48,11 @@ pub struct ImguiContext<'a> {
 
 #[cfg(feature = "dear-imgui")]
 impl<'a> ImguiContext<'a> {
+    /// Scale fonts and widget sizes, see `ImGuiBackend::set_ui_scale`. Cheap when unchanged.
+    pub fn set_ui_scale(&mut self, scale: f32) {
+        self.imgui_backend.set_ui_scale(self.imgui, scale);
+    }
+
     pub fn frame(self, callback: impl FnOnce(&imgui::Ui)) {
         let ui = self
             .imgui_backend
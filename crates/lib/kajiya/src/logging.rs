@@ -1,5 +1,12 @@
+/// Later calls do nothing, so apps can log before building their main loop, which sets up logging too.
 pub fn set_up_logging(default_log_level: log::LevelFilter) -> anyhow::Result<()> {
     use fern::colors::{Color, ColoredLevelConfig};
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    static SET_UP: AtomicBool = AtomicBool::new(false);
+    if SET_UP.swap(true, Ordering::SeqCst) {
+        return Ok(());
+    }
 
     // configure colors for the whole line
     let colors_line = ColoredLevelConfig::new()
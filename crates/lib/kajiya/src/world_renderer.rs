@@ -77,15 +77,32 @@ const MAX_GPU_MESHES: usize = 1024;
 const VERTEX_BUFFER_CAPACITY: usize = 1024 * 1024 * 1024;
 const TLAS_PREALLOCATE_BYTES: usize = 1024 * 1024 * 32;
 
+// Ray tracing instance mask bits. Must match `RT_INSTANCE_MASK_*` in `rt.hlsl`.
+const RT_INSTANCE_MASK_DEFAULT: u8 = 0x01;
+const RT_INSTANCE_MASK_SHADOW_CASTER: u8 = 0x02;
+
 #[derive(Clone, Copy)]
 pub struct InstanceDynamicParameters {
     pub emissive_multiplier: f32,
+    /// When off, shadow rays pass through the instance; it's still seen by all other rays
+    pub casts_shadow: bool,
 }
 
 impl Default for InstanceDynamicParameters {
     fn default() -> Self {
         Self {
             emissive_multiplier: 1.0,
+            casts_shadow: true,
+        }
+    }
+}
+
+impl InstanceDynamicParameters {
+    fn ray_tracing_mask(&self) -> u8 {
+        if self.casts_shadow {
+            RT_INSTANCE_MASK_DEFAULT | RT_INSTANCE_MASK_SHADOW_CASTER
+        } else {
+            RT_INSTANCE_MASK_DEFAULT
         }
     }
 }
@@ -248,6 +265,13 @@ impl DynamicExposureState {
         }
     }
 
+    /// Restart adaptation from the static exposure, so it ramps from there instead of
+    /// jumping to whatever the histogram state was when it was last enabled
+    pub fn reset(&mut self) {
+        self.ev_fast = -DYNAMIC_EXPOSURE_BIAS;
+        self.ev_slow = -DYNAMIC_EXPOSURE_BIAS;
+    }
+
     pub fn update(&mut self, ev: f32, dt: f32) {
         if !self.enabled {
             return;
@@ -844,6 +868,11 @@ impl WorldRenderer {
         }
     }
 
+    /// Every live instance, in no particular order
+    pub fn instance_handles(&self) -> &[InstanceHandle] {
+        &self.instance_handles
+    }
+
     pub fn set_instance_transform(&mut self, inst: InstanceHandle, transform: Affine3A) {
         let index = self.instance_handle_to_index[&inst];
         self.instances[index].transform = transform;
@@ -953,6 +982,7 @@ impl WorldRenderer {
                             blas: self.mesh_blas[inst.mesh.0].clone(),
                             transformation: inst.transform,
                             mesh_index: inst.mesh.0 as u32,
+                            mask: inst.dynamic_parameters.ray_tracing_mask(),
                         })
                         .collect::<Vec<_>>(),
                     preallocate_bytes: TLAS_PREALLOCATE_BYTES,
@@ -985,6 +1015,7 @@ impl WorldRenderer {
                 blas: self.mesh_blas[inst.mesh.0].clone(),
                 transformation: inst.transform,
                 mesh_index: inst.mesh.0 as u32,
+                mask: inst.dynamic_parameters.ray_tracing_mask(),
             })
             .collect::<Vec<_>>();
 
@@ -1,5 +1,5 @@
 use resource_streaming::{
-    ResourceStreamingManager, StreamingConfig, LoadPriority,
+    ResourceStreamingManager, StreamingConfig, LoadPriority, ResourceType,
     initialize_streaming
 };
 use anyhow::Result;
@@ -17,7 +17,14 @@ async fn main() -> Result<()> {
         medium_quality_distance: 100.0,
         low_quality_distance: 300.0,
         enable_predictive_loading: true,
+        lookahead_seconds: 1.0,
         asset_base_path: "assets".to_string(),
+        max_load_retries: 3,
+        load_retry_base_delay_ms: 100,
+        maintenance_interval_ms: 1000,
+        eviction_policy: Default::default(),
+        default_resource_distance: 100.0,
+        lod_hysteresis_distance: 5.0,
     };
     
     // Inicializar el gestor de streaming
@@ -33,9 +40,9 @@ async fn simulate_game_loop(streaming_manager: ResourceStreamingManager) -> Resu
     println!("Iniciando simulación del bucle principal del juego...");
     
     // Solicitar carga de varios recursos
-    let _mesh_handle = streaming_manager.request_resource("meshes/character.gltf", LoadPriority::High);
-    let _texture_handle = streaming_manager.request_resource("textures/character_diffuse.png", LoadPriority::High);
-    let _environment_handle = streaming_manager.request_resource("environments/forest.gltf", LoadPriority::Medium);
+    let _mesh_handle = streaming_manager.request_resource("meshes/character.gltf", LoadPriority::High, ResourceType::Mesh);
+    let _texture_handle = streaming_manager.request_resource("textures/character_diffuse.png", LoadPriority::High, ResourceType::Texture);
+    let _environment_handle = streaming_manager.request_resource("environments/forest.gltf", LoadPriority::Medium, ResourceType::Scene);
     
     // Simular posición de cámara
     let mut camera_position = [0.0, 0.0, 0.0];
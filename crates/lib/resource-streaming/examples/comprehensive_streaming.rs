@@ -19,7 +19,14 @@ async fn main() -> Result<()> {
         medium_quality_distance: 300.0,
         low_quality_distance: 1000.0,
         enable_predictive_loading: true,
+        lookahead_seconds: 1.0,
         asset_base_path: "assets".to_string(),
+        max_load_retries: 3,
+        load_retry_base_delay_ms: 100,
+        maintenance_interval_ms: 1000,
+        eviction_policy: Default::default(),
+        default_resource_distance: 100.0,
+        lod_hysteresis_distance: 5.0,
     };
 
     println!("Initializing resource streaming system...");
@@ -47,16 +54,16 @@ async fn simulate_basic_loading(manager: &ResourceStreamingManager) {
 
     // Request various resources with different priorities
     let resources = vec![
-        ("meshes/character.gltf", LoadPriority::Critical),
-        ("textures/terrain.png", LoadPriority::High),
-        ("audio/ambient.ogg", LoadPriority::Medium),
-        ("meshes/building.gltf", LoadPriority::Low),
+        ("meshes/character.gltf", LoadPriority::Critical, ResourceType::Mesh),
+        ("textures/terrain.png", LoadPriority::High, ResourceType::Texture),
+        ("audio/ambient.ogg", LoadPriority::Medium, ResourceType::Audio),
+        ("meshes/building.gltf", LoadPriority::Low, ResourceType::Mesh),
     ];
 
     // Request all resources
     let mut handles = Vec::new();
-    for (path, priority) in &resources {
-        let handle = manager.request_resource(path, *priority);
+    for (path, priority, resource_type) in &resources {
+        let handle = manager.request_resource(path, *priority, *resource_type);
         handles.push((handle, *path));
         println!("Requested resource: {} with priority {:?}", path, priority);
     }
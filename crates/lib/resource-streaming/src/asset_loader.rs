@@ -22,6 +22,8 @@ pub struct LoadRequest {
     pub path: String,
     pub priority: LoadPriority,
     pub lod_level: LodLevel,
+    /// Los reintentos no se procesan antes de este momento
+    pub not_before: Option<std::time::Instant>,
 }
 
 /// Tipo de asset detectado
@@ -53,12 +55,24 @@ impl AssetData {
     }
 }
 
+/// Información propia del formato del asset, leída de su cabecera
+#[derive(Debug, Clone, PartialEq)]
+pub enum FormatInfo {
+    Image { width: u32, height: u32 },
+    Mesh { node_count: usize, mesh_count: usize },
+    /// Formato sin información adicional, o que no se pudo leer
+    None,
+}
+
 /// Metadatos de un asset
 #[derive(Debug, Clone)]
 pub struct AssetMetadata {
     pub original_size: u64,
     pub compressed_size: u64,
+    /// Tamaño que ocupa el asset una vez decodificado (p.ej. los texels RGBA8 de una imagen)
+    pub logical_size: u64,
     pub format: String,
+    pub format_info: FormatInfo,
     pub creation_time: std::time::SystemTime,
     pub lod_level: LodLevel,
 }
@@ -68,7 +82,9 @@ impl Default for AssetMetadata {
         Self {
             original_size: 0,
             compressed_size: 0,
+            logical_size: 0,
             format: String::new(),
+            format_info: FormatInfo::None,
             creation_time: std::time::UNIX_EPOCH,
             lod_level: LodLevel::Medium,
         }
@@ -143,10 +159,19 @@ impl AssetLoader {
         // Procesar según el nivel de detalle solicitado
         let processed_data = self.process_lod_data(data, &asset_type, request.lod_level)?;
         
+        let format = self.get_format_string(&full_path);
+        let format_info = read_format_info(&processed_data, &asset_type, &format);
+        let logical_size = match format_info {
+            FormatInfo::Image { width, height } => width as u64 * height as u64 * 4,
+            _ => processed_data.len() as u64,
+        };
+
         let metadata = AssetMetadata {
             original_size,
             compressed_size: processed_data.len() as u64,
-            format: self.get_format_string(&full_path),
+            logical_size,
+            format,
+            format_info,
             creation_time: fs::metadata(&full_path)?.created().unwrap_or(std::time::SystemTime::now()),
             lod_level: request.lod_level,
         };
@@ -183,6 +208,7 @@ impl AssetLoader {
                     path: file_path,
                     priority: LoadPriority::Low,
                     lod_level: LodLevel::Low, // Precarga con baja calidad
+                    not_before: None,
                 };
                 load_requests.push(request);
             }
@@ -285,3 +311,74 @@ impl AssetLoader {
         Ok(matching_files)
     }
 }
+
+/// Lee la información de formato de la cabecera del asset, sin decodificarlo entero. Si no se
+/// puede leer se devuelve `FormatInfo::None`: la carga sigue siendo válida, solo pierde los metadatos.
+fn read_format_info(data: &[u8], asset_type: &AssetType, format: &str) -> FormatInfo {
+    let result = match (asset_type, format) {
+        (AssetType::Texture, _) => image::io::Reader::new(std::io::Cursor::new(data))
+            .with_guessed_format()
+            .map_err(anyhow::Error::from)
+            .and_then(|reader| Ok(reader.into_dimensions()?))
+            .map(|(width, height)| FormatInfo::Image { width, height }),
+        (AssetType::Mesh, "GLTF") | (AssetType::Mesh, "GLB") => gltf::Gltf::from_slice(data)
+            .map_err(anyhow::Error::from)
+            .map(|gltf| FormatInfo::Mesh {
+                node_count: gltf.nodes().count(),
+                mesh_count: gltf.meshes().count(),
+            }),
+        _ => Ok(FormatInfo::None),
+    };
+
+    result.unwrap_or_else(|err| {
+        debug!("No se pudo leer la información de formato ({}): {}", format, err);
+        FormatInfo::None
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn load(loader: &AssetLoader, path: &str) -> AssetData {
+        let request = LoadRequest {
+            resource_id: path.to_string(),
+            path: path.to_string(),
+            priority: LoadPriority::High,
+            lod_level: LodLevel::High,
+            not_before: None,
+        };
+        futures::executor::block_on(loader.load_asset(&request)).unwrap()
+    }
+
+    #[test]
+    fn test_loaded_image_reports_dimensions() {
+        let base_path = std::env::temp_dir().join(format!("asset-loader-image-test-{}", std::process::id()));
+        let loader = AssetLoader::new(1, &base_path.to_string_lossy()).unwrap();
+        image::RgbaImage::new(7, 3).save(base_path.join("test.png")).unwrap();
+
+        let asset = load(&loader, "test.png");
+        assert_eq!(asset.asset_type, AssetType::Texture);
+        assert_eq!(asset.metadata.format, "PNG");
+        assert_eq!(asset.metadata.format_info, FormatInfo::Image { width: 7, height: 3 });
+        assert_eq!(asset.metadata.logical_size, 7 * 3 * 4);
+
+        let _ = std::fs::remove_dir_all(base_path);
+    }
+
+    #[test]
+    fn test_loaded_gltf_reports_node_count() {
+        let base_path = std::env::temp_dir().join(format!("asset-loader-gltf-test-{}", std::process::id()));
+        let loader = AssetLoader::new(1, &base_path.to_string_lossy()).unwrap();
+        std::fs::write(
+            base_path.join("test.gltf"),
+            r#"{"asset": {"version": "2.0"}, "nodes": [{}, {"children": [0]}], "scenes": [{"nodes": [1]}]}"#,
+        )
+        .unwrap();
+
+        let asset = load(&loader, "test.gltf");
+        assert_eq!(asset.metadata.format_info, FormatInfo::Mesh { node_count: 2, mesh_count: 0 });
+
+        let _ = std::fs::remove_dir_all(base_path);
+    }
+}
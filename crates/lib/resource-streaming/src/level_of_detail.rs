@@ -96,6 +96,30 @@ impl LodManager {
         }
     }
     
+    /// Como `calculate_lod_level`, pero para cambiar desde `current` la distancia tiene que pasar
+    /// el umbral en más de `margin`; dentro de ese margen se queda en el nivel actual
+    pub fn calculate_lod_level_with_hysteresis(
+        &self,
+        distance: f32,
+        resource_type: &ResourceType,
+        current: LodLevel,
+        margin: f32,
+    ) -> LodLevel {
+        let target = self.calculate_lod_level(distance, resource_type);
+        if target == current {
+            return current;
+        }
+
+        // Se comprueba el nivel como si la cámara estuviera `margin` más cerca del nivel actual
+        let margin = margin.max(0.0);
+        let confirmed_distance = if (target as u8) > (current as u8) {
+            distance + margin
+        } else {
+            (distance - margin).max(0.0)
+        };
+        self.calculate_lod_level(confirmed_distance, resource_type)
+    }
+    
     /// Calcula el nivel de LOD basado en múltiples factores
     pub fn calculate_lod_advanced(
         &self,
@@ -179,8 +203,8 @@ impl LodManager {
     }
 }
 
-/// Tipo de recurso para cálculos de LOD
-#[derive(Debug, Clone, PartialEq)]
+/// Tipo de recurso para cálculos de LOD y de prioridad
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum ResourceType {
     Mesh,
     Texture,
@@ -203,6 +227,16 @@ impl From<&str> for ResourceType {
     }
 }
 
+impl ResourceType {
+    /// Tipo de un recurso según la extensión de su ruta
+    pub fn from_path(path: &str) -> Self {
+        std::path::Path::new(path)
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map_or(ResourceType::Other, ResourceType::from)
+    }
+}
+
 /// Estadísticas de uso de LOD
 #[derive(Debug, Default, Clone)]
 pub struct LodStats {
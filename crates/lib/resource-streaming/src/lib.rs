@@ -4,10 +4,10 @@ pub mod asset_loader;
 pub mod level_of_detail;
 pub mod priority_system;
 
-pub use resource_manager::ResourceStreamingManager;
-pub use streaming_cache::{StreamingCache, CacheConfig};
-pub use asset_loader::{AssetLoader, LoadRequest, LoadPriority};
-pub use level_of_detail::{LodLevel, LodManager};
+pub use resource_manager::{ResourceStreamingManager, SelfTestReport};
+pub use streaming_cache::{StreamingCache, CacheConfig, EvictionPolicy};
+pub use asset_loader::{AssetLoader, AssetMetadata, FormatInfo, LoadRequest, LoadPriority};
+pub use level_of_detail::{LodLevel, LodManager, ResourceType};
 pub use priority_system::{PriorityCalculator, StreamingPriority};
 
 use anyhow::Result;
@@ -27,8 +27,25 @@ pub struct StreamingConfig {
     pub low_quality_distance: f32,
     /// Habilitar precarga predictiva
     pub enable_predictive_loading: bool,
+    /// Segundos que la precarga predictiva se adelanta a la cámara, a la velocidad con que se
+    /// movió desde la actualización anterior
+    pub lookahead_seconds: f32,
     /// Directorio base para assets
     pub asset_base_path: String,
+    /// Reintentos de una carga fallida antes de marcar el recurso como `Failed`
+    pub max_load_retries: u32,
+    /// Espera antes del primer reintento; se duplica en cada reintento
+    pub load_retry_base_delay_ms: u64,
+    /// Intervalo del hilo que mantiene el cache y las estadísticas aunque no dejen de llegar
+    /// solicitudes de carga. Con 0 el mantenimiento solo se hace cuando el worker está ocioso.
+    pub maintenance_interval_ms: u64,
+    /// Qué desaloja el cache cuando se llena, y qué quita su mantenimiento
+    pub eviction_policy: EvictionPolicy,
+    /// Distancia que se asume para los recursos sin posición ni `bounds` registrados
+    pub default_resource_distance: f32,
+    /// Margen que la distancia tiene que pasar de un umbral de LOD para cambiar de nivel, para
+    /// no recargar una y otra vez un recurso cuando la cámara se queda cerca del umbral
+    pub lod_hysteresis_distance: f32,
 }
 
 impl Default for StreamingConfig {
@@ -40,7 +57,14 @@ impl Default for StreamingConfig {
             medium_quality_distance: 150.0,
             low_quality_distance: 500.0,
             enable_predictive_loading: true,
+            lookahead_seconds: 1.0,
             asset_base_path: "assets".to_string(),
+            max_load_retries: 3,
+            load_retry_base_delay_ms: 100,
+            maintenance_interval_ms: 1000,
+            eviction_policy: EvictionPolicy::default(),
+            default_resource_distance: 100.0,
+            lod_hysteresis_distance: 5.0,
         }
     }
 }
@@ -1,5 +1,7 @@
 use crate::asset_loader::LoadPriority;
+use crate::level_of_detail::ResourceType;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 /// Prioridad de streaming calculada dinámicamente
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
@@ -18,6 +20,20 @@ impl Default for StreamingPriority {
     }
 }
 
+impl StreamingPriority {
+    /// Prioridad para un nivel numérico, saturando en `Critical`
+    pub fn from_level(level: u8) -> Self {
+        match level {
+            0 => StreamingPriority::Invisible,
+            1 => StreamingPriority::VeryLow,
+            2 => StreamingPriority::Low,
+            3 => StreamingPriority::Medium,
+            4 => StreamingPriority::High,
+            _ => StreamingPriority::Critical,
+        }
+    }
+}
+
 impl From<LoadPriority> for StreamingPriority {
     fn from(load_priority: LoadPriority) -> Self {
         match load_priority {
@@ -29,6 +45,17 @@ impl From<LoadPriority> for StreamingPriority {
     }
 }
 
+impl From<StreamingPriority> for LoadPriority {
+    fn from(priority: StreamingPriority) -> Self {
+        match priority {
+            StreamingPriority::Invisible | StreamingPriority::VeryLow | StreamingPriority::Low => LoadPriority::Low,
+            StreamingPriority::Medium => LoadPriority::Medium,
+            StreamingPriority::High => LoadPriority::High,
+            StreamingPriority::Critical => LoadPriority::Critical,
+        }
+    }
+}
+
 impl From<StreamingPriority> for u8 {
     fn from(priority: StreamingPriority) -> Self {
         priority as u8
@@ -82,8 +109,13 @@ pub struct PriorityConfig {
     pub importance_weight: f32,
     /// Umbral de distancia máxima para considerar un recurso
     pub max_distance_threshold: f32,
-    /// Umbral mínimo de prioridad para cargar un recurso
+    /// Puntaje mínimo (0.0-1.0) para cargar un recurso; ver `should_load_resource`
     pub min_priority_threshold: f32,
+    /// Niveles de prioridad que sube un recurso visible en el frustum de la cámara
+    pub in_view_boost: u8,
+    /// Importancia base (0.0-1.0) de cada tipo de recurso. Los tipos que no aparecen valen 0.5,
+    /// salvo `Other`, que se estima a partir de la ruta.
+    pub type_importance: HashMap<ResourceType, f32>,
 }
 
 impl Default for PriorityConfig {
@@ -96,7 +128,15 @@ impl Default for PriorityConfig {
             recency_weight: 0.1,
             importance_weight: 0.05,
             max_distance_threshold: 1000.0,
-            min_priority_threshold: 0.1,
+            min_priority_threshold: 0.2,
+            in_view_boost: 1,
+            type_importance: HashMap::from([
+                (ResourceType::Scene, 0.9),
+                (ResourceType::Mesh, 0.7),
+                (ResourceType::Texture, 0.6),
+                (ResourceType::Material, 0.6),
+                (ResourceType::Audio, 0.4),
+            ]),
         }
     }
 }
@@ -105,21 +145,30 @@ impl Default for PriorityConfig {
 #[derive(Debug, Clone)]
 pub struct PriorityCalculator {
     config: PriorityConfig,
+    /// Importancia fijada para rutas concretas; tiene preferencia sobre la del tipo
+    importance_overrides: HashMap<String, f32>,
 }
 
 impl PriorityCalculator {
     pub fn new() -> Self {
-        Self {
-            config: PriorityConfig::default(),
-        }
+        Self::with_config(PriorityConfig::default())
     }
     
     pub fn with_config(config: PriorityConfig) -> Self {
-        Self { config }
+        Self {
+            config,
+            importance_overrides: HashMap::new(),
+        }
     }
     
     /// Calcula la prioridad de streaming basándose en múltiples factores
-    pub fn calculate_priority(&self, distance: f32, _camera_direction: &[f32; 3], resource_path: &str) -> StreamingPriority {
+    pub fn calculate_priority(
+        &self,
+        distance: f32,
+        _camera_direction: &[f32; 3],
+        resource_path: &str,
+        resource_type: ResourceType,
+    ) -> StreamingPriority {
         // Por ahora, una implementación simplificada basada principalmente en distancia
         // En una implementación completa, usarías todos los factores
         
@@ -132,11 +181,31 @@ impl PriorityCalculator {
         
         // Aplicar pesos (implementación simplificada)
         let final_priority = distance_priority * self.config.distance_weight + 
-                           self.get_base_importance(resource_path) * self.config.importance_weight;
+                           self.get_base_importance(resource_path, resource_type) * self.config.importance_weight;
         
         self.priority_score_to_enum(final_priority)
     }
     
+    /// Como `calculate_priority`, pero los recursos dentro del frustum de la cámara
+    /// suben `in_view_boost` niveles, para que lo visible cargue antes que lo que
+    /// está a la misma distancia fuera de pantalla
+    pub fn calculate_priority_in_view(
+        &self,
+        distance: f32,
+        camera_direction: &[f32; 3],
+        resource_path: &str,
+        resource_type: ResourceType,
+        in_view: bool,
+    ) -> StreamingPriority {
+        let priority = self.calculate_priority(distance, camera_direction, resource_path, resource_type);
+
+        if in_view {
+            StreamingPriority::from_level(priority as u8 + self.config.in_view_boost)
+        } else {
+            priority
+        }
+    }
+    
     /// Calcula la prioridad usando todos los factores disponibles
     pub fn calculate_priority_advanced(&self, factors: &PriorityFactors) -> StreamingPriority {
         let weighted_score = 
@@ -201,9 +270,10 @@ impl PriorityCalculator {
         }
     }
     
-    /// Determina si un recurso debe ser cargado basándose en su prioridad
+    /// Determina si un recurso debe ser cargado: su prioridad tiene que llegar al nivel que
+    /// corresponde a `min_priority_threshold`
     pub fn should_load_resource(&self, priority: StreamingPriority) -> bool {
-        priority as u8 >= StreamingPriority::Low as u8
+        priority >= self.priority_score_to_enum(self.config.min_priority_threshold)
     }
     
     /// Determina si un recurso debe ser descargado para liberar memoria
@@ -229,6 +299,17 @@ impl PriorityCalculator {
     pub fn get_config(&self) -> &PriorityConfig {
         &self.config
     }
+
+    /// Fija la importancia base (0.0-1.0) de una ruta, por encima de la de su tipo
+    pub fn set_importance_override(&mut self, resource_path: &str, importance: f32) {
+        self.importance_overrides
+            .insert(resource_path.to_string(), importance.clamp(0.0, 1.0));
+    }
+
+    /// Vuelve a calcular la importancia de una ruta a partir de su tipo
+    pub fn clear_importance_override(&mut self, resource_path: &str) {
+        self.importance_overrides.remove(resource_path);
+    }
     
     // Métodos privados
     
@@ -249,9 +330,22 @@ impl PriorityCalculator {
         }
     }
     
-    /// Obtiene la importancia base de un recurso según su tipo/ruta
-    fn get_base_importance(&self, resource_path: &str) -> f32 {
-        // Determinar importancia basándose en el tipo de archivo o ruta
+    /// Obtiene la importancia base de un recurso: la fijada para su ruta, si la hay, y si no
+    /// la de su tipo
+    fn get_base_importance(&self, resource_path: &str, resource_type: ResourceType) -> f32 {
+        if let Some(&importance) = self.importance_overrides.get(resource_path) {
+            return importance;
+        }
+
+        match self.config.type_importance.get(&resource_type) {
+            Some(&importance) => importance,
+            None if resource_type == ResourceType::Other => self.guess_importance_from_path(resource_path),
+            None => 0.5,
+        }
+    }
+
+    /// Importancia estimada por palabras de la ruta, para recursos de tipo desconocido
+    fn guess_importance_from_path(&self, resource_path: &str) -> f32 {
         if resource_path.contains("ui") || resource_path.contains("hud") {
             1.0 // UI es siempre crítica
         } else if resource_path.contains("character") || resource_path.contains("player") {
@@ -315,3 +409,44 @@ impl PriorityStats {
         self.total_resources - self.invisible_count
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_in_view_resource_outranks_equidistant_off_screen_one() {
+        let calculator = PriorityCalculator::new();
+        let camera_direction = [0.0, 0.0, -1.0];
+
+        let on_screen = calculator.calculate_priority_in_view(100.0, &camera_direction, "meshes/rock.gltf", ResourceType::Mesh, true);
+        let off_screen = calculator.calculate_priority_in_view(100.0, &camera_direction, "meshes/rock.gltf", ResourceType::Mesh, false);
+
+        assert!(on_screen > off_screen);
+        assert_eq!(off_screen, calculator.calculate_priority(100.0, &camera_direction, "meshes/rock.gltf", ResourceType::Mesh));
+    }
+
+    #[test]
+    fn test_in_view_boost_saturates() {
+        assert_eq!(StreamingPriority::from_level(StreamingPriority::Critical as u8 + 1), StreamingPriority::Critical);
+    }
+
+    #[test]
+    fn test_base_importance_comes_from_type_then_path_overrides() {
+        let mut calculator = PriorityCalculator::new();
+
+        // The type decides, whatever words the path contains
+        assert_eq!(calculator.get_base_importance("ui/terrain_rock.gltf", ResourceType::Mesh), 0.7);
+        // Only resources of unknown type are guessed from their path
+        assert_eq!(calculator.get_base_importance("ui/panel.bin", ResourceType::Other), 1.0);
+
+        calculator.set_importance_override("ui/terrain_rock.gltf", 0.2);
+        assert_eq!(calculator.get_base_importance("ui/terrain_rock.gltf", ResourceType::Mesh), 0.2);
+        calculator.clear_importance_override("ui/terrain_rock.gltf");
+
+        let mut config = PriorityConfig::default();
+        config.type_importance.insert(ResourceType::Mesh, 0.1);
+        calculator.update_config(config);
+        assert_eq!(calculator.get_base_importance("ui/terrain_rock.gltf", ResourceType::Mesh), 0.1);
+    }
+}
@@ -1,16 +1,18 @@
 use crate::{StreamingConfig, ResourceId, ResourceHandle};
 use crate::streaming_cache::{StreamingCache, CacheConfig};
-use crate::asset_loader::{AssetLoader, LoadRequest, LoadPriority};
-use crate::level_of_detail::{LodManager, LodLevel};
+use crate::asset_loader::{AssetData, AssetLoader, AssetMetadata, LoadRequest, LoadPriority};
+use crate::level_of_detail::{LodManager, LodLevel, ResourceType};
 use crate::priority_system::{PriorityCalculator, StreamingPriority};
 
-use anyhow::Result;
+use anyhow::{Context, Result};
+use std::path::Path;
 use std::sync::Arc;
 use std::collections::HashMap;
 use parking_lot::RwLock;
 use crossbeam_channel::{unbounded, Sender, Receiver};
 use std::thread::JoinHandle;
 use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Instant;
 use log::{info, debug, warn};
 
 /// Estado de un recurso en el sistema de streaming
@@ -32,23 +34,116 @@ pub struct ResourceInfo {
     pub id: ResourceId,
     pub handle: ResourceHandle,
     pub path: String,
+    /// Tipo indicado al solicitarlo; decide su importancia y sus distancias de LOD
+    pub resource_type: ResourceType,
     pub state: ResourceState,
     pub priority: StreamingPriority,
     pub last_accessed: std::time::Instant,
     pub memory_usage: u64,
+    /// Reintentos consumidos por la última carga
+    pub retry_count: u32,
+    /// Volumen que ocupa en el mundo lo que usa este recurso, si se conoce
+    pub bounds: Option<ResourceBounds>,
+    /// Posición en el mundo registrada para el recurso; tiene preferencia sobre `bounds`
+    /// para calcular la distancia a la cámara
+    pub world_position: Option<[f32; 3]>,
+    /// Metadatos de la última carga correcta
+    pub metadata: Option<AssetMetadata>,
+    /// Marcado por `cancel_resource`: el worker descarta las cargas pendientes antes de leerlas
+    /// o de guardarlas en el cache, y `update` no lo vuelve a cargar hasta que se pida otra vez
+    /// con `request_resource` o `force_load`
+    pub cancelled: bool,
+    /// Nivel de detalle que se está cargando para sustituir al actual. Mientras tanto el recurso
+    /// sigue `Loaded` con los datos anteriores en el cache.
+    pub pending_lod: Option<LodLevel>,
+    /// Prioridad y LOD que tenía al sacarlo del cache para hacer sitio o por `unload`. `update` no
+    /// lo vuelve a cargar mientras sigan siendo los mismos, o lo descargado se volvería a cargar
+    /// enseguida. La limpieza del cache no lo marca: lo que caduca se recarga si hace falta.
+    pub evicted: Option<(StreamingPriority, LodLevel)>,
 }
 
+impl ResourceInfo {
+    /// Deja el recurso como `NotLoaded` tras sacar sus datos del cache, para que `update` lo
+    /// vuelva a cargar en cuanto lo necesite
+    fn mark_not_loaded(&mut self) {
+        self.state = ResourceState::NotLoaded;
+        self.pending_lod = None;
+        self.memory_usage = 0;
+        self.evicted = None;
+    }
+
+    /// Como `mark_not_loaded`, pero recordando con qué prioridad y LOD estaba cargado
+    fn mark_evicted(&mut self) {
+        let evicted = match self.state {
+            ResourceState::Loaded(lod_level) => Some((self.priority, lod_level)),
+            _ => None,
+        };
+        self.mark_not_loaded();
+        self.evicted = evicted;
+    }
+}
+
+/// Esfera envolvente, en espacio de mundo, de los elementos que usan un recurso
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ResourceBounds {
+    pub center: [f32; 3],
+    pub radius: f32,
+}
+
+/// Reintentos con backoff exponencial para cargas que fallan de forma transitoria
+/// (p.ej. un archivo bloqueado mientras se exporta)
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    pub max_retries: u32,
+    pub base_delay: std::time::Duration,
+}
+
+impl RetryPolicy {
+    pub fn from_config(config: &StreamingConfig) -> Self {
+        Self {
+            max_retries: config.max_load_retries,
+            base_delay: std::time::Duration::from_millis(config.load_retry_base_delay_ms),
+        }
+    }
+
+    /// Espera antes del reintento número `retry` (empezando en 0)
+    pub fn delay_for_retry(&self, retry: u32) -> std::time::Duration {
+        self.base_delay * 2u32.saturating_pow(retry.min(16))
+    }
+}
+
+/// Resultado de `ResourceStreamingManager::self_test`
+#[derive(Debug, Clone)]
+pub struct SelfTestReport {
+    /// Qué falló, o `None` si el recurso hizo todo el recorrido
+    pub error: Option<String>,
+    /// Posibles problemas de configuración que no impiden pasar la prueba
+    pub warnings: Vec<String>,
+    /// Desde la solicitud hasta que el worker dejó el recurso cargado
+    pub load_time: Option<std::time::Duration>,
+    pub total_time: std::time::Duration,
+}
+
+impl SelfTestReport {
+    pub fn passed(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+const SELF_TEST_CONTENTS: &[u8] = b"darkmoon resource streaming self-test";
+
 /// Gestor principal del sistema de streaming de recursos
 pub struct ResourceStreamingManager {
     config: StreamingConfig,
     cache: Arc<RwLock<StreamingCache>>,
     asset_loader: AssetLoader,
     lod_manager: LodManager,
-    priority_calculator: PriorityCalculator,
+    priority_calculator: Arc<RwLock<PriorityCalculator>>,
     
     // Estado interno
     resources: Arc<RwLock<HashMap<ResourceId, ResourceInfo>>>,
-    load_queue: Arc<RwLock<Vec<LoadRequest>>>,
+    /// Posición de la cámara en la última actualización, para estimar su velocidad
+    camera_motion: parking_lot::Mutex<Option<([f32; 3], Instant)>>,
     
     // Canal de comunicación para solicitudes de carga
     load_sender: Sender<LoadRequest>,
@@ -57,6 +152,7 @@ pub struct ResourceStreamingManager {
     // Control del background worker
     worker_shutdown: Arc<AtomicBool>,
     worker_handle: Option<JoinHandle<()>>,
+    maintenance_handle: Option<JoinHandle<()>>,
     
     // Estadísticas
     stats: Arc<RwLock<StreamingStats>>,
@@ -71,6 +167,8 @@ pub struct StreamingStats {
     pub cache_hit_rate: f32,
     pub memory_used: u64,
     pub memory_limit: u64,
+    /// Veces que se ha ejecutado el mantenimiento del cache
+    pub maintenance_runs: u64,
 }
 
 impl ResourceStreamingManager {
@@ -79,7 +177,7 @@ impl ResourceStreamingManager {
         
         let cache_config = CacheConfig {
             max_size: config.max_cache_size,
-            eviction_policy: crate::streaming_cache::EvictionPolicy::LeastRecentlyUsed,
+            eviction_policy: config.eviction_policy.clone(),
         };
         
         let cache = Arc::new(RwLock::new(StreamingCache::new(cache_config)));
@@ -89,12 +187,11 @@ impl ResourceStreamingManager {
             config.medium_quality_distance,
             config.low_quality_distance,
         );
-        let priority_calculator = PriorityCalculator::new();
+        let priority_calculator = Arc::new(RwLock::new(PriorityCalculator::new()));
         
         let (load_sender, load_receiver) = unbounded::<LoadRequest>();
         
         let resources = Arc::new(RwLock::new(HashMap::new()));
-        let load_queue = Arc::new(RwLock::new(Vec::new()));
         let stats = Arc::new(RwLock::new(StreamingStats::default()));
         let worker_shutdown = Arc::new(AtomicBool::new(false));
         
@@ -106,16 +203,18 @@ impl ResourceStreamingManager {
             lod_manager,
             priority_calculator,
             resources: resources.clone(),
-            load_queue: load_queue.clone(),
+            camera_motion: parking_lot::Mutex::new(None),
             load_sender,
             load_receiver: Arc::new(parking_lot::Mutex::new(Some(load_receiver))),
             worker_shutdown: worker_shutdown.clone(),
             worker_handle: None,
+            maintenance_handle: None,
             stats: stats.clone(),
         };
         
         // Iniciar el worker en background
         manager.start_background_worker()?;
+        manager.start_maintenance_timer();
         
         info!("Sistema de streaming inicializado con éxito");
         Ok(manager)
@@ -134,26 +233,53 @@ impl ResourceStreamingManager {
         let lod_manager = self.lod_manager.clone();
         let stats = self.stats.clone();
         let shutdown = self.worker_shutdown.clone();
+        let retry_policy = RetryPolicy::from_config(&self.config);
         
         let handle = std::thread::spawn(move || {
             info!("Background streaming worker iniciado");
+
+            // Reintentos esperando su `not_before`; mientras tanto se atienden las demás solicitudes
+            let mut deferred: Vec<LoadRequest> = Vec::new();
+            let idle_timeout = std::time::Duration::from_millis(100);
             
             while !shutdown.load(Ordering::Relaxed) {
-                // Procesar solicitudes de carga con timeout
-                match load_receiver.recv_timeout(std::time::Duration::from_millis(100)) {
+                let now = std::time::Instant::now();
+                let due = deferred
+                    .iter()
+                    .position(|request| request.not_before.map_or(true, |time| time <= now));
+                let next_request = match due {
+                    Some(index) => Ok(deferred.swap_remove(index)),
+                    None => {
+                        let timeout = deferred
+                            .iter()
+                            .filter_map(|request| request.not_before)
+                            .min()
+                            .map_or(idle_timeout, |time| time.saturating_duration_since(now).min(idle_timeout));
+                        load_receiver.recv_timeout(timeout)
+                    }
+                };
+
+                match next_request {
+                    Ok(load_request) if load_request.not_before.map_or(false, |time| time > std::time::Instant::now()) => {
+                        deferred.push(load_request);
+                    }
                     Ok(load_request) => {
-                        futures::executor::block_on(Self::process_load_request(
+                        let retry = futures::executor::block_on(Self::process_load_request(
                             load_request,
                             &resources,
                             &cache,
                             &asset_loader,
                             &lod_manager,
                             &stats,
+                            &retry_policy,
                         ));
+                        deferred.extend(retry);
                     }
                     Err(crossbeam_channel::RecvTimeoutError::Timeout) => {
-                        // Timeout - realizar tareas de mantenimiento
-                        futures::executor::block_on(Self::perform_maintenance(&resources, &cache, &stats));
+                        // Timeout - realizar tareas de mantenimiento, salvo si solo se esperaba a un reintento
+                        if deferred.is_empty() {
+                            futures::executor::block_on(Self::perform_maintenance(&resources, &cache, &stats));
+                        }
                     }
                     Err(crossbeam_channel::RecvTimeoutError::Disconnected) => {
                         debug!("Load receiver closed, shutting down worker");
@@ -168,8 +294,37 @@ impl ResourceStreamingManager {
         self.worker_handle = Some(handle);
         Ok(())
     }
+
+    /// Inicia el hilo que ejecuta el mantenimiento cada `maintenance_interval_ms`. El worker solo
+    /// lo hace cuando no recibe solicitudes, así que bajo carga continua el cache crecería sin límite.
+    fn start_maintenance_timer(&mut self) {
+        if self.config.maintenance_interval_ms == 0 {
+            return;
+        }
+
+        let interval = std::time::Duration::from_millis(self.config.maintenance_interval_ms);
+        let resources = self.resources.clone();
+        let cache = self.cache.clone();
+        let stats = self.stats.clone();
+        let shutdown = self.worker_shutdown.clone();
+
+        self.maintenance_handle = Some(std::thread::spawn(move || {
+            let mut next_run = std::time::Instant::now() + interval;
+
+            while !shutdown.load(Ordering::Relaxed) {
+                // `shutdown` despierta al hilo, así que no hay que esperar al siguiente intervalo para cerrar
+                std::thread::park_timeout(next_run.saturating_duration_since(std::time::Instant::now()));
+
+                if std::time::Instant::now() >= next_run {
+                    futures::executor::block_on(Self::perform_maintenance(&resources, &cache, &stats));
+                    next_run += interval;
+                }
+            }
+        }));
+    }
     
-    /// Procesa una solicitud de carga de recurso en background
+    /// Procesa una solicitud de carga de recurso en background. Si la carga falla y quedan
+    /// reintentos, devuelve la solicitud a reintentar, que el worker aparca hasta su `not_before`.
     async fn process_load_request(
         request: LoadRequest,
         resources: &Arc<RwLock<HashMap<ResourceId, ResourceInfo>>>,
@@ -177,35 +332,132 @@ impl ResourceStreamingManager {
         asset_loader: &AssetLoader,
         _lod_manager: &LodManager,
         stats: &Arc<RwLock<StreamingStats>>,
-    ) {
+        retry_policy: &RetryPolicy,
+    ) -> Option<LoadRequest> {
         debug!("Procesando solicitud de carga: {:?}", request.path);
+
+        if Self::abandon_unneeded_load(&request, resources) {
+            debug!("Carga descartada antes de empezar: {}", request.path);
+            return None;
+        }
         
-        // Verificar si ya está en cache
-        {
-            let cache_read = cache.read();
-            if cache_read.contains(&request.path) {
-                debug!("Recurso encontrado en cache: {}", request.path);
-                Self::update_resource_state(
-                    &request.path,
-                    ResourceState::Loaded(request.lod_level),
-                    resources,
-                );
-                Self::update_stats(stats, 1, 0, 0, 0);
-                return;
+        // En un cambio de LOD lo que hay en el cache es el nivel anterior, que se sigue usando
+        // hasta tener el nuevo
+        let switching_lod = resources
+            .read()
+            .get(&request.resource_id)
+            .map_or(false, |info| info.pending_lod == Some(request.lod_level));
+
+        // Verificar si ya está en cache, contándolo como un uso para la política de desalojo. El
+        // cache se suelta antes de tocar `resources`, que en `update` se bloquea primero.
+        let cached_size = if switching_lod { None } else { cache.write().touch(&request.path) };
+        if let Some(size) = cached_size {
+            debug!("Recurso encontrado en cache: {}", request.path);
+            if let Some(info) = resources.write().get_mut(&request.path) {
+                info.memory_usage = size;
             }
+            Self::update_resource_state(
+                &request.path,
+                ResourceState::Loaded(request.lod_level),
+                resources,
+            );
+            Self::update_stats(stats, 1, 0, 0, 0);
+            return None;
         }
         
         // Marcar como loading
-        Self::update_resource_state(&request.path, ResourceState::Loading, resources);
+        if !switching_lod {
+            Self::update_resource_state(&request.path, ResourceState::Loading, resources);
+        }
         
-        // Intentar cargar el recurso
-        match asset_loader.load_asset(&request).await {
+        let result = asset_loader.load_asset(&request).await;
+        Self::handle_load_result(request, result, retry_policy, resources, cache, stats)
+    }
+
+    /// Termina una carga, salvo que haya fallado y queden reintentos: entonces devuelve la
+    /// solicitud a reintentar con backoff exponencial, sin bloquear el worker mientras tanto
+    fn handle_load_result(
+        request: LoadRequest,
+        result: Result<AssetData>,
+        retry_policy: &RetryPolicy,
+        resources: &Arc<RwLock<HashMap<ResourceId, ResourceInfo>>>,
+        cache: &Arc<RwLock<StreamingCache>>,
+        stats: &Arc<RwLock<StreamingStats>>,
+    ) -> Option<LoadRequest> {
+        if let Err(err) = &result {
+            if let Some(retry) = Self::schedule_retry(&request, err, retry_policy, resources) {
+                return Some(retry);
+            }
+        }
+
+        Self::finish_load_request(&request, result, resources, cache, stats);
+        None
+    }
+
+    /// La solicitud a reintentar tras un error, o `None` si ya no quedan reintentos
+    fn schedule_retry(
+        request: &LoadRequest,
+        err: &anyhow::Error,
+        retry_policy: &RetryPolicy,
+        resources: &Arc<RwLock<HashMap<ResourceId, ResourceInfo>>>,
+    ) -> Option<LoadRequest> {
+        let mut resources_write = resources.write();
+        let info = resources_write.get_mut(&request.resource_id)?;
+        if info.retry_count >= retry_policy.max_retries {
+            return None;
+        }
+
+        let delay = retry_policy.delay_for_retry(info.retry_count);
+        warn!(
+            "Error cargando {} (intento {}/{}), reintentando en {:?}: {}",
+            request.path,
+            info.retry_count + 1,
+            retry_policy.max_retries + 1,
+            delay,
+            err
+        );
+        info.retry_count += 1;
+
+        Some(LoadRequest {
+            not_before: Some(std::time::Instant::now() + delay),
+            ..request.clone()
+        })
+    }
+
+    /// Guarda el resultado de una carga en el cache y actualiza el estado del recurso
+    fn finish_load_request(
+        request: &LoadRequest,
+        result: Result<AssetData>,
+        resources: &Arc<RwLock<HashMap<ResourceId, ResourceInfo>>>,
+        cache: &Arc<RwLock<StreamingCache>>,
+        stats: &Arc<RwLock<StreamingStats>>,
+    ) {
+        match result {
+            Ok(_) if Self::abandon_unneeded_load(request, resources) => {
+                debug!("Carga descartada sin guardarla en el cache: {}", request.path);
+                Self::update_stats(stats, 0, -1, 0, 0);
+            }
             Ok(asset_data) => {
                 // Cargar exitosamente - agregar al cache
-                {
+                let (evicted, cached_size) = {
                     let mut cache_write = cache.write();
-                    cache_write.insert(request.path.clone(), asset_data.data);
+                    let evicted = cache_write.insert(request.path.clone(), asset_data.data);
+                    (evicted, cache_write.entry_size(&request.path).unwrap_or(0))
+                };
+
+                let mut resources_write = resources.write();
+                if let Some(info) = resources_write.get_mut(&request.path) {
+                    info.memory_usage = cached_size;
+                    info.metadata = Some(asset_data.metadata);
+                    info.pending_lod = None;
                 }
+                // Lo desalojado para hacer sitio tendrá que volver a cargarse
+                for id in evicted {
+                    if let Some(info) = resources_write.get_mut(&id) {
+                        info.mark_evicted();
+                    }
+                }
+                drop(resources_write);
                 
                 Self::update_resource_state(
                     &request.path,
@@ -219,6 +471,18 @@ impl ResourceStreamingManager {
             Err(err) => {
                 let error_msg = format!("Error cargando {}: {}", request.path, err);
                 warn!("{}", error_msg);
+
+                // Si falló un cambio de LOD, los datos del nivel anterior dejan de contar como cargados
+                let switched_lod = resources
+                    .write()
+                    .get_mut(&request.resource_id)
+                    .map_or(false, |info| {
+                        info.memory_usage = 0;
+                        info.pending_lod.take().is_some()
+                    });
+                if switched_lod {
+                    cache.write().remove(&request.path);
+                }
                 
                 Self::update_resource_state(
                     &request.path,
@@ -230,6 +494,25 @@ impl ResourceStreamingManager {
         }
     }
     
+    /// Si el resultado de una carga ya no hace falta porque el recurso se olvidó, se canceló su
+    /// carga o quedó `Invisible`. Los que siguen registrados quedan como `NotLoaded`, salvo que
+    /// se estuviera cambiando su LOD: esos se quedan con el nivel que ya tenían.
+    fn abandon_unneeded_load(
+        request: &LoadRequest,
+        resources: &Arc<RwLock<HashMap<ResourceId, ResourceInfo>>>,
+    ) -> bool {
+        match resources.write().get_mut(&request.resource_id) {
+            Some(info) if info.cancelled || info.priority == StreamingPriority::Invisible => {
+                if info.pending_lod.take().is_none() {
+                    info.state = ResourceState::NotLoaded;
+                }
+                true
+            }
+            Some(_) => false,
+            None => true,
+        }
+    }
+
     /// Actualiza el estado de un recurso
     fn update_resource_state(
         resource_id: &str,
@@ -260,34 +543,56 @@ impl ResourceStreamingManager {
     
     /// Realiza tareas de mantenimiento periódico
     async fn perform_maintenance(
-        _resources: &Arc<RwLock<HashMap<ResourceId, ResourceInfo>>>,
+        resources: &Arc<RwLock<HashMap<ResourceId, ResourceInfo>>>,
         cache: &Arc<RwLock<StreamingCache>>,
         stats: &Arc<RwLock<StreamingStats>>,
     ) {
         // Ejecutar limpieza del cache
-        {
-            let mut cache_write = cache.write();
-            cache_write.cleanup();
-        }
+        Self::cleanup_cache(resources, cache);
         
         // Actualizar estadísticas del cache
         {
             let cache_read = cache.read();
             let mut stats_write = stats.write();
             stats_write.memory_used = cache_read.get_memory_usage();
+            stats_write.maintenance_runs += 1;
+        }
+    }
+    
+    /// Limpia el cache y deja como `NotLoaded` los recursos que saca de él
+    fn cleanup_cache(
+        resources: &Arc<RwLock<HashMap<ResourceId, ResourceInfo>>>,
+        cache: &Arc<RwLock<StreamingCache>>,
+    ) {
+        // El cache se suelta antes de tocar `resources`, que en `update` se bloquea primero
+        let removed = cache.write().cleanup();
+        if removed.is_empty() {
+            return;
+        }
+
+        let mut resources_write = resources.write();
+        for id in &removed {
+            if let Some(info) = resources_write.get_mut(id) {
+                info.mark_not_loaded();
+            }
         }
+        debug!("Limpieza del cache: {} recursos eliminados", removed.len());
     }
     
-    /// Solicita la carga de un recurso con prioridad específica
-    pub fn request_resource(&self, path: &str, priority: LoadPriority) -> ResourceHandle {
+    /// Registra un recurso con prioridad específica. Queda como `NotLoaded` hasta que `update`
+    /// decida cargarlo según la prioridad que le calcule; para cargarlo ya, `force_load`.
+    pub fn request_resource(&self, path: &str, priority: LoadPriority, resource_type: ResourceType) -> ResourceHandle {
         let resource_id = path.to_string();
         let handle = self.generate_handle(&resource_id);
         
         let mut resources = self.resources.write();
         
-        // Si el recurso ya existe, actualizar prioridad si es mayor
+        // Si el recurso ya existe, actualizar prioridad si es mayor. Pedirlo de nuevo permite
+        // volver a cargarlo aunque se hubiera desalojado o cancelado su carga.
         if let Some(info) = resources.get_mut(&resource_id) {
             info.last_accessed = std::time::Instant::now();
+            info.evicted = None;
+            info.cancelled = false;
             if priority as u8 > info.priority as u8 {
                 info.priority = priority.into();
             }
@@ -299,52 +604,146 @@ impl ResourceStreamingManager {
             id: resource_id.clone(),
             handle,
             path: path.to_string(),
-            state: ResourceState::Loading,
+            resource_type,
+            state: ResourceState::NotLoaded,
             priority: priority.into(),
             last_accessed: std::time::Instant::now(),
             memory_usage: 0,
+            retry_count: 0,
+            bounds: None,
+            world_position: None,
+            metadata: None,
+            cancelled: false,
+            pending_lod: None,
+            evicted: None,
         };
-        
-        resources.insert(resource_id.clone(), resource_info);
-        
-        // Enviar solicitud de carga al worker en background
-        let load_request = LoadRequest {
-            resource_id: resource_id.clone(),
-            path: path.to_string(),
-            priority,
-            lod_level: self.lod_manager.calculate_lod_level(100.0, &crate::level_of_detail::ResourceType::Other), // TODO: usar posición real y tipo correcto
-        };
-        
-        if let Err(e) = self.load_sender.send(load_request) {
-            warn!("Error enviando solicitud de carga para {}: {}", path, e);
-            // Actualizar estado a fallido
-            if let Some(info) = resources.get_mut(&resource_id) {
-                info.state = ResourceState::Failed(format!("Error enviando solicitud: {}", e));
+
+        resources.insert(resource_id, resource_info);
+        handle
+    }
+    
+    /// Registra varios recursos de una vez, p.ej. todas las dependencias de una escena recién
+    /// cargada, para que `update` los vaya cargando antes de necesitarlos
+    pub fn prefetch<P: AsRef<str>>(
+        &self,
+        resources: impl IntoIterator<Item = (P, ResourceType)>,
+        priority: LoadPriority,
+    ) -> Vec<ResourceHandle> {
+        let handles: Vec<ResourceHandle> = resources
+            .into_iter()
+            .map(|(path, resource_type)| self.request_resource(path.as_ref(), priority, resource_type))
+            .collect();
+
+        debug!("Precarga de {} recursos con prioridad {:?}", handles.len(), priority);
+        handles
+    }
+    
+    /// Asocia un recurso ya solicitado con su posición en el mundo. Devuelve `false` si no se conoce el recurso.
+    pub fn set_resource_bounds(&self, path: &str, bounds: ResourceBounds) -> bool {
+        match self.resources.write().get_mut(path) {
+            Some(info) => {
+                info.bounds = Some(bounds);
+                true
             }
+            None => false,
         }
-        
-        handle
     }
     
+    /// Fija la importancia (0.0-1.0) de una ruta para el cálculo de prioridad, por encima de la
+    /// que le corresponde por su tipo. Se aplica a partir del siguiente `update`.
+    pub fn set_importance_override(&self, path: &str, importance: f32) {
+        self.priority_calculator.write().set_importance_override(path, importance);
+    }
+
+    /// Quita la importancia fijada con `set_importance_override`
+    pub fn clear_importance_override(&self, path: &str) {
+        self.priority_calculator.write().clear_importance_override(path);
+    }
+    
+    /// Registra la posición en el mundo de un recurso, con la que `update` elige su nivel de detalle.
+    /// Devuelve `false` si no se conoce el recurso.
+    pub fn register_resource_world_position(&self, handle: ResourceHandle, position: [f32; 3]) -> bool {
+        match self.resources.write().values_mut().find(|info| info.handle == handle) {
+            Some(info) => {
+                info.world_position = Some(position);
+                true
+            }
+            None => false,
+        }
+    }
+
     /// Actualiza el sistema de streaming basado en la posición de la cámara
     pub fn update(&self, camera_position: &[f32; 3], camera_direction: &[f32; 3]) {
+        self.update_with_visibility(camera_position, camera_direction, |_| false);
+    }
+
+    /// Como `update`, pero los recursos cuyos `bounds` cumplen `is_in_view`
+    /// (normalmente, una prueba contra el frustum de la cámara) reciben más prioridad
+    pub fn update_with_visibility(
+        &self,
+        camera_position: &[f32; 3],
+        camera_direction: &[f32; 3],
+        is_in_view: impl Fn(&ResourceBounds) -> bool,
+    ) {
         debug!("Actualizando sistema de streaming desde posición {:?}", camera_position);
         
-        // Calcular prioridades basadas en distancia y dirección de la cámara
+        // Calcular prioridades y niveles de detalle basados en distancia y dirección de la cámara
+        let stats = self.get_stats();
+        let memory_pressure = if stats.memory_limit > 0 {
+            stats.memory_used as f32 / stats.memory_limit as f32
+        } else {
+            0.0
+        };
+
+        let predicted_position = self.predict_camera_position(camera_position, camera_direction);
+
+        let priority_calculator = self.priority_calculator.read();
         let mut resources = self.resources.write();
         for (_, resource_info) in resources.iter_mut() {
-            // Aquí calcularías la distancia del recurso a la cámara
-            // Por ahora usamos un placeholder
-            let distance = self.calculate_resource_distance(&resource_info.path, camera_position);
-            let new_priority = self.priority_calculator.calculate_priority(
-                distance,
+            let distance = self.calculate_resource_distance(resource_info, camera_position);
+            // Lo que está cerca de donde estará la cámara se prioriza como si ya lo estuviera,
+            // pero el nivel de detalle sigue la distancia actual
+            let priority_distance = match predicted_position {
+                Some(position) => distance.min(self.calculate_resource_distance(resource_info, &position)),
+                None => distance,
+            };
+            let in_view = resource_info.bounds.as_ref().map_or(false, &is_in_view);
+            let new_priority = priority_calculator.calculate_priority_in_view(
+                priority_distance,
                 camera_direction,
                 &resource_info.path,
+                resource_info.resource_type,
+                in_view,
             );
             
             resource_info.priority = new_priority;
             resource_info.last_accessed = std::time::Instant::now();
+
+            // Las cargas en curso se corrigen en la siguiente actualización, y así no se encolan
+            // cargas duplicadas
+            let lod_level = self.lod_for_distance(resource_info, distance);
+            let should_unload = priority_calculator.should_unload_resource(new_priority, memory_pressure);
+            match resource_info.state {
+                ResourceState::Loaded(_) if should_unload => self.unload(resource_info),
+                ResourceState::Loaded(current_lod)
+                    if current_lod != lod_level && resource_info.pending_lod.is_none() =>
+                {
+                    self.reload_at_lod(resource_info, lod_level);
+                }
+                // Sin mirar `should_unload` se cargaría lo que la siguiente actualización descarga
+                ResourceState::NotLoaded
+                    if priority_calculator.should_load_resource(new_priority)
+                        && !should_unload
+                        && !resource_info.cancelled
+                        && resource_info.evicted != Some((new_priority, lod_level)) =>
+                {
+                    self.reload_at_lod(resource_info, lod_level);
+                }
+                _ => {}
+            }
         }
+        drop(resources);
+        drop(priority_calculator);
         
         // Actualizar estadísticas
         self.update_instance_stats();
@@ -361,6 +760,80 @@ impl ResourceStreamingManager {
             .map(|info| info.state.clone())
     }
     
+    /// Vuelve a encolar la carga de un recurso con prioridad `Critical`, p.ej. para recuperar uno
+    /// atascado o fallido. Devuelve `false` si no se conoce el recurso.
+    pub fn force_load(&self, handle: ResourceHandle) -> bool {
+        let mut resources = self.resources.write();
+        let info = match resources.values_mut().find(|info| info.handle == handle) {
+            Some(info) => info,
+            None => return false,
+        };
+
+        info.priority = StreamingPriority::Critical;
+        info.state = ResourceState::Loading;
+        info.cancelled = false;
+        info.pending_lod = None;
+        info.evicted = None;
+        info.retry_count = 0;
+        info.last_accessed = std::time::Instant::now();
+
+        let load_request = LoadRequest {
+            resource_id: info.id.clone(),
+            path: info.path.clone(),
+            priority: LoadPriority::Critical,
+            lod_level: self.lod_for_distance(info, self.config.default_resource_distance),
+            not_before: None,
+        };
+
+        if let Err(e) = self.load_sender.send(load_request) {
+            warn!("Error enviando solicitud de carga para {}: {}", info.path, e);
+            info.state = ResourceState::Failed(format!("Error enviando solicitud: {}", e));
+        }
+
+        info!("Carga forzada de {}", info.path);
+        true
+    }
+
+    /// Cancela la carga en curso de un recurso, p.ej. cuando la cámara se aleja antes de que el
+    /// worker llegue a él. El recurso queda como `NotLoaded` y lo que se llegue a cargar no entra
+    /// en el cache. Devuelve `false` si no se conoce el recurso o no se estaba cargando.
+    pub fn cancel_resource(&self, handle: ResourceHandle) -> bool {
+        let mut resources = self.resources.write();
+        let info = match resources.values_mut().find(|info| info.handle == handle) {
+            Some(info) => info,
+            None => return false,
+        };
+        if info.state != ResourceState::Loading {
+            return false;
+        }
+
+        info.cancelled = true;
+        info.state = ResourceState::NotLoaded;
+
+        debug!("Carga cancelada: {}", info.path);
+        true
+    }
+
+    /// Saca un recurso del cache y lo marca como `NotLoaded`, sin olvidarlo: una carga
+    /// posterior lo vuelve a traer. Devuelve `false` si no se conoce el recurso.
+    pub fn evict(&self, handle: ResourceHandle) -> bool {
+        let mut resources = self.resources.write();
+        let info = match resources.values_mut().find(|info| info.handle == handle) {
+            Some(info) => info,
+            None => return false,
+        };
+
+        self.unload(info);
+
+        info!("Recurso expulsado manualmente: {}", info.path);
+        true
+    }
+
+    /// Copia del estado de todos los recursos gestionados, p.ej. para inspeccionarlos desde la GUI
+    pub fn resources_snapshot(&self) -> Vec<ResourceInfo> {
+        self.resources.read().values().cloned().collect()
+    }
+    
     /// Obtiene las estadísticas actuales del streaming
     pub fn get_stats(&self) -> StreamingStats {
         (*self.stats.read()).clone()
@@ -390,7 +863,18 @@ impl ResourceStreamingManager {
     /// Cierra el sistema de streaming y limpia recursos
     pub async fn shutdown(&mut self) -> Result<()> {
         info!("Cerrando sistema de streaming...");
-        
+        self.stop_workers();
+        info!("Sistema de streaming cerrado");
+        Ok(())
+    }
+
+    /// Señala a los hilos en background que terminen y los espera. Los clones no tienen
+    /// hilos propios, así que para ellos no hace nada.
+    fn stop_workers(&mut self) {
+        if self.worker_handle.is_none() && self.maintenance_handle.is_none() {
+            return;
+        }
+
         // Señalar al worker que se cierre
         self.worker_shutdown.store(true, Ordering::Relaxed);
         
@@ -400,9 +884,91 @@ impl ResourceStreamingManager {
                 warn!("Error esperando el worker: {:?}", e);
             }
         }
-        
-        info!("Sistema de streaming cerrado");
-        Ok(())
+
+        if let Some(handle) = self.maintenance_handle.take() {
+            handle.thread().unpark();
+            if let Err(e) = handle.join() {
+                warn!("Error esperando el hilo de mantenimiento: {:?}", e);
+            }
+        }
+    }
+
+    /// Comprueba el recorrido completo de una carga (solicitud, worker, cache y lectura) con un
+    /// asset de prueba escrito en `asset_base_path`, para diagnosticar problemas de configuración
+    /// o un worker bloqueado. Bloquea hasta que termina, como mucho `timeout` esperando al worker.
+    pub fn self_test(&self, timeout: std::time::Duration) -> SelfTestReport {
+        let start = std::time::Instant::now();
+        let path = format!(".streaming-self-test-{}.bin", std::process::id());
+        let base_path = Path::new(&self.config.asset_base_path);
+        let full_path = base_path.join(&path);
+
+        let mut warnings = Vec::new();
+        let base_is_empty = std::fs::read_dir(base_path).map_or(true, |mut entries| entries.next().is_none());
+        if base_is_empty {
+            warnings.push(format!(
+                "El directorio base {} está vacío; ¿es correcto asset_base_path?",
+                base_path.display()
+            ));
+        }
+
+        let mut load_time = None;
+        let result = self.run_self_test(&path, &full_path, timeout, &mut load_time);
+
+        // No dejar rastro de la prueba, haya ido como haya ido
+        self.resources.write().remove(&path);
+        self.cache.write().remove(&path);
+        let _ = std::fs::remove_file(&full_path);
+
+        let report = SelfTestReport {
+            error: result.err().map(|err| format!("{:#}", err)),
+            warnings,
+            load_time,
+            total_time: start.elapsed(),
+        };
+        info!("Autoprueba de streaming: {:?}", report);
+        report
+    }
+
+    fn run_self_test(
+        &self,
+        path: &str,
+        full_path: &Path,
+        timeout: std::time::Duration,
+        load_time: &mut Option<std::time::Duration>,
+    ) -> Result<()> {
+        std::fs::write(full_path, SELF_TEST_CONTENTS)
+            .with_context(|| format!("No se pudo escribir el asset de prueba en {}", full_path.display()))?;
+
+        // Por si quedó algo de una prueba anterior, que haría saltarse al worker
+        self.resources.write().remove(path);
+        self.cache.write().remove(&path.to_string());
+
+        let requested = std::time::Instant::now();
+        let handle = self.request_resource(path, LoadPriority::Critical, ResourceType::Other);
+        self.force_load(handle);
+        loop {
+            match self.get_resource_state(handle) {
+                Some(ResourceState::Loaded(_)) => break,
+                Some(ResourceState::Failed(err)) => anyhow::bail!("El worker no pudo cargar el asset de prueba: {}", err),
+                None => anyhow::bail!("El recurso de prueba desapareció durante la carga"),
+                Some(ResourceState::Loading) | Some(ResourceState::NotLoaded) => {}
+            }
+
+            if requested.elapsed() > timeout {
+                anyhow::bail!(
+                    "El worker no cargó el asset de prueba en {:?}; puede estar bloqueado o detenido",
+                    timeout
+                );
+            }
+            std::thread::sleep(std::time::Duration::from_millis(5));
+        }
+        *load_time = Some(requested.elapsed());
+
+        match self.cache.write().get(&path.to_string()) {
+            Some(data) if data.as_slice() == SELF_TEST_CONTENTS => Ok(()),
+            Some(_) => anyhow::bail!("El cache devolvió datos distintos de los del asset de prueba"),
+            None => anyhow::bail!("El asset de prueba se cargó pero no está en el cache"),
+        }
     }
 
     /// Limpia el cache manualmente
@@ -414,8 +980,7 @@ impl ResourceStreamingManager {
     
     /// Fuerza la recolección de basura en el cache
     pub fn force_garbage_collection(&self) {
-        let mut cache = self.cache.write();
-        cache.cleanup();
+        Self::cleanup_cache(&self.resources, &self.cache);
         info!("Garbage collection ejecutado manualmente");
     }
 
@@ -430,10 +995,110 @@ impl ResourceStreamingManager {
         hasher.finish()
     }
     
-    fn calculate_resource_distance(&self, _resource_path: &str, _camera_position: &[f32; 3]) -> f32 {
-        // Placeholder - en una implementación real, calcularías la distancia
-        // basada en la posición del recurso en el mundo
-        100.0
+    /// Distancia de la cámara al recurso: a su posición registrada, o si no a la superficie de sus
+    /// `bounds`. Sin ninguna de las dos, `default_resource_distance`.
+    fn calculate_resource_distance(&self, info: &ResourceInfo, camera_position: &[f32; 3]) -> f32 {
+        let (center, radius) = match (info.world_position, info.bounds) {
+            (Some(position), _) => (position, 0.0),
+            (None, Some(bounds)) => (bounds.center, bounds.radius),
+            (None, None) => return self.config.default_resource_distance,
+        };
+
+        let distance = center
+            .iter()
+            .zip(camera_position)
+            .map(|(a, b)| (a - b) * (a - b))
+            .sum::<f32>()
+            .sqrt();
+        (distance - radius).max(0.0)
+    }
+
+    /// Punto `lookahead_seconds` por delante de la cámara, a la velocidad con que avanzó desde la
+    /// actualización anterior. `None` si la carga predictiva está desactivada o la cámara no
+    /// avanza en la dirección en que mira.
+    fn predict_camera_position(&self, camera_position: &[f32; 3], camera_direction: &[f32; 3]) -> Option<[f32; 3]> {
+        if !self.config.enable_predictive_loading {
+            return None;
+        }
+
+        let now = Instant::now();
+        let (previous_position, previous_time) = self.camera_motion.lock().replace((*camera_position, now))?;
+        let delta = now.duration_since(previous_time).as_secs_f32();
+        let direction_length = camera_direction.iter().map(|d| d * d).sum::<f32>().sqrt();
+        if delta <= 0.0 || direction_length <= 0.0 {
+            return None;
+        }
+
+        // Solo cuenta lo que la cámara avanza hacia donde mira; al retroceder no hay nada que adelantar
+        let forward_speed = (0..3)
+            .map(|i| (camera_position[i] - previous_position[i]) * camera_direction[i])
+            .sum::<f32>()
+            / direction_length
+            / delta;
+        if forward_speed <= 0.0 {
+            return None;
+        }
+
+        // Acotada para que un salto de la cámara no pida recursos de la otra punta del mundo
+        let lookahead_distance =
+            (forward_speed * self.config.lookahead_seconds).min(self.config.low_quality_distance);
+        let scale = lookahead_distance / direction_length;
+        Some([
+            camera_position[0] + camera_direction[0] * scale,
+            camera_position[1] + camera_direction[1] * scale,
+            camera_position[2] + camera_direction[2] * scale,
+        ])
+    }
+
+    /// Nivel de detalle para un recurso a `distance`; los ya cargados solo cambian de nivel al
+    /// pasar el umbral en más de `lod_hysteresis_distance`
+    fn lod_for_distance(&self, info: &ResourceInfo, distance: f32) -> LodLevel {
+        match info.state {
+            ResourceState::Loaded(current) => self.lod_manager.calculate_lod_level_with_hysteresis(
+                distance,
+                &info.resource_type,
+                current,
+                self.config.lod_hysteresis_distance,
+            ),
+            _ => self.lod_manager.calculate_lod_level(distance, &info.resource_type),
+        }
+    }
+
+    /// Saca los datos de un recurso del cache, dejándolo como `NotLoaded`
+    fn unload(&self, info: &mut ResourceInfo) {
+        debug!("Descargando {} (prioridad {:?})", info.path, info.priority);
+
+        self.cache.write().remove(&info.id);
+        info.mark_evicted();
+    }
+
+    /// Encola la carga de un recurso a otro nivel de detalle, o la primera si no estaba cargado.
+    /// Un recurso cargado sigue `Loaded` con sus datos actuales en el cache hasta que el worker
+    /// los sustituye por los del nuevo nivel.
+    fn reload_at_lod(&self, info: &mut ResourceInfo, lod_level: LodLevel) {
+        debug!("Cambiando LOD de {}: {:?} -> {:?}", info.path, info.state, lod_level);
+
+        if matches!(info.state, ResourceState::Loaded(_)) {
+            info.pending_lod = Some(lod_level);
+        } else {
+            info.state = ResourceState::Loading;
+            info.memory_usage = 0;
+        }
+        info.evicted = None;
+        info.retry_count = 0;
+
+        let load_request = LoadRequest {
+            resource_id: info.id.clone(),
+            path: info.path.clone(),
+            priority: info.priority.into(),
+            lod_level,
+            not_before: None,
+        };
+
+        if let Err(e) = self.load_sender.send(load_request) {
+            warn!("Error enviando solicitud de carga para {}: {}", info.path, e);
+            info.state = ResourceState::Failed(format!("Error enviando solicitud: {}", e));
+        }
     }
     
     fn update_instance_stats(&self) {
@@ -453,6 +1118,12 @@ impl ResourceStreamingManager {
     }
 }
 
+impl Drop for ResourceStreamingManager {
+    fn drop(&mut self) {
+        self.stop_workers();
+    }
+}
+
 impl Clone for ResourceStreamingManager {
     fn clone(&self) -> Self {
         // Para clonado, no incluimos el worker handle ni receiver
@@ -463,12 +1134,572 @@ impl Clone for ResourceStreamingManager {
             lod_manager: self.lod_manager.clone(),
             priority_calculator: self.priority_calculator.clone(),
             resources: self.resources.clone(),
-            load_queue: self.load_queue.clone(),
+            camera_motion: parking_lot::Mutex::new(*self.camera_motion.lock()),
             load_sender: self.load_sender.clone(),
             load_receiver: Arc::new(parking_lot::Mutex::new(None)),
             worker_shutdown: Arc::new(AtomicBool::new(false)),
             worker_handle: None,
+            maintenance_handle: None,
             stats: self.stats.clone(),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::streaming_cache::EvictionPolicy;
+
+    fn test_request(path: &str) -> LoadRequest {
+        LoadRequest {
+            resource_id: path.to_string(),
+            path: path.to_string(),
+            priority: LoadPriority::High,
+            lod_level: LodLevel::High,
+            not_before: None,
+        }
+    }
+
+    fn test_resources(path: &str) -> Arc<RwLock<HashMap<ResourceId, ResourceInfo>>> {
+        let info = ResourceInfo {
+            id: path.to_string(),
+            handle: 0,
+            path: path.to_string(),
+            resource_type: ResourceType::from_path(path),
+            state: ResourceState::Loading,
+            priority: LoadPriority::High.into(),
+            last_accessed: std::time::Instant::now(),
+            memory_usage: 0,
+            retry_count: 0,
+            bounds: None,
+            world_position: None,
+            metadata: None,
+            cancelled: false,
+            pending_lod: None,
+            evicted: None,
+        };
+        Arc::new(RwLock::new(HashMap::from([(path.to_string(), info)])))
+    }
+
+    fn test_cache() -> Arc<RwLock<StreamingCache>> {
+        Arc::new(RwLock::new(StreamingCache::new(CacheConfig {
+            max_size: 1024 * 1024,
+            eviction_policy: EvictionPolicy::LeastRecentlyUsed,
+        })))
+    }
+
+    fn asset(data: Vec<u8>) -> AssetData {
+        AssetData {
+            asset_type: crate::asset_loader::AssetType::Mesh,
+            data,
+            metadata: Default::default(),
+        }
+    }
+
+    /// Runs a load whose first `failures` attempts fail, and returns the final state
+    fn run_flaky_load(failures: u32, max_retries: u32) -> ResourceInfo {
+        let path = "meshes/flaky.gltf";
+        let mut request = test_request(path);
+        let resources = test_resources(path);
+        let cache = test_cache();
+        let stats = Arc::new(RwLock::new(StreamingStats::default()));
+        // Long enough that sleeping through it would hang the test
+        let retry_policy = RetryPolicy {
+            max_retries,
+            base_delay: std::time::Duration::from_secs(60),
+        };
+
+        let mut attempts = 0;
+        loop {
+            attempts += 1;
+            let result = if attempts <= failures {
+                Err(anyhow::anyhow!("file locked"))
+            } else {
+                Ok(asset(vec![1, 2, 3]))
+            };
+
+            let requested = std::time::Instant::now();
+            match ResourceStreamingManager::handle_load_result(request, result, &retry_policy, &resources, &cache, &stats) {
+                Some(retry) => {
+                    assert!(retry.not_before.unwrap() >= requested + retry_policy.base_delay);
+                    request = retry;
+                }
+                None => break,
+            }
+        }
+
+        let resources_read = resources.read();
+        resources_read[path].clone()
+    }
+
+    #[test]
+    fn test_load_succeeds_after_transient_failures() {
+        let info = run_flaky_load(2, 3);
+        assert_eq!(info.state, ResourceState::Loaded(LodLevel::High));
+        assert_eq!(info.retry_count, 2);
+    }
+
+    #[test]
+    fn test_load_fails_once_retries_are_exhausted() {
+        let info = run_flaky_load(5, 2);
+        assert!(matches!(info.state, ResourceState::Failed(_)));
+        assert_eq!(info.retry_count, 2);
+    }
+
+    #[test]
+    fn test_maintenance_unloads_expired_resources() {
+        let path = "meshes/expired.gltf";
+        let resources = test_resources(path);
+        {
+            let mut resources_write = resources.write();
+            let info = resources_write.get_mut(path).unwrap();
+            info.state = ResourceState::Loaded(LodLevel::High);
+            info.memory_usage = 4;
+        }
+        let cache = Arc::new(RwLock::new(StreamingCache::new(CacheConfig {
+            max_size: 1024,
+            eviction_policy: EvictionPolicy::TimeToLive(std::time::Duration::ZERO),
+        })));
+        cache.write().insert(path.to_string(), vec![0; 4]);
+        let stats = Arc::new(RwLock::new(StreamingStats::default()));
+
+        futures::executor::block_on(ResourceStreamingManager::perform_maintenance(&resources, &cache, &stats));
+
+        assert!(!cache.read().contains(&path.to_string()));
+        let info = resources.read()[path].clone();
+        assert_eq!(info.state, ResourceState::NotLoaded);
+        assert_eq!(info.memory_usage, 0);
+        assert_eq!(stats.read().memory_used, 0);
+    }
+
+    #[test]
+    fn test_prefetch_registers_all_paths() {
+        let base_path = std::env::temp_dir().join(format!("streaming-prefetch-test-{}", std::process::id()));
+        let manager = ResourceStreamingManager::new(StreamingConfig {
+            asset_base_path: base_path.to_string_lossy().to_string(),
+            max_load_retries: 0,
+            ..Default::default()
+        })
+        .unwrap();
+
+        let paths = ["meshes/a/scene.gltf", "meshes/b/scene.gltf", "textures/c.png"];
+        let handles = manager.prefetch(paths.map(|path| (path, ResourceType::from_path(path))), LoadPriority::High);
+
+        assert_eq!(handles.len(), paths.len());
+        for (path, handle) in paths.iter().zip(&handles) {
+            assert!(manager.resources.read().contains_key(*path));
+            assert!(manager.get_resource_state(*handle).is_some());
+        }
+        assert_eq!(manager.resources_snapshot().len(), paths.len());
+
+        let _ = std::fs::remove_dir_all(base_path);
+    }
+
+    fn test_manager(name: &str) -> (ResourceStreamingManager, std::path::PathBuf) {
+        test_manager_with_config(name, StreamingConfig::default())
+    }
+
+    /// A manager for `config`, with its assets in a directory of its own and no retries
+    fn test_manager_with_config(name: &str, config: StreamingConfig) -> (ResourceStreamingManager, std::path::PathBuf) {
+        let base_path = std::env::temp_dir().join(format!("streaming-{}-test-{}", name, std::process::id()));
+        let manager = ResourceStreamingManager::new(StreamingConfig {
+            asset_base_path: base_path.to_string_lossy().to_string(),
+            max_load_retries: 0,
+            ..config
+        })
+        .unwrap();
+        (manager, base_path)
+    }
+
+    /// Registers `NotLoaded` resources with the given handles, and world positions if any. They're
+    /// added directly rather than requested, so nothing reaches the worker until the test says so.
+    fn register_resources(manager: &ResourceStreamingManager, resources: &[(ResourceHandle, &str, Option<[f32; 3]>)]) {
+        for &(handle, path, position) in resources {
+            let mut info = test_resources(path).read()[path].clone();
+            info.handle = handle;
+            info.state = ResourceState::NotLoaded;
+            info.world_position = position;
+            manager.resources.write().insert(path.to_string(), info);
+        }
+    }
+
+    fn manager_with_resources(
+        name: &str,
+        resources: &[(ResourceHandle, &str, Option<[f32; 3]>)],
+    ) -> (ResourceStreamingManager, std::path::PathBuf) {
+        let (manager, base_path) = test_manager(name);
+        register_resources(&manager, resources);
+        (manager, base_path)
+    }
+
+    #[test]
+    fn test_force_load_boosts_to_critical() {
+        let (manager, base_path) = test_manager("force-load");
+
+        let handle = manager.request_resource("meshes/stuck.gltf", LoadPriority::Low, ResourceType::Mesh);
+        manager.resources.write().get_mut("meshes/stuck.gltf").unwrap().retry_count = 3;
+
+        assert!(manager.force_load(handle));
+        let info = manager.resources.read()["meshes/stuck.gltf"].clone();
+        assert_eq!(info.priority, StreamingPriority::Critical);
+        assert_eq!(info.retry_count, 0);
+
+        assert!(!manager.force_load(handle.wrapping_add(1)));
+
+        let _ = std::fs::remove_dir_all(base_path);
+    }
+
+    #[test]
+    fn test_evict_removes_from_cache() {
+        let path = "textures/evict.png";
+        let handle = 42;
+        let (manager, base_path) = manager_with_resources("evict", &[(handle, path, None)]);
+        if let Some(info) = manager.resources.write().get_mut(path) {
+            info.state = ResourceState::Loaded(LodLevel::High);
+            info.memory_usage = 64;
+        }
+        manager.cache.write().insert(path.to_string(), vec![0; 64]);
+        assert!(manager.cache.read().contains(&path.to_string()));
+
+        assert!(manager.evict(handle));
+        assert!(!manager.cache.read().contains(&path.to_string()));
+        assert_eq!(manager.get_resource_state(handle), Some(ResourceState::NotLoaded));
+
+        assert!(!manager.evict(handle.wrapping_add(1)));
+
+        let _ = std::fs::remove_dir_all(base_path);
+    }
+
+    #[test]
+    fn test_lod_follows_camera_distance() {
+        let path = "meshes/lod.gltf";
+        let handle = 7;
+        let (manager, base_path) = manager_with_resources("lod", &[(handle, path, None)]);
+        std::fs::create_dir_all(base_path.join("meshes")).unwrap();
+        std::fs::write(base_path.join(path), b"mesh").unwrap();
+
+        // Already loaded, so the only request the worker sees is the LOD switch
+        manager.resources.write().get_mut(path).unwrap().state = ResourceState::Loaded(LodLevel::High);
+        manager.cache.write().insert(path.to_string(), vec![0; 4]);
+
+        assert!(manager.register_resource_world_position(handle, [0.0, 0.0, 0.0]));
+        assert!(!manager.register_resource_world_position(handle.wrapping_add(1), [0.0, 0.0, 0.0]));
+
+        let high_distance = manager.config.high_quality_distance;
+        let margin = manager.config.lod_hysteresis_distance;
+        manager.update(&[0.0, 0.0, high_distance - 1.0], &[0.0, 0.0, -1.0]);
+        assert_eq!(manager.get_resource_state(handle), Some(ResourceState::Loaded(LodLevel::High)));
+
+        // Past the threshold, but within the hysteresis margin
+        manager.update(&[0.0, 0.0, high_distance + margin * 0.5], &[0.0, 0.0, -1.0]);
+        assert_eq!(manager.get_resource_state(handle), Some(ResourceState::Loaded(LodLevel::High)));
+        assert_eq!(manager.resources.read()[path].pending_lod, None);
+
+        // Past the margin, but still inside the medium distance. The high LOD stays usable
+        // until the medium one replaces it.
+        manager.update(&[0.0, 0.0, high_distance + margin + 1.0], &[0.0, 0.0, -1.0]);
+        assert!(manager.cache.read().contains(&path.to_string()));
+        assert_ne!(manager.get_resource_state(handle), Some(ResourceState::Loading));
+        let start = std::time::Instant::now();
+        while manager.get_resource_state(handle) != Some(ResourceState::Loaded(LodLevel::Medium)) {
+            assert!(start.elapsed() < std::time::Duration::from_secs(5), "{:?}", manager.get_resource_state(handle));
+            std::thread::sleep(std::time::Duration::from_millis(5));
+        }
+
+        drop(manager);
+        let _ = std::fs::remove_dir_all(base_path);
+    }
+
+    #[test]
+    fn test_cancelled_load_is_not_cached() {
+        let path = "meshes/cancelled.gltf";
+        let handle = 9;
+        let (manager, base_path) = manager_with_resources("cancel", &[(handle, path, None)]);
+        std::fs::create_dir_all(base_path.join("meshes")).unwrap();
+        std::fs::write(base_path.join(path), b"mesh").unwrap();
+
+        // Marked as loading, and the request below handed to the worker code by hand, so it
+        // runs after the cancellation
+        manager.resources.write().get_mut(path).unwrap().state = ResourceState::Loading;
+
+        assert!(manager.cancel_resource(handle));
+        assert!(!manager.cancel_resource(handle));
+        assert!(!manager.cancel_resource(handle.wrapping_add(1)));
+
+        futures::executor::block_on(ResourceStreamingManager::process_load_request(
+            test_request(path),
+            &manager.resources,
+            &manager.cache,
+            &manager.asset_loader,
+            &manager.lod_manager,
+            &manager.stats,
+            &RetryPolicy::from_config(&manager.config),
+        ));
+        assert!(!manager.cache.read().contains(&path.to_string()));
+        assert_eq!(manager.get_resource_state(handle), Some(ResourceState::NotLoaded));
+
+        // Also when the cancellation arrives while the asset is being read
+        manager.resources.write().get_mut(path).unwrap().state = ResourceState::Loading;
+        let result = Ok(asset(vec![1, 2, 3]));
+        ResourceStreamingManager::finish_load_request(&test_request(path), result, &manager.resources, &manager.cache, &manager.stats);
+        assert!(!manager.cache.read().contains(&path.to_string()));
+
+        // `update` leaves it alone until it's requested again
+        manager.update(&[0.0, 0.0, 0.0], &[0.0, 0.0, 1.0]);
+        assert_eq!(manager.get_resource_state(handle), Some(ResourceState::NotLoaded));
+
+        manager.request_resource(path, LoadPriority::High, ResourceType::Mesh);
+        manager.update(&[0.0, 0.0, 0.0], &[0.0, 0.0, 1.0]);
+        assert_ne!(manager.get_resource_state(handle), Some(ResourceState::NotLoaded));
+
+        drop(manager);
+        let _ = std::fs::remove_dir_all(base_path);
+    }
+
+    #[test]
+    fn test_resources_past_max_distance_are_not_loaded() {
+        let (manager, base_path) = test_manager("min-priority");
+        let max_distance = manager.priority_calculator.read().get_config().max_distance_threshold;
+
+        // Requesting only registers them; `update` decides what gets loaded
+        let far = manager.request_resource("meshes/far.gltf", LoadPriority::High, ResourceType::Mesh);
+        let far_loaded = manager.request_resource("meshes/far_loaded.gltf", LoadPriority::High, ResourceType::Mesh);
+        let near = manager.request_resource("meshes/near.gltf", LoadPriority::High, ResourceType::Mesh);
+        for handle in [far, far_loaded, near] {
+            assert_eq!(manager.get_resource_state(handle), Some(ResourceState::NotLoaded));
+        }
+
+        manager.resources.write().get_mut("meshes/far_loaded.gltf").unwrap().state = ResourceState::Loaded(LodLevel::Low);
+        manager.cache.write().insert("meshes/far_loaded.gltf".to_string(), vec![0; 4]);
+        manager.register_resource_world_position(far, [0.0, 0.0, max_distance + 10.0]);
+        manager.register_resource_world_position(far_loaded, [0.0, 0.0, max_distance + 10.0]);
+        manager.register_resource_world_position(near, [0.0, 0.0, 10.0]);
+
+        manager.update(&[0.0, 0.0, 0.0], &[0.0, 0.0, 1.0]);
+
+        let far_info = manager.resources.read()["meshes/far.gltf"].clone();
+        assert_eq!(far_info.priority, StreamingPriority::Invisible);
+        assert_eq!(far_info.state, ResourceState::NotLoaded);
+
+        assert_eq!(manager.get_resource_state(far_loaded), Some(ResourceState::NotLoaded));
+        assert!(!manager.cache.read().contains(&"meshes/far_loaded.gltf".to_string()));
+
+        // The near one was enqueued; the file doesn't exist, so it may have failed already
+        assert_ne!(manager.get_resource_state(near), Some(ResourceState::NotLoaded));
+
+        drop(manager);
+        let _ = std::fs::remove_dir_all(base_path);
+    }
+
+    fn wait_until_loaded(manager: &ResourceStreamingManager, handle: ResourceHandle) {
+        let start = std::time::Instant::now();
+        while !matches!(manager.get_resource_state(handle), Some(ResourceState::Loaded(_))) {
+            assert!(start.elapsed() < std::time::Duration::from_secs(5), "{:?}", manager.get_resource_state(handle));
+            std::thread::sleep(std::time::Duration::from_millis(5));
+        }
+    }
+
+    /// A manager with one mesh file on disk, requested and placed in front of the camera
+    fn manager_with_mesh_in_view(name: &str, config: StreamingConfig, path: &str) -> (ResourceStreamingManager, std::path::PathBuf, ResourceHandle) {
+        let (manager, base_path) = test_manager_with_config(name, config);
+        std::fs::create_dir_all(base_path.join("meshes")).unwrap();
+        std::fs::write(base_path.join(path), b"mesh").unwrap();
+
+        let handle = manager.request_resource(path, LoadPriority::High, ResourceType::Mesh);
+        manager.set_resource_bounds(path, ResourceBounds { center: [0.0, 0.0, 10.0], radius: 1.0 });
+        (manager, base_path, handle)
+    }
+
+    #[test]
+    fn test_expired_resource_in_view_is_reloaded() {
+        // Long enough to see it loaded before the worker's own maintenance expires it
+        let ttl = std::time::Duration::from_millis(500);
+        let path = "meshes/expiring.gltf";
+        let (manager, base_path, handle) = manager_with_mesh_in_view(
+            "expired-in-view",
+            StreamingConfig {
+                eviction_policy: EvictionPolicy::TimeToLive(ttl),
+                maintenance_interval_ms: 0,
+                ..Default::default()
+            },
+            path,
+        );
+        let (camera, direction) = ([0.0, 0.0, 0.0], [0.0, 0.0, 1.0]);
+
+        manager.update_with_visibility(&camera, &direction, |_| true);
+        wait_until_loaded(&manager, handle);
+
+        std::thread::sleep(ttl);
+        manager.force_garbage_collection();
+        let info = manager.resources.read()[path].clone();
+        assert_eq!(info.state, ResourceState::NotLoaded);
+        assert_eq!(info.evicted, None);
+
+        // Still in view with the same priority, so the next update loads it again
+        manager.update_with_visibility(&camera, &direction, |_| true);
+        assert_ne!(manager.get_resource_state(handle), Some(ResourceState::NotLoaded));
+
+        drop(manager);
+        let _ = std::fs::remove_dir_all(base_path);
+    }
+
+    #[test]
+    fn test_evicted_resource_waits_for_priority_change() {
+        let path = "meshes/evicted.gltf";
+        let (manager, base_path, handle) = manager_with_mesh_in_view("evicted", StreamingConfig::default(), path);
+        let (camera, direction) = ([0.0, 0.0, 0.0], [0.0, 0.0, 1.0]);
+
+        manager.update(&camera, &direction);
+        wait_until_loaded(&manager, handle);
+
+        // Nothing changed since it was evicted, so it stays out of the cache
+        assert!(manager.evict(handle));
+        manager.update(&camera, &direction);
+        assert_eq!(manager.get_resource_state(handle), Some(ResourceState::NotLoaded));
+
+        // Coming into view raises its priority, which brings it back
+        manager.update_with_visibility(&camera, &direction, |_| true);
+        assert_ne!(manager.get_resource_state(handle), Some(ResourceState::NotLoaded));
+
+        drop(manager);
+        let _ = std::fs::remove_dir_all(base_path);
+    }
+
+    #[test]
+    fn test_closer_resources_get_higher_priority() {
+        let (manager, base_path) = test_manager_with_config(
+            "distance",
+            StreamingConfig {
+                default_resource_distance: 800.0,
+                ..Default::default()
+            },
+        );
+        register_resources(
+            &manager,
+            &[
+                (1, "meshes/near.gltf", Some([0.0, 0.0, 5.0])),
+                (2, "meshes/far.gltf", Some([0.0, 0.0, 800.0])),
+                (3, "meshes/unplaced.gltf", None),
+            ],
+        );
+
+        manager.update(&[0.0, 0.0, 0.0], &[0.0, 0.0, 1.0]);
+        let priority = |path: &str| manager.resources.read()[path].priority;
+        assert!(priority("meshes/near.gltf") > priority("meshes/far.gltf"));
+        assert_eq!(priority("meshes/unplaced.gltf"), priority("meshes/far.gltf"));
+
+        // Moving the camera next to the far one turns the order around
+        manager.update(&[0.0, 0.0, 795.0], &[0.0, 0.0, 1.0]);
+        assert!(priority("meshes/far.gltf") > priority("meshes/near.gltf"));
+
+        drop(manager);
+        let _ = std::fs::remove_dir_all(base_path);
+    }
+
+    #[test]
+    fn test_predictive_loading_prefetches_ahead_of_camera() {
+        for enable_predictive_loading in [false, true] {
+            let (manager, base_path) = test_manager_with_config(
+                &format!("predictive-{}", enable_predictive_loading),
+                StreamingConfig {
+                    enable_predictive_loading,
+                    lookahead_seconds: 10.0,
+                    ..Default::default()
+                },
+            );
+            register_resources(&manager, &[(1, "meshes/ahead.gltf", Some([0.0, 0.0, 700.0]))]);
+
+            manager.update(&[0.0, 0.0, 0.0], &[0.0, 0.0, 1.0]);
+            std::thread::sleep(std::time::Duration::from_millis(10));
+            manager.update(&[0.0, 0.0, 100.0], &[0.0, 0.0, 1.0]);
+
+            // 600 away, far past the high quality distance and too far to load by distance alone.
+            // The file doesn't exist, so once requested the load may have failed already.
+            let state = manager.get_resource_state(1);
+            if enable_predictive_loading {
+                assert_ne!(state, Some(ResourceState::NotLoaded));
+            } else {
+                assert_eq!(state, Some(ResourceState::NotLoaded));
+            }
+
+            drop(manager);
+            let _ = std::fs::remove_dir_all(base_path);
+        }
+    }
+
+    #[test]
+    fn test_maintenance_runs_under_continuous_load() {
+        let base_path = std::env::temp_dir().join(format!("streaming-maintenance-test-{}", std::process::id()));
+        let mut manager = ResourceStreamingManager::new(StreamingConfig {
+            asset_base_path: base_path.to_string_lossy().to_string(),
+            max_load_retries: 0,
+            maintenance_interval_ms: 20,
+            ..Default::default()
+        })
+        .unwrap();
+
+        // Requests arrive far more often than the worker's idle timeout, so it never gets
+        // to run maintenance itself
+        let start = std::time::Instant::now();
+        let mut i = 0;
+        while start.elapsed() < std::time::Duration::from_millis(300) {
+            let handle = manager.request_resource(&format!("meshes/load-{}.gltf", i), LoadPriority::Medium, ResourceType::Mesh);
+            manager.force_load(handle);
+            i += 1;
+            std::thread::sleep(std::time::Duration::from_millis(1));
+        }
+
+        assert!(manager.get_stats().maintenance_runs >= 5);
+
+        futures::executor::block_on(manager.shutdown()).unwrap();
+        let _ = std::fs::remove_dir_all(base_path);
+    }
+
+    #[test]
+    fn test_self_test_round_trip() {
+        let (mut manager, base_path) = test_manager("self-test");
+
+        let report = manager.self_test(std::time::Duration::from_secs(5));
+        assert!(report.passed(), "{:?}", report.error);
+        assert!(report.load_time.is_some());
+        // Nothing else in the fresh base directory
+        assert_eq!(report.warnings.len(), 1);
+        assert!(manager.resources_snapshot().is_empty());
+        assert_eq!(std::fs::read_dir(&base_path).unwrap().count(), 0);
+
+        // Without a worker, the request can't go through
+        futures::executor::block_on(manager.shutdown()).unwrap();
+        let report = manager.self_test(std::time::Duration::from_millis(200));
+        assert!(!report.passed());
+        assert!(report.load_time.is_none());
+
+        let _ = std::fs::remove_dir_all(base_path);
+    }
+
+    #[test]
+    fn test_drop_stops_background_threads() {
+        let (manager, base_path) = test_manager("drop");
+        let stats = manager.stats.clone();
+
+        // A clone shares the state but not the threads, so dropping it leaves them running
+        drop(manager.clone());
+        assert!(Arc::strong_count(&stats) > 2);
+
+        // The worker and maintenance threads hold the other references, until they're joined
+        drop(manager);
+        assert_eq!(Arc::strong_count(&stats), 1);
+
+        let _ = std::fs::remove_dir_all(base_path);
+    }
+
+    #[test]
+    fn test_retry_delay_doubles() {
+        let policy = RetryPolicy {
+            max_retries: 3,
+            base_delay: std::time::Duration::from_millis(100),
+        };
+        assert_eq!(policy.delay_for_retry(0).as_millis(), 100);
+        assert_eq!(policy.delay_for_retry(1).as_millis(), 200);
+        assert_eq!(policy.delay_for_retry(2).as_millis(), 400);
+    }
+}
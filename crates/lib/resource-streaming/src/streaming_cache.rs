@@ -2,9 +2,10 @@ use crate::{ResourceId};
 use std::collections::HashMap;
 use bytesize::ByteSize;
 use log::{debug, warn};
+use serde::{Deserialize, Serialize};
 
 /// Política de desalojo del cache
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum EvictionPolicy {
     /// Least Recently Used - remueve los elementos menos recientemente usados
     LeastRecentlyUsed,
@@ -12,6 +13,15 @@ pub enum EvictionPolicy {
     LeastFrequentlyUsed,
     /// Basado en prioridad - remueve elementos de menor prioridad
     Priority,
+    /// Las entradas caducan pasado este tiempo desde que se insertaron, y `cleanup` las
+    /// remueve. Si falta espacio antes, se remueven las más antiguas.
+    TimeToLive(std::time::Duration),
+}
+
+impl Default for EvictionPolicy {
+    fn default() -> Self {
+        EvictionPolicy::LeastRecentlyUsed
+    }
 }
 
 /// Configuración del cache de streaming
@@ -26,7 +36,11 @@ pub struct CacheConfig {
 struct CacheEntry {
     data: Vec<u8>,
     access_count: u32,
+    inserted_at: std::time::Instant,
     last_accessed: std::time::Instant,
+    /// Valor de `StreamingCache::access_clock` en el último uso; ordena los accesos aunque
+    /// caigan en el mismo `Instant`
+    last_used: u64,
     size: u64,
     priority: u8,
 }
@@ -36,6 +50,7 @@ pub struct StreamingCache {
     config: CacheConfig,
     entries: HashMap<ResourceId, CacheEntry>,
     current_size: u64,
+    access_clock: u64,
     hit_count: u64,
     miss_count: u64,
 }
@@ -49,53 +64,71 @@ impl StreamingCache {
             config,
             entries: HashMap::new(),
             current_size: 0,
+            access_clock: 0,
             hit_count: 0,
             miss_count: 0,
         }
     }
     
-    /// Inserta un recurso en el cache
-    pub fn insert(&mut self, resource_id: ResourceId, data: Vec<u8>) {
+    /// Inserta un recurso en el cache, desalojando otros hasta que el total de bytes quepa en
+    /// `max_size`. Devuelve los recursos desalojados.
+    pub fn insert(&mut self, resource_id: ResourceId, data: Vec<u8>) -> Vec<ResourceId> {
         let size = data.len() as u64;
         
         // Si el recurso es demasiado grande para el cache, no lo almacenamos
         if size > self.config.max_size {
             warn!("Recurso {} es demasiado grande para el cache ({} > {})", 
                   resource_id, ByteSize(size), ByteSize(self.config.max_size));
-            return;
+            return Vec::new();
         }
         
-        // Hacer espacio si es necesario
-        self.make_space_for(size);
+        // Una versión anterior del mismo recurso no cuenta para hacer espacio
+        self.remove(&resource_id);
+        let evicted = self.make_space_for(size);
         
+        let now = std::time::Instant::now();
         let entry = CacheEntry {
             data,
             access_count: 1,
-            last_accessed: std::time::Instant::now(),
+            inserted_at: now,
+            last_accessed: now,
+            last_used: self.tick(),
             size,
             priority: 5, // Prioridad media por defecto
         };
         
-        // Si el recurso ya existía, actualizar el tamaño total
-        if let Some(old_entry) = self.entries.insert(resource_id.clone(), entry) {
-            self.current_size -= old_entry.size;
-        }
-        
+        self.entries.insert(resource_id.clone(), entry);
         self.current_size += size;
         
         debug!("Recurso {} insertado en cache. Uso actual: {}/{}", 
                resource_id, 
                ByteSize(self.current_size), 
                ByteSize(self.config.max_size));
+
+        evicted
     }
     
     /// Obtiene un recurso del cache
     pub fn get(&mut self, resource_id: &ResourceId) -> Option<&Vec<u8>> {
+        self.record_access(resource_id).map(|entry| &entry.data)
+    }
+
+    /// Cuenta un uso de un recurso igual que `get`, sin devolver sus datos. Devuelve los bytes
+    /// que ocupa en el cache, si está.
+    pub fn touch(&mut self, resource_id: &ResourceId) -> Option<u64> {
+        self.record_access(resource_id).map(|entry| entry.size)
+    }
+
+    /// Anota un acierto o un fallo, y en el primer caso el uso de la entrada para las políticas
+    /// de desalojo
+    fn record_access(&mut self, resource_id: &ResourceId) -> Option<&CacheEntry> {
+        let tick = self.tick();
         if let Some(entry) = self.entries.get_mut(resource_id) {
             entry.access_count += 1;
             entry.last_accessed = std::time::Instant::now();
+            entry.last_used = tick;
             self.hit_count += 1;
-            Some(&entry.data)
+            Some(entry)
         } else {
             self.miss_count += 1;
             None
@@ -117,6 +150,11 @@ impl StreamingCache {
     pub fn contains(&self, resource_id: &ResourceId) -> bool {
         self.entries.contains_key(resource_id)
     }
+
+    /// Bytes que ocupa un recurso en el cache, si está
+    pub fn entry_size(&self, resource_id: &ResourceId) -> Option<u64> {
+        self.entries.get(resource_id).map(|entry| entry.size)
+    }
     
     /// Obtiene el uso actual de memoria del cache
     pub fn current_size(&self) -> u64 {
@@ -156,15 +194,24 @@ impl StreamingCache {
         debug!("Cache completamente limpiado");
     }
     
-    /// Hace espacio en el cache para un nuevo recurso de tamaño específico
-    fn make_space_for(&mut self, required_size: u64) {
+    /// Hace espacio en el cache para un nuevo recurso de tamaño específico, devolviendo los
+    /// recursos desalojados
+    fn make_space_for(&mut self, required_size: u64) -> Vec<ResourceId> {
+        let mut evicted = Vec::new();
         while self.current_size + required_size > self.config.max_size && !self.entries.is_empty() {
             if let Some(resource_id) = self.select_victim() {
                 self.remove(&resource_id);
+                evicted.push(resource_id);
             } else {
                 break;
             }
         }
+        evicted
+    }
+
+    fn tick(&mut self) -> u64 {
+        self.access_clock += 1;
+        self.access_clock
     }
     
     /// Selecciona una víctima para desalojo según la política configurada
@@ -173,6 +220,7 @@ impl StreamingCache {
             EvictionPolicy::LeastRecentlyUsed => self.select_lru_victim(),
             EvictionPolicy::LeastFrequentlyUsed => self.select_lfu_victim(),
             EvictionPolicy::Priority => self.select_priority_victim(),
+            EvictionPolicy::TimeToLive(_) => self.select_oldest_victim(),
         }
     }
     
@@ -180,7 +228,7 @@ impl StreamingCache {
     fn select_lru_victim(&self) -> Option<ResourceId> {
         self.entries
             .iter()
-            .min_by_key(|(_, entry)| entry.last_accessed)
+            .min_by_key(|(_, entry)| entry.last_used)
             .map(|(id, _)| id.clone())
     }
     
@@ -192,6 +240,14 @@ impl StreamingCache {
             .map(|(id, _)| id.clone())
     }
     
+    /// Selecciona la entrada insertada hace más tiempo, que es la primera en caducar
+    fn select_oldest_victim(&self) -> Option<ResourceId> {
+        self.entries
+            .iter()
+            .min_by_key(|(_, entry)| entry.inserted_at)
+            .map(|(id, _)| id.clone())
+    }
+    
     /// Selecciona la víctima basada en prioridad
     fn select_priority_victim(&self) -> Option<ResourceId> {
         self.entries
@@ -220,20 +276,28 @@ impl StreamingCache {
         }
     }
     
-    /// Ejecuta limpieza del cache (elimina entradas antiguas)
-    pub fn cleanup(&mut self) {
-        let cutoff = std::time::Instant::now() - std::time::Duration::from_secs(300); // 5 minutos
-        let mut to_remove = Vec::new();
-        
-        for (id, entry) in &self.entries {
-            if entry.last_accessed < cutoff && entry.access_count <= 1 {
-                to_remove.push(id.clone());
-            }
-        }
+    /// Ejecuta limpieza del cache (elimina entradas antiguas). Con `TimeToLive` se van las
+    /// caducadas; con el resto, las que llevan 5 minutos sin usarse y solo se usaron al cargarse.
+    /// Devuelve los recursos eliminados.
+    pub fn cleanup(&mut self) -> Vec<ResourceId> {
+        let now = std::time::Instant::now();
+        let to_remove: Vec<ResourceId> = self
+            .entries
+            .iter()
+            .filter(|(_, entry)| match self.config.eviction_policy {
+                EvictionPolicy::TimeToLive(ttl) => now.duration_since(entry.inserted_at) >= ttl,
+                _ => {
+                    now.duration_since(entry.last_accessed) > std::time::Duration::from_secs(300)
+                        && entry.access_count <= 1
+                }
+            })
+            .map(|(id, _)| id.clone())
+            .collect();
         
-        for id in to_remove {
-            self.remove(&id);
+        for id in &to_remove {
+            self.remove(id);
         }
+        to_remove
     }
     
     /// Obtiene el uso actual de memoria del cache
@@ -253,3 +317,112 @@ pub struct CacheStats {
     pub hit_count: u64,
     pub miss_count: u64,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lru_cache(max_size: u64) -> StreamingCache {
+        StreamingCache::new(CacheConfig {
+            max_size,
+            eviction_policy: EvictionPolicy::LeastRecentlyUsed,
+        })
+    }
+
+    #[test]
+    fn test_insert_past_max_size_evicts_oldest() {
+        let mut cache = lru_cache(100);
+        assert!(cache.insert("a".to_string(), vec![0; 40]).is_empty());
+        assert!(cache.insert("b".to_string(), vec![0; 40]).is_empty());
+        assert_eq!(cache.current_size(), 80);
+
+        let evicted = cache.insert("c".to_string(), vec![0; 40]);
+        assert_eq!(evicted, vec!["a".to_string()]);
+        assert!(!cache.contains(&"a".to_string()));
+        assert_eq!(cache.current_size(), 80);
+        assert_eq!(cache.entry_size(&"c".to_string()), Some(40));
+
+        // Using an entry makes it the newest
+        cache.get(&"b".to_string());
+        assert_eq!(cache.insert("d".to_string(), vec![0; 40]), vec!["c".to_string()]);
+    }
+
+    #[test]
+    fn test_replacing_an_entry_counts_its_new_size() {
+        let mut cache = lru_cache(100);
+        cache.insert("a".to_string(), vec![0; 60]);
+        cache.insert("b".to_string(), vec![0; 30]);
+
+        // The old 60 bytes of "a" make room for its new version, so "b" stays
+        assert!(cache.insert("a".to_string(), vec![0; 70]).is_empty());
+        assert_eq!(cache.current_size(), 100);
+
+        // Too big to ever fit: nothing is evicted for it
+        assert!(cache.insert("huge".to_string(), vec![0; 101]).is_empty());
+        assert!(cache.contains(&"b".to_string()));
+    }
+
+    fn cache_with(eviction_policy: EvictionPolicy, max_size: u64) -> StreamingCache {
+        StreamingCache::new(CacheConfig {
+            max_size,
+            eviction_policy,
+        })
+    }
+
+    #[test]
+    fn test_lfu_evicts_least_used_entry() {
+        let mut cache = cache_with(EvictionPolicy::LeastFrequentlyUsed, 100);
+        cache.insert("popular".to_string(), vec![0; 40]);
+        cache.insert("rare".to_string(), vec![0; 40]);
+        for _ in 0..3 {
+            cache.get(&"popular".to_string());
+        }
+        // The most recent access doesn't save it, unlike with LRU
+        cache.get(&"rare".to_string());
+        cache.get(&"popular".to_string());
+        cache.get(&"rare".to_string());
+
+        assert_eq!(cache.insert("new".to_string(), vec![0; 40]), vec!["rare".to_string()]);
+    }
+
+    #[test]
+    fn test_touch_counts_as_a_use() {
+        let mut cache = cache_with(EvictionPolicy::LeastFrequentlyUsed, 100);
+        cache.insert("touched".to_string(), vec![0; 40]);
+        cache.insert("idle".to_string(), vec![0; 40]);
+
+        assert_eq!(cache.touch(&"touched".to_string()), Some(40));
+        assert_eq!(cache.touch(&"missing".to_string()), None);
+        assert_eq!(cache.get_hit_rate(), 50.0);
+
+        assert_eq!(cache.insert("new".to_string(), vec![0; 40]), vec!["idle".to_string()]);
+    }
+
+    #[test]
+    fn test_ttl_expires_entries_on_cleanup() {
+        let ttl = std::time::Duration::from_millis(50);
+        let mut cache = cache_with(EvictionPolicy::TimeToLive(ttl), 100);
+        cache.insert("old".to_string(), vec![0; 10]);
+        std::thread::sleep(ttl);
+        cache.insert("fresh".to_string(), vec![0; 10]);
+
+        // Being used doesn't extend the lifetime
+        cache.get(&"old".to_string());
+        assert_eq!(cache.cleanup(), vec!["old".to_string()]);
+        assert!(!cache.contains(&"old".to_string()));
+        assert!(cache.contains(&"fresh".to_string()));
+        assert_eq!(cache.current_size(), 10);
+
+        // Short of space before anything expires, the oldest goes first
+        cache.insert("next".to_string(), vec![0; 10]);
+        assert_eq!(cache.insert("big".to_string(), vec![0; 85]), vec!["fresh".to_string()]);
+    }
+
+    #[test]
+    fn test_cleanup_without_ttl_keeps_recent_entries() {
+        let mut cache = cache_with(EvictionPolicy::LeastRecentlyUsed, 100);
+        cache.insert("a".to_string(), vec![0; 10]);
+        assert!(cache.cleanup().is_empty());
+        assert!(cache.contains(&"a".to_string()));
+    }
+}